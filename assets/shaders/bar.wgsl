@@ -3,8 +3,14 @@ struct BarParams {
   value: f32,
   r: f32, g: f32, b: f32, a: f32,
   bg_r: f32, bg_g: f32, bg_b: f32, bg_a: f32,
+  mode: u32,
+  origin: f32,
+  opacity: f32,
 }
 
+// Fill modes, matching `BarParams::mode`
+const MODE_CENTER: u32 = 1u;
+
 
 @group(1) @binding(0)
 var<uniform> B: BarParams;
@@ -23,7 +29,9 @@ fn fragment(in: VSOut) -> @location(0) vec4<f32> {
   let fill = vec4<f32>(B.r, B.g, B.b, B.a);
   let bg = vec4<f32>(B.bg_r, B.bg_g, B.bg_b, B.bg_a);
   
-  // Simplified fill detection - avoid select function when possible
-  let is_fill = f32(uv_x <= value);
-  return mix(bg, fill, is_fill);
+  // Center mode fills the span between the origin and the value
+  let start = select(0.0, clamp(B.origin, 0.0, 1.0), B.mode == MODE_CENTER);
+  let is_fill = f32(uv_x >= min(start, value) && uv_x <= max(start, value));
+  let color = mix(bg, fill, is_fill);
+  return vec4<f32>(color.rgb, color.a * B.opacity);
 }
@@ -0,0 +1,70 @@
+// Bar grid shader: every bar of the grid drawn by one quad
+// Note: pack 4 values into one vec4 to satisfy uniform array stride alignment (16 bytes)
+const MAX_BARS: u32 = 32u;
+const PACK: u32 = 4u;
+const BARS_V4: u32 = MAX_BARS / PACK; // 8
+
+struct BarsParams {
+  rects: array<vec4<f32>, MAX_BARS>,
+  colors: array<vec4<f32>, MAX_BARS>,
+  values: array<vec4<f32>, BARS_V4>,
+  origins: array<vec4<f32>, BARS_V4>,
+  center_mask: u32,
+  bg_colors: array<vec4<f32>, MAX_BARS>,
+  count: u32,
+  opacity: f32,
+  average_mask: u32,
+  averages: array<vec4<f32>, BARS_V4>,
+}
+
+// Split bars: the value fills the top part and the rolling average the bottom
+// part, with a transparent gap between them (fractions of the bar height)
+const SPLIT_GAP: f32 = 0.1;
+// Opacity of the rolling average relative to the bar color
+const AVERAGE_ALPHA: f32 = 0.6;
+
+
+@group(1) @binding(0)
+var<uniform> B: BarsParams;
+
+
+struct VSOut { @builtin(position) pos: vec4<f32>, @location(0) uv: vec2<f32>, }
+
+
+@fragment
+fn fragment(in: VSOut) -> @location(0) vec4<f32> {
+  let uv = clamp(in.uv, vec2<f32>(0.0), vec2<f32>(1.0));
+  let count = min(B.count, MAX_BARS);
+
+  for (var i: u32 = 0u; i < count; i = i + 1u) {
+    let rect = B.rects[i];
+    let local = (uv - rect.xy) / max(rect.zw, vec2<f32>(1e-6));
+    if (any(local < vec2<f32>(0.0)) || any(local > vec2<f32>(1.0))) {
+      continue;
+    }
+
+    // Split bars show the value above and the rolling average below
+    var value = clamp(B.values[i / PACK][i % PACK], 0.0, 1.0);
+    var fill_color = B.colors[i];
+    if ((B.average_mask & (1u << i)) != 0u) {
+      let half_gap = SPLIT_GAP * 0.5;
+      if (abs(local.y - 0.5) < half_gap) {
+        return vec4<f32>(0.0);
+      }
+      if (local.y > 0.5) {
+        value = clamp(B.averages[i / PACK][i % PACK], 0.0, 1.0);
+        fill_color.a = fill_color.a * AVERAGE_ALPHA;
+      }
+    }
+
+    // Inside this bar: fill from its start up to its value, background beyond
+    let centered = (B.center_mask & (1u << i)) != 0u;
+    let start = select(0.0, clamp(B.origins[i / PACK][i % PACK], 0.0, 1.0), centered);
+    let is_fill = f32(local.x >= min(start, value) && local.x <= max(start, value));
+    let color = mix(B.bg_colors[i], fill_color, is_fill);
+    return vec4<f32>(color.rgb, color.a * B.opacity);
+  }
+
+  // Gaps between bars stay transparent
+  return vec4<f32>(0.0);
+}
@@ -4,9 +4,12 @@
 const SAMPLES: u32 = 256u;
 const PACK: u32 = 4u;
 const SAMPLES_V4: u32 = SAMPLES / PACK; // 64
+const MISSING_V4: u32 = SAMPLES / 128u; // one bit per sample
 
 struct MultiLineGraphParams {
   values: array<array<vec4<f32>, SAMPLES_V4>, 6u>,
+  ranges: array<array<vec4<u32>, SAMPLES_V4>, 6u>,
+  missing: array<array<vec4<u32>, MISSING_V4>, 6u>,
   length: u32,
   min_y: f32,
   max_y: f32,
@@ -21,9 +24,43 @@ struct MultiLineGraphParams {
   border_right: u32,
   border_top: u32,
   colors: array<vec4<f32>, 6u>,
+  scales: array<vec4<f32>, 6u>,
   curve_count: u32,
+  candlestick_mask: u32,
+  quality: u32,
+  draw_order: u32,
+  opacity: f32,
+  curve_styles: u32,
+  budget_lines: array<vec4<f32>, 6u>,
+  budget_warning_color: vec4<f32>,
+  budget_critical_color: vec4<f32>,
+  shading: array<vec4<f32>, 6u>,
+  shading_colors: array<vec4<f32>, 6u>,
+  grid_color: vec4<f32>,
+  grid_lines: u32,
+  line_smoothing: u32,
+  view_start: u32,
+  view_len: u32,
+  selection: vec2<f32>,
+  cursor_x: f32,
 }
 
+// Quality levels, matching `GraphQuality`
+const QUALITY_HIGH: u32 = 0u;
+const QUALITY_LOW: u32 = 2u;
+
+// Curve line styles, matching `CurveStyle`
+const STYLE_DASHED: u32 = 1u;
+const STYLE_DOTTED: u32 = 2u;
+const STYLE_FILLED_AREA: u32 = 3u;
+// Dashes and dots across the graph width
+const DASH_COUNT: f32 = 32.0;
+const DOT_COUNT: f32 = 96.0;
+
+// Line smoothing modes, matching `LineSmoothing`
+const SMOOTH_LINEAR: u32 = 0u;
+const SMOOTH_B_SPLINE: u32 = 2u;
+
 
 @group(1) @binding(0)
 var<uniform> P: MultiLineGraphParams;
@@ -32,6 +69,26 @@ var<uniform> P: MultiLineGraphParams;
 struct VSOut { @builtin(position) pos: vec4<f32>, @location(0) uv: vec2<f32>, }
 
 
+// Coverage of the threshold shading of curve c at height y, for a curve at
+// normalized height yn and threshold at normalized height tn
+fn shading_alpha(c: u32, y: f32, yn: f32, tn: f32) -> f32 {
+  let shade = P.shading[c];
+  if (shade.z < 0.5 || P.colors[c].a <= 0.0) {
+    return 0.0;
+  }
+  // Direction 1 shades the span above the threshold, -1 the span below it
+  let above = shade.y > 0.0 && y > tn && y < yn;
+  let below = shade.y < 0.0 && y < tn && y > yn;
+  return select(0.0, P.shading_colors[c].a, above || below);
+}
+
+// Whether sample i of curve c has no value (the curve breaks there)
+fn is_missing(c: u32, i: u32) -> bool {
+  let word = i / 32u;
+  let bits = P.missing[c][word / PACK][word % PACK];
+  return ((bits >> (i % 32u)) & 1u) != 0u;
+}
+
 // Optimized smooth_band function with precalculated constants
 fn smooth_band(distance: f32, inner: f32, outer: f32, range_reciprocal: f32) -> f32 {
   let normalized = clamp((outer - distance) * range_reciprocal, 0.0, 1.0);
@@ -62,6 +119,18 @@ fn cubic_hermite(p0: f32, p1: f32, m0: f32, m1: f32, t: f32) -> f32 {
   return h00 * p0 + h10 * m0 + h01 * p1 + h11 * m1;
 }
 
+// Uniform cubic B-spline segment between p0 and p1 (requires 4 points: p-1, p0, p1, p2)
+// The curve passes near the samples instead of through them, smoothing out jitter
+fn b_spline(p_minus1: f32, p0: f32, p1: f32, p2: f32, t: f32) -> f32 {
+  let t2 = t * t;
+  let t3 = t2 * t;
+  let u = 1.0 - t;
+  return (u * u * u * p_minus1
+    + (3.0 * t3 - 6.0 * t2 + 4.0) * p0
+    + (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) * p1
+    + t3 * p2) / 6.0;
+}
+
 @fragment
 fn fragment(in: VSOut) -> @location(0) vec4<f32> {
   // Pre-calculate constants and clamp once
@@ -71,17 +140,19 @@ fn fragment(in: VSOut) -> @location(0) vec4<f32> {
   // Pre-calculate length and scale factors
   let len = max(P.length, 1u);
   let len_minus_one = len - 1u;
-  let len_scale = max(f32(len_minus_one), 1e-6);
+  // Samples spread across the width: a zoomed window, or all of them
+  let zoomed = P.view_len >= 2u && len >= 2u;
+  let view_len = select(len, clamp(P.view_len, 2u, len), zoomed);
+  let view_start = select(0u, min(P.view_start, len - view_len), zoomed);
+  let len_scale = max(f32(view_len - 1u), 1e-6);
   let inv_len_scale = 1.0 / len_scale;
-  let y_range = max(P.max_y - P.min_y, 1e-6);
-  let inv_y_range = 1.0 / y_range;
-
-  // Pre-calculate thickness values
-  let thickness_inner = P.thickness * 0.6;
-  let thickness_outer = P.thickness * 1.2;
+  // Pre-calculate thickness values; low quality collapses the AA band to a hard edge
+  let hard_edge = P.quality >= QUALITY_LOW;
+  let thickness_inner = select(P.thickness * 0.6, P.thickness, hard_edge);
+  let thickness_outer = select(P.thickness * 1.2, P.thickness, hard_edge);
   let thickness_range_reciprocal = 1.0 / max(thickness_outer - thickness_inner, 1e-6);
 
-  let x = uv.x * f32(len_minus_one);
+  let x = f32(view_start) + uv.x * f32(view_len - 1u);
   let i0 = u32(floor(x));
   let i1 = min(i0 + 1u, len_minus_one);
   let t = fract(x);
@@ -93,52 +164,149 @@ fn fragment(in: VSOut) -> @location(0) vec4<f32> {
   let l1 = i1 % PACK;
 
   // Pre-calculate common values outside the loop
-  let x0 = f32(i0) * inv_len_scale;
-  let x1 = f32(i1) * inv_len_scale;
+  let x0 = (f32(i0) - f32(view_start)) * inv_len_scale;
+  let x1 = (f32(i1) - f32(view_start)) * inv_len_scale;
 
-  var best_alpha = 0.0;
-  var out_rgb = vec3<f32>(0.0);
+  // Curves composited bottom to top (premultiplied color and coverage)
+  var line_rgb = vec3<f32>(0.0);
+  var line_a = 0.0;
   
-  // Loop through curves with early exit
-  for (var c: u32 = 0u; c < P.curve_count; c = c + 1u) {
+  // Nearest sample, used by candlestick curves
+  let k = min(u32(round(x)), len_minus_one);
+  let jk = k / PACK;
+  let lk = k % PACK;
+  let xk = (f32(k) - f32(view_start)) * inv_len_scale;
+  let candle_half_width = 0.35 * inv_len_scale;
+
+  // Loop through curves in draw order, later layers on top
+  for (var n: u32 = 0u; n < P.curve_count; n = n + 1u) {
+    let c = (P.draw_order >> (4u * n)) & 0xFu;
+    // Each curve is scaled against its own (possibly per-group) Y range
+    let min_y = P.scales[c].x;
+    let inv_y_range = 1.0 / max(P.scales[c].y - min_y, 1e-6);
+
+    // Budget thresholds as dashed lines under the curve
+    let budget = P.budget_lines[c];
+    if (budget.z > 0.5 && P.colors[c].a > 0.0 && fract(uv.x * 24.0) < 0.5) {
+      let half_width = P.border_thickness_uv_y * 0.5;
+      let on_warning = abs(uv.y - (budget.x - min_y) * inv_y_range) < half_width;
+      let on_critical = abs(uv.y - (budget.y - min_y) * inv_y_range) < half_width;
+      if (on_warning || on_critical) {
+        let color = select(P.budget_warning_color, P.budget_critical_color, on_critical);
+        let ba = 0.6 * color.a;
+        line_rgb = color.rgb * ba + line_rgb * (1.0 - ba);
+        line_a = ba + line_a * (1.0 - ba);
+      }
+    }
+
+    // Rebound curves: samples before `series_start` belong to the previous
+    // metric, drawn at `previous_opacity` behind a dashed marker line
+    let series_start = u32(P.scales[c].z);
+    let previous_opacity = P.scales[c].w;
+    if (series_start > 0u) {
+      let marker_x = (f32(series_start) - f32(view_start) - 0.5) * inv_len_scale;
+      let on_marker = abs(uv.x - marker_x) < P.border_thickness_uv_x * 0.5 && fract(uv.y * 12.0) < 0.5;
+      let ma = select(0.0, 0.6 * previous_opacity * P.colors[c].a, on_marker);
+      line_rgb = P.colors[c].rgb * ma + line_rgb * (1.0 - ma);
+      line_a = ma + line_a * (1.0 - ma);
+    }
+
+    // Candlestick curves: min/max range bar plus a dot at the mean
+    if ((P.candlestick_mask & (1u << c)) != 0u) {
+      if (is_missing(c, k)) {
+        continue;
+      }
+      let mean_n = (P.values[c][jk][lk] - min_y) * inv_y_range;
+      let series_opacity = select(1.0, previous_opacity, k < series_start);
+
+      // Threshold shading up to the mean, under the candle
+      let sa = shading_alpha(c, uv.y, mean_n, (P.shading[c].x - min_y) * inv_y_range) * series_opacity;
+      line_rgb = P.shading_colors[c].rgb * sa + line_rgb * (1.0 - sa);
+      line_a = sa + line_a * (1.0 - sa);
+
+      let range = unpack2x16unorm(P.ranges[c][jk][lk]);
+      let in_range = abs(uv.x - xk) < candle_half_width && uv.y >= range.x && uv.y <= range.y;
+      let range_alpha = select(0.0, 0.45, in_range);
+      let dot_alpha = smooth_band(distance(uv, vec2<f32>(xk, mean_n)), thickness_inner, thickness_outer, thickness_range_reciprocal);
+      let ca = max(range_alpha, dot_alpha) * P.colors[c].a * series_opacity;
+      line_rgb = P.colors[c].rgb * ca + line_rgb * (1.0 - ca);
+      line_a = ca + line_a * (1.0 - ca);
+      continue;
+    }
+
+    // Segments touching a missing sample are broken; a lone valid end
+    // is still drawn as a dot so isolated samples stay visible.
+    // Series of different metrics are never joined either
+    let missing0 = is_missing(c, i0);
+    let missing1 = is_missing(c, i1) || (i0 < series_start && i1 >= series_start);
+    if (missing0 && missing1) {
+      continue;
+    }
+    let series_opacity = select(1.0, previous_opacity, i0 < series_start);
+
     // Get values for interpolation
     let v0 = P.values[c][j0];
     let v1 = P.values[c][j1];
     let y0 = v0[l0];
     let y1 = v1[l1];
     
-    // Get neighboring points for smooth interpolation
-    var y_minus1 = y0;  // Previous point
-    var y_plus1 = y1;   // Next point
+    // Lower quality levels skip the neighbor fetches and use linear segments
+    var y = mix(y0, y1, t);
+    let line_mode = (P.line_smoothing >> (2u * c)) & 0x3u;
+    if (P.quality == QUALITY_HIGH && line_mode != SMOOTH_LINEAR) {
+      // Get neighboring points for smooth interpolation
+      var y_minus1 = y0;  // Previous point
+      var y_plus1 = y1;   // Next point
     
-    // Get y_minus1: check if i0 > 0
-    if (i0 > 0) {
-      let j_minus1 = (i0 - 1) / PACK;
-      let l_minus1 = (i0 - 1) % PACK;
-      let v_minus1 = P.values[c][j_minus1];
-      y_minus1 = v_minus1[l_minus1];
-    }
+      // Get y_minus1: check if i0 > 0 (missing neighbors keep the flat tangent)
+      if (i0 > 0 && i0 != series_start && !is_missing(c, i0 - 1)) {
+        let j_minus1 = (i0 - 1) / PACK;
+        let l_minus1 = (i0 - 1) % PACK;
+        let v_minus1 = P.values[c][j_minus1];
+        y_minus1 = v_minus1[l_minus1];
+      }
     
-    // Get y_plus1: check if i1 < len_minus_one
-    if (i1 < len_minus_one) {
-      let j_plus1 = (i1 + 1) / PACK;
-      let l_plus1 = (i1 + 1) % PACK;
-      let v_plus1 = P.values[c][j_plus1];
-      y_plus1 = v_plus1[l_plus1];
-    }
+      // Get y_plus1: check if i1 < len_minus_one
+      if (i1 < len_minus_one && i1 + 1 != series_start && !is_missing(c, i1 + 1)) {
+        let j_plus1 = (i1 + 1) / PACK;
+        let l_plus1 = (i1 + 1) % PACK;
+        let v_plus1 = P.values[c][j_plus1];
+        y_plus1 = v_plus1[l_plus1];
+      }
     
-    // Use cubic Hermite for smooth interpolation
-    // Calculate tangents for Hermite spline
-    let m0 = 0.5 * (y1 - y_minus1);  // Tangent at point 0
-    let m1 = 0.5 * (y_plus1 - y0);   // Tangent at point 1
+      // Use cubic Hermite for smooth interpolation
+      // Calculate tangents for Hermite spline
+      let m0 = 0.5 * (y1 - y_minus1);  // Tangent at point 0
+      let m1 = 0.5 * (y_plus1 - y0);   // Tangent at point 1
     
-    // Perform cubic Hermite interpolation
-    let y = cubic_hermite(y0, y1, m0, m1, t);
+      // Perform cubic Hermite interpolation, or approximate the samples with a B-spline
+      if (line_mode == SMOOTH_B_SPLINE) {
+        y = b_spline(y_minus1, y0, y1, y_plus1, t);
+      } else {
+        y = cubic_hermite(y0, y1, m0, m1, t);
+      }
+    }
     
     // Normalize y values
-    let y0n = (y0 - P.min_y) * inv_y_range;
-    let y1n = (y1 - P.min_y) * inv_y_range;
-    let yn = (y - P.min_y) * inv_y_range;  // Normalized interpolated y value
+    let y0n = (y0 - min_y) * inv_y_range;
+    let y1n = (y1 - min_y) * inv_y_range;
+    let yn = (y - min_y) * inv_y_range;  // Normalized interpolated y value
+
+    // Threshold shading between the threshold and the curve, under the line;
+    // broken segments have no curve to shade up to
+    let style = (P.curve_styles >> (2u * c)) & 0x3u;
+    if (!missing0 && !missing1) {
+      let sa = shading_alpha(c, uv.y, yn, (P.shading[c].x - min_y) * inv_y_range) * series_opacity;
+      line_rgb = P.shading_colors[c].rgb * sa + line_rgb * (1.0 - sa);
+      line_a = sa + line_a * (1.0 - sa);
+
+      // Filled area from the bottom of the graph up to the curve
+      if (style == STYLE_FILLED_AREA && uv.y < yn) {
+        let fa = 0.25 * P.colors[c].a * series_opacity;
+        line_rgb = P.colors[c].rgb * fa + line_rgb * (1.0 - fa);
+        line_a = fa + line_a * (1.0 - fa);
+      }
+    }
     
     // Calculate positions for smooth curve
     let p0 = vec2<f32>(x0, y0n);
@@ -153,27 +321,57 @@ fn fragment(in: VSOut) -> @location(0) vec4<f32> {
     let tseg = clamp(dot(w, seg) / max(dot(seg, seg), 1e-6), 0.0, 1.0);
     let closest_on_line = p0 + seg * tseg;  // Closest point on the straight line segment
     
-    // Calculate distance to the interpolated point
-    let d = distance(uv, p);
-    
-    // Calculate alpha using precalculated reciprocal
-    let alpha = smooth_band(d, thickness_inner, thickness_outer, thickness_range_reciprocal);
-    let ca = alpha * P.colors[c].a;
-    
-    if (ca > best_alpha) {
-      best_alpha = ca;
-      out_rgb = P.colors[c].rgb;
+    // Calculate distance to the interpolated point, or to the valid end of a broken segment
+    var d = distance(uv, p);
+    if (missing0) {
+      d = distance(uv, p1);
+    } else if (missing1) {
+      d = distance(uv, p0);
     }
     
-    // Early exit optimization
-    if (best_alpha >= 0.995) {
-      break;
+    // Calculate alpha using precalculated reciprocal; dashed and dotted
+    // lines leave gaps along the X axis
+    var alpha = smooth_band(d, thickness_inner, thickness_outer, thickness_range_reciprocal);
+    if (style == STYLE_DASHED && fract(uv.x * DASH_COUNT) > 0.6) {
+      alpha = 0.0;
+    } else if (style == STYLE_DOTTED && fract(uv.x * DOT_COUNT) > 0.4) {
+      alpha = 0.0;
     }
+    let ca = alpha * P.colors[c].a * series_opacity;
+    line_rgb = P.colors[c].rgb * ca + line_rgb * (1.0 - ca);
+    line_a = ca + line_a * (1.0 - ca);
   }
   
+  // Horizontal grid lines over the background, evenly spaced from bottom to top
+  var bg_rgb = P.bg_color.rgb;
+  var bg_a = P.bg_color.a;
+  if (P.grid_lines >= 2u) {
+    let spacing = 1.0 / f32(P.grid_lines - 1u);
+    let d = abs(uv.y - round(uv.y / spacing) * spacing);
+    let px = fwidth(uv.y);
+    let ga = (1.0 - smoothstep(0.5 * px, 1.5 * px, d)) * P.grid_color.a;
+    bg_rgb = mix(bg_rgb, P.grid_color.rgb, ga);
+    bg_a = 1.0 - (1.0 - bg_a) * (1.0 - ga);
+  }
+
+  // Zoom selection being dragged, tinted in the border color
+  let selecting = P.selection.x != P.selection.y;
+  if (selecting && uv.x >= min(P.selection.x, P.selection.y) && uv.x <= max(P.selection.x, P.selection.y)) {
+    let sa = 0.2 * P.border_color.a;
+    bg_rgb = mix(bg_rgb, P.border_color.rgb, sa);
+    bg_a = 1.0 - (1.0 - bg_a) * (1.0 - sa);
+  }
+
+  // Column of the hovered sample, in the border color
+  if (P.cursor_x >= 0.0 && abs(uv.x - P.cursor_x) < P.border_thickness_uv_x * 0.5) {
+    let ca = 0.6 * P.border_color.a;
+    bg_rgb = mix(bg_rgb, P.border_color.rgb, ca);
+    bg_a = 1.0 - (1.0 - bg_a) * (1.0 - ca);
+  }
+
   // Final color blending
-  var comp_rgb = mix(P.bg_color.rgb, out_rgb, best_alpha);
-  var comp_a = 1.0 - (1.0 - P.bg_color.a) * (1.0 - best_alpha);
+  var comp_rgb = bg_rgb * (1.0 - line_a) + line_rgb;
+  var comp_a = 1.0 - (1.0 - bg_a) * (1.0 - line_a);
 
   // Pre-calculate border constants
   let btx = P.border_thickness_uv_x;
@@ -195,5 +393,5 @@ fn fragment(in: VSOut) -> @location(0) vec4<f32> {
   comp_rgb = mix(comp_rgb, P.border_color.rgb, b_alpha);
   comp_a = 1.0 - (1.0 - comp_a) * (1.0 - b_alpha);
   
-  return vec4<f32>(comp_rgb, comp_a);
+  return vec4<f32>(comp_rgb, comp_a * P.opacity);
 }
@@ -1,8 +1,5 @@
 use bevy::prelude::*;
-use bevy_perf_hud::{
-    BarConfig, BarScaleMode, BevyPerfHudPlugin, MetricDefinition, MetricSampleContext,
-    PerfHudAppExt, PerfHudSettings, PerfMetricProvider,
-};
+use bevy_perf_hud::prelude::*;
 
 /// Demonstrates different bar scaling modes for dynamic range adjustment
 fn main() {
@@ -63,8 +60,7 @@ fn create_scaling_demo_settings() -> PerfHudSettings {
             min_value: 0.0,
             max_value: 100.0,
             scale_mode: BarScaleMode::Fixed,
-            min_limit: None,
-            max_limit: None,
+            ..default()
         },
         // Auto mode bar - adapts to data range with smoothing
         BarConfig {
@@ -79,6 +75,7 @@ fn create_scaling_demo_settings() -> PerfHudSettings {
             },
             min_limit: Some(0.0),    // Hard minimum limit
             max_limit: Some(2500.0), // Hard maximum limit
+            ..default()
         },
         // Percentile mode bar - uses P5 to P95 range, good for spiky data
         BarConfig {
@@ -93,6 +90,8 @@ fn create_scaling_demo_settings() -> PerfHudSettings {
             },
             min_limit: Some(0.0),    // Hard minimum limit
             max_limit: Some(1000.0), // Hard maximum limit
+            column_span: 2,          // Full-width row for the spiky metric
+            ..default()
         },
     ];
 
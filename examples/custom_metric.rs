@@ -1,8 +1,5 @@
 use bevy::prelude::*;
-use bevy_perf_hud::{
-    BarConfig, BarScaleMode, BevyPerfHudPlugin, CurveConfig, MetricDefinition, MetricSampleContext,
-    PerfHudAppExt, PerfHudSettings, PerfMetricProvider,
-};
+use bevy_perf_hud::prelude::*;
 
 const CUSTOM_METRIC_ID: &str = "custom/network_latency_ms";
 
@@ -65,6 +62,14 @@ fn main() {
         autoscale: Some(false),
         smoothing: Some(0.25),
         quantize_step: Some(0.5),
+        candlestick: false,
+        style: CurveStyle::Dashed,
+        line_smoothing: LineSmoothing::CatmullRom,
+        precision: None,
+        group: None,
+        z_index: 0,
+        shading: None,
+        enabled: true,
     });
 
     // Add custom latency metric with percentile scaling
@@ -83,6 +88,7 @@ fn main() {
             },
             min_limit: Some(0.0),    // Hard minimum (latency can't be negative)
             max_limit: Some(1000.0), // Hard maximum (cap extreme outliers)
+            ..default()
         },
     );
 
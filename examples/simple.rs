@@ -1,7 +1,8 @@
 use bevy::math::primitives::Cuboid;
 use bevy::prelude::*;
 use bevy_perf_hud::{
-    BarConfig, BarScaleMode, BevyPerfHudPlugin, HudHandles, MetricDefinition, PerfHudSettings,
+    BarConfig, BarScaleMode, BevyPerfHudPlugin, FpsTarget, HudAnchor, HudHandles, MetricDefinition,
+    PerfHudSettings, ThresholdShading,
 };
 
 #[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
@@ -312,7 +313,8 @@ fn main() {
         .init_resource::<HudMode>()
         .insert_resource({
             let mut settings = PerfHudSettings {
-                origin: Vec2::new(960.0, 16.0),
+                anchor: HudAnchor::TopRight,
+                origin: Vec2::new(16.0, 16.0),
                 ..default()
             };
 
@@ -332,6 +334,22 @@ fn main() {
                 entity_bar.show_value = Some(true); // Show actual entity count
             }
 
+            // Color frame time and FPS by their budgets at the display refresh rate
+            settings.fps_target = FpsTarget::Display;
+
+            // Tint frame time above the 60 FPS budget so slow frames show as area
+            if let Some(frame_curve) = settings
+                .graph
+                .curves
+                .iter_mut()
+                .find(|curve| curve.metric.id == "frame_time_ms")
+            {
+                frame_curve.shading = Some(ThresholdShading::above(
+                    16.6,
+                    Color::srgba(1.0, 0.2, 0.2, 0.25),
+                ));
+            }
+
             // Add FPS bar with percentile scaling to handle frame spikes
             let fps_metric = MetricDefinition {
                 id: "fps".into(),
@@ -354,6 +372,9 @@ fn main() {
                     },
                     min_limit: Some(0.0),   // FPS can't be negative
                     max_limit: Some(300.0), // Cap at reasonable maximum
+                    // Show the 2 second average next to the instant FPS
+                    rolling_average: Some(std::time::Duration::from_secs(2)),
+                    ..default()
                 },
             );
 
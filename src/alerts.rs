@@ -0,0 +1,203 @@
+//! Actions run when a metric enters a critical state.
+//!
+//! Each frame, every sampled metric with a budget (see
+//! [`PerfHudSettings::budget_thresholds`]) is checked against it, and a
+//! [`MetricBudgetTransition`] is sent whenever its [`BudgetLevel`] changes.
+//! Register a one-shot system per alert with
+//! [`PerfHudAppExt::add_perf_alert`](crate::PerfHudAppExt) to dump a trace,
+//! toggle a debug view or lower quality settings as soon as a metric turns
+//! critical, instead of only changing what the HUD shows.
+//!
+//! A metric only recovers to a better level once it is
+//! [`PerfHudSettings::budget_hysteresis`] past the threshold, so one sitting
+//! on a threshold does not flap between levels.
+//!
+//! Fired alerts, and transitions with
+//! [`PerfHudSettings::log_budget_transitions`] set, are logged as structured
+//! `tracing` events with `metric_id`, `value` and `threshold` fields, under
+//! the `bevy_perf_hud::alerts` target, so they can be filtered with
+//! `RUST_LOG`.
+
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::{
+        event::{EventReader, EventWriter},
+        system::{Commands, Res, ResMut, SystemId},
+    },
+    log::{info, warn},
+    prelude::Resource,
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::{BudgetLevel, PerfHudSettings},
+    events::MetricBudgetTransition,
+    resources::{DisplayRefreshRate, SampledValues},
+};
+
+/// An action run once each time a metric enters its critical budget level.
+#[derive(Debug, Clone)]
+pub struct PerfHudAlert {
+    /// ID of the metric to watch
+    pub metric_id: String,
+    /// One-shot system run when the metric becomes critical
+    pub action: SystemId,
+    /// Whether the metric was critical on the latest sample
+    critical: bool,
+}
+
+impl PerfHudAlert {
+    /// Create an alert running the registered system `action`
+    pub fn new(metric_id: &str, action: SystemId) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            action,
+            critical: false,
+        }
+    }
+
+    /// Whether the metric was critical on the latest sample
+    pub fn is_critical(&self) -> bool {
+        self.critical
+    }
+}
+
+/// Resource holding the registered alerts.
+#[derive(Resource, Default)]
+pub struct PerfHudAlerts {
+    alerts: Vec<PerfHudAlert>,
+}
+
+impl PerfHudAlerts {
+    /// Register an alert
+    pub fn add(&mut self, alert: PerfHudAlert) {
+        self.alerts.push(alert);
+    }
+
+    /// Iterate over the registered alerts
+    pub fn iter(&self) -> impl Iterator<Item = &PerfHudAlert> {
+        self.alerts.iter()
+    }
+
+    /// Remove all alerts (their systems stay registered)
+    pub fn clear(&mut self) {
+        self.alerts.clear();
+    }
+}
+
+/// Resource holding the budget level of each metric with a budget, as of its
+/// latest fresh sample.
+#[derive(Resource, Default)]
+pub struct MetricBudgetLevels {
+    levels: HashMap<String, BudgetLevel>,
+}
+
+impl MetricBudgetLevels {
+    /// Budget level of a metric ([`BudgetLevel::Ok`] if it has no budget or
+    /// was not sampled yet)
+    pub fn get(&self, metric_id: &str) -> BudgetLevel {
+        self.levels.get(metric_id).copied().unwrap_or_default()
+    }
+}
+
+/// System checking fresh samples against their budgets and sending a
+/// [`MetricBudgetTransition`] for every metric whose level changed, with
+/// [`PerfHudSettings::budget_hysteresis`] applied to recoveries.
+pub fn track_budget_levels(
+    settings: Res<PerfHudSettings>,
+    refresh_rate: Res<DisplayRefreshRate>,
+    samples: Res<SampledValues>,
+    mut levels: ResMut<MetricBudgetLevels>,
+    mut transitions: EventWriter<MetricBudgetTransition>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Alerts);
+    for (metric_id, value) in samples.iter() {
+        if samples.get_fresh(metric_id).is_none() {
+            continue;
+        }
+        let Some(thresholds) = settings.budget_thresholds(metric_id, refresh_rate.hz) else {
+            continue;
+        };
+        // Metric IDs are only allocated the first time a metric is seen
+        let (level, previous) = match levels.levels.get_mut(metric_id) {
+            Some(slot) => {
+                let previous = *slot;
+                *slot =
+                    thresholds.level_with_hysteresis(value, previous, settings.budget_hysteresis);
+                (*slot, previous)
+            }
+            None => {
+                let level = thresholds.level(value);
+                levels.levels.insert(metric_id.to_owned(), level);
+                (level, BudgetLevel::Ok)
+            }
+        };
+        if level == previous {
+            continue;
+        }
+        // The threshold between the two levels that was crossed
+        let threshold = match level.max(previous) {
+            BudgetLevel::Critical => thresholds.critical,
+            _ => thresholds.warning,
+        };
+        if settings.log_budget_transitions {
+            if level > previous {
+                warn!(
+                    metric_id,
+                    value,
+                    threshold,
+                    level = ?level,
+                    "perf HUD: metric over budget"
+                );
+            } else {
+                info!(
+                    metric_id,
+                    value,
+                    threshold,
+                    level = ?level,
+                    "perf HUD: metric back within budget"
+                );
+            }
+        }
+        transitions.write(MetricBudgetTransition {
+            metric_id: metric_id.to_owned(),
+            value,
+            threshold,
+            previous,
+            level,
+        });
+    }
+}
+
+/// System running the action of each alert whose metric just became critical.
+///
+/// An alert fires once when its metric crosses the critical threshold of its
+/// budget and again only after the metric has recovered.
+pub fn run_perf_alerts(
+    mut commands: Commands,
+    mut transitions: EventReader<MetricBudgetTransition>,
+    mut alerts: ResMut<PerfHudAlerts>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Alerts);
+    for transition in transitions.read() {
+        let critical = transition.level == BudgetLevel::Critical;
+        for alert in &mut alerts.alerts {
+            if alert.metric_id != transition.metric_id {
+                continue;
+            }
+            if critical && !alert.critical {
+                warn!(
+                    metric_id = alert.metric_id.as_str(),
+                    value = transition.value,
+                    threshold = transition.threshold,
+                    "perf HUD: alert fired"
+                );
+                commands.run_system(alert.action);
+            }
+            alert.critical = critical;
+        }
+    }
+}
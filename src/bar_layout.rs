@@ -0,0 +1,130 @@
+//! Bar grid layout calculation
+//!
+//! This module packs the configured performance bars into rows, honoring
+//! each bar's column span and width weight, so the spawning system only has
+//! to turn the resulting slots into UI nodes.
+
+use crate::config::BarConfig;
+
+/// A single bar placement inside a row of the bar grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarSlot {
+    /// Index of the bar in `BarsSettings::bars`
+    pub index: usize,
+    /// Width of the bar in pixels
+    pub width: f32,
+}
+
+/// Pack bars into rows of `column_count` columns.
+///
+/// Bars are placed in configuration order. A bar occupies `column_span`
+/// columns (clamped to `1..=column_count`); when it does not fit in the
+/// remaining columns of the current row, a new row is started. The row's
+/// width (`column_width` per occupied column) is then distributed between
+/// its bars proportionally to `column_span * width_weight`, so with default
+/// settings every bar is exactly `column_width` wide.
+pub fn layout_bar_rows(
+    bars: &[BarConfig],
+    column_count: usize,
+    column_width: f32,
+) -> Vec<Vec<BarSlot>> {
+    let column_count = column_count.max(1);
+    let mut rows: Vec<Vec<(usize, usize)>> = Vec::new();
+    let mut used = column_count;
+
+    for (index, bar) in bars.iter().enumerate() {
+        let span = (bar.column_span as usize).clamp(1, column_count);
+        if used + span > column_count {
+            rows.push(Vec::new());
+            used = 0;
+        }
+        used += span;
+        if let Some(row) = rows.last_mut() {
+            row.push((index, span));
+        }
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            let columns: usize = row.iter().map(|&(_, span)| span).sum();
+            let row_width = column_width * columns as f32;
+            let weights: Vec<f32> = row
+                .iter()
+                .map(|&(index, span)| span as f32 * bars[index].width_weight.max(0.0))
+                .collect();
+            let total_weight: f32 = weights.iter().sum();
+
+            row.iter()
+                .zip(weights)
+                .map(|(&(index, span), weight)| BarSlot {
+                    index,
+                    width: if total_weight > 0.0 {
+                        row_width * weight / total_weight
+                    } else {
+                        column_width * span as f32
+                    },
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MetricDefinition;
+    use bevy::{color::Color, prelude::default};
+
+    fn bar(column_span: u32, width_weight: f32) -> BarConfig {
+        BarConfig {
+            metric: MetricDefinition {
+                id: "test".into(),
+                label: None,
+                unit: None,
+                precision: 0,
+                color: Color::WHITE,
+            },
+            max_value: 1.0,
+            column_span,
+            width_weight,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_uniform_grid() {
+        let bars = vec![bar(1, 1.0), bar(1, 1.0), bar(1, 1.0)];
+        let rows = layout_bar_rows(&bars, 2, 100.0);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].len(), 2);
+        assert_eq!(rows[1].len(), 1);
+        assert!(rows.iter().flatten().all(|slot| slot.width == 100.0));
+    }
+
+    #[test]
+    fn test_column_span_starts_new_row() {
+        let bars = vec![bar(1, 1.0), bar(2, 1.0), bar(1, 1.0)];
+        let rows = layout_bar_rows(&bars, 2, 100.0);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[1],
+            vec![BarSlot {
+                index: 1,
+                width: 200.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_width_weight_distribution() {
+        let bars = vec![bar(1, 2.0), bar(1, 1.0), bar(1, 1.0)];
+        let rows = layout_bar_rows(&bars, 3, 100.0);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0].width, 150.0);
+        assert_eq!(rows[0][1].width, 75.0);
+        assert_eq!(rows[0][2].width, 75.0);
+    }
+}
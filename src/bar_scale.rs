@@ -5,10 +5,11 @@
 //! using fixed min/max values.
 
 use crate::config::BarScaleMode;
+use bevy::reflect::Reflect;
 use std::collections::VecDeque;
 
 /// State for tracking dynamic bar scaling
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
 pub struct BarScaleState {
     /// Current minimum value for normalization
     pub current_min: f32,
@@ -0,0 +1,208 @@
+//! Accounting of the HUD's own per-frame cost.
+//!
+//! Each HUD update system times itself into [`HudUpdateCost`]. With
+//! [`PerfHudSettings::budget`](crate::PerfHudSettings) enabled, the total is
+//! checked against the budget every frame, and a HUD that keeps running over
+//! it logs a warning and refreshes its display less often, so the tool never
+//! becomes the problem it is measuring.
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    ecs::system::{Res, ResMut},
+    log::{info, warn},
+    platform::time::Instant,
+    prelude::Resource,
+};
+
+use crate::config::PerfHudSettings;
+
+/// Weight of the newest frame in the running average of the HUD's cost
+const COST_AVERAGE_WEIGHT: f32 = 0.1;
+
+/// Fraction of the budget the average cost must drop below to leave the
+/// degraded mode, so the HUD does not flip between modes near the limit
+const RECOVERY_FRACTION: f32 = 0.8;
+
+/// One of the HUD's timed update systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HudStage {
+    /// Applying [`PerfHudCommand`](crate::PerfHudCommand)s, including rebuilds
+    Commands,
+    /// Filling history from existing diagnostics when the HUD is enabled
+    Backfill,
+    /// Checking for abnormally long frames to exclude
+    LongFrames,
+    /// Logging slow frames
+    SlowFrames,
+    /// Sampling the metric providers
+    Sample,
+    /// Updating windowed metric statistics
+    Stats,
+    /// Checking screenshot triggers
+    Screenshots,
+    /// Checking alerts and queuing their actions
+    Alerts,
+    /// Aggregating samples into telemetry ticks
+    Telemetry,
+    /// Recording samples for Chrome trace export
+    Trace,
+    /// Recording provider samples or replaying a recording
+    Replay,
+    /// Checking entity count and memory trends for leaks
+    Leaks,
+    /// Folding samples into the graph history and trend
+    Capture,
+    /// Updating graph curves, bars and labels
+    Display,
+    /// Updating the percentile statistics panel
+    StatsPanel,
+    /// Updating the traffic light indicator
+    TrafficLight,
+    /// Updating the slow frame list
+    SlowFramePanel,
+    /// Reporting capacity usage and settings that exceed it
+    Capacity,
+    /// Dragging the HUD with the mouse
+    Drag,
+    /// Zooming the paused graph into a time range
+    Zoom,
+    /// Updating the graph tooltip under the pointer
+    Tooltip,
+    /// Keeping the HUD at its anchor, or moving it out of the way of the
+    /// cursor or other UI
+    Placement,
+    /// Animating rebuild cross-fades
+    Fades,
+}
+
+/// Resource holding the time each HUD update system took.
+#[derive(Resource, Default)]
+pub struct HudUpdateCost {
+    /// Stages timed so far during the current frame
+    current: HashMap<HudStage, Duration>,
+    /// Stages timed during the last completed frame
+    last_frame: HashMap<HudStage, Duration>,
+}
+
+impl HudUpdateCost {
+    /// Time spent in a stage during the last frame (None if it did not run)
+    pub fn get(&self, stage: HudStage) -> Option<Duration> {
+        self.last_frame.get(&stage).copied()
+    }
+
+    /// Iterate over the stages that ran during the last frame and their cost
+    pub fn iter(&self) -> impl Iterator<Item = (HudStage, Duration)> + '_ {
+        self.last_frame.iter().map(|(stage, cost)| (*stage, *cost))
+    }
+
+    /// Total time spent in HUD update systems during the last frame
+    pub fn total(&self) -> Duration {
+        self.last_frame.values().sum()
+    }
+
+    /// Time a stage until the returned guard is dropped
+    pub(crate) fn time(&mut self, stage: HudStage) -> StageTimer<'_> {
+        StageTimer {
+            cost: self,
+            stage,
+            started: Instant::now(),
+        }
+    }
+
+    /// Close the current frame, returning its total cost
+    fn finish_frame(&mut self) -> Duration {
+        std::mem::swap(&mut self.current, &mut self.last_frame);
+        self.current.clear();
+        self.total()
+    }
+}
+
+/// Guard adding the time until it is dropped to a stage's cost.
+pub(crate) struct StageTimer<'a> {
+    cost: &'a mut HudUpdateCost,
+    stage: HudStage,
+    started: Instant,
+}
+
+impl Drop for StageTimer<'_> {
+    fn drop(&mut self) {
+        *self.cost.current.entry(self.stage).or_default() += self.started.elapsed();
+    }
+}
+
+/// Resource tracking the HUD's cost against its budget.
+#[derive(Resource, Default)]
+pub struct HudBudgetState {
+    /// Running average of the cost of a frame that refreshes the display
+    pub average: Duration,
+    /// Whether the average is over the budget
+    pub over_budget: bool,
+    /// Whether the display is refreshed less often to get back within budget
+    pub degraded: bool,
+    /// Frames checked so far, used to space out degraded refreshes
+    frame: u64,
+}
+
+/// System checking the HUD's cost for the frame against its budget.
+///
+/// Runs after all other HUD update systems.
+pub fn check_hud_budget(
+    settings: Res<PerfHudSettings>,
+    mut cost: ResMut<HudUpdateCost>,
+    mut state: ResMut<HudBudgetState>,
+) {
+    let refreshed = cost.current.contains_key(&HudStage::Display);
+    let total = cost.finish_frame();
+    state.frame += 1;
+
+    let budget = &settings.budget;
+    if !budget.enabled {
+        state.over_budget = false;
+        state.degraded = false;
+        return;
+    }
+
+    // Frames skipped while degraded say nothing about the cost of a full refresh
+    if refreshed || !state.degraded {
+        state.average = if state.average.is_zero() {
+            total
+        } else {
+            state.average.mul_f32(1.0 - COST_AVERAGE_WEIGHT) + total.mul_f32(COST_AVERAGE_WEIGHT)
+        };
+    }
+
+    let average_ms = state.average.as_secs_f64() * 1000.0;
+    let limit_ms = budget.limit.as_secs_f64() * 1000.0;
+    if !state.over_budget && state.average > budget.limit {
+        state.over_budget = true;
+        if budget.degrade {
+            warn!(
+                "perf HUD update takes {average_ms:.3} ms per frame, over its {limit_ms:.3} ms budget; refreshing the display every {} frames",
+                budget.degraded_interval.max(1)
+            );
+        } else {
+            warn!("perf HUD update takes {average_ms:.3} ms per frame, over its {limit_ms:.3} ms budget");
+        }
+    } else if state.over_budget && state.average < budget.limit.mul_f32(RECOVERY_FRACTION) {
+        state.over_budget = false;
+        info!("perf HUD update is back within its {limit_ms:.3} ms budget");
+    }
+    state.degraded = state.over_budget && budget.degrade;
+}
+
+/// Run condition: the display is due for a refresh this frame.
+///
+/// Always true unless the HUD is degraded to stay within its budget, or has
+/// no settings yet (run conditions are all evaluated, even past a false one).
+pub fn display_refresh_due(
+    settings: Option<Res<PerfHudSettings>>,
+    state: Res<HudBudgetState>,
+) -> bool {
+    settings.is_some_and(|settings| {
+        !state.degraded
+            || state
+                .frame
+                .is_multiple_of(u64::from(settings.budget.degraded_interval.max(1)))
+    })
+}
@@ -0,0 +1,86 @@
+//! Build and session identification for captured performance data.
+//!
+//! [`BuildInfo`] is shown optionally in the HUD and embedded in every
+//! [`PerfHudSnapshot`](crate::PerfHudSnapshot), so numbers captured from a
+//! running app can be traced back to the build that produced them.
+
+use bevy::prelude::Resource;
+use uuid::Uuid;
+
+/// Resource identifying the running build and session.
+///
+/// The plugin inserts a default instance (random session ID, no version or
+/// git hash). Insert your own before adding the plugin to fill in the rest.
+///
+/// # Example
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::{BevyPerfHudPlugin, BuildInfo};
+///
+/// App::new()
+///     .insert_resource(
+///         BuildInfo::new(env!("CARGO_PKG_VERSION"))
+///             .with_git_hash(option_env!("GIT_HASH").unwrap_or("unknown")),
+///     )
+///     .add_plugins(BevyPerfHudPlugin);
+/// ```
+#[derive(Debug, Clone, PartialEq, Resource)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildInfo {
+    /// Version of the application (e.g. `CARGO_PKG_VERSION`)
+    pub app_version: String,
+    /// Git commit hash the application was built from, if known
+    pub git_hash: Option<String>,
+    /// Build profile, `"debug"` or `"release"`
+    pub profile: String,
+    /// Random ID generated once per app run
+    pub session_id: Uuid,
+}
+
+impl Default for BuildInfo {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl BuildInfo {
+    /// Create build info for the given app version with a fresh session ID.
+    ///
+    /// The profile is derived from whether debug assertions are enabled.
+    pub fn new(app_version: impl Into<String>) -> Self {
+        Self {
+            app_version: app_version.into(),
+            git_hash: None,
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            }
+            .to_owned(),
+            session_id: Uuid::new_v4(),
+        }
+    }
+
+    /// Set the git commit hash
+    pub fn with_git_hash(mut self, git_hash: impl Into<String>) -> Self {
+        self.git_hash = Some(git_hash.into());
+        self
+    }
+
+    /// One-line summary shown in the HUD, e.g. `v1.2.0 3f9c2a1 release #5d1e7b20`.
+    ///
+    /// The git hash and session ID are shortened; empty parts are omitted.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::with_capacity(4);
+        if !self.app_version.is_empty() {
+            parts.push(format!("v{}", self.app_version));
+        }
+        if let Some(hash) = &self.git_hash {
+            parts.push(hash.chars().take(7).collect());
+        }
+        parts.push(self.profile.clone());
+        let session = self.session_id.simple().to_string();
+        parts.push(format!("#{}", &session[..8]));
+        parts.join(" ")
+    }
+}
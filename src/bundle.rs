@@ -0,0 +1,199 @@
+//! Bug report bundles.
+//!
+//! [`PerfHudCommand::ExportBundle`](crate::PerfHudCommand) writes everything
+//! worth attaching to a bug ticket into one folder: the raw metric samples as
+//! CSV, a log of the samples past their critical budget, the HUD settings as
+//! RON (with the `serde` feature), the build info and a screenshot of the
+//! primary window.
+
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::{query::With, world::World},
+    log::warn,
+    prelude::Event,
+    render::view::screenshot::{save_to_disk, Screenshot},
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{
+    build_info::BuildInfo,
+    config::{BudgetLevel, PerfHudSettings},
+    constants::MAX_CURVES,
+    replay::SampleRecorder,
+    resources::{DisplayRefreshRate, HistoryBuffers},
+    slow_frames::SlowFrameLog,
+};
+
+/// File of the raw metric samples: the running sample recording if there is
+/// one, else the range of raw values behind each graph sample
+pub const BUNDLE_METRICS_FILE: &str = "metrics.csv";
+
+/// File listing the graph samples past their critical budget
+pub const BUNDLE_SPIKES_FILE: &str = "spikes.log";
+
+/// File with the HUD settings the bundle was exported with, as RON (only
+/// written with the `serde` feature)
+pub const BUNDLE_SETTINGS_FILE: &str = "settings.ron";
+
+/// File identifying the build and session
+pub const BUNDLE_BUILD_INFO_FILE: &str = "build_info.txt";
+
+/// Screenshot of the primary window
+pub const BUNDLE_SCREENSHOT_FILE: &str = "screenshot.png";
+
+/// Sent once a bug report bundle has been written.
+///
+/// The screenshot is saved asynchronously once the next frame is rendered,
+/// so it may appear in the folder shortly after this event.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PerfHudBundleExported {
+    /// Folder the bundle was written to
+    pub directory: PathBuf,
+    /// Files of the bundle, the screenshot included if one was requested
+    pub files: Vec<PathBuf>,
+}
+
+/// Write a bug report bundle to `directory` and send [`PerfHudBundleExported`].
+///
+/// Logs a warning instead if a file cannot be written.
+pub(crate) fn export_bundle(world: &mut World, directory: &Path) {
+    match write_bundle(world, directory) {
+        Ok(files) => {
+            world.send_event(PerfHudBundleExported {
+                directory: directory.to_owned(),
+                files,
+            });
+        }
+        Err(err) => warn!("perf HUD: cannot export bundle to {directory:?}: {err}"),
+    }
+}
+
+fn write_bundle(world: &mut World, directory: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+    let mut files = Vec::with_capacity(5);
+    let mut write = |name: &str, contents: String| {
+        let path = directory.join(name);
+        fs::write(&path, contents)?;
+        files.push(path);
+        io::Result::Ok(())
+    };
+
+    if let (Some(settings), Some(history)) = (
+        world.get_resource::<PerfHudSettings>(),
+        world.get_resource::<HistoryBuffers>(),
+    ) {
+        let display_hz = world
+            .get_resource::<DisplayRefreshRate>()
+            .and_then(|rate| rate.hz);
+        let metrics = match world.get_resource::<SampleRecorder>() {
+            Some(recorder) => recorder.recording.to_csv(),
+            None => metrics_csv(settings, history),
+        };
+        write(BUNDLE_METRICS_FILE, metrics)?;
+        let slow_frames = world.get_resource::<SlowFrameLog>();
+        write(
+            BUNDLE_SPIKES_FILE,
+            spike_log(settings, history, display_hz, slow_frames),
+        )?;
+        #[cfg(feature = "serde")]
+        write(
+            BUNDLE_SETTINGS_FILE,
+            ron::ser::to_string_pretty(settings, Default::default()).map_err(io::Error::other)?,
+        )?;
+    }
+    if let Some(build_info) = world.get_resource::<BuildInfo>() {
+        write(
+            BUNDLE_BUILD_INFO_FILE,
+            format!(
+                "app_version: {}\ngit_hash: {}\nprofile: {}\nsession_id: {}\n",
+                build_info.app_version,
+                build_info.git_hash.as_deref().unwrap_or("unknown"),
+                build_info.profile,
+                build_info.session_id
+            ),
+        )?;
+    }
+
+    let has_window = world
+        .query_filtered::<(), (With<Window>, With<PrimaryWindow>)>()
+        .iter(world)
+        .next()
+        .is_some();
+    if has_window {
+        let path = directory.join(BUNDLE_SCREENSHOT_FILE);
+        world
+            .spawn(Screenshot::primary_window())
+            .observe(save_to_disk(path.clone()));
+        files.push(path);
+    }
+    Ok(files)
+}
+
+/// Raw graph samples as CSV: the frame each sample was completed on, then the
+/// lowest and highest raw value aggregated into it for each curve (empty for
+/// missing samples). Unlike the plotted values, these are not smoothed.
+fn metrics_csv(settings: &PerfHudSettings, history: &HistoryBuffers) -> String {
+    let curves = &settings.graph.curves[..settings.graph.curves.len().min(MAX_CURVES)];
+    let mut csv = String::from("frame");
+    for curve in curves {
+        let id = &curve.metric.id;
+        let _ = write!(csv, ",{id}.min,{id}.max");
+    }
+    csv.push('\n');
+    for sample in 0..history.length as usize {
+        let _ = write!(csv, "{}", history.frames[sample]);
+        for index in 0..curves.len() {
+            for value in [history.mins[index][sample], history.maxs[index][sample]] {
+                csv.push(',');
+                if value.is_finite() {
+                    let _ = write!(csv, "{value}");
+                }
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// One line per graph sample whose worst raw value is past its curve's
+/// critical budget, then the logged slow frames
+fn spike_log(
+    settings: &PerfHudSettings,
+    history: &HistoryBuffers,
+    display_hz: Option<f32>,
+    slow_frames: Option<&SlowFrameLog>,
+) -> String {
+    let mut log = String::new();
+    for sample in 0..history.length as usize {
+        for (index, curve) in settings.graph.curves.iter().take(MAX_CURVES).enumerate() {
+            let id = &curve.metric.id;
+            let Some(thresholds) = settings.budget_thresholds(id, display_hz) else {
+                continue;
+            };
+            let value = if thresholds.lower_is_worse {
+                history.mins[index][sample]
+            } else {
+                history.maxs[index][sample]
+            };
+            if value.is_finite() && thresholds.level(value) == BudgetLevel::Critical {
+                let _ = writeln!(
+                    log,
+                    "frame {}: {id} = {value} (critical {})",
+                    history.frames[sample], thresholds.critical
+                );
+            }
+        }
+    }
+    if log.is_empty() {
+        log.push_str("no samples past their critical budget\n");
+    }
+    for slow_frame in slow_frames.into_iter().flat_map(SlowFrameLog::iter) {
+        let _ = writeln!(log, "slow frame {}: {slow_frame}", slow_frame.frame);
+    }
+    log
+}
@@ -0,0 +1,172 @@
+//! Introspection of the HUD's fixed capacities.
+//!
+//! The graph and bar shaders have compile-time limits ([`MAX_CURVES`],
+//! [`MAX_BARS`], [`MAX_SAMPLES`]). [`HudCapacity`] reports how much of each the
+//! current HUD uses, and a [`PerfHudCapacityExceeded`] event (plus a warning in
+//! the log) is sent when the settings ask for more than fits, instead of the
+//! extra curves or bars silently disappearing.
+
+use bevy::{
+    ecs::{
+        event::EventWriter,
+        system::{Res, ResMut},
+    },
+    log::warn,
+    prelude::{DetectChanges, Event, Resource},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::{GraphHistory, PerfHudSettings},
+    constants::{MAX_BARS, MAX_CURVES, MAX_SAMPLES},
+    resources::{HistoryBuffers, HudHandles},
+};
+
+/// One of the HUD's fixed capacities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HudCapacityKind {
+    /// Graph curves, limited to [`MAX_CURVES`]
+    Curves,
+    /// Bars in the bar grid, limited to [`MAX_BARS`]
+    Bars,
+    /// Samples of live graph history, limited to [`MAX_SAMPLES`]
+    Samples,
+}
+
+impl HudCapacityKind {
+    /// All capacities
+    pub const ALL: [Self; 3] = [Self::Curves, Self::Bars, Self::Samples];
+
+    /// Largest amount that fits
+    pub fn max(self) -> usize {
+        match self {
+            Self::Curves => MAX_CURVES,
+            Self::Bars => MAX_BARS,
+            Self::Samples => MAX_SAMPLES,
+        }
+    }
+
+    /// Name of what is counted, for log messages
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Curves => "graph curves",
+            Self::Bars => "bars",
+            Self::Samples => "history samples",
+        }
+    }
+
+    /// Amount the settings ask for (None if they cannot exceed the capacity)
+    fn requested(self, settings: &PerfHudSettings) -> Option<usize> {
+        match self {
+            Self::Curves => Some(settings.graph.curves.len()),
+            Self::Bars => Some(settings.bars.bars.len()),
+            Self::Samples => match settings.graph.history {
+                GraphHistory::Samples(samples) => Some(samples as usize),
+                GraphHistory::Full | GraphHistory::Seconds(_) => None,
+            },
+        }
+    }
+}
+
+/// Used and available amount of one capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CapacityUsage {
+    /// Amount currently in use
+    pub used: usize,
+    /// Amount available
+    pub max: usize,
+}
+
+/// Resource reporting how much of its capacities the HUD uses.
+#[derive(Resource, Debug, Clone, PartialEq, Default)]
+pub struct HudCapacity {
+    /// Curves drawn by the graph, out of [`MAX_CURVES`]
+    pub curves: CapacityUsage,
+    /// Bars drawn in the bar grid, out of [`MAX_BARS`]
+    pub bars: CapacityUsage,
+    /// Samples in the live graph history, out of the configured history window
+    pub samples: CapacityUsage,
+    /// Materials the HUD currently owns (graph, bar grid and per-core bars)
+    pub materials: usize,
+    /// Requested amount last reported as over each capacity, in
+    /// [`HudCapacityKind::ALL`] order, so a warning is not repeated
+    reported: [Option<usize>; 3],
+}
+
+impl HudCapacity {
+    /// Usage of the given capacity
+    pub fn get(&self, kind: HudCapacityKind) -> CapacityUsage {
+        match kind {
+            HudCapacityKind::Curves => self.curves,
+            HudCapacityKind::Bars => self.bars,
+            HudCapacityKind::Samples => self.samples,
+        }
+    }
+}
+
+/// Sent when the settings ask for more than a capacity of the HUD allows.
+///
+/// The amount over the capacity is not shown. Sent once per setting change
+/// that leads to a new requested amount.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PerfHudCapacityExceeded {
+    /// Capacity that was exceeded
+    pub kind: HudCapacityKind,
+    /// Amount the settings ask for
+    pub requested: usize,
+    /// Largest amount that fits
+    pub max: usize,
+}
+
+/// System updating [`HudCapacity`] and reporting settings that exceed it.
+pub fn update_hud_capacity(
+    settings: Res<PerfHudSettings>,
+    handles: Option<Res<HudHandles>>,
+    history: Res<HistoryBuffers>,
+    mut capacity: ResMut<HudCapacity>,
+    mut exceeded: EventWriter<PerfHudCapacityExceeded>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Capacity);
+
+    if settings.is_changed() {
+        for (slot, kind) in HudCapacityKind::ALL.into_iter().enumerate() {
+            let max = kind.max();
+            let requested = kind.requested(&settings).filter(|&n| n > max);
+            if let Some(requested) = requested.filter(|&n| capacity.reported[slot] != Some(n)) {
+                warn!(
+                    "perf HUD settings ask for {requested} {} but only {max} fit; the rest are not shown",
+                    kind.noun()
+                );
+                exceeded.write(PerfHudCapacityExceeded {
+                    kind,
+                    requested,
+                    max,
+                });
+            }
+            capacity.reported[slot] = requested;
+        }
+        capacity.curves = CapacityUsage {
+            used: settings.graph.curves.len().min(MAX_CURVES),
+            max: MAX_CURVES,
+        };
+        capacity.bars = CapacityUsage {
+            used: settings.bars.bars.len().min(MAX_BARS),
+            max: MAX_BARS,
+        };
+    }
+
+    let samples = CapacityUsage {
+        used: history.length as usize,
+        max: history.capacity as usize,
+    };
+    let materials = handles.map_or(0, |h| {
+        usize::from(h.graph_material.is_some())
+            + usize::from(h.bars_material.is_some())
+            + h.cpu_core_bars.len()
+    });
+    if capacity.samples != samples || capacity.materials != materials {
+        capacity.samples = samples;
+        capacity.materials = materials;
+    }
+}
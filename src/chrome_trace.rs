@@ -0,0 +1,197 @@
+//! Metric samples as Chrome trace counters.
+//!
+//! With [`PerfHudSettings::chrome_trace`](crate::PerfHudSettings) enabled,
+//! every fresh metric sample is recorded with its timestamp.
+//! [`PerfHudCommand::ExportChromeTrace`](crate::PerfHudCommand) writes the
+//! recording as counter events in the Chrome trace (catapult JSON) format, so
+//! it can be loaded next to a `bevy/trace_chrome` capture in Perfetto or
+//! `chrome://tracing` and correlated with its spans.
+
+use std::{collections::VecDeque, fmt::Write as _, fs, path::Path};
+
+use bevy::{
+    ecs::system::{Res, ResMut},
+    log::{info, warn},
+    prelude::Resource,
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    resources::SampledValues,
+};
+
+/// Process ID written to the trace, the one `bevy/trace_chrome` uses, so the
+/// counters show up in the same process as the spans
+const TRACE_PID: u32 = 1;
+
+/// One recorded metric sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TraceSample {
+    /// Real time since startup in microseconds
+    time_us: f64,
+    /// Index of the metric ID in [`ChromeTraceRecorder::metric_ids`]
+    metric: u32,
+    value: f32,
+}
+
+/// Resource recording metric samples for Chrome trace export.
+///
+/// Keeps the latest [`ChromeTraceSettings::capacity`](crate::ChromeTraceSettings)
+/// samples; older ones are dropped.
+#[derive(Resource, Default)]
+pub struct ChromeTraceRecorder {
+    /// IDs of the metrics recorded so far, referenced by index from the samples
+    metric_ids: Vec<String>,
+    samples: VecDeque<TraceSample>,
+}
+
+impl ChromeTraceRecorder {
+    /// Number of samples recorded
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether no sample is recorded
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Drop all recorded samples
+    pub fn clear(&mut self) {
+        self.metric_ids.clear();
+        self.samples.clear();
+    }
+
+    /// Record `value` of `metric_id` at `time_us`, keeping at most `capacity` samples
+    pub fn record(&mut self, metric_id: &str, time_us: f64, value: f32, capacity: usize) {
+        let metric = match self.metric_ids.iter().position(|id| id == metric_id) {
+            Some(index) => index,
+            None => {
+                self.metric_ids.push(metric_id.to_owned());
+                self.metric_ids.len() - 1
+            }
+        } as u32;
+        while self.samples.len() >= capacity.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(TraceSample {
+            time_us,
+            metric,
+            value,
+        });
+    }
+
+    /// The recording as a Chrome trace JSON document of counter events
+    pub fn to_chrome_trace(&self) -> String {
+        let mut json = String::with_capacity(64 + self.samples.len() * 80);
+        json.push_str("{\"traceEvents\":[");
+        let _ = write!(
+            json,
+            "{{\"name\":\"process_name\",\"ph\":\"M\",\"pid\":{TRACE_PID},\"tid\":0,\"args\":{{\"name\":\"perf HUD\"}}}}"
+        );
+        for sample in &self.samples {
+            json.push_str(",{\"name\":");
+            push_json_string(&mut json, &self.metric_ids[sample.metric as usize]);
+            let _ = write!(
+                json,
+                ",\"cat\":\"perf_hud\",\"ph\":\"C\",\"ts\":{:.3},\"pid\":{TRACE_PID},\"tid\":0,\"args\":{{\"value\":{}}}}}",
+                sample.time_us, sample.value
+            );
+        }
+        json.push_str("]}\n");
+        json
+    }
+}
+
+/// Append `text` as a JSON string literal
+fn push_json_string(json: &mut String, text: &str) {
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// System recording the fresh sample of every metric selected for tracing.
+pub fn record_chrome_trace(
+    settings: Res<PerfHudSettings>,
+    samples: Res<SampledValues>,
+    time: Res<Time<Real>>,
+    mut recorder: ResMut<ChromeTraceRecorder>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Trace);
+    let trace = &settings.chrome_trace;
+    if !trace.enabled {
+        if !recorder.is_empty() {
+            recorder.clear();
+        }
+        return;
+    }
+
+    let time_us = time.elapsed_secs_f64() * 1_000_000.0;
+    for (id, _) in samples.iter() {
+        if !trace.metrics.is_empty() && !trace.metrics.iter().any(|metric| metric == id) {
+            continue;
+        }
+        if let Some(value) = samples.get_fresh(id).filter(|value| value.is_finite()) {
+            recorder.record(id, time_us, value, trace.capacity);
+        }
+    }
+}
+
+/// Write the recorded samples to `path` as a Chrome trace.
+pub(crate) fn export_chrome_trace(recorder: &ChromeTraceRecorder, path: &Path) {
+    let written = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, recorder.to_chrome_trace()));
+    match written {
+        Ok(()) => info!(
+            "perf HUD: wrote {} metric samples to {path:?}",
+            recorder.len()
+        ),
+        Err(err) => warn!("perf HUD: cannot write Chrome trace to {path:?}: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_events_in_recording_order() {
+        let mut recorder = ChromeTraceRecorder::default();
+        recorder.record("fps", 1000.0, 60.0, 8);
+        recorder.record("a\"b", 2000.5, 1.5, 8);
+
+        let json = recorder.to_chrome_trace();
+        assert!(json.starts_with("{\"traceEvents\":[{\"name\":\"process_name\""));
+        assert!(json.contains(
+            "{\"name\":\"fps\",\"cat\":\"perf_hud\",\"ph\":\"C\",\"ts\":1000.000,\"pid\":1,\"tid\":0,\"args\":{\"value\":60}}"
+        ));
+        assert!(json
+            .contains("{\"name\":\"a\\\"b\",\"cat\":\"perf_hud\",\"ph\":\"C\",\"ts\":2000.500,"));
+        assert!(json.ends_with("]}\n"));
+    }
+
+    #[test]
+    fn test_recording_keeps_latest_samples() {
+        let mut recorder = ChromeTraceRecorder::default();
+        for i in 0..5 {
+            recorder.record("fps", i as f64, i as f32, 3);
+        }
+        assert_eq!(recorder.len(), 3);
+        assert_eq!(recorder.samples.front().map(|s| s.value), Some(2.0));
+    }
+}
@@ -0,0 +1,32 @@
+//! Bevy version compatibility layer.
+//!
+//! The crate targets Bevy 0.16 only; there are no per-version cargo features.
+//! HUD code goes through these helpers for the entity hierarchy APIs, which
+//! changed shape the most between recent releases (`ChildOf` replacing
+//! `Parent`, recursive despawn by default), so porting the hierarchy code to
+//! another Bevy version only touches this module. Other version-sensitive
+//! APIs are called directly and need porting at their call sites: `Query::single` returning `Result`
+//! (0.16, `get_single` before), `EventWriter::write` (0.16, `send` before),
+//! `TextShadow` (0.16+), `MaterialNode`/`UiMaterial` (0.15+) and
+//! `Query<&Children>::iter_descendants` (0.16, `HierarchyQueryExt`).
+
+use bevy::ecs::{
+    entity::Entity,
+    hierarchy::{ChildOf, Children},
+    system::Commands,
+};
+
+/// Attach `child` to `parent` in the UI hierarchy.
+pub(crate) fn set_parent(commands: &mut Commands, child: Entity, parent: Entity) {
+    commands.entity(child).insert(ChildOf(parent));
+}
+
+/// Despawn `entity` and all of its descendants, ignoring missing entities.
+pub(crate) fn despawn_tree(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).try_despawn();
+}
+
+/// Despawn all descendants of `entity`, keeping `entity` itself.
+pub(crate) fn despawn_descendants(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).despawn_related::<Children>();
+}
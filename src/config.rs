@@ -2,8 +2,19 @@
 //!
 //! This module contains all configuration types for customizing the performance HUD.
 
+use std::{path::PathBuf, time::Duration};
+
 use crate::constants::*;
-use bevy::{color::Color, math::Vec2, prelude::Resource};
+#[cfg(feature = "hud")]
+use crate::render::MultiLineGraphParams;
+use bevy::{
+    color::{Alpha, Color},
+    ecs::entity::Entity,
+    math::{curve::EaseFunction, Rect, Vec2},
+    prelude::{default, ReflectResource, Resource},
+    reflect::Reflect,
+    ui::Val,
+};
 
 /// Main configuration resource for the performance HUD.
 ///
@@ -13,28 +24,111 @@ use bevy::{color::Color, math::Vec2, prelude::Resource};
 /// # Example
 /// ```rust
 /// use bevy::prelude::*;
-/// use bevy_perf_hud::PerfHudSettings;
+/// use bevy_perf_hud::{HudAnchor, PerfHudSettings};
 ///
 /// App::new()
 ///     .insert_resource(PerfHudSettings {
-///         origin: Vec2::new(10.0, 10.0), // Top-left corner
+///         anchor: HudAnchor::TopLeft,
+///         origin: Vec2::new(10.0, 10.0), // 10 px from the top-left corner
 ///         ..default()
 ///     })
 ///     .run();
 /// ```
-#[derive(Debug, Resource)]
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PerfHudSettings {
-    /// Whether the HUD is currently enabled and visible
+    /// Whether the HUD is currently enabled and visible; while disabled, its
+    /// metrics are neither sampled nor recorded
     pub enabled: bool,
-    /// Screen position (in pixels) where the HUD should be anchored
+    /// Offset in pixels of the HUD from its `anchor`, measured inwards from
+    /// the anchored window edges
     pub origin: Vec2,
+    /// Window corner (or center) the HUD is positioned relative to
+    pub anchor: HudAnchor,
+    /// How the HUD is positioned; smart modes override `origin` at runtime
+    pub placement: HudPlacement,
+    /// UI node to embed the HUD in, e.g. a debug menu panel (None = the HUD
+    /// gets its own absolutely positioned root and UI camera). When set,
+    /// `origin` and `placement` are ignored and the parent's layout applies.
+    pub parent: Option<Entity>,
     /// Configuration for the performance graph display
     pub graph: GraphSettings,
     /// Configuration for the performance bars display
     pub bars: BarsSettings,
+    /// Configuration for the per-core CPU usage strip
+    pub cpu_cores: CpuCoresSettings,
+    /// Automatic screenshots when metrics cross critical thresholds
+    pub screenshots: ScreenshotSettings,
+    /// Optional shadow drawn behind all HUD text (None = no shadow)
+    pub text_shadow: Option<HudTextShadow>,
+    /// Optional background chip behind each HUD label (None = no chips)
+    pub text_background: Option<HudTextBackground>,
+    /// Whether to show the [`BuildInfo`](crate::BuildInfo) summary below the HUD
+    pub show_build_info: bool,
+    /// Whether to show a row of control buttons below the HUD: pause, start
+    /// and stop a sample recording, export a bug report, cycle the presets
+    /// and rebuild
+    pub show_controls: bool,
+    /// Files written by the control buttons
+    pub controls: ControlStripSettings,
+    /// Preset these settings were made from (None = custom), the starting
+    /// point of [`PerfHudCommand::CyclePreset`](crate::PerfHudCommand)
+    pub preset: Option<HudPreset>,
+    /// HUD elements that capture pointer input; everything else lets it
+    /// through to the game UI and scene below
+    pub interaction: HudInteraction,
+    /// Whether to log a warning when a metric provider panics or stops returning values
+    pub log_provider_errors: bool,
+    /// Show raw values with instant scaling, for benchmarking readouts:
+    /// disables curve smoothing and quantization, Y-axis step quantization
+    /// and easing, auto bar range smoothing and bar color transitions
+    pub instant: bool,
+    /// Duration of the cross-fade from the old HUD to the new one when it is
+    /// rebuilt, e.g. after switching presets (zero = swap instantly)
+    pub transition: Duration,
+    /// Limit on the HUD's own per-frame update cost
+    pub budget: HudBudgetSettings,
+    /// Per-metric budgets; curves, bars and labels of a metric over its
+    /// budget switch to the warning or critical color
+    pub metric_budgets: Vec<MetricBudget>,
+    /// Colors of metrics over their budget
+    pub budget_colors: BudgetColors,
+    /// Whether to log metrics going over their budget (as warnings) and
+    /// recovering (as info)
+    pub log_budget_transitions: bool,
+    /// Fraction of a threshold a metric must recover past it before its
+    /// budget level improves, so a metric sitting on a threshold does not
+    /// flap between levels (0.0 = none)
+    pub budget_hysteresis: f32,
+    /// Per-metric label filters against flickering digits; labels of other
+    /// metrics show the latest value every frame
+    pub label_filters: Vec<LabelFilter>,
+    /// Frame rate the `fps` and `frame_time_ms` budgets derive from, unless
+    /// `metric_budgets` sets their budgets explicitly (off by default)
+    pub fps_target: FpsTarget,
+    /// Periodic [`TelemetryTick`](crate::TelemetryTick) events for external ingestion
+    pub telemetry: TelemetrySettings,
+    /// Recording of metric samples for Chrome trace export
+    pub chrome_trace: ChromeTraceSettings,
+    /// Limits of sample recordings started with
+    /// [`PerfHudCommand::StartSampleRecording`](crate::PerfHudCommand)
+    pub sample_recording: SampleRecordingSettings,
+    /// Heuristic warning when entity count and process memory keep rising
+    pub leak_detection: LeakDetectionSettings,
+    /// Exclusion of abnormally long frames, e.g. debugger breaks
+    pub long_frames: LongFrameSettings,
+    /// Percentile statistics of selected metrics shown under the graph
+    pub stats_panel: StatsPanelSettings,
+    /// Single colored indicator of frame time health above the graph
+    pub traffic_light: TrafficLightSettings,
+    /// List of the latest slow frames with the time they happened
+    pub slow_frames: SlowFrameSettings,
 }
 
 impl Default for PerfHudSettings {
+    #[allow(deprecated)]
     fn default() -> Self {
         let frame_metric = MetricDefinition {
             id: "frame_time_ms".into(),
@@ -74,33 +168,58 @@ impl Default for PerfHudSettings {
 
         Self {
             enabled: true,
-            origin: Vec2::new(960.0, 16.0),
+            origin: Vec2::new(16.0, 16.0),
+            anchor: HudAnchor::TopLeft,
+            placement: HudPlacement::Fixed,
+            parent: None,
             graph: GraphSettings {
                 enabled: true,
                 size: Vec2::new(300.0, 80.0),
-                label_width: 60.0,
+                label_column: LabelColumn::Left(60.0),
+                y_scale: GraphYScale::Linear,
                 min_y: 0.0,
                 max_y: 30.0,
                 thickness: 0.012,
+                thickness_px: Some(2.0),
                 curves: vec![
                     CurveConfig {
                         metric: frame_metric.clone(),
                         autoscale: None,
                         smoothing: Some(0.25),
                         quantize_step: Some(0.1),
+                        candlestick: false,
+                        style: CurveStyle::Solid,
+                        line_smoothing: LineSmoothing::Linear,
+                        precision: None,
+                        group: None,
+                        // Keep frame time on top of the FPS curve
+                        z_index: 1,
+                        shading: None,
+                        enabled: true,
                     },
                     CurveConfig {
                         metric: fps_metric.clone(),
                         autoscale: None,
                         smoothing: None,
                         quantize_step: None,
+                        candlestick: false,
+                        style: CurveStyle::Solid,
+                        line_smoothing: LineSmoothing::Linear,
+                        precision: None,
+                        group: None,
+                        z_index: 0,
+                        shading: None,
+                        enabled: true,
                     },
                 ],
                 curve_defaults: CurveDefaults {
                     autoscale: true,
                     smoothing: 0.2,
                     quantize_step: 1.0,
+                    shading: None,
                 },
+                auto_curves: None,
+                size_percent: None,
                 bg_color: Color::srgba(0.0, 0.0, 0.0, 0.25),
                 border: GraphBorder {
                     color: Color::srgba(1.0, 1.0, 1.0, 1.0),
@@ -111,16 +230,39 @@ impl Default for PerfHudSettings {
                     top: false,
                 },
                 y_ticks: 2,
+                y_tick_labels: true,
+                grid_color: Color::srgba(1.0, 1.0, 1.0, 0.12),
                 y_include_zero: true,
                 y_min_span: 5.0,
                 y_margin_frac: 0.10,
                 y_step_quantize: 5.0,
                 y_scale_smoothing: 0.3,
+                scale_easing: ScaleEasing::Lerp,
+                samples_per_point: 1,
+                history: GraphHistory::Full,
+                x_axis: GraphXAxis::Time,
+                time_axis_labels: false,
+                quality: GraphQuality::High,
+                hidden_groups: Vec::new(),
+                autoscale_per_group: false,
+                missing_data: MissingData::Hold,
+                warmup_samples: 30,
+                view: GraphView::Live,
+                // 256 trend samples cover about ten minutes
+                trend_interval: Duration::from_millis(2500),
+                title: None,
+                caption: None,
+                caption_style: GraphCaptionStyle::default(),
+                show_budget_lines: true,
+                shared_unit_axis: true,
             },
             bars: BarsSettings {
                 enabled: true,
                 bg_color: Color::srgba(0.12, 0.12, 0.12, 0.6),
+                row_tint: None,
+                color_transition: None,
                 show_value_default: true,
+                label: BarLabelLayout::default(),
                 bars: vec![
                     BarConfig {
                         metric: sys_cpu_metric,
@@ -128,8 +270,7 @@ impl Default for PerfHudSettings {
                         min_value: 0.0,
                         max_value: 100.0,                // CPU usage percentage
                         scale_mode: BarScaleMode::Fixed, // Keep fixed for CPU % (known 0-100% range)
-                        min_limit: None,
-                        max_limit: None,
+                        ..default()
                     },
                     BarConfig {
                         metric: sys_mem_metric,
@@ -137,12 +278,10 @@ impl Default for PerfHudSettings {
                         min_value: 0.0,
                         max_value: 100.0,                // Memory usage percentage
                         scale_mode: BarScaleMode::Fixed, // Keep fixed for memory % (known 0-100% range)
-                        min_limit: None,
-                        max_limit: None,
+                        ..default()
                     },
                     BarConfig {
                         metric: entity_metric,
-                        show_value: None,
                         min_value: 0.0,
                         max_value: 10000.0, // Entity count range - fallback values
                         scale_mode: BarScaleMode::Auto {
@@ -152,41 +291,513 @@ impl Default for PerfHudSettings {
                         },
                         min_limit: Some(0.0),     // Entities can't be negative
                         max_limit: Some(50000.0), // Cap at reasonable maximum
+                        ..default()
                     },
                 ],
             },
+            cpu_cores: CpuCoresSettings::default(),
+            screenshots: ScreenshotSettings::default(),
+            text_shadow: None,
+            text_background: None,
+            show_build_info: false,
+            show_controls: false,
+            controls: ControlStripSettings::default(),
+            preset: None,
+            interaction: HudInteraction::default(),
+            log_provider_errors: false,
+            instant: false,
+            transition: Duration::ZERO,
+            budget: HudBudgetSettings::default(),
+            metric_budgets: Vec::new(),
+            budget_colors: BudgetColors::default(),
+            log_budget_transitions: false,
+            budget_hysteresis: 0.05,
+            label_filters: vec![
+                LabelFilter::median("fps", 15, 10),
+                LabelFilter::median(&frame_metric.id, 15, 10),
+            ],
+            fps_target: FpsTarget::Off,
+            telemetry: TelemetrySettings::default(),
+            chrome_trace: ChromeTraceSettings::default(),
+            sample_recording: SampleRecordingSettings::default(),
+            leak_detection: LeakDetectionSettings::default(),
+            long_frames: LongFrameSettings::default(),
+            stats_panel: StatsPanelSettings::default(),
+            traffic_light: TrafficLightSettings::default(),
+            slow_frames: SlowFrameSettings::default(),
+        }
+    }
+}
+
+/// Ready-made HUD layouts.
+///
+/// Use [`HudPreset::settings`] as a starting point to tweak, or
+/// [`spawn_perf_hud`](crate::spawn_perf_hud) to show one as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HudPreset {
+    /// Small FPS-only graph for a screen corner, its value drawn over it
+    Minimal,
+    /// Frame time and FPS graph with system CPU and memory bars
+    #[default]
+    Compact,
+    /// The full panel: the compact layout plus an entity count bar, per-core
+    /// CPU usage, percentile statistics and the control buttons
+    Detailed,
+    /// Only the frame time traffic light, measured against the display
+    /// refresh rate, for builds where anything more would be too obtrusive
+    TrafficLight,
+}
+
+impl HudPreset {
+    /// Settings of this layout
+    pub fn settings(self) -> PerfHudSettings {
+        let mut settings = PerfHudSettings::default();
+        match self {
+            Self::Minimal => {
+                settings.origin = Vec2::new(16.0, 16.0);
+                settings.graph.size = Vec2::new(120.0, 32.0);
+                settings.graph.label_column = LabelColumn::Overlay;
+                settings.graph.curves.retain(|c| c.metric.id == "fps");
+                settings.bars.enabled = false;
+            }
+            Self::Compact => {
+                settings.bars.bars.retain(|b| b.metric.id != "entity_count");
+            }
+            Self::TrafficLight => {
+                settings.origin = Vec2::new(8.0, 8.0);
+                settings.graph.enabled = false;
+                settings.bars.enabled = false;
+                settings.traffic_light.enabled = true;
+                settings.fps_target = FpsTarget::Display;
+            }
+            Self::Detailed => {
+                settings.cpu_cores.enabled = true;
+                settings.stats_panel.enabled = true;
+                settings.stats_panel.metrics = vec!["frame_time_ms".into(), "fps".into()];
+                settings.show_controls = true;
+            }
+        }
+        settings.preset = Some(self);
+        settings
+    }
+
+    /// Preset after this one, wrapping around, in declaration order
+    pub fn next(self) -> Self {
+        match self {
+            Self::Minimal => Self::Compact,
+            Self::Compact => Self::Detailed,
+            Self::Detailed => Self::TrafficLight,
+            Self::TrafficLight => Self::Minimal,
+        }
+    }
+}
+
+impl From<HudPreset> for PerfHudSettings {
+    fn from(preset: HudPreset) -> Self {
+        preset.settings()
+    }
+}
+
+impl PerfHudSettings {
+    /// Label filter of a metric, None with `instant` set or no filter configured
+    pub fn label_filter(&self, metric_id: &str) -> Option<&LabelFilter> {
+        if self.instant {
+            return None;
+        }
+        self.label_filters
+            .iter()
+            .find(|filter| filter.metric_id == metric_id)
+    }
+
+    /// Definition shown in place of the `fps` or `frame_time_ms` metric when
+    /// toggling the frame unit (see
+    /// [`PerfHudCommand::ToggleFrameUnit`](crate::PerfHudCommand)): the other
+    /// one as defined by a curve or bar of these settings, or by default.
+    /// Keeps `metric`'s color, so its curve stays recognizable. None for
+    /// other metrics.
+    pub fn frame_unit_counterpart(&self, metric: &MetricDefinition) -> Option<MetricDefinition> {
+        let id = match metric.id.as_str() {
+            "fps" => "frame_time_ms",
+            "frame_time_ms" => "fps",
+            _ => return None,
+        };
+        let defined = self
+            .graph
+            .curves
+            .iter()
+            .map(|curve| &curve.metric)
+            .chain(self.bars.bars.iter().map(|bar| &bar.metric))
+            .find(|m| m.id == id)
+            .cloned();
+        let mut counterpart = defined.or_else(|| {
+            Self::default()
+                .graph
+                .curves
+                .into_iter()
+                .map(|curve| curve.metric)
+                .find(|m| m.id == id)
+        })?;
+        counterpart.color = metric.color;
+        Some(counterpart)
+    }
+
+    /// Budget thresholds of a metric: those set in `metric_budgets`, or for
+    /// `fps` and `frame_time_ms` those derived from `fps_target`.
+    ///
+    /// # Arguments
+    /// * `metric_id` - ID of the metric
+    /// * `display_hz` - Refresh rate of the display showing the app, if known
+    pub fn budget_thresholds(
+        &self,
+        metric_id: &str,
+        display_hz: Option<f32>,
+    ) -> Option<BudgetThresholds> {
+        if let Some(budget) = self
+            .metric_budgets
+            .iter()
+            .find(|b| b.metric_id == metric_id)
+        {
+            return Some(budget.thresholds());
+        }
+        let target = self.fps_target.resolve(display_hz)?;
+        match metric_id {
+            "fps" => Some(BudgetThresholds {
+                warning: target * 0.95,
+                critical: target * 0.5,
+                lower_is_worse: true,
+            }),
+            "frame_time_ms" => {
+                let frame_ms = 1000.0 / target;
+                Some(BudgetThresholds {
+                    warning: frame_ms * 1.05,
+                    critical: frame_ms * 2.0,
+                    lower_is_worse: false,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Budget level of a metric's value (always `Ok` for metrics without a budget)
+    pub fn budget_level(
+        &self,
+        metric_id: &str,
+        value: f32,
+        display_hz: Option<f32>,
+    ) -> BudgetLevel {
+        self.budget_thresholds(metric_id, display_hz)
+            .map_or(BudgetLevel::Ok, |thresholds| thresholds.level(value))
+    }
+
+    /// Color a metric is drawn in at `value`: its own color within budget,
+    /// or the warning or critical color past its budget
+    pub fn metric_color(
+        &self,
+        definition: &MetricDefinition,
+        value: f32,
+        display_hz: Option<f32>,
+    ) -> Color {
+        self.level_color(
+            definition,
+            self.budget_level(&definition.id, value, display_hz),
+        )
+    }
+
+    /// Color a metric is drawn in at a budget level: its own color within
+    /// budget, or the warning or critical color
+    pub fn level_color(&self, definition: &MetricDefinition, level: BudgetLevel) -> Color {
+        match level {
+            BudgetLevel::Ok => definition.color,
+            BudgetLevel::Warning => self.budget_colors.warning,
+            BudgetLevel::Critical => self.budget_colors.critical,
+        }
+    }
+
+    /// Preset focused on fixed timestep health.
+    ///
+    /// Graphs the time spent in fixed steps and the accumulated lag against
+    /// the fixed delta, which they should stay well below, and shows steps per
+    /// frame as a bar that fills at 4 steps, where the fixed loop is falling
+    /// behind. Its budget turns critical past 2 steps per frame, which takes
+    /// a screenshot once `screenshots.enabled` is set.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_perf_hud::PerfHudSettings;
+    ///
+    /// App::new().insert_resource(PerfHudSettings::fixed_timestep());
+    /// ```
+    pub fn fixed_timestep() -> Self {
+        let delta_metric = MetricDefinition {
+            id: FIXED_DELTA_ID.to_owned(),
+            label: Some("Step:".into()),
+            unit: Some("ms".into()),
+            precision: 1,
+            color: Color::srgb(0.4, 0.4, 0.4),
+        };
+        let update_time_metric = MetricDefinition {
+            id: FIXED_UPDATE_TIME_ID.to_owned(),
+            label: Some("Fixed:".into()),
+            unit: Some("ms".into()),
+            precision: 2,
+            color: Color::srgb(0.96, 0.76, 0.18),
+        };
+        let overstep_metric = MetricDefinition {
+            id: FIXED_OVERSTEP_ID.to_owned(),
+            label: Some("Lag:".into()),
+            unit: Some("ms".into()),
+            precision: 1,
+            color: Color::srgb(0.28, 0.56, 0.89),
+        };
+        let steps_metric = MetricDefinition {
+            id: FIXED_STEPS_PER_FRAME_ID.to_owned(),
+            label: Some("Steps".into()),
+            unit: None,
+            precision: 0,
+            color: Color::srgb(0.1, 0.8, 0.4),
+        };
+        let curve = |metric: MetricDefinition, smoothing: Option<f32>| CurveConfig {
+            metric,
+            autoscale: None,
+            smoothing,
+            quantize_step: Some(0.01),
+            candlestick: false,
+            style: CurveStyle::Solid,
+            line_smoothing: LineSmoothing::Linear,
+            precision: None,
+            group: None,
+            z_index: 0,
+            shading: None,
+            enabled: true,
+        };
+
+        let mut settings = Self::default();
+        settings.graph.curves = vec![
+            curve(delta_metric, None),
+            CurveConfig {
+                z_index: 1,
+                ..curve(update_time_metric, Some(0.25))
+            },
+            curve(overstep_metric, None),
+        ];
+        settings.graph.y_min_span = 1.0;
+        settings.graph.y_step_quantize = 1.0;
+        settings.bars.bars = vec![BarConfig {
+            metric: steps_metric,
+            show_value: Some(true),
+            max_value: 4.0,
+            column_span: 2,
+            ..default()
+        }];
+        settings
+            .metric_budgets
+            .push(MetricBudget::max(FIXED_STEPS_PER_FRAME_ID, 1.0, 2.0));
+        settings.screenshots.metrics = vec![FIXED_STEPS_PER_FRAME_ID.to_owned()];
+        settings
+    }
+}
+
+/// Point of the window the HUD is positioned relative to.
+///
+/// The HUD follows its anchor when the window is resized, so a HUD anchored
+/// to the right stays next to the right edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HudAnchor {
+    /// Offset right and down from the top-left corner
+    #[default]
+    TopLeft,
+    /// Offset left and down from the top-right corner
+    TopRight,
+    /// Offset right and up from the bottom-left corner
+    BottomLeft,
+    /// Offset left and up from the bottom-right corner
+    BottomRight,
+    /// Centered in the window, shifted right and down by the offset
+    Center,
+}
+
+impl HudAnchor {
+    /// Top-left position of a HUD of `size` anchored with `offset` in a
+    /// window of `window` size, all in logical pixels
+    pub fn position(self, offset: Vec2, window: Vec2, size: Vec2) -> Vec2 {
+        let far = window - size - offset;
+        match self {
+            Self::TopLeft => offset,
+            Self::TopRight => Vec2::new(far.x, offset.y),
+            Self::BottomLeft => Vec2::new(offset.x, far.y),
+            Self::BottomRight => far,
+            Self::Center => (window - size) * 0.5 + offset,
+        }
+    }
+}
+
+/// Placement strategy for the HUD on screen.
+///
+/// The smart modes move the HUD to a window corner where it does not cover
+/// the area of interest, and only move again once that corner gets covered.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HudPlacement {
+    /// Stay at [`PerfHudSettings::origin`] from [`PerfHudSettings::anchor`]
+    #[default]
+    Fixed,
+    /// Move to a corner away from the mouse cursor
+    AvoidCursor {
+        /// Distance in pixels from the window edges
+        margin: f32,
+    },
+    /// Move to a corner that does not overlap a gameplay-critical area
+    AvoidRect {
+        /// Area to keep clear, in logical window pixels (origin top-left)
+        rect: Rect,
+        /// Distance in pixels from the window edges
+        margin: f32,
+    },
+}
+
+/// HUD elements that capture pointer input.
+///
+/// By default no HUD node blocks pointer input: clicks and hovers over the
+/// HUD reach the game UI below it (and, with the `picking` feature, the
+/// scene). Control buttons still work, they just do not swallow the click.
+/// Applied when the HUD is spawned; send
+/// [`PerfHudCommand::Rebuild`](crate::PerfHudCommand) to apply changes at
+/// runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HudInteraction {
+    /// Whether control strip buttons block pointer input below them
+    pub controls: bool,
+    /// Whether graph curve labels get an [`Interaction`](bevy::ui::Interaction)
+    /// and block pointer input; clicking a label hides or shows its curve
+    /// (see [`CurveConfig::enabled`])
+    pub legend: bool,
+    /// Whether dragging across the paused or frozen graph zooms into that time range,
+    /// see [`GraphZoom`](crate::GraphZoom); takes effect without a rebuild
+    pub zoom: bool,
+    /// Whether hovering the graph shows a tooltip with the exact value of
+    /// each curve at the sample under the pointer, marked by a cursor line
+    pub tooltip: bool,
+}
+
+/// Shadow drawn behind HUD label text.
+///
+/// Keeps the small white labels readable over bright scenes. Applied when
+/// the HUD is spawned; send [`PerfHudCommand::Rebuild`](crate::PerfHudCommand)
+/// to apply changes at runtime.
+#[derive(Debug, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HudTextShadow {
+    /// Shadow displacement in pixels
+    pub offset: Vec2,
+    /// Color of the shadow (supports transparency)
+    pub color: Color,
+}
+
+impl Default for HudTextShadow {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::new(1.0, 1.0),
+            color: Color::srgba(0.0, 0.0, 0.0, 0.85),
+        }
+    }
+}
+
+/// Background chip drawn behind each HUD label.
+///
+/// A rounded, semi-transparent rectangle that follows the size of its text,
+/// a cheaper way than text outlines to keep labels readable over arbitrary
+/// scenes. Applied when the HUD is spawned; send
+/// [`PerfHudCommand::Rebuild`](crate::PerfHudCommand) to apply changes at
+/// runtime.
+#[derive(Debug, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HudTextBackground {
+    /// Color of the chips (None = the graph background color, so the chips
+    /// match the HUD's palette)
+    pub color: Option<Color>,
+    /// Opacity of the chips, multiplied with the color's own alpha
+    pub opacity: f32,
+    /// Corner radius in pixels
+    pub corner_radius: f32,
+    /// Space between the text and the chip's edges in pixels
+    pub padding: Vec2,
+}
+
+impl Default for HudTextBackground {
+    fn default() -> Self {
+        Self {
+            color: None,
+            opacity: 0.8,
+            corner_radius: 3.0,
+            padding: Vec2::new(3.0, 1.0),
         }
     }
 }
 
+impl HudTextBackground {
+    /// Color the chips are drawn in, given the graph background color
+    pub fn resolved_color(&self, graph_bg: Color) -> Color {
+        let color = self.color.unwrap_or(graph_bg);
+        color.with_alpha(color.alpha() * self.opacity.clamp(0.0, 1.0))
+    }
+}
+
 /// Configuration for the performance graph (chart) display.
 ///
 /// Controls how performance metrics are visualized as time-series graphs,
 /// including appearance, scaling behavior, and which metrics to show.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GraphSettings {
     /// Whether the graph is enabled and should be rendered
     pub enabled: bool,
     /// Size of the graph area in pixels (width, height)
     pub size: Vec2,
-    /// Width in pixels reserved for metric labels on the left side
-    pub label_width: f32,
+    /// Size of the graph area in percent of the window (width, height),
+    /// replacing `size` so the HUD scales with the window (None = use `size`).
+    /// The bars grid follows the graph width; `size` still decides which bars
+    /// share a row.
+    pub size_percent: Option<Vec2>,
+    /// Placement of the metric labels relative to the graph area
+    pub label_column: LabelColumn,
+    /// Linear or logarithmic Y axis
+    pub y_scale: GraphYScale,
     /// Fixed minimum Y-axis value (used when autoscale is disabled)
     pub min_y: f32,
     /// Fixed maximum Y-axis value (used when autoscale is disabled)
     pub max_y: f32,
-    /// Line thickness for graph curves (0.0-1.0 in normalized coordinates)
+    /// Line thickness for graph curves (0.0-1.0 in normalized coordinates),
+    /// used when `thickness_px` is None
+    #[deprecated(note = "use `thickness_px`, which is in pixels")]
     pub thickness: f32,
+    /// Line width of graph curves in pixels, converted using the graph's
+    /// current node size (clamped to 0.5 pixels up to the graph height)
+    pub thickness_px: Option<f32>,
     /// List of curves (metrics) to display on this graph
     pub curves: Vec<CurveConfig>,
     /// Default settings for curves that don't specify their own values
     pub curve_defaults: CurveDefaults,
+    /// Add a curve for every registered metric matching a filter (None = only `curves`).
+    /// Not serialized, as the filter is a function.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[reflect(ignore)]
+    pub auto_curves: Option<AutoCurves>,
     /// Background color of the graph area (supports transparency)
     pub bg_color: Color,
     /// Border configuration for the graph edges
     pub border: GraphBorder,
-    /// Number of horizontal grid lines to display (minimum 2)
+    /// Number of horizontal grid lines and Y-axis ticks, evenly spaced from the
+    /// bottom edge to the top edge of the graph (minimum 2)
     pub y_ticks: u32,
+    /// Whether to label the Y-axis ticks with their values, along the graph's right edge
+    pub y_tick_labels: bool,
+    /// Color of the horizontal grid lines (fully transparent = no grid lines)
+    pub grid_color: Color,
     /// Whether to always include zero in the Y-axis range
     pub y_include_zero: bool,
     /// Minimum Y-axis range to prevent overly compressed scales
@@ -195,12 +806,389 @@ pub struct GraphSettings {
     pub y_margin_frac: f32,
     /// Step size for quantizing Y-axis min/max values (0 = disabled)
     pub y_step_quantize: f32,
-    /// Smoothing factor for Y-axis scale transitions (0.0-1.0)
+    /// Smoothing factor for Y-axis scale transitions (0.0-1.0), used by [`ScaleEasing::Lerp`]
     pub y_scale_smoothing: f32,
+    /// Easing of autoscaled Y-axis range changes
+    /// The range always widens at once to keep the newest sample on screen
+    pub scale_easing: ScaleEasing,
+    /// Number of frames folded into each displayed sample (minimum 1)
+    /// Values above 1 keep the per-sample min/max for candlestick curves
+    pub samples_per_point: u32,
+    /// Length of the live history shown across the graph's width
+    pub history: GraphHistory,
+    /// What the graph's X axis counts: wall-clock time or frames
+    pub x_axis: GraphXAxis,
+    /// Label the time window shown by the graph ("-5s" to "now") under it,
+    /// when the X axis follows time
+    pub time_axis_labels: bool,
+    /// Shader quality, trading curve fidelity for GPU cost
+    pub quality: GraphQuality,
+    /// Curve groups currently hidden, see [`CurveConfig::group`]
+    pub hidden_groups: Vec<String>,
+    /// Autoscale each curve group on its own Y range instead of the graph's
+    /// shared range (ungrouped curves keep sharing the graph range)
+    pub autoscale_per_group: bool,
+    /// How samples are drawn while a curve's provider returns no value
+    pub missing_data: MissingData,
+    /// Number of samples after startup or a rebuild that autoscale ignores,
+    /// so startup spikes do not dominate the Y range (0 = none)
+    pub warmup_samples: u32,
+    /// History shown by the graph: recent samples or the long-term trend
+    pub view: GraphView,
+    /// Time folded into each sample of the trend history
+    pub trend_interval: Duration,
+    /// Title drawn above the graph (None = no title)
+    pub title: Option<String>,
+    /// Caption drawn below the graph (None = no caption)
+    pub caption: Option<String>,
+    /// Font sizes and color of the title and caption
+    pub caption_style: GraphCaptionStyle,
+    /// Whether to draw the thresholds of curves with a [`MetricBudget`] as dashed lines
+    pub show_budget_lines: bool,
+    /// Show the unit once in the graph's corner instead of in every label
+    /// when all curves share it
+    pub shared_unit_axis: bool,
+}
+
+impl Default for GraphSettings {
+    /// The graph of [`PerfHudSettings::default`]
+    fn default() -> Self {
+        PerfHudSettings::default().graph
+    }
+}
+
+impl GraphSettings {
+    /// Unit shared by all curves, shown once on the graph when
+    /// [`shared_unit_axis`](Self::shared_unit_axis) is set.
+    ///
+    /// None when disabled, when there are no curves or when any curve has a
+    /// different unit or none.
+    pub fn shared_unit(&self) -> Option<&str> {
+        if !self.shared_unit_axis {
+            return None;
+        }
+        let mut units = self.curves.iter().map(|c| c.metric.unit.as_deref());
+        let first = units.next()??;
+        (!first.is_empty() && units.all(|unit| unit == Some(first))).then_some(first)
+    }
+
+    /// Width of the graph area as a UI value, see [`size_percent`](Self::size_percent).
+    pub fn width_val(&self) -> Val {
+        match self.size_percent {
+            Some(percent) => Val::Vw(percent.x),
+            None => Val::Px(self.size.x),
+        }
+    }
+
+    /// Height of the graph area as a UI value, see [`size_percent`](Self::size_percent).
+    pub fn height_val(&self) -> Val {
+        match self.size_percent {
+            Some(percent) => Val::Vh(percent.y),
+            None => Val::Px(self.size.y),
+        }
+    }
+
+    /// Line thickness in the shader's normalized units for a graph `height` pixels tall.
+    ///
+    /// Legacy `thickness` values above 0.5 would fill the whole graph, so they
+    /// are taken to be pixels instead.
+    pub fn line_thickness_uv(&self, height: f32) -> f32 {
+        let height = height.max(1.0);
+        #[allow(deprecated)]
+        let px = match self.thickness_px {
+            Some(px) => px,
+            None if self.thickness > 0.5 => self.thickness,
+            None => return self.thickness.max(0.0),
+        };
+        // The shader measures distance from the line center, i.e. half the width
+        0.5 * px.clamp(0.5, height) / height
+    }
+
+    /// Curves as drawn by the graph material, in slot order.
+    ///
+    /// Only the first [`MAX_CURVES`] curves get a slot; use this to build
+    /// legends that match the graph's colors and stacking exactly.
+    pub fn curve_slots(&self) -> impl Iterator<Item = CurveSlot<'_>> {
+        let order = self.draw_order();
+        self.curves
+            .iter()
+            .take(MAX_CURVES)
+            .enumerate()
+            .map(move |(index, curve)| CurveSlot {
+                index,
+                layer: order
+                    .iter()
+                    .position(|&slot| slot == index)
+                    .unwrap_or(index),
+                metric_id: &curve.metric.id,
+                color: curve.metric.color,
+            })
+    }
+
+    /// Slot indices of the drawn curves from bottom to top.
+    ///
+    /// Curves are ordered by [`CurveConfig::z_index`], keeping configuration
+    /// order between curves with the same value.
+    pub fn draw_order(&self) -> Vec<usize> {
+        let (order, count) = self.draw_order_slots();
+        order[..count].to_vec()
+    }
+
+    /// [`draw_order`](Self::draw_order) packed for the graph material, without allocating
+    #[cfg(feature = "hud")]
+    pub fn packed_draw_order(&self) -> u32 {
+        let (order, count) = self.draw_order_slots();
+        MultiLineGraphParams::pack_draw_order(&order[..count])
+    }
+
+    /// Draw order in a fixed array, with the number of drawn curves
+    fn draw_order_slots(&self) -> ([usize; MAX_CURVES], usize) {
+        let count = self.curves.len().min(MAX_CURVES);
+        let mut order: [usize; MAX_CURVES] = std::array::from_fn(|slot| slot);
+        // The slot breaks ties, so the unstable (non-allocating) sort keeps configuration order
+        order[..count].sort_unstable_by_key(|&slot| (self.curves[slot].z_index, slot));
+        (order, count)
+    }
+
+    /// Whether curves tagged with `group` are currently shown
+    pub fn is_group_visible(&self, group: &str) -> bool {
+        !self.hidden_groups.iter().any(|hidden| hidden == group)
+    }
+
+    /// Show or hide all curves tagged with `group`
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        self.hidden_groups.retain(|hidden| hidden != group);
+        if !visible {
+            self.hidden_groups.push(group.to_owned());
+        }
+    }
+
+    /// Whether `curve` is shown, i.e. it is enabled and ungrouped or its
+    /// group is visible
+    pub fn is_curve_visible(&self, curve: &CurveConfig) -> bool {
+        curve.enabled
+            && curve
+                .group
+                .as_deref()
+                .is_none_or(|group| self.is_group_visible(group))
+    }
+}
+
+/// A graph curve's slot in the graph material.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveSlot<'a> {
+    /// Index of the curve in the material (configuration order, 0 = first)
+    pub index: usize,
+    /// Position of the curve in the draw order (0 = bottom)
+    pub layer: usize,
+    /// ID of the metric the curve displays
+    pub metric_id: &'a str,
+    /// Color the curve is drawn with
+    pub color: Color,
+}
+
+/// Render quality of the graph shader.
+///
+/// Lower levels skip per-pixel work so the HUD stays cheap on low-end GPUs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphQuality {
+    /// Cubic Hermite curves with anti-aliased edges
+    #[default]
+    High = 0,
+    /// Linear segments with anti-aliased edges
+    Medium = 1,
+    /// Linear segments with hard edges
+    Low = 2,
+}
+
+/// Rendering of graph samples whose metric had no value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingData {
+    /// Keep drawing the last sampled value (0 before the first sample)
+    #[default]
+    Hold,
+    /// Leave a break in the curve until values are available again
+    Gap,
+}
+
+/// Length of the live history a graph shows.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphHistory {
+    /// The most recent [`MAX_SAMPLES`] samples
+    #[default]
+    Full,
+    /// The most recent samples, up to [`MAX_SAMPLES`]
+    Samples(u32),
+    /// The last given number of seconds. Windows longer than [`MAX_SAMPLES`]
+    /// samples fold several frames into each sample, like `samples_per_point`
+    Seconds(f32),
+}
+
+impl GraphHistory {
+    /// Minimum time between two samples, so the window fits in [`MAX_SAMPLES`]
+    pub fn sample_interval(self) -> Duration {
+        match self {
+            // Windows too long to represent never collect a second sample
+            Self::Seconds(seconds) if seconds > 0.0 => {
+                Duration::try_from_secs_f32(seconds / MAX_SAMPLES as f32).unwrap_or(Duration::MAX)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Number of samples shown, given the average time between samples
+    pub fn capacity(self, sample_period: Option<Duration>) -> usize {
+        let samples = match self {
+            Self::Full => MAX_SAMPLES,
+            Self::Samples(samples) => samples as usize,
+            Self::Seconds(seconds) => sample_period
+                .filter(|period| !period.is_zero())
+                .map_or(MAX_SAMPLES, |period| {
+                    (seconds / period.as_secs_f32()).round() as usize
+                }),
+        };
+        samples.clamp(2, MAX_SAMPLES)
+    }
+}
+
+/// Scale of the graph's Y axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphYScale {
+    /// Values are plotted as they are
+    #[default]
+    Linear,
+    /// Values are plotted as `log10(1 + value)` (negative values mirrored), so
+    /// metrics spanning orders of magnitude stay readable and zero stays on
+    /// the axis. Autoscale works on the logarithms: `y_min_span` is the span
+    /// of the logarithm of its value and `y_step_quantize` does not apply
+    Log,
+}
+
+impl GraphYScale {
+    /// Position of `value` on the axis
+    pub fn to_axis(self, value: f32) -> f32 {
+        match self {
+            Self::Linear => value,
+            Self::Log => value.signum() * value.abs().ln_1p() / std::f32::consts::LN_10,
+        }
+    }
+
+    /// Value at `position` on the axis, the inverse of [`to_axis`](Self::to_axis)
+    pub fn from_axis(self, position: f32) -> f32 {
+        match self {
+            Self::Linear => position,
+            Self::Log => position.signum() * (position.abs() * std::f32::consts::LN_10).exp_m1(),
+        }
+    }
+}
+
+/// What the graph's X axis counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphXAxis {
+    /// Samples follow wall-clock time, folding frames together as set by
+    /// `samples_per_point` and `history`
+    #[default]
+    Time,
+    /// One sample per frame, with the numbers of the oldest and newest frame
+    /// shown under the graph; for debugging deterministic or lockstep
+    /// simulations, where frames matter more than seconds
+    Frames,
+}
+
+/// History shown by the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphView {
+    /// Recent samples, one per `samples_per_point` frames
+    #[default]
+    Live,
+    /// Long-term trend, one sample per `trend_interval`
+    Trend,
+}
+
+impl GraphView {
+    /// The other view
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Live => Self::Trend,
+            Self::Trend => Self::Live,
+        }
+    }
+}
+
+/// Easing of autoscaled Y-axis range transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScaleEasing {
+    /// Move a fixed fraction ([`GraphSettings::y_scale_smoothing`]) of the way each frame
+    #[default]
+    Lerp,
+    /// Critically damped spring with the given angular frequency in 1/s;
+    /// frame-rate independent and never overshoots the target
+    Spring(f32),
+}
+
+/// Placement of the per-curve value labels of a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelColumn {
+    /// Labels in a column left of the graph, with the given width in pixels (minimum 40)
+    Left(f32),
+    /// No labels; the graph uses no extra horizontal space
+    Hidden,
+    /// Labels drawn on top of the graph area, in its top-left corner
+    Overlay,
+}
+
+impl Default for LabelColumn {
+    fn default() -> Self {
+        Self::Left(60.0)
+    }
+}
+
+impl LabelColumn {
+    /// Horizontal space in pixels reserved left of the graph for labels
+    pub fn reserved_width(&self) -> f32 {
+        match self {
+            Self::Left(width) => width.max(40.0),
+            Self::Hidden | Self::Overlay => 0.0,
+        }
+    }
+}
+
+/// Styling of the graph title and caption.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphCaptionStyle {
+    /// Font size of the title in pixels
+    pub title_font_size: f32,
+    /// Font size of the caption in pixels
+    pub caption_font_size: f32,
+    /// Color of the title text
+    pub title_color: Color,
+    /// Color of the caption text
+    pub caption_color: Color,
+}
+
+impl Default for GraphCaptionStyle {
+    fn default() -> Self {
+        Self {
+            title_font_size: 12.0,
+            caption_font_size: 9.0,
+            title_color: Color::WHITE,
+            caption_color: Color::srgba(1.0, 1.0, 1.0, 0.6),
+        }
+    }
 }
 
 /// Configuration for graph border appearance.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphBorder {
     /// Color of the border lines (supports transparency)
     pub color: Color,
@@ -220,22 +1208,668 @@ pub struct GraphBorder {
 ///
 /// Performance bars show current metric values as horizontal progress bars,
 /// useful for displaying things like CPU usage, memory usage, etc.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BarsSettings {
     /// Whether the bars are enabled and should be rendered
     pub enabled: bool,
-    /// List of bars (metrics) to display
+    /// List of bars (metrics) to display; only the first `MAX_BARS` are shown
     pub bars: Vec<BarConfig>,
     /// Background color for all bars (supports transparency)
     pub bg_color: Color,
+    /// Background color of every other row of the grid, starting with the
+    /// second, to make dense grids easier to scan (None = no striping)
+    pub row_tint: Option<Color>,
     /// Default setting for whether bars should show their numeric values
     pub show_value_default: bool,
+    /// Placement and font of the labels drawn over the bars
+    pub label: BarLabelLayout,
+    /// Animated color change of bars moving between budget zones
+    /// (None = the color switches instantly; ignored in instant mode)
+    pub color_transition: Option<BarColorTransition>,
+}
+
+/// Animated color change of a bar moving between budget zones.
+///
+/// Also delays a bar's return to a better zone until its value has recovered
+/// past the threshold by a margin, so a metric hovering at a threshold does
+/// not make the bar flicker between colors.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BarColorTransition {
+    /// Time the color takes to change
+    pub duration: Duration,
+    /// Easing of the change
+    pub easing: EaseFunction,
+    /// Fraction of a threshold the value must recover past it before the bar
+    /// returns to the better zone (0.0 = none)
+    pub hysteresis: f32,
+}
+
+impl Default for BarColorTransition {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(250),
+            easing: EaseFunction::SmoothStep,
+            hysteresis: 0.05,
+        }
+    }
+}
+
+impl Default for BarsSettings {
+    /// The bars of [`PerfHudSettings::default`]
+    fn default() -> Self {
+        PerfHudSettings::default().bars
+    }
+}
+
+impl BarsSettings {
+    /// Background color of a bar laid out in the given grid row: its own
+    /// override, else the row tint on odd rows, else the shared background
+    pub fn bar_bg_color(&self, bar: &BarConfig, row: usize) -> Color {
+        bar.bg_color
+            .or(self.row_tint.filter(|_| row % 2 == 1))
+            .unwrap_or(self.bg_color)
+    }
+}
+
+/// Layout of the labels drawn over the bars.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BarLabelLayout {
+    /// Font size of the label text
+    pub font_size: f32,
+    /// Space kept between the bar's left and right edges and the label in pixels
+    pub padding_x: f32,
+    /// Vertical offset from the bar's center in pixels (positive = down)
+    pub offset_y: f32,
+    /// Horizontal alignment of the label within the bar
+    pub align: BarLabelAlign,
+    /// Whether long labels wrap onto further lines instead of being clipped
+    pub wrap: bool,
+}
+
+impl Default for BarLabelLayout {
+    fn default() -> Self {
+        Self {
+            font_size: 10.0,
+            padding_x: 6.0,
+            offset_y: 0.0,
+            align: BarLabelAlign::Start,
+            wrap: false,
+        }
+    }
+}
+
+/// Horizontal alignment of a bar label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BarLabelAlign {
+    /// Against the bar's left edge
+    #[default]
+    Start,
+    /// Centered on the bar
+    Center,
+    /// Against the bar's right edge
+    End,
+}
+
+/// Configuration for the compact per-core CPU usage strip.
+///
+/// When enabled, one thin bar per logical CPU core is shown below the bars,
+/// fed by the `system/cpu_core_usage/<index>` metrics.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuCoresSettings {
+    /// Whether the per-core strip is enabled and should be rendered
+    pub enabled: bool,
+    /// Number of core bars per row
+    pub columns: usize,
+    /// Height of each core bar in pixels
+    pub bar_height: f32,
+    /// Gap between core bars in pixels
+    pub gap: f32,
+    /// Fill color of the core bars
+    pub color: Color,
+    /// Background color of the core bars (supports transparency)
+    pub bg_color: Color,
+}
+
+impl Default for CpuCoresSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            columns: 4,
+            bar_height: 4.0,
+            gap: 2.0,
+            color: Color::srgb(0.96, 0.76, 0.18),
+            bg_color: Color::srgba(0.12, 0.12, 0.12, 0.6),
+        }
+    }
+}
+
+/// Budget of a metric, e.g. `frame_time_ms` at most 16.6 ms.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetricBudget {
+    /// ID of the metric the budget applies to
+    pub metric_id: String,
+    /// Value past which the metric is over budget
+    pub warning: f32,
+    /// Value past which the metric is critically over budget
+    pub critical: f32,
+    /// Whether lower values are worse, e.g. for FPS (false = higher is worse)
+    pub lower_is_worse: bool,
+}
+
+impl MetricBudget {
+    /// Budget for a metric that should stay below `warning`
+    pub fn max(metric_id: &str, warning: f32, critical: f32) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            warning,
+            critical,
+            lower_is_worse: false,
+        }
+    }
+
+    /// Budget for a metric that should stay above `warning`
+    pub fn min(metric_id: &str, warning: f32, critical: f32) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            warning,
+            critical,
+            lower_is_worse: true,
+        }
+    }
+
+    /// Warning and critical thresholds of the budget
+    pub fn thresholds(&self) -> BudgetThresholds {
+        BudgetThresholds {
+            warning: self.warning,
+            critical: self.critical,
+            lower_is_worse: self.lower_is_worse,
+        }
+    }
+
+    /// Budget level of `value`
+    pub fn level(&self, value: f32) -> BudgetLevel {
+        self.thresholds().level(value)
+    }
+}
+
+/// Label filter of a metric, e.g. for `fps`.
+///
+/// The label shows the median of the metric's last `samples` values and is
+/// updated only every `interval` display refreshes, so digits stay readable
+/// at high refresh rates. Ignored with [`PerfHudSettings::instant`].
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelFilter {
+    /// ID of the metric whose graph and bar labels are filtered
+    pub metric_id: String,
+    /// Number of recent values the median is taken of (minimum 1)
+    pub samples: usize,
+    /// Display refreshes between label updates (minimum 1)
+    pub interval: u32,
+}
+
+impl LabelFilter {
+    /// Median of the last `samples` values, shown every `interval` refreshes
+    pub fn median(metric_id: &str, samples: usize, interval: u32) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            samples,
+            interval,
+        }
+    }
+}
+
+/// Warning and critical thresholds of a budget, without the metric it applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BudgetThresholds {
+    /// Value past which the metric is over budget
+    pub warning: f32,
+    /// Value past which the metric is critically over budget
+    pub critical: f32,
+    /// Whether lower values are worse, e.g. for FPS (false = higher is worse)
+    pub lower_is_worse: bool,
+}
+
+impl BudgetThresholds {
+    /// Budget level of `value`
+    pub fn level(self, value: f32) -> BudgetLevel {
+        let past = |threshold: f32| {
+            if self.lower_is_worse {
+                value < threshold
+            } else {
+                value > threshold
+            }
+        };
+        if past(self.critical) {
+            BudgetLevel::Critical
+        } else if past(self.warning) {
+            BudgetLevel::Warning
+        } else {
+            BudgetLevel::Ok
+        }
+    }
+
+    /// Budget level of `value` coming from the `previous` level: a better
+    /// level is only returned once the value is `hysteresis` (a fraction of
+    /// the threshold) past the threshold it recovers over
+    pub fn level_with_hysteresis(
+        self,
+        value: f32,
+        previous: BudgetLevel,
+        hysteresis: f32,
+    ) -> BudgetLevel {
+        let level = self.level(value);
+        if level >= previous {
+            return level;
+        }
+        // Thresholds moved towards the good side are crossed back more easily
+        let shift = |threshold: f32| {
+            let margin = threshold.abs() * hysteresis;
+            if self.lower_is_worse {
+                threshold + margin
+            } else {
+                threshold - margin
+            }
+        };
+        let shifted = Self {
+            warning: shift(self.warning),
+            critical: shift(self.critical),
+            ..self
+        };
+        shifted.level(value).clamp(level, previous)
+    }
+}
+
+/// Frame rate the built-in frame budgets are measured against.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FpsTarget {
+    /// No frame budgets unless set in `metric_budgets`
+    #[default]
+    Off,
+    /// Refresh rate of the display the primary window is on, following the
+    /// window across monitors (60 Hz while unknown)
+    Display,
+    /// A fixed frame rate
+    Fixed(f32),
+}
+
+impl FpsTarget {
+    /// Frame rate used while the display refresh rate is unknown
+    pub const FALLBACK_HZ: f32 = 60.0;
+
+    /// Target frame rate given the display refresh rate, if any target is set
+    pub fn resolve(self, display_hz: Option<f32>) -> Option<f32> {
+        match self {
+            Self::Off => None,
+            Self::Display => Some(display_hz.unwrap_or(Self::FALLBACK_HZ)),
+            Self::Fixed(hz) => Some(hz),
+        }
+        .filter(|hz| *hz > 0.0)
+    }
+}
+
+/// How far a metric is past its budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BudgetLevel {
+    /// Within budget
+    #[default]
+    Ok,
+    /// Past the warning threshold
+    Warning,
+    /// Past the critical threshold
+    Critical,
+}
+
+/// Colors of metrics over their budget.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BudgetColors {
+    /// Color of metrics past their warning threshold
+    pub warning: Color,
+    /// Color of metrics past their critical threshold
+    pub critical: Color,
+}
+
+impl Default for BudgetColors {
+    fn default() -> Self {
+        Self {
+            warning: Color::srgb(1.0, 0.75, 0.2),
+            critical: Color::srgb(1.0, 0.25, 0.2),
+        }
+    }
+}
+
+/// Files written by the buttons of the control strip, see
+/// [`PerfHudSettings::show_controls`].
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlStripSettings {
+    /// File a sample recording is saved to when it is stopped
+    pub recording_path: PathBuf,
+    /// Directory bug report bundles are exported to
+    pub export_directory: PathBuf,
+}
+
+impl Default for ControlStripSettings {
+    fn default() -> Self {
+        Self {
+            recording_path: PathBuf::from("perf_hud_recording.csv"),
+            export_directory: PathBuf::from("perf_hud_report"),
+        }
+    }
+}
+
+/// Configuration for automatic screenshots on critical metric values.
+///
+/// When enabled, the primary window is captured to `directory` as soon as a
+/// watched metric passes the critical threshold of its budget (see
+/// [`PerfHudSettings::budget_thresholds`]), the same transition that fires
+/// alerts. A [`PerfHudSnapshot`](crate::PerfHudSnapshot) is saved next to the
+/// image with the `serde` feature, and a
+/// [`PerfHudScreenshot`](crate::PerfHudScreenshot) event is sent.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScreenshotSettings {
+    /// Whether threshold screenshots are taken
+    pub enabled: bool,
+    /// IDs of the metrics watched (empty = every metric with a budget)
+    pub metrics: Vec<String>,
+    /// Minimum time between two screenshots, shared by all watched metrics
+    pub cooldown: Duration,
+    /// Directory screenshots are saved to (created if missing)
+    pub directory: PathBuf,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            metrics: Vec::new(),
+            cooldown: Duration::from_secs(10),
+            directory: PathBuf::from("perf_hud_screenshots"),
+        }
+    }
+}
+
+impl ScreenshotSettings {
+    /// Whether a critical `metric_id` triggers a screenshot
+    pub fn watches(&self, metric_id: &str) -> bool {
+        self.metrics.is_empty() || self.metrics.iter().any(|id| id == metric_id)
+    }
+}
+
+/// Configuration of the HUD's own per-frame update budget.
+///
+/// When enabled, the time spent in the HUD's update systems (see
+/// [`HudUpdateCost`](crate::HudUpdateCost)) is averaged over recent frames. A
+/// HUD running over its budget logs a warning and, with `degrade` set,
+/// refreshes its graph, bars and labels only every `degraded_interval` frames
+/// until a full refresh fits in the budget again.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HudBudgetSettings {
+    /// Whether the HUD's update cost is checked against the budget
+    pub enabled: bool,
+    /// Time the HUD update systems may take together each frame
+    pub limit: Duration,
+    /// Whether to refresh the display less often while over budget (false = only warn)
+    pub degrade: bool,
+    /// While degraded, refresh the display once every this many frames
+    pub degraded_interval: u32,
+}
+
+impl Default for HudBudgetSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            limit: Duration::from_micros(200),
+            degrade: true,
+            degraded_interval: 4,
+        }
+    }
+}
+
+/// Configuration of the [`TelemetryTick`](crate::TelemetryTick) events.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TelemetrySettings {
+    /// Whether telemetry ticks are sent
+    pub enabled: bool,
+    /// Time the statistics of each tick are aggregated over
+    pub interval: Duration,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Configuration of the [`ChromeTraceRecorder`](crate::ChromeTraceRecorder).
+///
+/// Send [`PerfHudCommand::ExportChromeTrace`](crate::PerfHudCommand) to write
+/// the recording to a file.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChromeTraceSettings {
+    /// Whether metric samples are recorded
+    pub enabled: bool,
+    /// IDs of the metrics recorded (empty = all sampled metrics)
+    pub metrics: Vec<String>,
+    /// Number of samples kept; the oldest are dropped first
+    pub capacity: usize,
+}
+
+impl Default for ChromeTraceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            metrics: Vec::new(),
+            capacity: 100_000,
+        }
+    }
+}
+
+/// Limits of a [`SampleRecorder`](crate::SampleRecorder) recording; the
+/// oldest frames are dropped first.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SampleRecordingSettings {
+    /// Number of frames kept
+    pub capacity: usize,
+    /// Time span kept, from the oldest to the newest frame
+    pub duration: Duration,
+}
+
+impl Default for SampleRecordingSettings {
+    fn default() -> Self {
+        Self {
+            capacity: 36_000,
+            duration: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Exclusion of abnormally long frames, see [`LongFrameState`](crate::LongFrameState).
+///
+/// Frames longer than `threshold` are left out of the graph history and
+/// autoscale, statistics, alerts, screenshots and exports.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongFrameSettings {
+    /// Whether long frames are excluded
+    pub enabled: bool,
+    /// Duration past which a frame counts as a debugger break or sleep
+    pub threshold: Duration,
+}
+
+impl Default for LongFrameSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Configuration of the slow frame list, see [`SlowFrameLog`](crate::SlowFrameLog).
+///
+/// Frames over `threshold` are logged with their wall-clock time and
+/// duration, and the latest `max_entries` are listed in a panel under the graph.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlowFrameSettings {
+    /// Whether slow frames are logged and listed
+    pub enabled: bool,
+    /// Duration past which a frame counts as slow (None = the critical
+    /// budget of `frame_time_ms`, see [`PerfHudSettings::budget_thresholds`];
+    /// no frame is slow without one)
+    pub threshold: Option<Duration>,
+    /// Number of slow frames kept and listed, newest first
+    pub max_entries: usize,
+    /// Font size of the list
+    pub font_size: f32,
+}
+
+impl Default for SlowFrameSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: None,
+            max_entries: 5,
+            font_size: 9.0,
+        }
+    }
+}
+
+/// Configuration of the [`PerfHudLeakSuspected`](crate::PerfHudLeakSuspected)
+/// heuristic.
+///
+/// A leak is suspected when lines fitted to the entity count and to the
+/// process memory over `window` both rise faster than their slope and fit
+/// the samples at least as well as `min_fit`.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LeakDetectionSettings {
+    /// Whether leak detection runs
+    pub enabled: bool,
+    /// Time the trends are fitted over
+    pub window: Duration,
+    /// Time between two samples of the entity count and memory
+    pub sample_interval: Duration,
+    /// Entities added per minute above which the entity count counts as rising
+    pub entity_slope: f32,
+    /// Megabytes added per minute above which process memory counts as rising
+    pub memory_slope: f32,
+    /// Minimum goodness of fit (0.0-1.0) for a rise to count as steady
+    /// rather than noise or a one-off jump
+    pub min_fit: f32,
+}
+
+impl Default for LeakDetectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: Duration::from_secs(300),
+            sample_interval: Duration::from_secs(5),
+            entity_slope: 10.0,
+            memory_slope: 5.0,
+            min_fit: 0.8,
+        }
+    }
+}
+
+/// Configuration of the percentile statistics panel under the graph.
+///
+/// Each row shows the p50, p95, p99 and maximum of one metric over the
+/// samples the graph currently shows, so only metrics drawn as graph curves
+/// have statistics.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatsPanelSettings {
+    /// Whether the panel is shown
+    pub enabled: bool,
+    /// IDs of the metrics shown, one row each
+    pub metrics: Vec<String>,
+    /// Font size of the rows
+    pub font_size: f32,
+}
+
+impl Default for StatsPanelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            metrics: vec!["frame_time_ms".into()],
+            font_size: 9.0,
+        }
+    }
+}
+
+/// Configuration of the traffic light indicator.
+///
+/// A single square or circle colored by the budget level of a metric (see
+/// [`PerfHudSettings::budget_thresholds`]): `ok_color` within budget, then
+/// the [`BudgetColors`] warning and critical colors. Shown alone with
+/// [`HudPreset::TrafficLight`] when a graph would be too obtrusive.
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrafficLightSettings {
+    /// Whether the indicator is shown
+    pub enabled: bool,
+    /// ID of the metric whose budget level is shown
+    pub metric_id: String,
+    /// Width and height of the indicator in pixels
+    pub size: f32,
+    /// Shape of the indicator
+    pub shape: TrafficLightShape,
+    /// Color while the metric is within budget
+    pub ok_color: Color,
+    /// Period of the blink while the metric is critical (None = steady)
+    pub blink: Option<Duration>,
+}
+
+impl Default for TrafficLightSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            metric_id: "frame_time_ms".into(),
+            size: 10.0,
+            shape: TrafficLightShape::Circle,
+            ok_color: Color::srgb(0.2, 0.8, 0.3),
+            blink: Some(Duration::from_millis(500)),
+        }
+    }
+}
+
+/// Shape of the traffic light indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrafficLightShape {
+    /// Square
+    Square,
+    /// Circle
+    #[default]
+    Circle,
 }
 
 /// Configuration for a single curve (line) in a performance graph.
 ///
 /// Each curve represents one metric tracked over time, such as FPS or frame time.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurveConfig {
     /// The metric this curve represents (ID, label, color, etc.)
     pub metric: MetricDefinition,
@@ -247,12 +1881,157 @@ pub struct CurveConfig {
     /// Quantization step for values (None = use graph default)
     /// Values are rounded to nearest multiple of this step
     pub quantize_step: Option<f32>,
+    /// Render each sample as a min/max range with the mean as a dot
+    /// instead of a continuous line
+    pub candlestick: bool,
+    /// How the line is drawn, to tell overlapping curves apart beyond color
+    /// (ignored by candlestick curves)
+    pub style: CurveStyle,
+    /// Visual smoothing of the line between samples, independent of
+    /// [`smoothing`](Self::smoothing): plotted values and labels stay exact
+    /// (drawn with [`GraphQuality::High`] only, lower qualities draw straight segments)
+    pub line_smoothing: LineSmoothing,
+    /// Decimal places for this curve's graph label (None = use metric precision)
+    pub precision: Option<u32>,
+    /// Group name (e.g. "net") used to show/hide related curves together and,
+    /// with [`GraphSettings::autoscale_per_group`], to share a Y range (None = ungrouped)
+    pub group: Option<String>,
+    /// Draw priority; curves with higher values are drawn on top of lower ones
+    /// (curves with equal values are drawn in configuration order, later on top)
+    pub z_index: i32,
+    /// Shading of the area past a threshold (None = use graph default)
+    pub shading: Option<ThresholdShading>,
+    /// Whether the curve is drawn; a disabled curve keeps its greyed out
+    /// label, and clicking the label toggles this with
+    /// [`HudInteraction::legend`] set
+    pub enabled: bool,
+}
+
+/// Line style of a graph curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurveStyle {
+    /// Continuous line
+    #[default]
+    Solid,
+    /// Line broken into dashes
+    Dashed,
+    /// Line of dots
+    Dotted,
+    /// Continuous line with the area below it filled in the curve's color
+    FilledArea,
+}
+
+/// Shape of a graph curve's line between samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineSmoothing {
+    /// Straight segments from sample to sample
+    #[default]
+    Linear,
+    /// Catmull-Rom spline through every sample
+    CatmullRom,
+    /// Uniform cubic B-spline guided by the samples, passing near rather than
+    /// through them, which hides frame-to-frame jitter
+    BSpline,
+}
+
+/// Automatic curves for registered metrics.
+///
+/// Every metric with a registered provider whose ID passes `filter` gets a
+/// curve when the HUD is spawned, appended to [`GraphSettings::curves`] unless
+/// already there. Auto curves autoscale, are labeled with the metric ID and
+/// belong to the [`AUTO_CURVE_GROUP`] group, so they can be hidden together.
+///
+/// # Example
+/// ```rust
+/// use bevy_perf_hud::{AutoCurves, PerfHudSettings};
+///
+/// let mut settings = PerfHudSettings::default();
+/// settings.graph.auto_curves = Some(AutoCurves::matching(|id| id.starts_with("fixed/")));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AutoCurves {
+    /// Whether a metric gets a curve, given its ID
+    pub filter: fn(&str) -> bool,
+    /// Also add curves for metrics registered after the HUD was spawned,
+    /// rebuilding the HUD to show them
+    pub follow_registrations: bool,
+}
+
+impl AutoCurves {
+    /// Curves for every registered metric
+    pub fn all() -> Self {
+        Self::matching(|_| true)
+    }
+
+    /// Curves for the registered metrics whose ID passes `filter`
+    pub fn matching(filter: fn(&str) -> bool) -> Self {
+        Self {
+            filter,
+            follow_registrations: false,
+        }
+    }
+}
+
+/// Group of the curves added by [`AutoCurves`]
+pub const AUTO_CURVE_GROUP: &str = "auto";
+
+/// Colors of automatic curves, cycled in the order they are added
+const AUTO_CURVE_COLORS: [Color; MAX_CURVES] = [
+    Color::srgb(0.95, 0.77, 0.06),
+    Color::srgb(0.2, 0.8, 0.8),
+    Color::srgb(0.9, 0.4, 0.8),
+    Color::srgb(0.55, 0.85, 0.3),
+    Color::srgb(1.0, 0.55, 0.25),
+    Color::srgb(0.6, 0.6, 1.0),
+];
+
+impl GraphSettings {
+    /// Append auto curves for the given registered metric IDs.
+    ///
+    /// # Returns
+    /// Whether any curve was added
+    pub fn add_auto_curves<'a>(&mut self, metric_ids: impl IntoIterator<Item = &'a str>) -> bool {
+        let Some(auto) = self.auto_curves else {
+            return false;
+        };
+        let before = self.curves.len();
+        for id in metric_ids {
+            if !(auto.filter)(id) || self.curves.iter().any(|c| c.metric.id == id) {
+                continue;
+            }
+            let color = AUTO_CURVE_COLORS[self.curves.len() % AUTO_CURVE_COLORS.len()];
+            self.curves.push(CurveConfig {
+                metric: MetricDefinition {
+                    id: id.to_owned(),
+                    label: None,
+                    unit: None,
+                    precision: 2,
+                    color,
+                },
+                autoscale: Some(true),
+                smoothing: None,
+                quantize_step: Some(0.0),
+                candlestick: false,
+                style: CurveStyle::Solid,
+                line_smoothing: LineSmoothing::Linear,
+                precision: None,
+                group: Some(AUTO_CURVE_GROUP.to_owned()),
+                z_index: 0,
+                shading: None,
+                enabled: true,
+            });
+        }
+        self.curves.len() != before
+    }
 }
 
 /// Default values for curve configuration options.
 ///
 /// These values are used when individual curves don't specify their own settings.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurveDefaults {
     /// Default autoscale setting for curves
     pub autoscale: bool,
@@ -260,12 +2039,53 @@ pub struct CurveDefaults {
     pub smoothing: f32,
     /// Default quantization step for curve values
     pub quantize_step: f32,
+    /// Default shading past a threshold, applied to every curve without its
+    /// own (None = no shading)
+    pub shading: Option<ThresholdShading>,
+}
+
+/// Shading of the area between a curve and a threshold it crosses.
+///
+/// Makes the time spent over a budget visible as a colored area, e.g.
+/// everything above 16.6 ms of frame time tinted red.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThresholdShading {
+    /// Value the curve is shaded past, in the curve's units
+    pub threshold: f32,
+    /// Shade where the curve drops below the threshold instead of above it,
+    /// for metrics where lower is worse such as FPS
+    pub below: bool,
+    /// Color of the shaded area; use a low alpha to keep the curves readable
+    pub color: Color,
+}
+
+impl ThresholdShading {
+    /// Shade where the curve rises above `threshold`
+    pub fn above(threshold: f32, color: Color) -> Self {
+        Self {
+            threshold,
+            below: false,
+            color,
+        }
+    }
+
+    /// Shade where the curve drops below `threshold`
+    pub fn below(threshold: f32, color: Color) -> Self {
+        Self {
+            threshold,
+            below: true,
+            color,
+        }
+    }
 }
 
 /// Bar scaling mode determines how the bar range is calculated.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BarScaleMode {
     /// Fixed range using min_value and max_value (default behavior)
+    #[default]
     Fixed,
     /// Automatic range adjustment based on historical data
     Auto {
@@ -287,21 +2107,18 @@ pub enum BarScaleMode {
     },
 }
 
-impl Default for BarScaleMode {
-    fn default() -> Self {
-        Self::Fixed
-    }
-}
-
 /// Configuration for a single performance bar.
 ///
 /// Each bar represents one metric displayed as a horizontal progress indicator.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BarConfig {
     /// The metric this bar represents (ID, label, color, etc.)
     pub metric: MetricDefinition,
     /// Whether to show numeric value and unit (None = use bars default)
     pub show_value: Option<bool>,
+    /// Decimal places for this bar's value label (None = use metric precision)
+    pub precision: Option<u32>,
     /// Minimum value for bar normalization (0% fill) - used in Fixed mode or as hard limit
     pub min_value: f32,
     /// Maximum value for bar normalization (100% fill) - used in Fixed mode or as hard limit
@@ -312,13 +2129,109 @@ pub struct BarConfig {
     pub min_limit: Option<f32>,
     /// Hard maximum limit (values above this are clamped) - optional override
     pub max_limit: Option<f32>,
+    /// Number of grid columns this bar occupies (clamped to the column count)
+    pub column_span: u32,
+    /// Relative width weight versus other bars in the same row (1.0 = even share)
+    pub width_weight: f32,
+    /// Where the bar fill starts from
+    pub fill: BarFill,
+    /// Window of a rolling average drawn as a second, thinner bar below the
+    /// value in the same slot (None = value only)
+    pub rolling_average: Option<Duration>,
+    /// Arrow next to the value showing whether it is rising or falling
+    /// (None = no arrow)
+    pub trend: Option<BarTrend>,
+    /// Background color of this bar (None = the grid's background or row tint)
+    pub bg_color: Option<Color>,
+}
+
+impl Default for BarConfig {
+    /// A fixed 0-100 bar filled from the left, taking one column
+    fn default() -> Self {
+        Self {
+            metric: MetricDefinition::default(),
+            show_value: None,
+            precision: None,
+            min_value: 0.0,
+            max_value: 100.0,
+            scale_mode: BarScaleMode::Fixed,
+            min_limit: None,
+            max_limit: None,
+            column_span: 1,
+            width_weight: 1.0,
+            fill: BarFill::Start,
+            rolling_average: None,
+            trend: None,
+            bg_color: None,
+        }
+    }
+}
+
+/// Short-term trend arrow of a bar, see [`BarConfig::trend`].
+///
+/// The slope of the metric is fitted over `window`; it counts as rising or
+/// falling once it changes the value by more than `sensitivity` times the
+/// bar's current range per second.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BarTrend {
+    /// Time span the slope is fitted over
+    pub window: Duration,
+    /// Fraction of the bar's range per second past which the value is
+    /// rising or falling (lower = more sensitive)
+    pub sensitivity: f32,
+    /// Shown while the value rises
+    pub rising: String,
+    /// Shown while the value falls
+    pub falling: String,
+    /// Shown while the value is steady (empty = nothing)
+    pub steady: String,
+}
+
+impl Default for BarTrend {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(2),
+            sensitivity: 0.05,
+            rising: "^".into(),
+            falling: "v".into(),
+            steady: "=".into(),
+        }
+    }
+}
+
+impl BarTrend {
+    /// Symbol for a metric changing by `slope` per second on a bar spanning `range`
+    pub fn symbol(&self, slope: f32, range: f32) -> &str {
+        let threshold = self.sensitivity.max(0.0) * range.abs();
+        if slope > threshold {
+            &self.rising
+        } else if slope < -threshold {
+            &self.falling
+        } else {
+            &self.steady
+        }
+    }
+}
+
+/// Origin of a bar's fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BarFill {
+    /// Fill from the left edge (the range minimum) up to the value
+    #[default]
+    Start,
+    /// Fill from the position of zero within the range towards the value,
+    /// extending left for negative and right for positive values
+    Center,
 }
 
 /// Definition of a performance metric for display purposes.
 ///
 /// This structure defines how a metric should be presented in the HUD,
 /// including its visual appearance and formatting options.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetricDefinition {
     /// Unique identifier for this metric (must match provider metric_id)
     pub id: String,
@@ -327,6 +2240,7 @@ pub struct MetricDefinition {
     /// Unit string to show after values (e.g., "ms", "fps", "%")
     pub unit: Option<String>,
     /// Number of decimal places to display in values
+    /// (graph curves and bars can override this individually)
     pub precision: u32,
     /// Color for this metric's curve/bar
     pub color: Color,
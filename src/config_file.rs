@@ -0,0 +1,237 @@
+//! HUD layout loaded from a RON file.
+//!
+//! With the `config-file` feature, [`BevyPerfHudPlugin::from_config_file`]
+//! loads a [`PerfHudConfigFile`] through the asset server and inserts it as
+//! [`PerfHudSettings`], replacing settings the app inserted itself. The file
+//! uses the serialized form of the settings (see the `serde` feature), so a
+//! customized layout can be saved with `ron::ser::to_string_pretty` and loaded
+//! back. When the asset server watches for changes (bevy's `file_watcher`
+//! feature), saving the file rebuilds the HUD with the new layout, so colors
+//! and layout can be tweaked without recompiling.
+//!
+//! ```ron
+//! (
+//!     anchor: TopLeft,
+//!     origin: (16.0, 16.0),
+//!     graph: (
+//!         size: (300.0, 80.0),
+//!         curves: [(
+//!             metric: (
+//!                 id: "fps",
+//!                 label: "FPS:",
+//!                 unit: "fps",
+//!                 precision: 0,
+//!                 color: Srgba((red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0)),
+//!             ),
+//!             candlestick: false,
+//!             style: Solid,
+//!             line_smoothing: Linear,
+//!             z_index: 0,
+//!             enabled: true,
+//!         )],
+//!     ),
+//! )
+//! ```
+//!
+//! Fields left out take their default values, and options may be written
+//! without `Some(..)`.
+//!
+//! [`BevyPerfHudPlugin::from_config_file`]: crate::BevyPerfHudPlugin::from_config_file
+
+use std::fmt;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{
+        io::Reader, Asset, AssetApp, AssetEvent, AssetLoader, AssetServer, Assets, Handle,
+        LoadContext,
+    },
+    ecs::{
+        event::EventReader,
+        schedule::IntoScheduleConfigs,
+        system::{Commands, Res},
+        world::World,
+    },
+    log::info,
+    prelude::Resource,
+    reflect::TypePath,
+};
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+use crate::{
+    config::PerfHudSettings,
+    events::PerfHudCommand,
+    resources::{FrameUnitToggle, HudHandles},
+    PerfHudSystems,
+};
+
+/// HUD settings as read from a config file.
+///
+/// The file holds a serialized [`PerfHudSettings`]; fields left out of it,
+/// and of its `graph` and `bars` sections, take their default values.
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct PerfHudConfigFile(pub PerfHudSettings);
+
+impl PerfHudConfigFile {
+    /// Parse a config file from RON text
+    pub fn from_ron(text: &[u8]) -> Result<Self, PerfHudConfigError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_bytes(text)
+            .map_err(PerfHudConfigError::Ron)
+    }
+}
+
+/// Error loading a [`PerfHudConfigFile`].
+#[derive(Debug)]
+pub enum PerfHudConfigError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file is not a valid config
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for PerfHudConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "cannot read perf HUD config: {err}"),
+            Self::Ron(err) => write!(f, "invalid perf HUD config: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PerfHudConfigError {}
+
+/// Asset loader of [`PerfHudConfigFile`]s from `.ron` files.
+#[derive(Default)]
+pub struct PerfHudConfigLoader;
+
+impl AssetLoader for PerfHudConfigLoader {
+    type Asset = PerfHudConfigFile;
+    type Settings = ();
+    type Error = PerfHudConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(PerfHudConfigError::Io)?;
+        PerfHudConfigFile::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Resource holding the config file the HUD layout is loaded from.
+#[derive(Resource, Debug, Clone)]
+pub struct PerfHudConfigHandle(pub Handle<PerfHudConfigFile>);
+
+/// Plugin loading the HUD layout from a config file, added by
+/// [`BevyPerfHudPlugin::from_config_file`](crate::BevyPerfHudPlugin::from_config_file).
+///
+/// Must be added after the asset plugin.
+pub struct PerfHudConfigFilePlugin {
+    /// Asset path of the config file
+    pub path: String,
+}
+
+impl Plugin for PerfHudConfigFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<PerfHudConfigFile>()
+            .init_asset_loader::<PerfHudConfigLoader>()
+            .add_systems(Update, apply_hud_config_file.before(PerfHudSystems));
+        let handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<PerfHudConfigFile>(self.path.clone());
+        app.insert_resource(PerfHudConfigHandle(handle));
+    }
+}
+
+/// System inserting the config file as [`PerfHudSettings`] once it is loaded
+/// and whenever it changes, rebuilding a spawned HUD.
+pub fn apply_hud_config_file(
+    mut events: EventReader<AssetEvent<PerfHudConfigFile>>,
+    config: Res<PerfHudConfigHandle>,
+    files: Res<Assets<PerfHudConfigFile>>,
+    mut commands: Commands,
+) {
+    let id = config.0.id();
+    let changed = events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(id) || event.is_modified(id));
+    let Some(file) = files.get(id).filter(|_| changed).cloned() else {
+        return;
+    };
+    commands.queue(move |world: &mut World| {
+        world.insert_resource(file.0);
+        world.insert_resource(FrameUnitToggle::default());
+        if world.contains_resource::<HudHandles>() {
+            info!("perf HUD config file changed, rebuilding the HUD");
+            world.send_event(PerfHudCommand::Rebuild);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{color::Color, math::Vec2};
+
+    use super::*;
+    use crate::config::HudAnchor;
+
+    #[test]
+    fn test_missing_fields_take_default_values() {
+        let file = PerfHudConfigFile::from_ron(
+            br#"(
+                origin: (4.0, 8.0),
+                graph: (curves: [(
+                    metric: (
+                        id: "fps",
+                        label: "FPS",
+                        precision: 0,
+                        color: Srgba((red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0)),
+                    ),
+                    candlestick: false,
+                    style: Solid,
+                    line_smoothing: CatmullRom,
+                    z_index: 0,
+                    enabled: true,
+                )]),
+            )"#,
+        )
+        .unwrap();
+        let settings = file.0;
+        let defaults = PerfHudSettings::default();
+
+        assert_eq!(settings.origin, Vec2::new(4.0, 8.0));
+        assert_eq!(settings.anchor, HudAnchor::TopLeft);
+        assert_eq!(settings.graph.size, defaults.graph.size);
+        assert_eq!(settings.graph.curves.len(), 1);
+        assert_eq!(
+            settings.graph.curves[0].metric.label.as_deref(),
+            Some("FPS")
+        );
+        assert_eq!(
+            settings.graph.curves[0].metric.color,
+            Color::srgb(1.0, 0.0, 0.0)
+        );
+        assert_eq!(settings.bars.bars.len(), defaults.bars.bars.len());
+    }
+
+    #[test]
+    fn test_invalid_field_is_an_error() {
+        let result = PerfHudConfigFile::from_ron(br#"(bars: (bars: [(metric: (id: "fps"))]))"#);
+        assert!(matches!(result, Err(PerfHudConfigError::Ron(_))));
+    }
+}
@@ -12,6 +12,15 @@ pub const MAX_CURVES: usize = 6;
 /// Number of Vec4 elements needed to pack all samples for shader
 pub const SAMPLES_VEC4: usize = MAX_SAMPLES / 4;
 
+/// Number of UVec4 elements needed to pack one missing-sample bit per sample for shader
+pub const MISSING_VEC4: usize = MAX_SAMPLES / 128;
+
+/// Maximum number of bars that can be displayed in the bar grid
+pub const MAX_BARS: usize = 32;
+
+/// Number of Vec4 elements needed to pack all bar values for shader
+pub const BARS_VEC4: usize = MAX_BARS / 4;
+
 /// Metric ID for system-wide CPU usage percentage
 pub const SYSTEM_CPU_USAGE_ID: &str = "system/cpu_usage";
 
@@ -23,3 +32,98 @@ pub const PROCESS_CPU_USAGE_ID: &str = "process/cpu_usage";
 
 /// Metric ID for process-specific memory usage in bytes
 pub const PROCESS_MEM_USAGE_ID: &str = "process/mem_usage";
+
+/// Metric ID for used system memory in GiB
+pub const SYSTEM_MEM_USED_ID: &str = "system/mem_used_gb";
+
+/// Metric ID for total system memory in GiB
+pub const SYSTEM_MEM_TOTAL_ID: &str = "system/mem_total_gb";
+
+/// Metric ID for process-specific resident memory in MiB
+pub const PROCESS_MEM_RSS_ID: &str = "process/mem_rss_mb";
+
+/// Metric ID for the number of logical CPU cores
+pub const SYSTEM_CPU_CORE_COUNT_ID: &str = "system/cpu_core_count";
+
+/// Metric ID prefix for per-core CPU usage percentage, followed by the core index
+pub const SYSTEM_CPU_CORE_USAGE_PREFIX: &str = "system/cpu_core_usage/";
+
+/// Metric ID for the number of threads in the compute task pool
+pub const TASK_POOL_COMPUTE_THREADS_ID: &str = "task_pool/compute/threads";
+
+/// Metric ID for the queue latency of the compute task pool in milliseconds
+pub const TASK_POOL_COMPUTE_LATENCY_ID: &str = "task_pool/compute/queue_latency_ms";
+
+/// Metric ID for the number of threads in the async compute task pool
+pub const TASK_POOL_ASYNC_COMPUTE_THREADS_ID: &str = "task_pool/async_compute/threads";
+
+/// Metric ID for the queue latency of the async compute task pool in milliseconds
+pub const TASK_POOL_ASYNC_COMPUTE_LATENCY_ID: &str = "task_pool/async_compute/queue_latency_ms";
+
+/// Metric ID for the number of threads in the IO task pool
+pub const TASK_POOL_IO_THREADS_ID: &str = "task_pool/io/threads";
+
+/// Metric ID for the queue latency of the IO task pool in milliseconds
+pub const TASK_POOL_IO_LATENCY_ID: &str = "task_pool/io/queue_latency_ms";
+
+/// Metric ID for the length of one fixed timestep in milliseconds
+pub const FIXED_DELTA_ID: &str = "fixed/delta_ms";
+
+/// Metric ID for the number of fixed steps run during the last frame
+pub const FIXED_STEPS_PER_FRAME_ID: &str = "fixed/steps_per_frame";
+
+/// Metric ID for the time accumulated towards the next fixed step in milliseconds
+pub const FIXED_OVERSTEP_ID: &str = "fixed/overstep_ms";
+
+/// Metric ID for the time spent in fixed steps during the last frame in milliseconds
+pub const FIXED_UPDATE_TIME_ID: &str = "fixed/update_time_ms";
+
+/// Metric ID for the approximate time from an input event to the presentation
+/// of the first frame reacting to it in milliseconds, recorded by
+/// [`InputLatencyDiagnosticsPlugin`](crate::InputLatencyDiagnosticsPlugin)
+pub const INPUT_LATENCY_ID: &str = "input_latency_ms";
+
+/// Metric ID prefix for the execution time in milliseconds of the system at a
+/// rank on the latest frame, followed by the zero-based rank (slowest first),
+/// recorded by [`SystemTimingsPlugin`](crate::SystemTimingsPlugin)
+pub const SYSTEM_TIME_RANK_PREFIX: &str = "system_time/top/";
+
+/// Metric ID for the number of loaded meshes
+pub const ASSET_MESH_COUNT_ID: &str = "assets/mesh_count";
+
+/// Metric ID for the estimated memory of the loaded meshes in MiB
+pub const ASSET_MESH_MEMORY_ID: &str = "assets/mesh_memory_mb";
+
+/// Metric ID for the number of loaded images
+pub const ASSET_IMAGE_COUNT_ID: &str = "assets/image_count";
+
+/// Metric ID for the estimated memory of the loaded images in MiB
+pub const ASSET_IMAGE_MEMORY_ID: &str = "assets/image_memory_mb";
+
+/// Metric ID for the number of loaded standard materials
+pub const ASSET_MATERIAL_COUNT_ID: &str = "assets/material_count";
+
+/// Metric ID for the estimated memory of the loaded standard materials in MiB
+pub const ASSET_MATERIAL_MEMORY_ID: &str = "assets/material_memory_mb";
+
+/// Metric ID for the number of entities visible in any view, recorded by
+/// [`RenderStatsDiagnosticsPlugin`](crate::RenderStatsDiagnosticsPlugin)
+pub const RENDER_VISIBLE_ENTITIES_ID: &str = "render/visible_entities";
+
+/// Metric ID for the number of visible mesh instances, one draw each before
+/// batching, recorded by [`RenderStatsDiagnosticsPlugin`](crate::RenderStatsDiagnosticsPlugin)
+pub const RENDER_MESH_DRAWS_ID: &str = "render/mesh_draws";
+
+/// Metric ID for the number of triangles of the visible meshes, recorded by
+/// [`RenderStatsDiagnosticsPlugin`](crate::RenderStatsDiagnosticsPlugin)
+pub const RENDER_TRIANGLES_ID: &str = "render/triangles";
+
+/// Metric ID for the number of window and input events sent during the last frame
+pub const WINDOW_EVENTS_ID: &str = "events/window";
+
+/// Metric ID for the number of asset events (loads, modifications, removals)
+/// of images, meshes, fonts and shaders sent during the last frame
+pub const ASSET_EVENTS_ID: &str = "events/assets";
+
+/// Metric ID for the number of displayed metrics whose provider failed on the latest frame
+pub const PROVIDER_ERRORS_ID: &str = "perf_hud/provider_errors";
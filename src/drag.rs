@@ -0,0 +1,153 @@
+//! Moving the HUD with the mouse.
+//!
+//! Insert [`HudDraggable`] on the HUD root to let players drag the HUD by
+//! its graph out of the way of whatever they are looking at.
+
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Query, Res, ResMut},
+    },
+    input::{mouse::MouseButton, ButtonInput},
+    prelude::*,
+    ui::{ComputedNode, Node, Val},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    resources::{HudHandles, HudPauseState},
+};
+
+/// Lets the HUD be dragged with the left mouse button.
+///
+/// Insert on the HUD root ([`HudHandles::root`]). Dragging starts on the
+/// graph, or anywhere on the HUD when it has no graph. The position the HUD
+/// was dragged to is kept here and carried over when the HUD is rebuilt.
+/// Ignored for HUDs embedded in a [`PerfHudSettings::parent`] node.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::{HudDraggable, HudHandles};
+///
+/// fn make_hud_draggable(mut commands: Commands, handles: Res<HudHandles>) {
+///     if let Some(root) = handles.root {
+///         commands.entity(root).insert(HudDraggable::default());
+///     }
+/// }
+///
+/// App::new().add_systems(
+///     Update,
+///     make_hud_draggable.run_if(resource_added::<HudHandles>),
+/// );
+/// ```
+#[derive(Component, Debug, Clone, Default)]
+pub struct HudDraggable {
+    /// Position the HUD was last dragged to, in logical window pixels
+    /// (None = not dragged yet)
+    pub position: Option<Vec2>,
+    /// Offset from the HUD's top-left corner to the cursor while dragging
+    grab: Option<Vec2>,
+}
+
+impl HudDraggable {
+    /// Whether the HUD is being dragged right now
+    pub fn is_dragging(&self) -> bool {
+        self.grab.is_some()
+    }
+
+    /// Copy to insert on a rebuilt HUD, keeping the position but not an
+    /// ongoing drag
+    pub(crate) fn carried_over(&self) -> Self {
+        Self {
+            position: self.position,
+            grab: None,
+        }
+    }
+}
+
+/// System moving a [`HudDraggable`] HUD root with the mouse.
+#[allow(clippy::too_many_arguments)]
+pub fn drag_hud(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    pause: Res<HudPauseState>,
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    areas: Query<(&ComputedNode, &GlobalTransform)>,
+    mut roots: Query<(&mut Node, &mut HudDraggable)>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Drag);
+    // Embedded HUDs are laid out by their parent
+    if settings.parent.is_some() {
+        return;
+    }
+    let Some(root) = handles.root else {
+        return;
+    };
+    let Ok((mut node, mut draggable)) = roots.get_mut(root) else {
+        return;
+    };
+
+    // Restore the position of a HUD dragged before it was rebuilt
+    if draggable.is_added() {
+        if let Some(position) = draggable.position {
+            node.left = Val::Px(position.x);
+            node.top = Val::Px(position.y);
+        }
+    }
+
+    let Some(mouse) = mouse else {
+        return;
+    };
+    if !mouse.pressed(MouseButton::Left) {
+        if draggable.grab.is_some() {
+            draggable.grab = None;
+        }
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        // Hit-test in physical pixels, which UI layout works in
+        let Some(physical_cursor) = window.physical_cursor_position() else {
+            return;
+        };
+        // Dragging across a zoomable frozen graph selects a range instead
+        let zooming = settings.interaction.zoom && pause.is_graph_frozen();
+        let area = match handles.graph_entity {
+            Some(_) if zooming => return,
+            Some(graph) => graph,
+            None => root,
+        };
+        let hit = areas.get(area).is_ok_and(|(computed, transform)| {
+            Rect::from_center_size(transform.translation().truncate(), computed.size())
+                .contains(physical_cursor)
+        });
+        if hit {
+            let current = match (node.left, node.top) {
+                (Val::Px(x), Val::Px(y)) => Vec2::new(x, y),
+                _ => settings.origin,
+            };
+            draggable.grab = Some(cursor - current);
+        }
+    }
+
+    let Some(grab) = draggable.grab else {
+        return;
+    };
+    let position = cursor - grab;
+    if draggable.position != Some(position) {
+        draggable.position = Some(position);
+        node.left = Val::Px(position.x);
+        node.top = Val::Px(position.y);
+    }
+}
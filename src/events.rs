@@ -0,0 +1,186 @@
+//! Events for controlling the performance HUD at runtime.
+//!
+//! Send these events from any system to operate the HUD without touching
+//! its internal entities and resources directly.
+
+use std::{path::PathBuf, time::Duration};
+
+use bevy::prelude::{Component, Event};
+
+use crate::config::{BudgetLevel, MetricDefinition};
+
+/// Commands that can be sent to the performance HUD.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::PerfHudCommand;
+///
+/// fn rebuild_on_f5(keys: Res<ButtonInput<KeyCode>>, mut hud: EventWriter<PerfHudCommand>) {
+///     if keys.just_pressed(KeyCode::F5) {
+///         hud.write(PerfHudCommand::Rebuild);
+///     }
+/// }
+/// ```
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum PerfHudCommand {
+    /// Despawn the HUD and spawn it again from the current settings and providers
+    Rebuild,
+    /// Freeze or resume the graph, bars and labels at their current values
+    TogglePause,
+    /// Freeze or resume only the graph: no new samples are added to its
+    /// history, while the bars and labels keep updating
+    ToggleGraphFreeze,
+    /// Show or hide all graph curves tagged with this group
+    ToggleCurveGroup(String),
+    /// Enable or disable the curve at this index in
+    /// [`GraphSettings::curves`](crate::GraphSettings)
+    ToggleCurve(usize),
+    /// Switch the graph between the live and trend views
+    ToggleGraphView,
+    /// Show the primary frame metric in the other unit: the curves, bars and
+    /// statistics rows of whichever of `fps` and `frame_time_ms` is shown
+    /// first switch to the other one, see
+    /// [`PerfHudSettings::frame_unit_counterpart`](crate::PerfHudSettings::frame_unit_counterpart).
+    /// Recorded graph samples, shading thresholds and explicit budgets are
+    /// converted, and curves already showing the other metric are hidden.
+    /// Toggling again restores them, see [`FrameUnitToggle`](crate::FrameUnitToggle)
+    ToggleFrameUnit,
+    /// Bind a graph curve to another metric, e.g. to reuse a "debug slot"
+    RebindCurve {
+        /// Index of the curve in [`GraphSettings::curves`](crate::GraphSettings)
+        curve: usize,
+        /// Metric the curve shows from now on
+        metric: MetricDefinition,
+        /// What happens to the samples recorded for the previous metric
+        history: RebindHistory,
+    },
+    /// Write a bug report bundle (raw samples CSV, spike log, settings,
+    /// build info and a screenshot) into this folder, see
+    /// [`PerfHudBundleExported`](crate::PerfHudBundleExported)
+    ExportBundle(PathBuf),
+    /// Write the samples recorded with
+    /// [`PerfHudSettings::chrome_trace`](crate::PerfHudSettings) to this file
+    /// as a Chrome trace
+    ExportChromeTrace(PathBuf),
+    /// Start recording every metric sample, dropping any previous recording,
+    /// see [`SampleRecorder`](crate::SampleRecorder)
+    StartSampleRecording,
+    /// Stop recording and write the recorded samples to this file as CSV
+    SaveSampleRecording(PathBuf),
+    /// Start recording if no recording runs, otherwise save it to this file
+    /// as with [`SaveSampleRecording`](Self::SaveSampleRecording)
+    ToggleSampleRecording(PathBuf),
+    /// Replace the settings with the next [`HudPreset`](crate::HudPreset)
+    /// after [`PerfHudSettings::preset`](crate::PerfHudSettings::preset) and
+    /// rebuild the HUD. The control strip and its files are kept
+    CyclePreset,
+    /// Replay samples saved with [`SaveSampleRecording`](Self::SaveSampleRecording)
+    /// from this file instead of sampling the providers, see
+    /// [`SampleReplay`](crate::SampleReplay)
+    ReplaySamples(PathBuf),
+    /// Stop replaying and sample the providers again
+    StopReplay,
+}
+
+/// What happens to a curve's history when it is bound to another metric.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RebindHistory {
+    /// Drop the old series; the curve starts over empty
+    #[default]
+    Clear,
+    /// Keep the old series, separated from the new one by a marker line
+    Marker,
+    /// Keep the old series and its marker, fading them out over the given duration
+    Crossfade(Duration),
+}
+
+/// Button of the HUD control strip, sending its command when pressed.
+///
+/// Spawned when [`PerfHudSettings::show_controls`](crate::PerfHudSettings) is set.
+#[derive(Component, Debug, Clone)]
+pub struct HudControlButton(pub PerfHudCommand);
+
+/// Sent when a watched metric turned critical and a capture was requested,
+/// see [`ScreenshotSettings`](crate::ScreenshotSettings).
+///
+/// The image and the snapshot are written asynchronously.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PerfHudScreenshot {
+    /// ID of the metric that turned critical
+    pub metric_id: String,
+    /// Value of the metric when the screenshot was triggered
+    pub value: f32,
+    /// Critical threshold of the metric's budget
+    pub threshold: f32,
+    /// File the screenshot is being saved to
+    pub path: PathBuf,
+    /// File the [`PerfHudSnapshot`](crate::PerfHudSnapshot) taken with the
+    /// screenshot is being saved to, as RON (None without the `serde` feature)
+    pub snapshot: Option<PathBuf>,
+}
+
+/// Sent when a metric's [`BudgetLevel`] changes, see
+/// [`PerfHudSettings::budget_thresholds`](crate::PerfHudSettings::budget_thresholds).
+///
+/// Alerts and threshold screenshots react to metrics turning critical;
+/// read it to hook other actions to the same transitions.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct MetricBudgetTransition {
+    /// ID of the metric whose level changed
+    pub metric_id: String,
+    /// Value of the metric on the sample that changed its level
+    pub value: f32,
+    /// Budget threshold crossed between the two levels
+    pub threshold: f32,
+    /// Level on the previous sample
+    pub previous: BudgetLevel,
+    /// Level on this sample
+    pub level: BudgetLevel,
+}
+
+/// A metric value pushed by game code or a plugin instead of being polled.
+///
+/// Send it whenever a value is measured, e.g. once per received network
+/// packet; the HUD reports the latest value of each metric until a newer one
+/// arrives. Several samples of a metric sent in the same frame are reported
+/// as their mean, with their minimum and maximum kept for candlestick curves
+/// and statistics. Samples with a timestamp older than one already applied for the
+/// same metric are dropped, so values measured out of order do not move the
+/// metric backwards. Ignored while a recording is replayed.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::MetricSample;
+///
+/// fn on_packet(mut samples: EventWriter<MetricSample>) {
+///     samples.write(MetricSample::new("net/latency_ms", 42.0));
+/// }
+/// ```
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    /// ID of the metric the value belongs to
+    pub metric_id: String,
+    /// Measured value
+    pub value: f32,
+    /// When the value was measured, e.g. `Time<Real>::elapsed` (None = now)
+    pub timestamp: Option<Duration>,
+}
+
+impl MetricSample {
+    /// Sample of `metric_id` measured now
+    pub fn new(metric_id: impl Into<String>, value: f32) -> Self {
+        Self {
+            metric_id: metric_id.into(),
+            value,
+            timestamp: None,
+        }
+    }
+
+    /// Set when the value was measured
+    pub fn at(mut self, timestamp: Duration) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
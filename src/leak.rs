@@ -0,0 +1,204 @@
+//! Heuristic detection of entity and memory leaks.
+//!
+//! With [`PerfHudSettings::leak_detection`](crate::PerfHudSettings) enabled,
+//! the entity count and process memory are sampled every few seconds and a
+//! line is fitted to each over a window of minutes. When both keep rising
+//! steadily and faster than the configured slopes, a warning is logged and a
+//! [`PerfHudLeakSuspected`] event is sent. This is only a heuristic: streaming
+//! in a large level for minutes looks the same as a leak.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::{
+        event::EventWriter,
+        system::{Res, ResMut},
+    },
+    log::{info, warn},
+    prelude::{Event, Resource},
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    constants::PROCESS_MEM_RSS_ID,
+    resources::SampledValues,
+};
+
+/// Metric ID of the built-in entity count provider
+const ENTITY_COUNT_ID: &str = "entity_count";
+
+/// Sent when the entity count and process memory both keep rising.
+///
+/// Sent once when the leak becomes suspected, and again only after both
+/// trends stopped meeting the criteria in between.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PerfHudLeakSuspected {
+    /// Trend of the entity count over the window
+    pub entities: LeakTrend,
+    /// Trend of the process memory over the window, in megabytes
+    pub memory: LeakTrend,
+}
+
+/// Trend of one metric over the leak detection window.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LeakTrend {
+    /// Change per minute of the line fitted to the samples
+    pub slope: f32,
+    /// How well the line fits the samples, from 0 (not at all) to 1 (exactly)
+    pub fit: f32,
+}
+
+impl LeakTrend {
+    /// Fit a line to `(minutes, value)` points by least squares
+    ///
+    /// Returns None for fewer than two points or points all at the same time.
+    pub fn fit(points: impl Iterator<Item = (f64, f64)> + Clone) -> Option<Self> {
+        let (count, sum_x, sum_y) = points.clone().fold((0.0, 0.0, 0.0), |(n, sx, sy), (x, y)| {
+            (n + 1.0, sx + x, sy + y)
+        });
+        if count < 2.0 {
+            return None;
+        }
+        let (mean_x, mean_y) = (sum_x / count, sum_y / count);
+        let (sxx, syy, sxy) = points.fold((0.0, 0.0, 0.0), |(sxx, syy, sxy), (x, y)| {
+            let (dx, dy) = (x - mean_x, y - mean_y);
+            (sxx + dx * dx, syy + dy * dy, sxy + dx * dy)
+        });
+        if sxx <= f64::EPSILON {
+            return None;
+        }
+        // A flat series has no trend to fit
+        let fit = if syy <= f64::EPSILON {
+            0.0
+        } else {
+            sxy * sxy / (sxx * syy)
+        };
+        Some(Self {
+            slope: (sxy / sxx) as f32,
+            fit: fit as f32,
+        })
+    }
+}
+
+/// Resource holding the leak detection samples and the latest trends.
+#[derive(Resource, Default)]
+pub struct LeakDetector {
+    /// Trend of the entity count (None until the window is filled)
+    pub entities: Option<LeakTrend>,
+    /// Trend of the process memory in megabytes (None until the window is filled)
+    pub memory: Option<LeakTrend>,
+    /// Whether a leak is currently suspected
+    pub suspected: bool,
+    /// Real time in seconds, entity count and memory of each sample in the window
+    samples: VecDeque<(f64, f32, f32)>,
+}
+
+/// System sampling the entity count and process memory and checking their
+/// trends for a leak.
+pub fn detect_leaks(
+    settings: Res<PerfHudSettings>,
+    samples: Res<SampledValues>,
+    time: Res<Time<Real>>,
+    mut detector: ResMut<LeakDetector>,
+    mut suspected: EventWriter<PerfHudLeakSuspected>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Leaks);
+    let config = &settings.leak_detection;
+    if !config.enabled {
+        if !detector.samples.is_empty() || detector.suspected {
+            *detector = LeakDetector::default();
+        }
+        return;
+    }
+
+    let now = time.elapsed_secs_f64();
+    let interval = config.sample_interval.as_secs_f64();
+    if detector
+        .samples
+        .back()
+        .is_some_and(|&(time, ..)| now - time < interval)
+    {
+        return;
+    }
+    let (Some(entities), Some(memory)) = (
+        samples.get_fresh(ENTITY_COUNT_ID),
+        samples.get_fresh(PROCESS_MEM_RSS_ID),
+    ) else {
+        return;
+    };
+    let window = config.window.as_secs_f64();
+    detector.samples.push_back((now, entities, memory));
+    while detector
+        .samples
+        .front()
+        .is_some_and(|&(time, ..)| now - time > window)
+    {
+        detector.samples.pop_front();
+    }
+
+    // Judge only a window's worth of samples, not the first minutes after startup
+    let span = detector
+        .samples
+        .front()
+        .map_or(0.0, |&(time, ..)| now - time);
+    if span + interval < window {
+        return;
+    }
+    let start = now - span;
+    let minutes = |time: f64| (time - start) / 60.0;
+    let trend = |value: fn(&(f64, f32, f32)) -> f32| {
+        LeakTrend::fit(
+            detector
+                .samples
+                .iter()
+                .map(move |sample| (minutes(sample.0), f64::from(value(sample)))),
+        )
+    };
+    let (Some(entities), Some(memory)) = (trend(|s| s.1), trend(|s| s.2)) else {
+        return;
+    };
+    detector.entities = Some(entities);
+    detector.memory = Some(memory);
+
+    let rising = |trend: LeakTrend, slope: f32| trend.slope > slope && trend.fit >= config.min_fit;
+    let leaking = rising(entities, config.entity_slope) && rising(memory, config.memory_slope);
+    if leaking && !detector.suspected {
+        warn!(
+            "possible leak: entity count rising by {:.1} and process memory by {:.2} MB per minute over the last {:.0} s",
+            entities.slope, memory.slope, window
+        );
+        suspected.write(PerfHudLeakSuspected { entities, memory });
+    } else if !leaking && detector.suspected {
+        info!("entity count and process memory stopped rising together");
+    }
+    detector.suspected = leaking;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_of_noisy_rise() {
+        let points = [(0.0, 10.0), (1.0, 12.0), (2.0, 13.0), (3.0, 16.0)];
+        let trend = LeakTrend::fit(points.into_iter()).unwrap();
+        assert!((trend.slope - 1.9).abs() < 1e-5);
+        assert!(trend.fit > 0.9 && trend.fit < 1.0);
+    }
+
+    #[test]
+    fn test_fit_of_flat_series_has_no_trend() {
+        let points = [(0.0, 5.0), (1.0, 5.0), (2.0, 5.0)];
+        assert_eq!(
+            LeakTrend::fit(points.into_iter()),
+            Some(LeakTrend {
+                slope: 0.0,
+                fit: 0.0
+            })
+        );
+        assert_eq!(LeakTrend::fit([(1.0, 5.0)].into_iter()), None);
+    }
+}
@@ -5,21 +5,133 @@
 //! - System resource usage bars (CPU, memory)
 //! - Custom metric tracking with extensible provider system
 //! - Configurable visual appearance and positioning
+//!
+//! Most applications only need [`prelude`]. Everything else re-exported at
+//! the crate root is public for advanced use; the HUD's systems and render
+//! materials are re-exported as well but hidden from the docs, since they
+//! are implementation details that may change in any release.
+//!
+//! The HUD is behind the default `hud` feature. Without it only the settings,
+//! events and extension traits are compiled, and the plugins and app
+//! extensions do nothing, so shipping builds can turn default features off
+//! and keep their call sites.
 
-mod bar_scale;
+// Settings, events and extension traits game code uses, with or without the HUD
+mod build_info;
 mod config;
 mod constants;
-mod plugin;
+mod events;
+mod localization;
+pub mod prelude;
 mod providers;
+
+#[cfg(feature = "hud")]
+mod alerts;
+#[cfg(feature = "hud")]
+mod bar_layout;
+#[cfg(feature = "hud")]
+mod bar_scale;
+#[cfg(feature = "hud")]
+mod budget;
+#[cfg(feature = "hud")]
+mod bundle;
+#[cfg(feature = "hud")]
+mod capacity;
+#[cfg(feature = "hud")]
+mod chrome_trace;
+#[cfg(feature = "hud")]
+mod compat;
+#[cfg(all(feature = "hud", feature = "config-file"))]
+mod config_file;
+#[cfg(feature = "hud")]
+mod drag;
+#[cfg(feature = "hud")]
+mod leak;
+#[cfg(feature = "hud")]
+mod long_frames;
+#[cfg(feature = "hud")]
+mod metric_stats;
+#[cfg(not(feature = "hud"))]
+mod noop;
+#[cfg(feature = "hud")]
+mod plugin;
+#[cfg(feature = "hud")]
 mod render;
+#[cfg(feature = "hud")]
+mod replay;
+#[cfg(feature = "hud")]
 mod resources;
+#[cfg(feature = "hud")]
+mod slow_frames;
+#[cfg(feature = "hud")]
+mod snapshot;
+#[cfg(feature = "hud")]
 mod systems;
+#[cfg(feature = "hud")]
+mod telemetry;
+#[cfg(all(feature = "hud", feature = "test-utils"))]
+mod test_utils;
+#[cfg(feature = "hud")]
+mod transition;
+#[cfg(feature = "hud")]
+mod zoom;
 
-pub use bar_scale::*;
+pub use build_info::*;
 pub use config::*;
 pub use constants::*;
-pub use plugin::BevyPerfHudPlugin;
+pub use events::*;
+pub use localization::*;
 pub use providers::*;
+
+#[cfg(feature = "hud")]
+pub use alerts::*;
+#[cfg(feature = "hud")]
+pub use bar_layout::*;
+#[cfg(feature = "hud")]
+pub use bar_scale::*;
+#[cfg(feature = "hud")]
+pub use budget::*;
+#[cfg(feature = "hud")]
+pub use bundle::*;
+#[cfg(feature = "hud")]
+pub use capacity::*;
+#[cfg(feature = "hud")]
+pub use chrome_trace::*;
+#[cfg(all(feature = "hud", feature = "config-file"))]
+pub use config_file::*;
+#[cfg(feature = "hud")]
+pub use drag::*;
+#[cfg(feature = "hud")]
+pub use leak::*;
+#[cfg(feature = "hud")]
+pub use long_frames::*;
+#[cfg(feature = "hud")]
+pub use metric_stats::*;
+#[cfg(not(feature = "hud"))]
+pub use noop::*;
+#[cfg(feature = "hud")]
+pub use plugin::{BevyPerfHudPlugin, PerfHudSystems};
+#[cfg(feature = "hud")]
+#[doc(hidden)]
 pub use render::*;
+#[cfg(feature = "hud")]
+pub use replay::*;
+#[cfg(feature = "hud")]
 pub use resources::*;
+#[cfg(feature = "hud")]
+pub use slow_frames::*;
+#[cfg(feature = "hud")]
+pub use snapshot::*;
+#[cfg(feature = "hud")]
+#[doc(hidden)]
 pub use systems::*;
+#[cfg(feature = "hud")]
+pub use systems::{sample_diagnostics, spawn_perf_hud};
+#[cfg(feature = "hud")]
+pub use telemetry::*;
+#[cfg(all(feature = "hud", feature = "test-utils"))]
+pub use test_utils::*;
+#[cfg(feature = "hud")]
+pub use transition::*;
+#[cfg(feature = "hud")]
+pub use zoom::*;
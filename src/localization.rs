@@ -0,0 +1,69 @@
+//! Localization hook for HUD label text.
+//!
+//! Insert a [`LabelLocalizer`] to translate metric labels and control button
+//! captions with any i18n crate, without rewriting the metric definitions.
+
+use bevy::prelude::Resource;
+
+/// Label ID of the pause button in the control strip
+pub const CONTROL_PAUSE_LABEL_ID: &str = "control/pause";
+
+/// Label ID of the record button in the control strip, while not recording
+pub const CONTROL_RECORD_LABEL_ID: &str = "control/record";
+
+/// Label ID of the record button in the control strip, while recording
+pub const CONTROL_STOP_RECORDING_LABEL_ID: &str = "control/stop_recording";
+
+/// Label ID of the export button in the control strip
+pub const CONTROL_EXPORT_LABEL_ID: &str = "control/export";
+
+/// Label ID of the preset button in the control strip
+pub const CONTROL_PRESET_LABEL_ID: &str = "control/preset";
+
+/// Label ID of the rebuild button in the control strip
+pub const CONTROL_REBUILD_LABEL_ID: &str = "control/rebuild";
+
+/// Label ID of the graph title
+pub const GRAPH_TITLE_LABEL_ID: &str = "graph/title";
+
+/// Label ID of the graph caption
+pub const GRAPH_CAPTION_LABEL_ID: &str = "graph/caption";
+
+/// Lookup taking a label ID and its untranslated text.
+type LabelLookup = Box<dyn Fn(&str, &str) -> String + Send + Sync>;
+
+/// Resource translating HUD labels.
+///
+/// Consulted whenever a label is spawned or updated with the label's ID
+/// (the metric ID for metric labels, one of the `CONTROL_*_LABEL_ID`
+/// constants for control buttons, or one of the `GRAPH_*_LABEL_ID` constants
+/// for the graph title and caption) and its untranslated text.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::LabelLocalizer;
+///
+/// App::new().insert_resource(LabelLocalizer::new(|id, default| match id {
+///     "fps" => "IPS:".to_owned(),
+///     _ => default.to_owned(),
+/// }));
+/// ```
+#[derive(Resource)]
+pub struct LabelLocalizer {
+    lookup: LabelLookup,
+}
+
+impl LabelLocalizer {
+    /// Create a localizer from a lookup taking the label ID and default text
+    pub fn new(lookup: impl Fn(&str, &str) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            lookup: Box::new(lookup),
+        }
+    }
+
+    /// Translate the label `id`, whose untranslated text is `default`
+    pub fn localize(&self, id: &str, default: &str) -> String {
+        (self.lookup)(id, default)
+    }
+}
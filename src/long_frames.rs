@@ -0,0 +1,76 @@
+//! Exclusion of abnormally long frames.
+//!
+//! A frame that takes seconds is almost never the game's doing: a debugger
+//! stopped at a breakpoint, or the OS put the machine to sleep. With
+//! [`PerfHudSettings::long_frames`](crate::PerfHudSettings) enabled, such a
+//! frame is left out of the graph history and autoscale, the statistics,
+//! alerts and exports, and the frame time diagnostics are restarted, so a
+//! single breakpoint does not skew the rest of the session.
+
+use std::time::Duration;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::system::{Res, ResMut},
+    log::info,
+    prelude::Resource,
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+};
+
+/// Resource tracking the frames excluded for taking abnormally long.
+#[derive(Resource, Debug, Default)]
+pub struct LongFrameState {
+    /// Whether the current frame is excluded
+    pub excluded: bool,
+    /// Number of frames excluded so far
+    pub count: u64,
+    /// Duration of the longest excluded frame
+    pub longest: Duration,
+}
+
+/// System checking whether the current frame took abnormally long.
+///
+/// Runs after the frame time diagnostics are measured, so their history can
+/// be restarted without the long frame in it.
+pub fn detect_long_frames(
+    settings: Res<PerfHudSettings>,
+    time: Res<Time<Real>>,
+    diagnostics: Option<ResMut<DiagnosticsStore>>,
+    mut state: ResMut<LongFrameState>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::LongFrames);
+    let config = &settings.long_frames;
+    let delta = time.delta();
+    state.excluded = config.enabled && delta > config.threshold;
+    if !state.excluded {
+        return;
+    }
+
+    state.count += 1;
+    state.longest = state.longest.max(delta);
+    if let Some(mut diagnostics) = diagnostics {
+        for path in [
+            &FrameTimeDiagnosticsPlugin::FRAME_TIME,
+            &FrameTimeDiagnosticsPlugin::FPS,
+        ] {
+            if let Some(diagnostic) = diagnostics.get_mut(path) {
+                diagnostic.clear_history();
+            }
+        }
+    }
+    info!(
+        "perf HUD: excluded a {:.1} s frame (debugger break or sleep?) from history and statistics",
+        delta.as_secs_f32()
+    );
+}
+
+/// Run condition: the current frame is not excluded for taking abnormally long.
+pub fn not_long_frame(state: Res<LongFrameState>) -> bool {
+    !state.excluded
+}
@@ -0,0 +1,290 @@
+//! Rolling statistics over fixed time windows per metric.
+//!
+//! Register analysis windows (e.g. 10 s, 60 s, 5 min) for any metric on the
+//! [`MetricStats`] resource; the plugin feeds every sampled value into them
+//! and keeps min/max/mean up to date incrementally.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use bevy::{
+    ecs::system::{Res, ResMut},
+    prelude::{DetectChanges, Resource},
+    time::{Real, Time},
+};
+
+use crate::{HudStage, HudUpdateCost, PerfHudSettings, SampledValues};
+
+/// Samples of one metric over a sliding time window.
+///
+/// Min and max are tracked with monotonic queues and the mean with a running
+/// sum, so each sample costs amortized O(1). Percentiles are computed on demand.
+#[derive(Debug, Clone)]
+pub struct StatsWindow {
+    /// Length of the window
+    pub duration: Duration,
+    samples: VecDeque<(f64, f32)>,
+    min_queue: VecDeque<(f64, f32)>,
+    max_queue: VecDeque<(f64, f32)>,
+    sum: f64,
+}
+
+impl StatsWindow {
+    /// Create an empty window of the given length
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            samples: VecDeque::new(),
+            min_queue: VecDeque::new(),
+            max_queue: VecDeque::new(),
+            sum: 0.0,
+        }
+    }
+
+    /// Add a sample taken at `time` (seconds) and drop samples older than the window
+    pub fn push(&mut self, time: f64, value: f32) {
+        self.push_range(time, value, value, value);
+    }
+
+    /// Add a sample folding several values measured at `time` (seconds): `value`
+    /// counts towards the mean and percentiles, `min` and `max` towards the
+    /// window's extremes. Drops samples older than the window.
+    pub fn push_range(&mut self, time: f64, value: f32, min: f32, max: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        let (min, max) = (min.min(value), max.max(value));
+
+        self.samples.push_back((time, value));
+        self.sum += value as f64;
+        while self.min_queue.back().is_some_and(|&(_, v)| v >= min) {
+            self.min_queue.pop_back();
+        }
+        self.min_queue.push_back((time, min));
+        while self.max_queue.back().is_some_and(|&(_, v)| v <= max) {
+            self.max_queue.pop_back();
+        }
+        self.max_queue.push_back((time, max));
+
+        // Evict everything that fell out of the window
+        let cutoff = time - self.duration.as_secs_f64();
+        while let Some(&(t, v)) = self.samples.front() {
+            if t >= cutoff {
+                break;
+            }
+            self.samples.pop_front();
+            self.sum -= v as f64;
+        }
+        while self.min_queue.front().is_some_and(|&(t, _)| t < cutoff) {
+            self.min_queue.pop_front();
+        }
+        while self.max_queue.front().is_some_and(|&(t, _)| t < cutoff) {
+            self.max_queue.pop_front();
+        }
+    }
+
+    /// Number of samples currently in the window
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether the window holds no samples
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Smallest value in the window
+    pub fn min(&self) -> Option<f32> {
+        self.min_queue.front().map(|&(_, v)| v)
+    }
+
+    /// Largest value in the window
+    pub fn max(&self) -> Option<f32> {
+        self.max_queue.front().map(|&(_, v)| v)
+    }
+
+    /// Arithmetic mean of the values in the window
+    pub fn mean(&self) -> Option<f32> {
+        (!self.samples.is_empty()).then(|| (self.sum / self.samples.len() as f64) as f32)
+    }
+
+    /// Least-squares slope of the values in the window, in units per second
+    pub fn slope(&self) -> Option<f32> {
+        let &(start, _) = self.samples.front()?;
+        let n = self.samples.len() as f64;
+        // Times relative to the oldest sample keep the sums well conditioned
+        let (mut sum_t, mut sum_v, mut sum_tt, mut sum_tv) = (0.0, 0.0, 0.0, 0.0);
+        for &(t, v) in &self.samples {
+            let (t, v) = (t - start, v as f64);
+            sum_t += t;
+            sum_v += v;
+            sum_tt += t * t;
+            sum_tv += t * v;
+        }
+        let denominator = n * sum_tt - sum_t * sum_t;
+        (denominator > f64::EPSILON).then(|| ((n * sum_tv - sum_t * sum_v) / denominator) as f32)
+    }
+
+    /// Value at the given percentile (0.0-100.0) of the window
+    pub fn percentile(&self, percentile: f32) -> Option<f32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut values: Vec<f32> = self.samples.iter().map(|&(_, v)| v).collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let idx = ((percentile.clamp(0.0, 100.0) / 100.0) * (values.len() - 1) as f32) as usize;
+        Some(values[idx.min(values.len() - 1)])
+    }
+}
+
+/// Resource holding the registered analysis windows of each metric.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::MetricStats;
+///
+/// fn register_windows(mut stats: ResMut<MetricStats>) {
+///     stats.register_window("fps", Duration::from_secs(10));
+///     stats.register_window("fps", Duration::from_secs(60));
+/// }
+///
+/// fn report(stats: Res<MetricStats>) {
+///     if let Some(window) = stats.window("fps", Duration::from_secs(60)) {
+///         info!("fps p5 over 60s: {:?}", window.percentile(5.0));
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct MetricStats {
+    /// Map from metric ID to its windows
+    windows: HashMap<String, Vec<StatsWindow>>,
+}
+
+impl MetricStats {
+    /// Register a window for a metric; registering the same length twice has no effect
+    pub fn register_window(&mut self, metric_id: &str, duration: Duration) {
+        let windows = self.windows.entry(metric_id.to_owned()).or_default();
+        if !windows.iter().any(|w| w.duration == duration) {
+            windows.push(StatsWindow::new(duration));
+        }
+    }
+
+    /// Remove a previously registered window
+    pub fn unregister_window(&mut self, metric_id: &str, duration: Duration) {
+        if let Some(windows) = self.windows.get_mut(metric_id) {
+            windows.retain(|w| w.duration != duration);
+        }
+    }
+
+    /// Get the window of the given length for a metric
+    pub fn window(&self, metric_id: &str, duration: Duration) -> Option<&StatsWindow> {
+        self.windows
+            .get(metric_id)?
+            .iter()
+            .find(|w| w.duration == duration)
+    }
+
+    /// Get all windows registered for a metric
+    pub fn windows(&self, metric_id: &str) -> &[StatsWindow] {
+        self.windows.get(metric_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Feed the current value of every metric with windows into them
+    pub fn record(&mut self, time: f64, samples: &SampledValues) {
+        for (metric_id, windows) in &mut self.windows {
+            let (Some(value), Some((min, max))) =
+                (samples.get(metric_id), samples.range(metric_id))
+            else {
+                continue;
+            };
+            for window in windows {
+                window.push_range(time, value, min, max);
+            }
+        }
+    }
+}
+
+/// System that feeds the latest sampled values into the registered windows.
+///
+/// Also registers the windows of the rolling averages shown by bars
+/// ([`BarConfig::rolling_average`](crate::BarConfig)) and trend arrows
+/// ([`BarConfig::trend`](crate::BarConfig)) whenever the settings change.
+pub fn update_metric_stats(
+    settings: Res<PerfHudSettings>,
+    time: Res<Time<Real>>,
+    samples: Res<SampledValues>,
+    mut stats: ResMut<MetricStats>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Stats);
+    if settings.is_changed() {
+        for bar in &settings.bars.bars {
+            if let Some(window) = bar.rolling_average {
+                stats.register_window(&bar.metric.id, window);
+            }
+            if let Some(trend) = &bar.trend {
+                stats.register_window(&bar.metric.id, trend.window);
+            }
+        }
+    }
+    stats.record(time.elapsed_secs_f64(), &samples);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_stats() {
+        let mut window = StatsWindow::new(Duration::from_secs(10));
+        for (t, v) in [(0.0, 5.0), (1.0, 1.0), (2.0, 9.0), (3.0, 3.0)] {
+            window.push(t, v);
+        }
+
+        assert_eq!(window.len(), 4);
+        assert_eq!(window.min(), Some(1.0));
+        assert_eq!(window.max(), Some(9.0));
+        assert_eq!(window.mean(), Some(4.5));
+        assert_eq!(window.percentile(100.0), Some(9.0));
+    }
+
+    #[test]
+    fn test_window_eviction() {
+        let mut window = StatsWindow::new(Duration::from_secs(2));
+        window.push(0.0, 100.0);
+        window.push(1.0, 10.0);
+        window.push(3.0, 20.0);
+
+        // The 100.0 sample is older than 2 seconds and must be gone
+        assert_eq!(window.len(), 2);
+        assert_eq!(window.max(), Some(20.0));
+        assert_eq!(window.min(), Some(10.0));
+        assert_eq!(window.mean(), Some(15.0));
+    }
+
+    #[test]
+    fn test_window_slope() {
+        let mut window = StatsWindow::new(Duration::from_secs(10));
+        assert_eq!(window.slope(), None);
+        window.push(100.0, 5.0);
+        assert_eq!(window.slope(), None);
+        for (t, v) in [(100.5, 6.0), (101.0, 7.0), (101.5, 8.0)] {
+            window.push(t, v);
+        }
+        assert_eq!(window.slope(), Some(2.0));
+    }
+
+    #[test]
+    fn test_register_window_once() {
+        let mut stats = MetricStats::default();
+        stats.register_window("fps", Duration::from_secs(10));
+        stats.register_window("fps", Duration::from_secs(10));
+        stats.register_window("fps", Duration::from_secs(60));
+
+        assert_eq!(stats.windows("fps").len(), 2);
+        assert!(stats.window("fps", Duration::from_secs(60)).is_some());
+        assert!(stats.windows("frame_time_ms").is_empty());
+    }
+}
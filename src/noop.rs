@@ -0,0 +1,199 @@
+//! No-op stand-ins for builds without the `hud` feature.
+//!
+//! The HUD's systems, materials and providers are not compiled. What game
+//! code calls keeps its signature and does nothing, so shipping builds can
+//! turn off default features without any cfgs at the call sites.
+
+use bevy::{
+    app::{App, Plugin},
+    ecs::{
+        event::Event,
+        schedule::SystemSet,
+        system::{Commands, IntoSystem},
+        world::World,
+    },
+    prelude::Resource,
+};
+
+use crate::{
+    config::HudPreset,
+    events::{MetricSample, PerfHudCommand},
+    providers::{PerfHudAppExt, PerfMetricProvider},
+};
+
+/// System set of the HUD's systems (empty without the `hud` feature).
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PerfHudSystems;
+
+/// Main plugin for the Bevy Performance HUD.
+///
+/// Without the `hud` feature it adds no systems, materials or diagnostics
+/// plugins. It only registers [`PerfCounters`], [`PerfHudCommand`] and
+/// [`MetricSample`], so game code using them keeps running.
+#[derive(Default)]
+pub struct BevyPerfHudPlugin;
+
+impl Plugin for BevyPerfHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerfCounters>()
+            .add_event::<PerfHudCommand>()
+            .add_event::<MetricSample>();
+    }
+}
+
+#[cfg(feature = "config-file")]
+impl BevyPerfHudPlugin {
+    /// The HUD plugin; the config file is not loaded without the `hud` feature
+    pub fn from_config_file(path: impl Into<String>) -> (Self, PerfHudConfigFilePlugin) {
+        (Self, PerfHudConfigFilePlugin { path: path.into() })
+    }
+}
+
+/// Plugin loading the HUD layout from a config file (does nothing without
+/// the `hud` feature).
+#[cfg(feature = "config-file")]
+pub struct PerfHudConfigFilePlugin {
+    /// Asset path of the config file
+    pub path: String,
+}
+
+#[cfg(feature = "config-file")]
+impl Plugin for PerfHudConfigFilePlugin {
+    fn build(&self, _app: &mut App) {}
+}
+
+/// Counters and gauges set by game code (ignored without the `hud` feature).
+#[derive(Resource, Default)]
+pub struct PerfCounters;
+
+impl PerfCounters {
+    /// Add `amount` to a counter reporting its total over each frame
+    pub fn add(&mut self, _metric_id: &str, _amount: f32) {}
+
+    /// Set a gauge, reported until it is set again
+    pub fn set(&mut self, _metric_id: &str, _value: f32) {}
+
+    /// Current value of a counter (always None without the `hud` feature)
+    pub fn get(&self, _metric_id: &str) -> Option<f32> {
+        None
+    }
+}
+
+/// Show the HUD with a ready-made layout (does nothing without the `hud`
+/// feature).
+pub fn spawn_perf_hud(_commands: &mut Commands, _preset: HudPreset) {}
+
+impl PerfHudAppExt for App {
+    fn add_perf_metric_provider<P: PerfMetricProvider>(&mut self, _provider: P) -> &mut Self {
+        self
+    }
+
+    fn add_event_count_metric<E: Event>(&mut self, _metric_id: &str) -> &mut Self {
+        self
+    }
+
+    fn add_perf_metric_fn(
+        &mut self,
+        _metric_id: &str,
+        _read: impl Fn(&World) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self {
+        self
+    }
+
+    fn add_perf_alert<M>(
+        &mut self,
+        _metric_id: &str,
+        _action: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self {
+        self
+    }
+}
+
+/// Plugin recording the input latency diagnostic (does nothing without the
+/// `hud` feature).
+#[derive(Default)]
+pub struct InputLatencyDiagnosticsPlugin;
+
+impl Plugin for InputLatencyDiagnosticsPlugin {
+    fn build(&self, _app: &mut App) {}
+}
+
+/// Plugin recording renderer statistics diagnostics (does nothing without
+/// the `hud` feature).
+#[derive(Default)]
+pub struct RenderStatsDiagnosticsPlugin;
+
+impl Plugin for RenderStatsDiagnosticsPlugin {
+    fn build(&self, _app: &mut App) {}
+}
+
+/// Plugin recording asset memory diagnostics (does nothing without the `hud`
+/// feature).
+#[derive(Default)]
+pub struct AssetMemoryDiagnosticsPlugin;
+
+impl Plugin for AssetMemoryDiagnosticsPlugin {
+    fn build(&self, _app: &mut App) {}
+}
+
+/// Plugin recording fixed timestep diagnostics (does nothing without the
+/// `hud` feature).
+#[derive(Default)]
+pub struct FixedTimestepDiagnosticsPlugin;
+
+impl Plugin for FixedTimestepDiagnosticsPlugin {
+    fn build(&self, _app: &mut App) {}
+}
+
+#[cfg(feature = "system-timings")]
+pub use system_timings::*;
+
+#[cfg(feature = "system-timings")]
+mod system_timings {
+    use bevy::{
+        app::{App, Plugin},
+        color::Color,
+        log::BoxedLayer,
+    };
+
+    use crate::constants::SYSTEM_TIME_RANK_PREFIX;
+
+    /// Build the metric ID reporting the execution time of the system ranked
+    /// `rank` (zero-based, slowest first) on the latest frame.
+    pub fn system_time_rank_id(rank: usize) -> String {
+        format!("{SYSTEM_TIME_RANK_PREFIX}{rank}")
+    }
+
+    /// Tracing layer timing system spans (none without the `hud` feature)
+    pub fn system_timings_layer(_app: &mut App) -> Option<BoxedLayer> {
+        None
+    }
+
+    /// Plugin ranking systems by execution time (does nothing without the
+    /// `hud` feature).
+    pub struct SystemTimingsPlugin {
+        /// Number of slowest systems exposed as metrics
+        pub top: usize,
+        /// Whether to show the ranked systems as a bar group below the HUD
+        pub show_bars: bool,
+        /// Fill color of the ranked system bars
+        pub color: Color,
+        /// Background color of the ranked system bars (supports transparency)
+        pub bg_color: Color,
+    }
+
+    impl Default for SystemTimingsPlugin {
+        fn default() -> Self {
+            Self {
+                top: 5,
+                show_bars: true,
+                color: Color::srgb(0.58, 0.48, 0.96),
+                bg_color: Color::srgba(0.12, 0.12, 0.12, 0.6),
+            }
+        }
+    }
+
+    impl Plugin for SystemTimingsPlugin {
+        fn build(&self, _app: &mut App) {}
+    }
+}
@@ -3,22 +3,54 @@
 //! This module contains the main [`BevyPerfHudPlugin`] and its setup logic.
 
 use bevy::{
-    app::{App, Plugin, Startup, Update},
+    app::{App, Last, Plugin, Startup, Update},
+    asset::AssetEvent,
     diagnostic::{
         EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
         SystemInformationDiagnosticsPlugin,
     },
-    ecs::schedule::common_conditions::resource_changed,
-    prelude::IntoScheduleConfigs,
+    ecs::{
+        schedule::{
+            common_conditions::{on_event, resource_exists, resource_exists_and_changed},
+            SystemSet,
+        },
+        system::Res,
+    },
+    image::Image,
+    prelude::{Condition, IntoScheduleConfigs, Mesh, Shader},
+    text::Font,
     ui::UiMaterialPlugin,
+    window::{WindowCreated, WindowEvent, WindowMoved, WindowScaleFactorChanged},
 };
 
 use crate::{
-    sample_diagnostics, setup_hud, sync_hud_visibility, update_graph_and_bars, BarMaterial,
-    BarScaleStates, GraphScaleState, HistoryBuffers, MetricProviders, MultiLineGraphMaterial,
-    PerfHudSettings, SampledValues,
+    add_auto_curves, animate_hud_fades, apply_metric_samples, backfill_history,
+    capture_graph_samples, capture_threshold_screenshots, check_hud_budget, detect_leaks,
+    detect_long_frames, detect_slow_frames, display_refresh_due, drag_hud, handle_hud_commands,
+    handle_hud_control_buttons, handle_hud_legend_clicks, not_long_frame, not_replaying,
+    record_chrome_trace, record_perf_counters, record_samples, repair_hud, replay_samples,
+    run_perf_alerts, sample_diagnostics, send_telemetry_ticks, setup_hud, sync_hud_visibility,
+    track_budget_levels, update_display_refresh_rate, update_graph_and_bars, update_graph_tooltip,
+    update_hud_anchor, update_hud_capacity, update_hud_placement, update_metric_stats,
+    update_record_button, update_slow_frame_panel, update_stats_panel, update_traffic_light,
+    zoom_graph, AssetMemoryDiagnosticsPlugin, BarColorStates, BarConfig, BarMaterial, BarScaleMode,
+    BarScaleStates, BarsMaterial, BarsSettings, BuildInfo, ChromeTraceRecorder, CurveConfig,
+    DisplayRefreshRate, FixedTimestepDiagnosticsPlugin, FrameUnitToggle, GraphScaleState,
+    GraphSettings, GraphZoom, GroupScaleStates, HistoryBuffers, HudBudgetState, HudCapacity,
+    HudHandles, HudPauseState, HudUpdateCost, LabelFilterStates, LeakDetector, LongFrameState,
+    MetricBudgetLevels, MetricBudgetTransition, MetricDefinition, MetricProviders, MetricSample,
+    MetricStats, MultiLineGraphMaterial, PerfCounters, PerfHudAlerts, PerfHudAppExt,
+    PerfHudBundleExported, PerfHudCapacityExceeded, PerfHudCommand, PerfHudLeakSuspected,
+    PerfHudScreenshot, PerfHudSettings, ProviderHealth, SampleAggregator, SampleRecorder,
+    SampleReplay, SampledValues, SlowFrameLog, TelemetryTick, TrendHistory, ASSET_EVENTS_ID,
+    WINDOW_EVENTS_ID,
 };
 
+/// System set of the HUD's systems in [`Update`] and [`Last`], for ordering
+/// app systems before or after the HUD.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PerfHudSystems;
+
 /// Main plugin for the Bevy Performance HUD.
 ///
 /// This plugin sets up all the necessary resources, systems, and materials
@@ -35,9 +67,40 @@ use crate::{
 /// app.add_plugins(BevyPerfHudPlugin::default());
 /// app.run();
 /// ```
+///
+/// Without the `hud` cargo feature the plugin adds no systems, materials or
+/// diagnostics plugins. It only registers [`PerfCounters`], [`PerfHudCommand`]
+/// and [`MetricSample`], so game code using them keeps working in shipping builds.
 #[derive(Default)]
 pub struct BevyPerfHudPlugin;
 
+#[cfg(feature = "config-file")]
+impl BevyPerfHudPlugin {
+    /// The HUD plugin with its layout loaded from a RON config file at the
+    /// asset `path`, see [`PerfHudConfigFile`](crate::PerfHudConfigFile).
+    ///
+    /// The HUD is spawned once the file is loaded and rebuilt whenever the
+    /// asset server reports it modified (with bevy's `file_watcher` feature).
+    ///
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_perf_hud::BevyPerfHudPlugin;
+    ///
+    /// App::new()
+    ///     .add_plugins(DefaultPlugins)
+    ///     .add_plugins(BevyPerfHudPlugin::from_config_file("perf_hud.ron"))
+    ///     .run();
+    /// ```
+    pub fn from_config_file(
+        path: impl Into<String>,
+    ) -> (Self, crate::config_file::PerfHudConfigFilePlugin) {
+        (
+            Self,
+            crate::config_file::PerfHudConfigFilePlugin { path: path.into() },
+        )
+    }
+}
+
 impl Plugin for BevyPerfHudPlugin {
     fn build(&self, app: &mut App) {
         // Add diagnostic plugins if not already present
@@ -54,25 +117,170 @@ impl Plugin for BevyPerfHudPlugin {
             app.add_plugins(SystemInformationDiagnosticsPlugin);
         };
 
+        if !app.is_plugin_added::<FixedTimestepDiagnosticsPlugin>() {
+            app.add_plugins(FixedTimestepDiagnosticsPlugin);
+        };
+
+        if !app.is_plugin_added::<AssetMemoryDiagnosticsPlugin>() {
+            app.add_plugins(AssetMemoryDiagnosticsPlugin);
+        };
+
+        // Count event churn that can cause hitches not visible in frame time
+        app.add_event_count_metric::<WindowEvent>(WINDOW_EVENTS_ID)
+            .add_event_count_metric::<AssetEvent<Image>>(ASSET_EVENTS_ID)
+            .add_event_count_metric::<AssetEvent<Mesh>>(ASSET_EVENTS_ID)
+            .add_event_count_metric::<AssetEvent<Font>>(ASSET_EVENTS_ID)
+            .add_event_count_metric::<AssetEvent<Shader>>(ASSET_EVENTS_ID);
+
         // Register custom UI materials for graph and bar rendering
         // These use custom shaders for efficient real-time performance visualization
         app.add_plugins(UiMaterialPlugin::<MultiLineGraphMaterial>::default())
             .add_plugins(UiMaterialPlugin::<BarMaterial>::default())
+            .add_plugins(UiMaterialPlugin::<BarsMaterial>::default())
             // Initialize core resources for HUD state management
             .init_resource::<SampledValues>() // Current metric values
             .init_resource::<MetricProviders>() // Registry of metric sources
             .init_resource::<HistoryBuffers>() // Historical data for graphs
+            .init_resource::<SampleAggregator>() // Per-sample frame aggregation
+            .init_resource::<TrendHistory>() // Long-term trend history
             .init_resource::<GraphScaleState>() // Dynamic scaling state
             .init_resource::<BarScaleStates>() // Bar scaling states
+            .init_resource::<GroupScaleStates>() // Per-group graph scaling states
+            .init_resource::<MetricStats>() // Windowed per-metric statistics
+            .init_resource::<ProviderHealth>() // Provider failures and sampling cost
+            .init_resource::<HudPauseState>() // Paused display updates
+            .init_resource::<HudUpdateCost>() // Time spent in HUD update systems
+            .init_resource::<HudBudgetState>() // HUD cost against its budget
+            .init_resource::<PerfHudAlerts>() // Actions run on critical metrics
+            .init_resource::<FrameUnitToggle>() // Entries switched to the other frame unit
+            .init_resource::<MetricBudgetLevels>() // Budget level of each metric
+            .init_resource::<DisplayRefreshRate>() // Default FPS target
+            .init_resource::<BuildInfo>() // Build and session identification
+            .init_resource::<HudCapacity>() // Capacity usage introspection
+            .init_resource::<LeakDetector>() // Entity and memory trends
+            .init_resource::<BarColorStates>() // Bar color zone transitions
+            .init_resource::<LabelFilterStates>() // Recent values of filtered labels
+            .init_resource::<LongFrameState>() // Frames excluded as debugger breaks
+            .init_resource::<ChromeTraceRecorder>() // Samples for Chrome trace export
+            .init_resource::<PerfCounters>() // Counters and gauges set by game code
+            .init_resource::<SlowFrameLog>() // Latest slow frames
+            .init_resource::<GraphZoom>() // Zoomed range of the paused graph
+            .add_event::<PerfHudCommand>() // Runtime HUD commands
+            .add_event::<MetricSample>() // Values pushed by game code
+            // Reflect settings and scale states for inspectors and scenes;
+            // nested config types are registered along with the settings
+            .register_type::<PerfHudSettings>()
+            .register_type::<GraphSettings>()
+            .register_type::<BarsSettings>()
+            .register_type::<CurveConfig>()
+            .register_type::<BarConfig>()
+            .register_type::<MetricDefinition>()
+            .register_type::<BarScaleMode>()
+            .register_type::<GraphScaleState>()
+            .register_type::<GroupScaleStates>()
+            .register_type::<BarScaleStates>()
+            .add_event::<MetricBudgetTransition>() // Metrics crossing budget thresholds
+            .add_event::<PerfHudScreenshot>() // Threshold screenshot notifications
+            .add_event::<TelemetryTick>() // Aggregated metrics for external telemetry
+            .add_event::<PerfHudCapacityExceeded>() // Settings over the HUD's capacity
+            .add_event::<PerfHudLeakSuspected>() // Possible entity or memory leaks
+            .add_event::<PerfHudBundleExported>() // Written bug report bundles
             // Register systems for HUD lifecycle
-            .add_systems(Startup, setup_hud) // Create HUD entities on startup
+            // Create HUD entities on startup, or on the first frame settings exist
+            // if the app inserts them later (e.g. from its own Startup system)
+            .add_systems(
+                Startup,
+                (add_auto_curves, setup_hud.run_if(hud_not_spawned)).chain(),
+            )
             .add_systems(
                 Update,
                 (
-                    sync_hud_visibility.run_if(resource_changed::<PerfHudSettings>),
-                    (sample_diagnostics, update_graph_and_bars).chain(),
-                ),
-            ); // Update loop
+                    sync_hud_visibility.run_if(resource_exists_and_changed::<PerfHudSettings>),
+                    handle_hud_control_buttons.before(handle_hud_commands),
+                    handle_hud_legend_clicks.before(handle_hud_commands),
+                    // Follow the window across monitors with different refresh rates
+                    update_display_refresh_rate
+                        .run_if(
+                            on_event::<WindowCreated>
+                                .or(on_event::<WindowMoved>)
+                                .or(on_event::<WindowScaleFactorChanged>),
+                        )
+                        .before(update_graph_and_bars),
+                    (
+                        add_auto_curves,
+                        setup_hud.run_if(hud_not_spawned),
+                        repair_hud.run_if(resource_exists::<HudHandles>),
+                        handle_hud_commands.run_if(on_event::<PerfHudCommand>),
+                        backfill_history.run_if(resource_exists_and_changed::<PerfHudSettings>),
+                        (
+                            // Measured frame time is restarted without a long frame
+                            detect_long_frames.after(FrameTimeDiagnosticsPlugin::diagnostic_system),
+                            // A replayed recording stands in for the providers
+                            (
+                                sample_diagnostics.run_if(not_replaying),
+                                apply_metric_samples.run_if(not_replaying),
+                                replay_samples.run_if(resource_exists::<SampleReplay>),
+                                record_samples.run_if(resource_exists::<SampleRecorder>),
+                            )
+                                .chain(),
+                            (
+                                update_metric_stats,
+                                track_budget_levels,
+                                capture_threshold_screenshots,
+                                run_perf_alerts,
+                                send_telemetry_ticks,
+                                record_chrome_trace,
+                                detect_leaks,
+                                detect_slow_frames,
+                            )
+                                .chain()
+                                .run_if(not_long_frame),
+                            (
+                                capture_graph_samples
+                                    .run_if(not_long_frame)
+                                    .run_if(not_paused),
+                                update_graph_and_bars
+                                    .run_if(not_long_frame)
+                                    .run_if(resource_exists::<HudHandles>)
+                                    .run_if(not_paused)
+                                    .run_if(display_refresh_due),
+                            )
+                                .chain(),
+                            update_stats_panel
+                                .run_if(resource_exists::<HudHandles>)
+                                .run_if(not_paused)
+                                .run_if(display_refresh_due),
+                            update_traffic_light
+                                .run_if(resource_exists::<HudHandles>)
+                                .run_if(not_paused),
+                            (update_slow_frame_panel, update_record_button)
+                                .run_if(resource_exists::<HudHandles>),
+                            update_hud_capacity,
+                            drag_hud.run_if(resource_exists::<HudHandles>),
+                            zoom_graph.run_if(resource_exists::<HudHandles>),
+                            update_graph_tooltip.run_if(resource_exists::<HudHandles>),
+                            update_hud_anchor.run_if(resource_exists::<HudHandles>),
+                            update_hud_placement.run_if(resource_exists::<HudHandles>),
+                        )
+                            .chain()
+                            // A disabled HUD neither samples its providers nor updates
+                            .run_if(hud_enabled),
+                        animate_hud_fades,
+                        check_hud_budget.run_if(hud_enabled),
+                    )
+                        .chain()
+                        // Near-zero cost until the app provides HUD settings
+                        .run_if(resource_exists::<PerfHudSettings>),
+                )
+                    .in_set(PerfHudSystems),
+            ) // Update loop
+            // Record the counters once game systems are done with the frame
+            .add_systems(
+                Last,
+                record_perf_counters
+                    .in_set(PerfHudSystems)
+                    .run_if(resource_exists::<PerfHudSettings>),
+            );
 
         // Register default metric providers (FPS, frame time, entity count, system info)
         app.world_mut()
@@ -80,3 +288,24 @@ impl Plugin for BevyPerfHudPlugin {
             .ensure_default_entries();
     }
 }
+
+/// Run condition: HUD display updates are not paused.
+fn not_paused(pause: Res<HudPauseState>) -> bool {
+    !pause.paused
+}
+
+/// Run condition: the HUD is not turned off with [`PerfHudSettings::enabled`].
+///
+/// Apps without settings count as enabled, so the diagnostics plugins also
+/// record on their own.
+pub(crate) fn hud_enabled(settings: Option<Res<PerfHudSettings>>) -> bool {
+    settings.is_none_or(|settings| settings.enabled)
+}
+
+/// Run condition: HUD settings are present but the HUD has not been spawned yet.
+fn hud_not_spawned(
+    settings: Option<Res<PerfHudSettings>>,
+    handles: Option<Res<HudHandles>>,
+) -> bool {
+    settings.is_some() && handles.is_none()
+}
@@ -0,0 +1,28 @@
+//! The types most applications need, for a single glob import.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_perf_hud::prelude::*;
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .insert_resource(PerfHudSettings::default())
+//!         .add_plugins(BevyPerfHudPlugin)
+//!         .run();
+//! }
+//! ```
+
+#[cfg(all(feature = "hud", feature = "config-file"))]
+pub use crate::config_file::PerfHudConfigFile;
+#[cfg(feature = "config-file")]
+pub use crate::PerfHudConfigFilePlugin;
+pub use crate::{
+    config::{
+        BarConfig, BarFill, BarScaleMode, BarTrend, BarsSettings, CurveConfig, CurveStyle,
+        GraphSettings, HudAnchor, HudPreset, LineSmoothing, MetricDefinition, PerfHudSettings,
+    },
+    events::{MetricSample, PerfHudCommand},
+    providers::{MetricSampleContext, PerfHudAppExt, PerfMetricProvider},
+    spawn_perf_hud, BevyPerfHudPlugin, PerfCounters,
+};
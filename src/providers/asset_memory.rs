@@ -0,0 +1,224 @@
+//! Asset memory metric providers.
+//!
+//! [`AssetMemoryDiagnosticsPlugin`] counts the loaded meshes, images and
+//! standard materials and estimates the memory their CPU-side data takes,
+//! and records them as regular diagnostics that the providers below read
+//! back. Totals are only recomputed for asset types that sent asset events.
+
+use std::mem::size_of;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    asset::{Asset, AssetEvent, AssetEvents, Assets},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::{
+        event::{EventCursor, Events},
+        system::{Local, Res},
+    },
+    image::Image,
+    pbr::StandardMaterial,
+    prelude::IntoScheduleConfigs,
+    render::mesh::{Indices, Mesh},
+};
+
+use super::{diagnostic_history, MetricSampleContext, PerfMetricProvider};
+use crate::{constants::*, plugin::hud_enabled};
+
+/// One of the asset memory metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetMemoryMetric {
+    /// Number of loaded meshes
+    MeshCount,
+    /// Estimated memory of the loaded meshes' vertex and index data in MiB
+    MeshMemory,
+    /// Number of loaded images
+    ImageCount,
+    /// Estimated memory of the loaded images' pixel data in MiB
+    ImageMemory,
+    /// Number of loaded standard materials
+    MaterialCount,
+    /// Estimated memory of the loaded standard materials in MiB, excluding
+    /// their textures (counted as images)
+    MaterialMemory,
+}
+
+impl AssetMemoryMetric {
+    /// All asset memory metrics
+    pub const ALL: [Self; 6] = [
+        Self::MeshCount,
+        Self::MeshMemory,
+        Self::ImageCount,
+        Self::ImageMemory,
+        Self::MaterialCount,
+        Self::MaterialMemory,
+    ];
+
+    /// Metric ID reporting this value
+    pub fn metric_id(self) -> &'static str {
+        match self {
+            Self::MeshCount => ASSET_MESH_COUNT_ID,
+            Self::MeshMemory => ASSET_MESH_MEMORY_ID,
+            Self::ImageCount => ASSET_IMAGE_COUNT_ID,
+            Self::ImageMemory => ASSET_IMAGE_MEMORY_ID,
+            Self::MaterialCount => ASSET_MATERIAL_COUNT_ID,
+            Self::MaterialMemory => ASSET_MATERIAL_MEMORY_ID,
+        }
+    }
+
+    /// Diagnostic the value is recorded to
+    fn diagnostic_path(self) -> DiagnosticPath {
+        match self {
+            Self::MeshCount => DiagnosticPath::const_new("perf_hud/assets/mesh_count"),
+            Self::MeshMemory => DiagnosticPath::const_new("perf_hud/assets/mesh_memory_mb"),
+            Self::ImageCount => DiagnosticPath::const_new("perf_hud/assets/image_count"),
+            Self::ImageMemory => DiagnosticPath::const_new("perf_hud/assets/image_memory_mb"),
+            Self::MaterialCount => DiagnosticPath::const_new("perf_hud/assets/material_count"),
+            Self::MaterialMemory => DiagnosticPath::const_new("perf_hud/assets/material_memory_mb"),
+        }
+    }
+}
+
+/// Plugin recording asset memory diagnostics.
+///
+/// Added automatically by [`BevyPerfHudPlugin`](crate::BevyPerfHudPlugin).
+/// Assets whose data only lives in the render world (see
+/// `RenderAssetUsages`) are counted but take no memory in the estimate.
+#[derive(Default)]
+pub struct AssetMemoryDiagnosticsPlugin;
+
+impl Plugin for AssetMemoryDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        for metric in AssetMemoryMetric::ALL {
+            app.register_diagnostic(Diagnostic::new(metric.diagnostic_path()));
+        }
+        app.add_systems(
+            PostUpdate,
+            record_asset_memory_diagnostics
+                .after(AssetEvents)
+                .run_if(hud_enabled),
+        );
+    }
+}
+
+/// Count and estimated size of the assets of one type.
+#[derive(Default, Clone, Copy)]
+struct AssetTally {
+    count: usize,
+    bytes: usize,
+}
+
+/// Latest tally of one asset type, recomputed when it sends asset events.
+struct AssetTracker<A: Asset> {
+    cursor: EventCursor<AssetEvent<A>>,
+    tally: AssetTally,
+}
+
+impl<A: Asset> Default for AssetTracker<A> {
+    fn default() -> Self {
+        Self {
+            cursor: EventCursor::default(),
+            tally: AssetTally::default(),
+        }
+    }
+}
+
+impl<A: Asset> AssetTracker<A> {
+    fn update(
+        &mut self,
+        assets: Option<&Assets<A>>,
+        events: Option<&Events<AssetEvent<A>>>,
+        size: impl Fn(&A) -> usize,
+    ) -> AssetTally {
+        let changed = events.is_some_and(|events| self.cursor.read(events).count() > 0);
+        if changed {
+            self.tally = assets.map_or_else(AssetTally::default, |assets| AssetTally {
+                count: assets.len(),
+                bytes: assets.iter().map(|(_, asset)| size(asset)).sum(),
+            });
+        }
+        self.tally
+    }
+}
+
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * size_of::<u16>(),
+        Some(Indices::U32(indices)) => indices.len() * size_of::<u32>(),
+        None => 0,
+    };
+    mesh.get_vertex_buffer_size() + indices
+}
+
+fn image_bytes(image: &Image) -> usize {
+    image.data.as_ref().map_or(0, Vec::len)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_asset_memory_diagnostics(
+    mut diagnostics: Diagnostics,
+    meshes: Option<Res<Assets<Mesh>>>,
+    mesh_events: Option<Res<Events<AssetEvent<Mesh>>>>,
+    images: Option<Res<Assets<Image>>>,
+    image_events: Option<Res<Events<AssetEvent<Image>>>>,
+    materials: Option<Res<Assets<StandardMaterial>>>,
+    material_events: Option<Res<Events<AssetEvent<StandardMaterial>>>>,
+    mut trackers: Local<(
+        AssetTracker<Mesh>,
+        AssetTracker<Image>,
+        AssetTracker<StandardMaterial>,
+    )>,
+) {
+    let (mesh_tracker, image_tracker, material_tracker) = &mut *trackers;
+    let mesh = mesh_tracker.update(meshes.as_deref(), mesh_events.as_deref(), mesh_bytes);
+    let image = image_tracker.update(images.as_deref(), image_events.as_deref(), image_bytes);
+    let material =
+        material_tracker.update(materials.as_deref(), material_events.as_deref(), |_| {
+            size_of::<StandardMaterial>()
+        });
+
+    let mib = |tally: AssetTally| tally.bytes as f64 / (1024.0 * 1024.0);
+    let values = [
+        (AssetMemoryMetric::MeshCount, mesh.count as f64),
+        (AssetMemoryMetric::MeshMemory, mib(mesh)),
+        (AssetMemoryMetric::ImageCount, image.count as f64),
+        (AssetMemoryMetric::ImageMemory, mib(image)),
+        (AssetMemoryMetric::MaterialCount, material.count as f64),
+        (AssetMemoryMetric::MaterialMemory, mib(material)),
+    ];
+    for (metric, value) in values {
+        diagnostics.add_measurement(&metric.diagnostic_path(), || value);
+    }
+}
+
+/// Built-in metric provider for an asset memory metric.
+///
+/// Reads the diagnostics recorded by [`AssetMemoryDiagnosticsPlugin`].
+pub struct AssetMemoryMetricProvider {
+    metric: AssetMemoryMetric,
+    path: DiagnosticPath,
+}
+
+impl AssetMemoryMetricProvider {
+    /// Create a provider for the given metric
+    pub fn new(metric: AssetMemoryMetric) -> Self {
+        Self {
+            metric,
+            path: metric.diagnostic_path(),
+        }
+    }
+}
+
+impl PerfMetricProvider for AssetMemoryMetricProvider {
+    fn metric_id(&self) -> &str {
+        self.metric.metric_id()
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
@@ -0,0 +1,277 @@
+//! Built-in metric providers for Bevy's frame time, entity count and system
+//! information diagnostics.
+
+use bevy::diagnostic::{
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, SystemInformationDiagnosticsPlugin,
+};
+
+use super::{diagnostic_history, MetricSampleContext, PerfMetricProvider};
+use crate::constants::*;
+
+/// Built-in metric provider for frames per second (FPS).
+///
+/// Provides the current FPS value calculated by Bevy's frame time diagnostics.
+/// The value is floored to the nearest integer for display purposes.
+#[derive(Default)]
+pub struct FpsMetricProvider;
+
+impl PerfMetricProvider for FpsMetricProvider {
+    fn metric_id(&self) -> &str {
+        "fps"
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let fps = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)?
+            .average()?;
+        Some(fps as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &FrameTimeDiagnosticsPlugin::FPS)
+    }
+}
+
+/// Built-in metric provider for frame time in milliseconds.
+///
+/// Provides the smoothed frame time duration from Bevy's diagnostics,
+/// converted to milliseconds and floored to the nearest integer.
+#[derive(Default)]
+pub struct FrameTimeMetricProvider;
+
+impl PerfMetricProvider for FrameTimeMetricProvider {
+    fn metric_id(&self) -> &str {
+        "frame_time_ms"
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let frame_time = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)?
+            .smoothed()?;
+        Some(frame_time as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &FrameTimeDiagnosticsPlugin::FRAME_TIME)
+    }
+}
+
+/// Built-in metric provider for the total number of entities.
+///
+/// Provides the current entity count from Bevy's entity diagnostics.
+/// Useful for monitoring memory usage and performance impact of entities.
+#[derive(Default)]
+pub struct EntityCountMetricProvider;
+
+impl PerfMetricProvider for EntityCountMetricProvider {
+    fn metric_id(&self) -> &str {
+        "entity_count"
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let entities = diagnostics
+            .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)?
+            .value()?;
+        Some(entities as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+    }
+}
+
+/// Built-in metric provider for system-wide CPU usage percentage.
+///
+/// Provides the overall CPU usage across all cores and processes,
+/// as reported by Bevy's system information diagnostics.
+#[derive(Default)]
+pub struct SystemCpuUsageMetricProvider;
+
+impl PerfMetricProvider for SystemCpuUsageMetricProvider {
+    fn metric_id(&self) -> &str {
+        SYSTEM_CPU_USAGE_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::SYSTEM_CPU_USAGE)?
+            .value()?;
+        Some(usage as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::SYSTEM_CPU_USAGE)
+    }
+}
+
+/// Built-in metric provider for system-wide memory usage percentage.
+///
+/// Provides the overall memory usage as a percentage of total system RAM,
+/// as reported by Bevy's system information diagnostics.
+#[derive(Default)]
+pub struct SystemMemUsageMetricProvider;
+
+impl PerfMetricProvider for SystemMemUsageMetricProvider {
+    fn metric_id(&self) -> &str {
+        SYSTEM_MEM_USAGE_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::SYSTEM_MEM_USAGE)?
+            .value()?;
+        Some(usage as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::SYSTEM_MEM_USAGE)
+    }
+}
+
+/// Built-in metric provider for process-specific CPU usage percentage.
+///
+/// Provides the CPU usage of the current Bevy application process,
+/// as reported by Bevy's system information diagnostics.
+#[derive(Default)]
+pub struct ProcessCpuUsageMetricProvider;
+
+impl PerfMetricProvider for ProcessCpuUsageMetricProvider {
+    fn metric_id(&self) -> &str {
+        PROCESS_CPU_USAGE_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::PROCESS_CPU_USAGE)?
+            .value()?;
+        Some(usage as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::PROCESS_CPU_USAGE)
+    }
+}
+
+/// Built-in metric provider for process-specific memory usage in bytes.
+///
+/// Provides the memory usage of the current Bevy application process,
+/// as reported by Bevy's system information diagnostics.
+#[derive(Default)]
+pub struct ProcessMemUsageMetricProvider;
+
+impl PerfMetricProvider for ProcessMemUsageMetricProvider {
+    fn metric_id(&self) -> &str {
+        PROCESS_MEM_USAGE_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE)?
+            .value()?;
+        Some(usage as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE)
+    }
+}
+
+/// Number of MiB in one GiB, used to convert Bevy's process memory diagnostic.
+const MIB_PER_GIB: f32 = 1024.0;
+
+/// Total system memory in GiB as reported by Bevy's [`SystemInfo`](bevy::diagnostic::SystemInfo).
+///
+/// [`SystemInfo`](bevy::diagnostic::SystemInfo) only carries a preformatted string such as `"15.5 GiB"`.
+fn total_memory_gib(ctx: MetricSampleContext) -> Option<f32> {
+    ctx.system_info?
+        .memory
+        .trim()
+        .trim_end_matches("GiB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Built-in metric provider for used system memory in GiB.
+///
+/// Derived from the system memory usage percentage and the total system
+/// memory, so the HUD can show absolute values alongside percentages.
+#[derive(Default)]
+pub struct SystemMemUsedMetricProvider;
+
+impl PerfMetricProvider for SystemMemUsedMetricProvider {
+    fn metric_id(&self) -> &str {
+        SYSTEM_MEM_USED_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let total = total_memory_gib(ctx)?;
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::SYSTEM_MEM_USAGE)?
+            .value()?;
+        Some(total * usage as f32 / 100.0)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        let Some(total) = total_memory_gib(ctx) else {
+            return Vec::new();
+        };
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::SYSTEM_MEM_USAGE)
+            .into_iter()
+            .map(|usage| total * usage / 100.0)
+            .collect()
+    }
+}
+
+/// Built-in metric provider for total system memory in GiB.
+///
+/// Reports the installed system memory, as reported by Bevy's system
+/// information resource. Useful as the upper bound of a used-memory bar.
+#[derive(Default)]
+pub struct SystemMemTotalMetricProvider;
+
+impl PerfMetricProvider for SystemMemTotalMetricProvider {
+    fn metric_id(&self) -> &str {
+        SYSTEM_MEM_TOTAL_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        total_memory_gib(ctx)
+    }
+}
+
+/// Built-in metric provider for process resident memory in MiB.
+///
+/// Provides the memory footprint of the current Bevy application process,
+/// converted from the GiB value reported by Bevy's system information diagnostics.
+#[derive(Default)]
+pub struct ProcessMemRssMetricProvider;
+
+impl PerfMetricProvider for ProcessMemRssMetricProvider {
+    fn metric_id(&self) -> &str {
+        PROCESS_MEM_RSS_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let diagnostics = ctx.diagnostics?;
+        let usage = diagnostics
+            .get(&SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE)?
+            .value()?;
+        Some(usage as f32 * MIB_PER_GIB)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE)
+            .into_iter()
+            .map(|usage| usage * MIB_PER_GIB)
+            .collect()
+    }
+}
@@ -0,0 +1,155 @@
+//! User counter metrics.
+//!
+//! Game code counts things through the [`PerfCounters`] resource from any
+//! system, without writing a provider: each counter becomes a metric the
+//! first time it is used. Counters are recorded as diagnostics at the end of
+//! the frame, so they also have history to backfill from.
+
+use std::collections::HashMap;
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore},
+    ecs::system::ResMut,
+    platform::time::Instant,
+    prelude::Resource,
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+
+/// Diagnostic a counter is recorded to.
+fn counter_path(metric_id: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!("perf_hud/counter/{metric_id}"))
+}
+
+/// Counters and gauges set by game code, each shown as the metric of its ID.
+///
+/// Counter IDs must be valid diagnostic paths (no empty segments between
+/// slashes). A counter takes the kind of the latest call: mixing
+/// [`add`](Self::add) and [`set`](Self::set) on one ID is not supported.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::PerfCounters;
+///
+/// fn spawn_bullets(mut perf_counters: ResMut<PerfCounters>) {
+///     perf_counters.add("game/bullets_spawned", 3.0);
+///     perf_counters.set("game/players", 12.0);
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct PerfCounters {
+    counters: HashMap<String, Counter>,
+}
+
+struct Counter {
+    /// Whether the value is summed per frame rather than held
+    per_frame: bool,
+    value: f64,
+    path: DiagnosticPath,
+    /// Diagnostic and provider registered
+    registered: bool,
+}
+
+impl PerfCounters {
+    /// Add `amount` to a counter reporting its total over each frame.
+    pub fn add(&mut self, metric_id: &str, amount: f32) {
+        let counter = self.counter(metric_id);
+        if !counter.per_frame {
+            counter.per_frame = true;
+            counter.value = 0.0;
+        }
+        counter.value += f64::from(amount);
+    }
+
+    /// Set a gauge, reported until it is set again.
+    pub fn set(&mut self, metric_id: &str, value: f32) {
+        let counter = self.counter(metric_id);
+        counter.per_frame = false;
+        counter.value = f64::from(value);
+    }
+
+    /// Current value of a counter: the total added this frame, or the gauge's value.
+    pub fn get(&self, metric_id: &str) -> Option<f32> {
+        self.counters
+            .get(metric_id)
+            .map(|counter| counter.value as f32)
+    }
+
+    fn counter(&mut self, metric_id: &str) -> &mut Counter {
+        // Only allocate the key for new counters
+        if !self.counters.contains_key(metric_id) {
+            self.counters.insert(
+                metric_id.to_owned(),
+                Counter {
+                    per_frame: false,
+                    value: 0.0,
+                    path: counter_path(metric_id),
+                    registered: false,
+                },
+            );
+        }
+        self.counters.get_mut(metric_id).unwrap()
+    }
+}
+
+/// System recording every counter and resetting the per-frame ones.
+///
+/// Registers the diagnostic and a [`CounterMetricProvider`] of counters used
+/// for the first time.
+pub fn record_perf_counters(
+    mut counters: ResMut<PerfCounters>,
+    mut store: ResMut<DiagnosticsStore>,
+    mut providers: ResMut<MetricProviders>,
+) {
+    let now = Instant::now();
+    for (metric_id, counter) in counters.counters.iter_mut() {
+        if !counter.registered {
+            counter.registered = true;
+            store.add(Diagnostic::new(counter.path.clone()));
+            if !providers.contains(metric_id) {
+                providers.add_provider(CounterMetricProvider::new(metric_id));
+            }
+        }
+        if let Some(diagnostic) = store.get_mut(&counter.path) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: now,
+                value: counter.value,
+            });
+        }
+        if counter.per_frame {
+            counter.value = 0.0;
+        }
+    }
+}
+
+/// Metric provider for a counter of [`PerfCounters`].
+pub struct CounterMetricProvider {
+    metric_id: String,
+    path: DiagnosticPath,
+}
+
+impl CounterMetricProvider {
+    /// Create a provider for the counter `metric_id`
+    pub fn new(metric_id: &str) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            path: counter_path(metric_id),
+        }
+    }
+}
+
+impl PerfMetricProvider for CounterMetricProvider {
+    fn metric_id(&self) -> &str {
+        &self.metric_id
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
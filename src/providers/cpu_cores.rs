@@ -0,0 +1,154 @@
+//! Per-core CPU metric providers.
+//!
+//! Bevy's system information diagnostics only report aggregate CPU usage,
+//! which hides a single saturated core on machines with many cores. These
+//! providers report the logical core count and the usage of each core.
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use bevy::tasks::available_parallelism;
+
+use self::platform::CpuCoreSamplerState;
+use super::{MetricSampleContext, PerfMetricProvider};
+use crate::constants::*;
+
+/// Build the metric ID reporting the usage percentage of a single CPU core.
+///
+/// # Arguments
+/// * `core` - Zero-based logical core index
+pub fn cpu_core_usage_id(core: usize) -> String {
+    format!("{SYSTEM_CPU_CORE_USAGE_PREFIX}{core}")
+}
+
+/// Built-in metric provider for the number of logical CPU cores.
+#[derive(Default)]
+pub struct CpuCoreCountMetricProvider;
+
+impl PerfMetricProvider for CpuCoreCountMetricProvider {
+    fn metric_id(&self) -> &str {
+        SYSTEM_CPU_CORE_COUNT_ID
+    }
+
+    fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+        // Querying the core count reads cgroup files on Linux; it does not change
+        static CORE_COUNT: OnceLock<usize> = OnceLock::new();
+        Some(*CORE_COUNT.get_or_init(available_parallelism) as f32)
+    }
+}
+
+/// Shared per-core CPU usage sampler.
+///
+/// Clones share the same underlying state, so all per-core providers refresh
+/// the operating system counters at most once per sysinfo update interval.
+/// On platforms without sysinfo support no usage is ever reported.
+#[derive(Clone, Default)]
+pub struct CpuCoreSampler {
+    state: Arc<Mutex<CpuCoreSamplerState>>,
+}
+
+impl CpuCoreSampler {
+    /// Get the latest usage percentage of a core, refreshing it if stale.
+    ///
+    /// # Arguments
+    /// * `core` - Zero-based logical core index
+    pub fn core_usage(&self, core: usize) -> Option<f32> {
+        let mut state = self.state.lock().ok()?;
+        state.refresh();
+        state.usage.get(core).copied()
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "windows",
+    target_os = "android",
+    target_os = "macos"
+))]
+mod platform {
+    use bevy::platform::time::Instant;
+    use sysinfo::{CpuRefreshKind, RefreshKind, System};
+
+    #[derive(Default)]
+    pub(super) struct CpuCoreSamplerState {
+        system: Option<System>,
+        last_refresh: Option<Instant>,
+        pub(super) usage: Vec<f32>,
+    }
+
+    impl CpuCoreSamplerState {
+        pub(super) fn refresh(&mut self) {
+            // Refreshing more often than this interval does not yield new data
+            if self
+                .last_refresh
+                .is_some_and(|t| t.elapsed() < sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)
+            {
+                return;
+            }
+
+            let system = self.system.get_or_insert_with(|| {
+                System::new_with_specifics(
+                    RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing().with_cpu_usage()),
+                )
+            });
+            system.refresh_cpu_usage();
+            self.usage.clear();
+            self.usage
+                .extend(system.cpus().iter().map(|cpu| cpu.cpu_usage()));
+            self.last_refresh = Some(Instant::now());
+        }
+    }
+}
+
+// sysinfo is unavailable on this target: no per-core usage is reported
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "windows",
+    target_os = "android",
+    target_os = "macos"
+)))]
+mod platform {
+    #[derive(Default)]
+    pub(super) struct CpuCoreSamplerState {
+        pub(super) usage: Vec<f32>,
+    }
+
+    impl CpuCoreSamplerState {
+        pub(super) fn refresh(&mut self) {}
+    }
+}
+
+/// Built-in metric provider for the usage percentage of a single CPU core.
+///
+/// One provider is registered per logical core, all sharing a [`CpuCoreSampler`].
+/// sysinfo allocates while reading the operating system counters, which
+/// happens at most once per `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL`.
+pub struct CpuCoreUsageMetricProvider {
+    id: String,
+    core: usize,
+    sampler: CpuCoreSampler,
+}
+
+impl CpuCoreUsageMetricProvider {
+    /// Create a provider for one core backed by a shared sampler.
+    ///
+    /// # Arguments
+    /// * `core` - Zero-based logical core index
+    /// * `sampler` - Sampler shared between all core providers
+    pub fn new(core: usize, sampler: CpuCoreSampler) -> Self {
+        Self {
+            id: cpu_core_usage_id(core),
+            core,
+            sampler,
+        }
+    }
+}
+
+impl PerfMetricProvider for CpuCoreUsageMetricProvider {
+    fn metric_id(&self) -> &str {
+        &self.id
+    }
+
+    fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+        self.sampler.core_usage(self.core)
+    }
+}
@@ -0,0 +1,127 @@
+//! Per-frame event count metric providers.
+//!
+//! Input storms and asset churn can cause hitches that frame time alone does
+//! not explain. These metrics count how many events of selected types were
+//! sent each frame: window events (every input and window event forwarded
+//! from winit) and asset events are built in, and any other event type can be
+//! counted with [`PerfHudAppExt::add_event_count_metric`](super::PerfHudAppExt).
+
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+};
+
+use bevy::{
+    app::{App, Last},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::{
+        event::{Event, EventReader},
+        schedule::SystemSet,
+        system::ResMut,
+    },
+    prelude::{IntoScheduleConfigs, Resource},
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+use crate::plugin::hud_enabled;
+
+/// Events counted per metric since the last recording.
+#[derive(Resource, Default)]
+struct EventCounts {
+    counts: HashMap<DiagnosticPath, u32>,
+    /// Event types already counted into each metric
+    counted: HashSet<(TypeId, String)>,
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct CountEvents;
+
+/// Diagnostic an event count metric is recorded to.
+fn event_count_path(metric_id: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!("perf_hud/{metric_id}"))
+}
+
+/// Count events of type `E` sent each frame into the metric `metric_id`.
+///
+/// Several event types counted into the same metric are summed. Also
+/// registers an [`EventCountMetricProvider`] for the metric.
+pub(super) fn add_event_count_metric<E: Event>(app: &mut App, metric_id: &str) {
+    if !app.world().contains_resource::<EventCounts>() {
+        app.init_resource::<EventCounts>().add_systems(
+            Last,
+            record_event_counts.after(CountEvents).run_if(hud_enabled),
+        );
+    }
+    let key = (TypeId::of::<E>(), metric_id.to_owned());
+    let mut counts = app.world_mut().resource_mut::<EventCounts>();
+    if !counts.counted.insert(key) {
+        return;
+    }
+
+    let path = event_count_path(metric_id);
+    let new_metric = !counts.counts.contains_key(&path);
+    counts.counts.entry(path.clone()).or_default();
+    if new_metric {
+        app.register_diagnostic(Diagnostic::new(path.clone()));
+    }
+
+    // Reading needs the event registered, even if nothing ever sends it
+    app.add_event::<E>().add_systems(
+        Last,
+        (move |mut events: EventReader<E>, mut counts: ResMut<EventCounts>| {
+            if let Some(count) = counts.counts.get_mut(&path) {
+                *count += events.read().count() as u32;
+            }
+        })
+        .in_set(CountEvents)
+        .run_if(hud_enabled),
+    );
+
+    app.init_resource::<MetricProviders>();
+    let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+    if !providers.contains(metric_id) {
+        providers.add_provider(EventCountMetricProvider::new(metric_id));
+    }
+}
+
+fn record_event_counts(mut diagnostics: Diagnostics, mut counts: ResMut<EventCounts>) {
+    for (path, count) in counts.counts.iter_mut() {
+        let value = *count as f64;
+        diagnostics.add_measurement(path, || value);
+        *count = 0;
+    }
+}
+
+/// Metric provider for a per-frame event count.
+///
+/// Reads the counts recorded for metrics set up with
+/// [`PerfHudAppExt::add_event_count_metric`](super::PerfHudAppExt).
+pub struct EventCountMetricProvider {
+    metric_id: String,
+    path: DiagnosticPath,
+}
+
+impl EventCountMetricProvider {
+    /// Create a provider for the event count metric `metric_id`
+    pub fn new(metric_id: &str) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            path: event_count_path(metric_id),
+        }
+    }
+}
+
+impl PerfMetricProvider for EventCountMetricProvider {
+    fn metric_id(&self) -> &str {
+        &self.metric_id
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
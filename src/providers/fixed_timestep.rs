@@ -0,0 +1,165 @@
+//! Fixed timestep health metric providers.
+//!
+//! Bevy does not publish diagnostics for its fixed main loop, so
+//! [`FixedTimestepDiagnosticsPlugin`] measures the fixed delta, steps run per
+//! frame, accumulated lag and time spent in `FixedMain`, and records them as
+//! regular diagnostics that the providers below read back.
+
+use std::time::Duration;
+
+use bevy::{
+    app::{App, FixedFirst, FixedLast, Plugin, RunFixedMainLoop, RunFixedMainLoopSystem},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::system::{Res, ResMut},
+    platform::time::Instant,
+    prelude::{IntoScheduleConfigs, Resource},
+    time::{Fixed, Time},
+};
+
+use super::{diagnostic_history, MetricSampleContext, PerfMetricProvider};
+use crate::{constants::*, plugin::hud_enabled};
+
+/// One of the fixed timestep health metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedTimestepMetric {
+    /// Length of one fixed step in milliseconds
+    Delta,
+    /// Number of fixed steps run during the last frame
+    StepsPerFrame,
+    /// Time accumulated towards the next fixed step in milliseconds
+    Overstep,
+    /// Time spent running fixed steps during the last frame in milliseconds
+    UpdateTime,
+}
+
+impl FixedTimestepMetric {
+    /// All fixed timestep metrics
+    pub const ALL: [Self; 4] = [
+        Self::Delta,
+        Self::StepsPerFrame,
+        Self::Overstep,
+        Self::UpdateTime,
+    ];
+
+    /// Metric ID reporting this value
+    pub fn metric_id(self) -> &'static str {
+        match self {
+            Self::Delta => FIXED_DELTA_ID,
+            Self::StepsPerFrame => FIXED_STEPS_PER_FRAME_ID,
+            Self::Overstep => FIXED_OVERSTEP_ID,
+            Self::UpdateTime => FIXED_UPDATE_TIME_ID,
+        }
+    }
+
+    /// Diagnostic the value is recorded to
+    fn diagnostic_path(self) -> DiagnosticPath {
+        match self {
+            Self::Delta => DiagnosticPath::const_new("perf_hud/fixed/delta_ms"),
+            Self::StepsPerFrame => DiagnosticPath::const_new("perf_hud/fixed/steps_per_frame"),
+            Self::Overstep => DiagnosticPath::const_new("perf_hud/fixed/overstep_ms"),
+            Self::UpdateTime => DiagnosticPath::const_new("perf_hud/fixed/update_time_ms"),
+        }
+    }
+}
+
+/// Plugin recording fixed timestep diagnostics.
+///
+/// Added automatically by [`BevyPerfHudPlugin`](crate::BevyPerfHudPlugin).
+#[derive(Default)]
+pub struct FixedTimestepDiagnosticsPlugin;
+
+impl Plugin for FixedTimestepDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        for metric in FixedTimestepMetric::ALL {
+            app.register_diagnostic(Diagnostic::new(metric.diagnostic_path()));
+        }
+        app.init_resource::<FixedStepTiming>()
+            .add_systems(FixedFirst, begin_fixed_step.run_if(hud_enabled))
+            .add_systems(FixedLast, end_fixed_step.run_if(hud_enabled))
+            .add_systems(
+                RunFixedMainLoop,
+                record_fixed_timestep_diagnostics
+                    .in_set(RunFixedMainLoopSystem::AfterFixedMainLoop)
+                    .run_if(hud_enabled),
+            );
+    }
+}
+
+/// Fixed steps run and time spent in them since the last recording.
+#[derive(Resource, Default)]
+struct FixedStepTiming {
+    steps: u32,
+    busy: Duration,
+    step_started: Option<Instant>,
+}
+
+fn begin_fixed_step(mut timing: ResMut<FixedStepTiming>) {
+    timing.step_started = Some(Instant::now());
+}
+
+fn end_fixed_step(mut timing: ResMut<FixedStepTiming>) {
+    if let Some(started) = timing.step_started.take() {
+        timing.busy += started.elapsed();
+        timing.steps += 1;
+    }
+}
+
+fn record_fixed_timestep_diagnostics(
+    mut diagnostics: Diagnostics,
+    time: Res<Time<Fixed>>,
+    mut timing: ResMut<FixedStepTiming>,
+) {
+    let values = [
+        (
+            FixedTimestepMetric::Delta,
+            time.timestep().as_secs_f64() * 1000.0,
+        ),
+        (FixedTimestepMetric::StepsPerFrame, timing.steps as f64),
+        (
+            FixedTimestepMetric::Overstep,
+            time.overstep().as_secs_f64() * 1000.0,
+        ),
+        (
+            FixedTimestepMetric::UpdateTime,
+            timing.busy.as_secs_f64() * 1000.0,
+        ),
+    ];
+    for (metric, value) in values {
+        diagnostics.add_measurement(&metric.diagnostic_path(), || value);
+    }
+    timing.steps = 0;
+    timing.busy = Duration::ZERO;
+}
+
+/// Built-in metric provider for a fixed timestep health metric.
+///
+/// Reads the diagnostics recorded by [`FixedTimestepDiagnosticsPlugin`].
+pub struct FixedTimestepMetricProvider {
+    metric: FixedTimestepMetric,
+    path: DiagnosticPath,
+}
+
+impl FixedTimestepMetricProvider {
+    /// Create a provider for the given metric
+    pub fn new(metric: FixedTimestepMetric) -> Self {
+        Self {
+            metric,
+            path: metric.diagnostic_path(),
+        }
+    }
+}
+
+impl PerfMetricProvider for FixedTimestepMetricProvider {
+    fn metric_id(&self) -> &str {
+        self.metric.metric_id()
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
@@ -0,0 +1,139 @@
+//! Input latency metric provider.
+//!
+//! Responsiveness problems do not show up in raw FPS, but they are what
+//! players feel. [`InputLatencyDiagnosticsPlugin`] approximates the time from
+//! an input event to the presentation of the first frame that could react to
+//! it: the input is stamped with the start of the frame that reads it, and
+//! the frame counts as presented when the frame after its rendering starts.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    app::{App, First, Plugin, PreUpdate},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, FrameCount, RegisterDiagnostic},
+    ecs::{
+        event::EventReader,
+        system::{Res, ResMut},
+    },
+    input::{
+        gamepad::GamepadButtonChangedEvent, keyboard::KeyboardInput, mouse::MouseButtonInput,
+        touch::TouchInput, InputSystem,
+    },
+    platform::time::Instant,
+    prelude::{IntoScheduleConfigs, Resource},
+    render::pipelined_rendering::PipelinedRenderingPlugin,
+    time::{Real, Time, TimeSystem},
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+use crate::{constants::*, plugin::hud_enabled};
+
+const INPUT_LATENCY_PATH: DiagnosticPath = DiagnosticPath::const_new("perf_hud/input_latency_ms");
+
+/// Plugin recording the input latency diagnostic read by
+/// [`InputLatencyMetricProvider`].
+///
+/// Opt-in: add it to the app to enable the `input_latency_ms` metric. Key
+/// presses, mouse buttons, touches and gamepad buttons are measured; pointer
+/// motion is not, as it arrives continuously.
+#[derive(Default)]
+pub struct InputLatencyDiagnosticsPlugin;
+
+impl Plugin for InputLatencyDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(INPUT_LATENCY_PATH))
+            .init_resource::<PendingInputs>()
+            .add_systems(
+                First,
+                record_input_latency.after(TimeSystem).run_if(hud_enabled),
+            )
+            .add_systems(
+                PreUpdate,
+                stamp_input_events.after(InputSystem).run_if(hud_enabled),
+            );
+
+        app.init_resource::<MetricProviders>();
+        let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+        if !providers.contains(INPUT_LATENCY_ID) {
+            providers.add_provider(InputLatencyMetricProvider);
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Pipelined rendering presents a frame while the next one updates
+        if app.is_plugin_added::<PipelinedRenderingPlugin>() {
+            app.world_mut().resource_mut::<PendingInputs>().render_lag = 1;
+        }
+    }
+}
+
+/// Inputs waiting for the frame that reacts to them to be presented.
+#[derive(Resource, Default)]
+struct PendingInputs {
+    /// Frame at whose start the reacting frame counts as presented, and the
+    /// start of the frame that read the input
+    queue: VecDeque<(u32, Instant)>,
+    /// Frames between a frame's update and its presentation
+    render_lag: u32,
+}
+
+fn stamp_input_events(
+    mut keys: EventReader<KeyboardInput>,
+    mut mouse_buttons: EventReader<MouseButtonInput>,
+    mut touches: EventReader<TouchInput>,
+    mut gamepad_buttons: EventReader<GamepadButtonChangedEvent>,
+    frame: Res<FrameCount>,
+    time: Res<Time<Real>>,
+    mut pending: ResMut<PendingInputs>,
+) {
+    // `count` drains every reader, so each frame's inputs are consumed once
+    let inputs = keys.read().count()
+        + mouse_buttons.read().count()
+        + touches.read().count()
+        + gamepad_buttons.read().count();
+    if inputs == 0 {
+        return;
+    }
+    let presented_at = frame.0 + 1 + pending.render_lag;
+    let started = time.last_update().unwrap_or_else(Instant::now);
+    pending.queue.push_back((presented_at, started));
+}
+
+fn record_input_latency(
+    mut diagnostics: Diagnostics,
+    frame: Res<FrameCount>,
+    time: Res<Time<Real>>,
+    mut pending: ResMut<PendingInputs>,
+) {
+    let now = time.last_update().unwrap_or_else(Instant::now);
+    while let Some(&(presented_at, started)) = pending.queue.front() {
+        if frame.0 < presented_at {
+            break; // Not presented yet
+        }
+        pending.queue.pop_front();
+        let latency = now.saturating_duration_since(started).as_secs_f64() * 1000.0;
+        diagnostics.add_measurement(&INPUT_LATENCY_PATH, || latency);
+    }
+}
+
+/// Metric provider for the input-to-present latency in milliseconds.
+///
+/// Reads the diagnostic recorded by [`InputLatencyDiagnosticsPlugin`], and
+/// keeps the latency of the latest input until the next one.
+#[derive(Default)]
+pub struct InputLatencyMetricProvider;
+
+impl PerfMetricProvider for InputLatencyMetricProvider {
+    fn metric_id(&self) -> &str {
+        INPUT_LATENCY_ID
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&INPUT_LATENCY_PATH)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &INPUT_LATENCY_PATH)
+    }
+}
@@ -0,0 +1,82 @@
+//! Metric providers reading a value straight from the world.
+//!
+//! [`PerfHudAppExt::add_perf_metric_fn`](super::PerfHudAppExt) plots any
+//! value the app already keeps, such as a resource field, without writing a
+//! provider type. The closure runs each frame in a read-only system just
+//! before the HUD samples its providers, and its values are recorded as a
+//! diagnostic, so the metric also has history to backfill from.
+
+use bevy::{
+    app::{App, Update},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::world::World,
+    prelude::IntoScheduleConfigs,
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+use crate::{plugin::hud_enabled, PerfHudSystems};
+
+/// Diagnostic a closure metric is recorded to.
+fn metric_fn_path(metric_id: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!("perf_hud/fn/{metric_id}"))
+}
+
+/// Record the value `read` returns each frame as the metric `metric_id`.
+///
+/// Also registers a [`MetricFnProvider`] for the metric, replacing any
+/// provider already registered under its ID.
+pub(super) fn add_metric_fn<F>(app: &mut App, metric_id: &str, read: F)
+where
+    F: Fn(&World) -> f32 + Send + Sync + 'static,
+{
+    let path = metric_fn_path(metric_id);
+    app.register_diagnostic(Diagnostic::new(path.clone()));
+    app.add_systems(
+        Update,
+        (move |world: &World, mut diagnostics: Diagnostics| {
+            let value = f64::from(read(world));
+            diagnostics.add_measurement(&path, || value);
+        })
+        .before(PerfHudSystems)
+        .run_if(hud_enabled),
+    );
+
+    app.init_resource::<MetricProviders>();
+    let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+    providers.remove(metric_id);
+    providers.add_provider(MetricFnProvider::new(metric_id));
+}
+
+/// Metric provider for a value read by a closure.
+///
+/// Reads the values recorded for metrics set up with
+/// [`PerfHudAppExt::add_perf_metric_fn`](super::PerfHudAppExt).
+pub struct MetricFnProvider {
+    metric_id: String,
+    path: DiagnosticPath,
+}
+
+impl MetricFnProvider {
+    /// Create a provider for the closure metric `metric_id`
+    pub fn new(metric_id: &str) -> Self {
+        Self {
+            metric_id: metric_id.to_owned(),
+            path: metric_fn_path(metric_id),
+        }
+    }
+}
+
+impl PerfMetricProvider for MetricFnProvider {
+    fn metric_id(&self) -> &str {
+        &self.metric_id
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
@@ -3,16 +3,65 @@
 //! This module contains the trait-based system that allows the HUD to display
 //! both built-in and custom performance metrics.
 
+#[cfg(feature = "hud")]
+use bevy::{app::App, diagnostic::DiagnosticPath, tasks::available_parallelism};
 use bevy::{
-    app::App,
-    diagnostic::{
-        DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
-        SystemInformationDiagnosticsPlugin,
-    },
+    diagnostic::{DiagnosticsStore, SystemInfo},
+    ecs::event::Event,
+    ecs::system::IntoSystem,
+    ecs::world::World,
     prelude::Resource,
 };
 
-use crate::constants::*;
+#[cfg(feature = "hud")]
+use crate::{PerfHudAlert, PerfHudAlerts};
+
+// Providers and diagnostics plugins are only compiled with the HUD
+#[cfg(feature = "hud")]
+mod asset_memory;
+#[cfg(feature = "hud")]
+mod builtin;
+#[cfg(feature = "hud")]
+mod counters;
+#[cfg(feature = "hud")]
+mod cpu_cores;
+#[cfg(feature = "hud")]
+mod event_counts;
+#[cfg(feature = "hud")]
+mod fixed_timestep;
+#[cfg(feature = "hud")]
+mod input_latency;
+#[cfg(feature = "hud")]
+mod metric_fn;
+#[cfg(feature = "hud")]
+mod render_stats;
+#[cfg(all(feature = "hud", feature = "system-timings"))]
+mod system_timings;
+#[cfg(feature = "hud")]
+mod task_pools;
+
+#[cfg(feature = "hud")]
+pub use asset_memory::*;
+#[cfg(feature = "hud")]
+pub use builtin::*;
+#[cfg(feature = "hud")]
+pub use counters::*;
+#[cfg(feature = "hud")]
+pub use cpu_cores::*;
+#[cfg(feature = "hud")]
+pub use event_counts::EventCountMetricProvider;
+#[cfg(feature = "hud")]
+pub use fixed_timestep::*;
+#[cfg(feature = "hud")]
+pub use input_latency::*;
+#[cfg(feature = "hud")]
+pub use metric_fn::MetricFnProvider;
+#[cfg(feature = "hud")]
+pub use render_stats::*;
+#[cfg(all(feature = "hud", feature = "system-timings"))]
+pub use system_timings::*;
+#[cfg(feature = "hud")]
+pub use task_pools::*;
 
 /// Context passed to metric providers during sampling.
 ///
@@ -22,8 +71,18 @@ use crate::constants::*;
 pub struct MetricSampleContext<'a> {
     /// Reference to Bevy's diagnostics store for built-in metrics
     pub diagnostics: Option<&'a DiagnosticsStore>,
+    /// Static system information (total memory, core count) when available
+    pub system_info: Option<&'a SystemInfo>,
 }
 
+/// Diagnostics store sampled instead of the main world's [`DiagnosticsStore`].
+///
+/// Insert this resource when the diagnostics of interest live outside the
+/// main world (e.g. in a render sub-app) and keep it up to date from there;
+/// while it exists, providers receive it as [`MetricSampleContext::diagnostics`].
+#[derive(Resource, Default)]
+pub struct HudDiagnosticsSource(pub DiagnosticsStore);
+
 /// Trait for implementing custom performance metric providers.
 ///
 /// This trait allows you to create custom metrics that can be displayed
@@ -61,18 +120,31 @@ pub trait PerfMetricProvider: Send + Sync + 'static {
     /// # Returns
     /// The current metric value, or None if unavailable
     fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32>;
+
+    /// Return previously recorded values for this metric, oldest first.
+    ///
+    /// Used to backfill the graph history when the HUD (re)starts so the graph
+    /// is not empty. Providers without recorded history return an empty vector.
+    ///
+    /// # Arguments
+    /// * `ctx` - Context containing diagnostic data and other resources
+    fn history(&self, _ctx: MetricSampleContext) -> Vec<f32> {
+        Vec::new()
+    }
 }
 
 /// Resource managing the registry of all metric providers.
 ///
 /// This resource maintains a collection of all metric providers (both built-in
 /// and custom) and handles the sampling process during each frame update.
+#[cfg(feature = "hud")]
 #[derive(Resource, Default)]
 pub struct MetricProviders {
     /// Collection of all registered metric providers
     providers: Vec<Box<dyn PerfMetricProvider>>,
 }
 
+#[cfg(feature = "hud")]
 impl MetricProviders {
     /// Register a new metric provider.
     ///
@@ -93,6 +165,37 @@ impl MetricProviders {
         self.providers.iter().any(|p| p.metric_id() == id)
     }
 
+    /// Get a registered provider by its metric ID.
+    ///
+    /// # Arguments
+    /// * `id` - The metric ID to look up
+    pub fn get(&self, id: &str) -> Option<&dyn PerfMetricProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.metric_id() == id)
+            .map(|p| p.as_ref())
+    }
+
+    /// IDs of all registered providers, in registration order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|p| p.metric_id())
+    }
+
+    /// Remove the provider for a metric, e.g. a built-in one the app does not want.
+    ///
+    /// # Returns
+    /// true if a provider for this metric was registered
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.providers.len();
+        self.providers.retain(|p| p.metric_id() != id);
+        self.providers.len() != before
+    }
+
+    /// Remove all registered providers, including the built-in ones
+    pub fn clear(&mut self) {
+        self.providers.clear();
+    }
+
     /// Register all built-in metric providers if they haven't been added yet.
     ///
     /// This is called automatically by the plugin to ensure standard metrics
@@ -105,6 +208,31 @@ impl MetricProviders {
         self.ensure_provider(SystemMemUsageMetricProvider);
         self.ensure_provider(ProcessCpuUsageMetricProvider);
         self.ensure_provider(ProcessMemUsageMetricProvider);
+        self.ensure_provider(SystemMemUsedMetricProvider);
+        self.ensure_provider(SystemMemTotalMetricProvider);
+        self.ensure_provider(ProcessMemRssMetricProvider);
+        self.ensure_provider(CpuCoreCountMetricProvider);
+
+        let sampler = CpuCoreSampler::default();
+        for core in 0..available_parallelism() {
+            self.ensure_provider(CpuCoreUsageMetricProvider::new(core, sampler.clone()));
+        }
+
+        for kind in [
+            TaskPoolKind::Compute,
+            TaskPoolKind::AsyncCompute,
+            TaskPoolKind::Io,
+        ] {
+            self.ensure_provider(TaskPoolThreadsMetricProvider::new(kind));
+        }
+
+        for metric in FixedTimestepMetric::ALL {
+            self.ensure_provider(FixedTimestepMetricProvider::new(metric));
+        }
+
+        for metric in AssetMemoryMetric::ALL {
+            self.ensure_provider(AssetMemoryMetricProvider::new(metric));
+        }
     }
 
     /// Get a mutable iterator over all registered providers.
@@ -126,6 +254,7 @@ impl MetricProviders {
 ///
 /// This trait provides a convenient way to add custom metric providers
 /// to your Bevy application without needing to manually access resources.
+/// Without the `hud` cargo feature every method does nothing.
 ///
 /// # Example
 /// ```no_run
@@ -159,8 +288,74 @@ pub trait PerfHudAppExt {
     /// # Returns
     /// The app instance for method chaining
     fn add_perf_metric_provider<P: PerfMetricProvider>(&mut self, provider: P) -> &mut Self;
+
+    /// Count events of type `E` sent each frame and report them as the metric `metric_id`.
+    ///
+    /// Event types counted into the same metric are summed. The metric ID
+    /// must be a valid diagnostic path (no empty segments between slashes).
+    ///
+    /// # Returns
+    /// The app instance for method chaining
+    fn add_event_count_metric<E: Event>(&mut self, metric_id: &str) -> &mut Self;
+
+    /// Plot the value `read` returns each frame as the metric `metric_id`,
+    /// e.g. a field of a resource, without implementing [`PerfMetricProvider`].
+    ///
+    /// `read` gets read-only access to the world and runs every frame just
+    /// before the HUD samples its providers. The metric ID must be a valid
+    /// diagnostic path (no empty segments between slashes).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_perf_hud::PerfHudAppExt;
+    ///
+    /// #[derive(Resource)]
+    /// struct Enemies {
+    ///     alive: usize,
+    /// }
+    ///
+    /// App::new().add_perf_metric_fn("game/enemies", |world| {
+    ///     world.resource::<Enemies>().alive as f32
+    /// });
+    /// ```
+    ///
+    /// # Returns
+    /// The app instance for method chaining
+    fn add_perf_metric_fn(
+        &mut self,
+        metric_id: &str,
+        read: impl Fn(&World) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self;
+
+    /// Run `action` as a one-shot system each time the metric `metric_id`
+    /// passes the critical threshold of its budget, see
+    /// [`PerfHudSettings::budget_thresholds`](crate::PerfHudSettings::budget_thresholds).
+    /// Metrics other than `fps` and `frame_time_ms` need an entry in
+    /// [`PerfHudSettings::metric_budgets`](crate::PerfHudSettings::metric_budgets).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_perf_hud::PerfHudAppExt;
+    ///
+    /// fn lower_detail() {
+    ///     info!("frame time over budget, lowering detail");
+    /// }
+    ///
+    /// App::new().add_perf_alert("frame_time_ms", lower_detail);
+    /// ```
+    ///
+    /// # Returns
+    /// The app instance for method chaining
+    fn add_perf_alert<M>(
+        &mut self,
+        metric_id: &str,
+        action: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self;
 }
 
+#[cfg(feature = "hud")]
 impl PerfHudAppExt for App {
     fn add_perf_metric_provider<P: PerfMetricProvider>(&mut self, provider: P) -> &mut Self {
         self.init_resource::<MetricProviders>();
@@ -169,151 +364,40 @@ impl PerfHudAppExt for App {
             .add_provider(provider);
         self
     }
-}
-
-/// Built-in metric provider for frames per second (FPS).
-///
-/// Provides the current FPS value calculated by Bevy's frame time diagnostics.
-/// The value is floored to the nearest integer for display purposes.
-#[derive(Default)]
-pub struct FpsMetricProvider;
-
-impl PerfMetricProvider for FpsMetricProvider {
-    fn metric_id(&self) -> &str {
-        "fps"
-    }
-
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let fps = diagnostics
-            .get(&FrameTimeDiagnosticsPlugin::FPS)?
-            .average()?;
-        Some(fps as f32)
-    }
-}
-
-/// Built-in metric provider for frame time in milliseconds.
-///
-/// Provides the smoothed frame time duration from Bevy's diagnostics,
-/// converted to milliseconds and floored to the nearest integer.
-#[derive(Default)]
-pub struct FrameTimeMetricProvider;
-
-impl PerfMetricProvider for FrameTimeMetricProvider {
-    fn metric_id(&self) -> &str {
-        "frame_time_ms"
-    }
-
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let frame_time = diagnostics
-            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)?
-            .smoothed()?;
-        Some(frame_time as f32)
-    }
-}
-
-/// Built-in metric provider for the total number of entities.
-///
-/// Provides the current entity count from Bevy's entity diagnostics.
-/// Useful for monitoring memory usage and performance impact of entities.
-#[derive(Default)]
-pub struct EntityCountMetricProvider;
-
-impl PerfMetricProvider for EntityCountMetricProvider {
-    fn metric_id(&self) -> &str {
-        "entity_count"
-    }
-
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let entities = diagnostics
-            .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)?
-            .value()?;
-        Some(entities as f32)
-    }
-}
-
-/// Built-in metric provider for system-wide CPU usage percentage.
-///
-/// Provides the overall CPU usage across all cores and processes,
-/// as reported by Bevy's system information diagnostics.
-#[derive(Default)]
-pub struct SystemCpuUsageMetricProvider;
-
-impl PerfMetricProvider for SystemCpuUsageMetricProvider {
-    fn metric_id(&self) -> &str {
-        SYSTEM_CPU_USAGE_ID
-    }
-
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let usage = diagnostics
-            .get(&SystemInformationDiagnosticsPlugin::SYSTEM_CPU_USAGE)?
-            .value()?;
-        Some(usage as f32)
-    }
-}
-
-/// Built-in metric provider for system-wide memory usage percentage.
-///
-/// Provides the overall memory usage as a percentage of total system RAM,
-/// as reported by Bevy's system information diagnostics.
-#[derive(Default)]
-pub struct SystemMemUsageMetricProvider;
-
-impl PerfMetricProvider for SystemMemUsageMetricProvider {
-    fn metric_id(&self) -> &str {
-        SYSTEM_MEM_USAGE_ID
-    }
 
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let usage = diagnostics
-            .get(&SystemInformationDiagnosticsPlugin::SYSTEM_MEM_USAGE)?
-            .value()?;
-        Some(usage as f32)
+    fn add_event_count_metric<E: Event>(&mut self, metric_id: &str) -> &mut Self {
+        event_counts::add_event_count_metric::<E>(self, metric_id);
+        self
     }
-}
 
-/// Built-in metric provider for process-specific CPU usage percentage.
-///
-/// Provides the CPU usage of the current Bevy application process,
-/// as reported by Bevy's system information diagnostics.
-#[derive(Default)]
-pub struct ProcessCpuUsageMetricProvider;
-
-impl PerfMetricProvider for ProcessCpuUsageMetricProvider {
-    fn metric_id(&self) -> &str {
-        PROCESS_CPU_USAGE_ID
+    fn add_perf_metric_fn(
+        &mut self,
+        metric_id: &str,
+        read: impl Fn(&World) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self {
+        metric_fn::add_metric_fn(self, metric_id, read);
+        self
     }
 
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let usage = diagnostics
-            .get(&SystemInformationDiagnosticsPlugin::PROCESS_CPU_USAGE)?
-            .value()?;
-        Some(usage as f32)
+    fn add_perf_alert<M>(
+        &mut self,
+        metric_id: &str,
+        action: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self {
+        let action = self.register_system(action);
+        self.init_resource::<PerfHudAlerts>();
+        self.world_mut()
+            .resource_mut::<PerfHudAlerts>()
+            .add(PerfHudAlert::new(metric_id, action));
+        self
     }
 }
 
-/// Built-in metric provider for process-specific memory usage in bytes.
-///
-/// Provides the memory usage of the current Bevy application process,
-/// as reported by Bevy's system information diagnostics.
-#[derive(Default)]
-pub struct ProcessMemUsageMetricProvider;
-
-impl PerfMetricProvider for ProcessMemUsageMetricProvider {
-    fn metric_id(&self) -> &str {
-        PROCESS_MEM_USAGE_ID
-    }
-
-    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
-        let diagnostics = ctx.diagnostics?;
-        let usage = diagnostics
-            .get(&SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE)?
-            .value()?;
-        Some(usage as f32)
-    }
+/// Read the recorded values of a Bevy diagnostic, oldest first.
+#[cfg(feature = "hud")]
+fn diagnostic_history(ctx: MetricSampleContext, path: &DiagnosticPath) -> Vec<f32> {
+    ctx.diagnostics
+        .and_then(|diagnostics| diagnostics.get(path))
+        .map(|diagnostic| diagnostic.values().map(|&value| value as f32).collect())
+        .unwrap_or_default()
 }
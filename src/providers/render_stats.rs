@@ -0,0 +1,163 @@
+//! Renderer statistics metric providers.
+//!
+//! Bevy does not count draw calls, so [`RenderStatsDiagnosticsPlugin`]
+//! measures what the renderer is asked to draw instead: once visibility has
+//! been computed each frame it counts the visible entities, the visible mesh
+//! instances and their triangles, and records them as regular diagnostics
+//! that the providers below read back.
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    asset::Assets,
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::system::{Query, Res},
+    prelude::IntoScheduleConfigs,
+    render::{
+        mesh::{Mesh, Mesh2d, Mesh3d, PrimitiveTopology},
+        view::{ViewVisibility, VisibilitySystems},
+    },
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+use crate::{constants::*, plugin::hud_enabled};
+
+/// One of the renderer statistics metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStatsMetric {
+    /// Number of entities visible in any view
+    VisibleEntities,
+    /// Number of visible 2D and 3D mesh instances, i.e. draws before batching
+    MeshDraws,
+    /// Number of triangles of the visible meshes
+    Triangles,
+}
+
+impl RenderStatsMetric {
+    /// All renderer statistics metrics
+    pub const ALL: [Self; 3] = [Self::VisibleEntities, Self::MeshDraws, Self::Triangles];
+
+    /// Metric ID reporting this value
+    pub fn metric_id(self) -> &'static str {
+        match self {
+            Self::VisibleEntities => RENDER_VISIBLE_ENTITIES_ID,
+            Self::MeshDraws => RENDER_MESH_DRAWS_ID,
+            Self::Triangles => RENDER_TRIANGLES_ID,
+        }
+    }
+
+    /// Diagnostic the value is recorded to
+    fn diagnostic_path(self) -> DiagnosticPath {
+        match self {
+            Self::VisibleEntities => DiagnosticPath::const_new("perf_hud/render/visible_entities"),
+            Self::MeshDraws => DiagnosticPath::const_new("perf_hud/render/mesh_draws"),
+            Self::Triangles => DiagnosticPath::const_new("perf_hud/render/triangles"),
+        }
+    }
+}
+
+/// Plugin recording renderer statistics diagnostics.
+///
+/// Opt-in: add it to the app to enable the `render/*` metrics. Counting walks
+/// every entity with visibility each frame. Meshes whose asset only lives in
+/// the render world contribute no triangles.
+#[derive(Default)]
+pub struct RenderStatsDiagnosticsPlugin;
+
+impl Plugin for RenderStatsDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        for metric in RenderStatsMetric::ALL {
+            app.register_diagnostic(Diagnostic::new(metric.diagnostic_path()));
+        }
+        app.add_systems(
+            PostUpdate,
+            record_render_stats
+                .after(VisibilitySystems::CheckVisibility)
+                .run_if(hud_enabled),
+        );
+
+        app.init_resource::<MetricProviders>();
+        let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+        for metric in RenderStatsMetric::ALL {
+            if !providers.contains(metric.metric_id()) {
+                providers.add_provider(RenderStatsMetricProvider::new(metric));
+            }
+        }
+    }
+}
+
+fn record_render_stats(
+    mut diagnostics: Diagnostics,
+    visibility: Query<(&ViewVisibility, Option<&Mesh3d>, Option<&Mesh2d>)>,
+    meshes: Option<Res<Assets<Mesh>>>,
+) {
+    let mut visible_entities = 0usize;
+    let mut mesh_draws = 0usize;
+    let mut triangles = 0usize;
+    for (view_visibility, mesh_3d, mesh_2d) in &visibility {
+        if !view_visibility.get() {
+            continue;
+        }
+        visible_entities += 1;
+        let Some(handle) = mesh_3d.map(|mesh| &mesh.0).or(mesh_2d.map(|mesh| &mesh.0)) else {
+            continue;
+        };
+        mesh_draws += 1;
+        if let Some(mesh) = meshes.as_ref().and_then(|meshes| meshes.get(handle)) {
+            triangles += triangle_count(mesh);
+        }
+    }
+
+    let values = [
+        (RenderStatsMetric::VisibleEntities, visible_entities),
+        (RenderStatsMetric::MeshDraws, mesh_draws),
+        (RenderStatsMetric::Triangles, triangles),
+    ];
+    for (metric, value) in values {
+        diagnostics.add_measurement(&metric.diagnostic_path(), || value as f64);
+    }
+}
+
+/// Number of triangles a mesh is drawn with; lines and points have none.
+fn triangle_count(mesh: &Mesh) -> usize {
+    let vertices = mesh
+        .indices()
+        .map_or_else(|| mesh.count_vertices(), |indices| indices.len());
+    match mesh.primitive_topology() {
+        PrimitiveTopology::TriangleList => vertices / 3,
+        PrimitiveTopology::TriangleStrip => vertices.saturating_sub(2),
+        _ => 0,
+    }
+}
+
+/// Built-in metric provider for a renderer statistics metric.
+///
+/// Reads the diagnostics recorded by [`RenderStatsDiagnosticsPlugin`].
+pub struct RenderStatsMetricProvider {
+    metric: RenderStatsMetric,
+    path: DiagnosticPath,
+}
+
+impl RenderStatsMetricProvider {
+    /// Create a provider for the given metric
+    pub fn new(metric: RenderStatsMetric) -> Self {
+        Self {
+            metric,
+            path: metric.diagnostic_path(),
+        }
+    }
+}
+
+impl PerfMetricProvider for RenderStatsMetricProvider {
+    fn metric_id(&self) -> &str {
+        self.metric.metric_id()
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
@@ -0,0 +1,579 @@
+//! Per-system execution time metric providers.
+//!
+//! With Bevy's `trace` feature every system runs inside a `system` tracing
+//! span. [`system_timings_layer`] adds up how long each of those spans is
+//! entered, and [`SystemTimingsPlugin`] ranks the systems once per frame,
+//! exposing the slowest ones as the `system_time/top/<rank>` metrics and
+//! optionally as a bar group below the HUD naming them.
+
+use std::{
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bevy::{
+    app::{App, First, Plugin, Update},
+    asset::{Assets, Handle},
+    color::Color,
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::{
+        entity::Entity,
+        system::{Commands, Query, Res, ResMut},
+    },
+    log::{
+        tracing::{
+            field::{Field, Visit},
+            span::{Attributes, Id},
+            Subscriber,
+        },
+        tracing_subscriber::{layer::Context, registry::LookupSpan, Layer},
+        warn, BoxedLayer,
+    },
+    platform::time::Instant,
+    prelude::{default, Resource},
+    text::{TextColor, TextFont},
+    ui::{widget::Text, AlignItems, FlexDirection, MaterialNode, Node, UiRect, Val},
+};
+
+use super::{diagnostic_history, MetricProviders, MetricSampleContext, PerfMetricProvider};
+use crate::{
+    compat::{despawn_tree, set_parent},
+    constants::*,
+    render::{BarMaterial, BarParams},
+    HudHandles, PerfHudSettings,
+};
+
+/// Build the metric ID reporting the execution time of the system ranked
+/// `rank` (zero-based, slowest first) on the latest frame.
+pub fn system_time_rank_id(rank: usize) -> String {
+    format!("{SYSTEM_TIME_RANK_PREFIX}{rank}")
+}
+
+fn system_time_rank_path(rank: usize) -> DiagnosticPath {
+    DiagnosticPath::new(format!("perf_hud/system_time/top/{rank}"))
+}
+
+/// Create the tracing layer timing system spans, for
+/// [`LogPlugin::custom_layer`](bevy::log::LogPlugin::custom_layer).
+///
+/// Also inserts the [`SystemTimings`] resource the layer records to. Without
+/// Bevy's `trace` feature systems do not emit spans and nothing is recorded.
+///
+/// # Example
+/// ```rust,no_run
+/// use bevy::{log::LogPlugin, prelude::*};
+/// use bevy_perf_hud::{system_timings_layer, BevyPerfHudPlugin, SystemTimingsPlugin};
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins.set(LogPlugin {
+///         custom_layer: system_timings_layer,
+///         ..default()
+///     }))
+///     .add_plugins((BevyPerfHudPlugin, SystemTimingsPlugin::default()))
+///     .run();
+/// ```
+pub fn system_timings_layer(app: &mut App) -> Option<BoxedLayer> {
+    let timings = SystemTimings::default();
+    app.insert_resource(timings.clone());
+    Some(Box::new(SystemTimingLayer { timings }))
+}
+
+/// Execution time accumulated per system since the last frame's ranking.
+///
+/// Clones share the same systems; the layer only takes the lock when a system
+/// span is created, running systems add to their own atomic counter.
+#[derive(Resource, Clone, Default)]
+pub struct SystemTimings {
+    systems: Arc<Mutex<Vec<SystemTimingEntry>>>,
+}
+
+struct SystemTimingEntry {
+    name: String,
+    short_name: Arc<str>,
+    nanos: Arc<AtomicU64>,
+}
+
+impl SystemTimings {
+    /// Counter of the system `name`, shared by all spans with that name.
+    fn counter(&self, name: &str) -> Option<Arc<AtomicU64>> {
+        let mut systems = self.systems.lock().ok()?;
+        if let Some(entry) = systems.iter().find(|entry| entry.name == name) {
+            return Some(entry.nanos.clone());
+        }
+        let nanos = Arc::new(AtomicU64::new(0));
+        systems.push(SystemTimingEntry {
+            name: name.to_owned(),
+            short_name: short_system_name(name).into(),
+            nanos: nanos.clone(),
+        });
+        Some(nanos)
+    }
+}
+
+/// Strip the module paths from a system name, keeping generic arguments.
+fn short_system_name(name: &str) -> String {
+    let is_delimiter =
+        |c: char| matches!(c, '<' | '>' | '(' | ')' | '[' | ']' | ',' | ';' | ' ' | '&');
+    let mut short = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(pos) = rest.find(is_delimiter) {
+        let (path, tail) = rest.split_at(pos);
+        short.push_str(path.rsplit("::").next().unwrap_or(path));
+        short.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+    short.push_str(rest.rsplit("::").next().unwrap_or(rest));
+    short
+}
+
+/// Tracing layer adding up the time spent in `system` spans.
+struct SystemTimingLayer {
+    timings: SystemTimings,
+}
+
+/// Timing state stored in the extensions of a system span.
+struct SystemSpanTiming {
+    nanos: Arc<AtomicU64>,
+    entered: Option<Instant>,
+}
+
+/// Reads the `name` field of a system span.
+#[derive(Default)]
+struct SystemNameVisitor(Option<String>);
+
+impl Visit for SystemNameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.0 = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "name" && self.0.is_none() {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl<S> Layer<S> for SystemTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "system" {
+            return;
+        }
+        let mut visitor = SystemNameVisitor::default();
+        attrs.record(&mut visitor);
+        let (Some(name), Some(span)) = (visitor.0, ctx.span(id)) else {
+            return;
+        };
+        if let Some(nanos) = self.timings.counter(&name) {
+            span.extensions_mut().insert(SystemSpanTiming {
+                nanos,
+                entered: None,
+            });
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SystemSpanTiming>() {
+            timing.entered = Some(Instant::now());
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SystemSpanTiming>() {
+            if let Some(entered) = timing.entered.take() {
+                let nanos = u64::try_from(entered.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                timing.nanos.fetch_add(nanos, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Plugin ranking systems by execution time and recording the slowest ones
+/// as the diagnostics read by [`SystemTimeRankMetricProvider`].
+///
+/// Opt-in: requires Bevy's `trace` feature (enabled by this crate's
+/// `system-timings` feature) and [`system_timings_layer`] installed in the
+/// [`LogPlugin`](bevy::log::LogPlugin).
+pub struct SystemTimingsPlugin {
+    /// Number of slowest systems exposed as metrics
+    pub top: usize,
+    /// Whether to show the ranked systems as a bar group below the HUD
+    pub show_bars: bool,
+    /// Fill color of the ranked system bars
+    pub color: Color,
+    /// Background color of the ranked system bars (supports transparency)
+    pub bg_color: Color,
+}
+
+impl Default for SystemTimingsPlugin {
+    fn default() -> Self {
+        Self {
+            top: 5,
+            show_bars: true,
+            color: Color::srgb(0.58, 0.48, 0.96),
+            bg_color: Color::srgba(0.12, 0.12, 0.12, 0.6),
+        }
+    }
+}
+
+impl Plugin for SystemTimingsPlugin {
+    fn build(&self, app: &mut App) {
+        for rank in 0..self.top {
+            app.register_diagnostic(Diagnostic::new(system_time_rank_path(rank)));
+        }
+        app.insert_resource(SystemTimingRanking {
+            top: self.top,
+            ..default()
+        })
+        .add_systems(First, rank_system_timings);
+
+        if self.show_bars {
+            app.insert_resource(SystemTimingBars {
+                color: self.color,
+                bg_color: self.bg_color,
+                ..default()
+            })
+            .add_systems(Update, update_system_timing_bars);
+        }
+
+        app.init_resource::<MetricProviders>();
+        let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+        for rank in 0..self.top {
+            let id = system_time_rank_id(rank);
+            if !providers.contains(&id) {
+                providers.add_provider(SystemTimeRankMetricProvider::new(rank));
+            }
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if !app.world().contains_resource::<SystemTimings>() {
+            warn!(
+                "SystemTimingsPlugin needs `system_timings_layer` as the LogPlugin's custom layer; \
+                 no system timings will be recorded"
+            );
+        }
+    }
+}
+
+/// Systems ranked by their execution time on the latest frame, slowest first.
+#[derive(Resource, Default)]
+pub struct SystemTimingRanking {
+    top: usize,
+    ranked: Vec<RankedSystem>,
+}
+
+/// A system and its execution time on the latest frame.
+#[derive(Clone, Debug)]
+pub struct RankedSystem {
+    /// System name without module paths
+    pub name: Arc<str>,
+    /// Time spent running the system in milliseconds
+    pub time_ms: f32,
+}
+
+impl SystemTimingRanking {
+    /// The slowest systems of the latest frame, at most the plugin's `top`.
+    pub fn top(&self) -> &[RankedSystem] {
+        &self.ranked[..self.top.min(self.ranked.len())]
+    }
+}
+
+/// Take the time accumulated by each system and rank the systems.
+fn rank_system_timings(
+    timings: Option<Res<SystemTimings>>,
+    mut ranking: ResMut<SystemTimingRanking>,
+    mut diagnostics: Diagnostics,
+) {
+    let Some(timings) = timings else {
+        return;
+    };
+    let Ok(systems) = timings.systems.lock() else {
+        return;
+    };
+
+    // Reuse the ranked entries to keep the per-frame work allocation free
+    let ranking = &mut *ranking;
+    ranking.ranked.truncate(systems.len());
+    for (i, entry) in systems.iter().enumerate() {
+        let time_ms = entry.nanos.swap(0, Ordering::Relaxed) as f32 / 1_000_000.0;
+        match ranking.ranked.get_mut(i) {
+            Some(ranked) => {
+                if !Arc::ptr_eq(&ranked.name, &entry.short_name) {
+                    ranked.name = entry.short_name.clone();
+                }
+                ranked.time_ms = time_ms;
+            }
+            None => ranking.ranked.push(RankedSystem {
+                name: entry.short_name.clone(),
+                time_ms,
+            }),
+        }
+    }
+    drop(systems);
+    ranking
+        .ranked
+        .sort_unstable_by(|a, b| b.time_ms.total_cmp(&a.time_ms));
+
+    for rank in 0..ranking.top {
+        // Fewer systems than ranks: report the missing ones as idle
+        let time_ms = ranking
+            .ranked
+            .get(rank)
+            .map_or(0.0, |ranked| ranked.time_ms);
+        diagnostics.add_measurement(&system_time_rank_path(rank), || f64::from(time_ms));
+    }
+}
+
+/// Metric provider for the execution time of the system at one rank.
+///
+/// Which system holds the rank changes from frame to frame; see
+/// [`SystemTimingRanking`] for the names.
+pub struct SystemTimeRankMetricProvider {
+    id: String,
+    path: DiagnosticPath,
+}
+
+impl SystemTimeRankMetricProvider {
+    /// Create a provider for one rank.
+    ///
+    /// # Arguments
+    /// * `rank` - Zero-based rank, slowest system first
+    pub fn new(rank: usize) -> Self {
+        Self {
+            id: system_time_rank_id(rank),
+            path: system_time_rank_path(rank),
+        }
+    }
+}
+
+impl PerfMetricProvider for SystemTimeRankMetricProvider {
+    fn metric_id(&self) -> &str {
+        &self.id
+    }
+
+    fn sample(&mut self, ctx: MetricSampleContext) -> Option<f32> {
+        let value = ctx.diagnostics?.get(&self.path)?.value()?;
+        Some(value as f32)
+    }
+
+    fn history(&self, ctx: MetricSampleContext) -> Vec<f32> {
+        diagnostic_history(ctx, &self.path)
+    }
+}
+
+/// Bar group listing the slowest systems below the HUD.
+#[derive(Resource, Default)]
+struct SystemTimingBars {
+    color: Color,
+    bg_color: Color,
+    /// HUD root the group was spawned under, to respawn it after a rebuild
+    hud_root: Option<Entity>,
+    root: Option<Entity>,
+    rows: Vec<(Handle<BarMaterial>, Entity)>,
+}
+
+/// Spawn the ranked system bars under the HUD and show the latest ranking.
+///
+/// Bars are scaled relative to the slowest system.
+fn update_system_timing_bars(
+    mut commands: Commands,
+    handles: Option<Res<HudHandles>>,
+    settings: Option<Res<PerfHudSettings>>,
+    ranking: Res<SystemTimingRanking>,
+    mut bars: ResMut<SystemTimingBars>,
+    mut bar_mats: ResMut<Assets<BarMaterial>>,
+    mut texts: Query<&mut Text>,
+) {
+    let bars = &mut *bars;
+    let hud_root = handles.and_then(|handles| handles.root);
+    if bars.hud_root != hud_root {
+        // The HUD was rebuilt or despawned along with the previous group
+        if let Some(root) = bars.root.take() {
+            despawn_tree(&mut commands, root);
+        }
+        for (material, _) in bars.rows.drain(..) {
+            bar_mats.remove(&material);
+        }
+        bars.hud_root = hud_root;
+        if let (Some(hud_root), Some(settings)) = (hud_root, settings) {
+            spawn_system_timing_bars(
+                &mut commands,
+                bars,
+                &mut bar_mats,
+                &settings,
+                hud_root,
+                ranking.top,
+            );
+        }
+        return;
+    }
+
+    let ranked = ranking.top();
+    let slowest = ranked.first().map_or(0.0, |ranked| ranked.time_ms);
+    for (rank, (material, label)) in bars.rows.iter().enumerate() {
+        let (fill, name, time_ms) = match ranked.get(rank) {
+            Some(ranked) if slowest > 0.0 => {
+                (ranked.time_ms / slowest, &*ranked.name, ranked.time_ms)
+            }
+            Some(ranked) => (0.0, &*ranked.name, ranked.time_ms),
+            None => (0.0, "", 0.0),
+        };
+        let params = BarParams::new(fill.clamp(0.0, 1.0), bars.color, bars.bg_color);
+        if bar_mats
+            .get(material)
+            .is_some_and(|mat| mat.params != params)
+        {
+            if let Some(mat) = bar_mats.get_mut(material) {
+                mat.params = params;
+            }
+        }
+        if let Ok(mut text) = texts.get_mut(*label) {
+            // Rewrite in place to keep the existing allocation
+            text.0.clear();
+            if !name.is_empty() {
+                let _ = write!(text.0, "{time_ms:.2} ms {name}");
+            }
+        }
+    }
+}
+
+fn spawn_system_timing_bars(
+    commands: &mut Commands,
+    bars: &mut SystemTimingBars,
+    bar_mats: &mut Assets<BarMaterial>,
+    settings: &PerfHudSettings,
+    hud_root: Entity,
+    top: usize,
+) {
+    let root = commands
+        .spawn(Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            margin: UiRect {
+                left: Val::Px(settings.graph.label_column.reserved_width()),
+                top: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+    set_parent(commands, root, hud_root);
+    bars.root = Some(root);
+
+    let bar_width = settings.graph.size.x * 0.4;
+    for _ in 0..top {
+        let row = commands
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            })
+            .id();
+        set_parent(commands, row, root);
+
+        let material = bar_mats.add(BarMaterial {
+            params: BarParams::new(0.0, bars.color, bars.bg_color),
+        });
+        let bar = commands
+            .spawn((
+                MaterialNode(material.clone()),
+                Node {
+                    width: Val::Px(bar_width),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+            ))
+            .id();
+        set_parent(commands, bar, row);
+
+        let label = commands
+            .spawn((
+                Text::new(String::with_capacity(64)),
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.8)),
+                TextFont {
+                    font_size: 9.0,
+                    ..default()
+                },
+            ))
+            .id();
+        set_parent(commands, label, row);
+        bars.rows.push((material, label));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use bevy::{
+        diagnostic::DiagnosticsStore,
+        log::{
+            info_span, tracing,
+            tracing_subscriber::{self, prelude::*},
+        },
+        prelude::*,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_short_system_name_strips_module_paths() {
+        assert_eq!(short_system_name("my_game::physics::step"), "step");
+        assert_eq!(
+            short_system_name("bevy_ecs::event::event_update_system<my_game::Hit>"),
+            "event_update_system<Hit>"
+        );
+        assert_eq!(short_system_name("tick"), "tick");
+    }
+
+    #[test]
+    fn test_slowest_system_ranks_first() {
+        let mut app = App::new();
+        app.add_plugins(SystemTimingsPlugin {
+            top: 2,
+            show_bars: false,
+            ..default()
+        });
+        let layer = system_timings_layer(&mut app).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            for (name, millis) in [("game::fast", 1), ("game::slow", 6), ("game::fast", 1)] {
+                let span = info_span!("system", name = name);
+                let _entered = span.enter();
+                thread::sleep(Duration::from_millis(millis));
+            }
+            app.update();
+        });
+
+        let ranking = app.world().resource::<SystemTimingRanking>();
+        let names: Vec<_> = ranking.top().iter().map(|r| &*r.name).collect();
+        assert_eq!(names, ["slow", "fast"]);
+        assert!(ranking.top()[0].time_ms >= 6.0);
+        assert!(ranking.top()[1].time_ms >= 2.0);
+
+        let diagnostics = app.world().resource::<DiagnosticsStore>();
+        let slowest = diagnostics
+            .get(&system_time_rank_path(0))
+            .and_then(|d| d.value())
+            .unwrap();
+        assert!(slowest >= 6.0);
+    }
+}
@@ -0,0 +1,145 @@
+//! Task pool utilization metric providers.
+//!
+//! Bevy does not expose queue depths of its global task pools, so saturation
+//! is measured indirectly: each provider keeps one tiny probe task in flight
+//! and reports how long it waited before a worker thread picked it up.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+use bevy::{
+    platform::time::Instant,
+    tasks::{AsyncComputeTaskPool, ComputeTaskPool, IoTaskPool, TaskPool},
+};
+
+use super::{MetricSampleContext, PerfMetricProvider};
+use crate::constants::*;
+
+/// One of Bevy's global task pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskPoolKind {
+    /// [`ComputeTaskPool`], used for per-frame parallel work such as system execution
+    Compute,
+    /// [`AsyncComputeTaskPool`], used for long-running background work
+    AsyncCompute,
+    /// [`IoTaskPool`], used for asset loading and other IO
+    Io,
+}
+
+impl TaskPoolKind {
+    /// Get the global pool, if it has been initialized
+    fn pool(self) -> Option<&'static TaskPool> {
+        match self {
+            Self::Compute => ComputeTaskPool::try_get().map(|p| &**p),
+            Self::AsyncCompute => AsyncComputeTaskPool::try_get().map(|p| &**p),
+            Self::Io => IoTaskPool::try_get().map(|p| &**p),
+        }
+    }
+
+    /// Metric ID reporting the pool's thread count
+    pub fn threads_metric_id(self) -> &'static str {
+        match self {
+            Self::Compute => TASK_POOL_COMPUTE_THREADS_ID,
+            Self::AsyncCompute => TASK_POOL_ASYNC_COMPUTE_THREADS_ID,
+            Self::Io => TASK_POOL_IO_THREADS_ID,
+        }
+    }
+
+    /// Metric ID reporting the pool's queue latency
+    pub fn latency_metric_id(self) -> &'static str {
+        match self {
+            Self::Compute => TASK_POOL_COMPUTE_LATENCY_ID,
+            Self::AsyncCompute => TASK_POOL_ASYNC_COMPUTE_LATENCY_ID,
+            Self::Io => TASK_POOL_IO_LATENCY_ID,
+        }
+    }
+}
+
+/// Built-in metric provider for the number of threads in a task pool.
+pub struct TaskPoolThreadsMetricProvider {
+    kind: TaskPoolKind,
+}
+
+impl TaskPoolThreadsMetricProvider {
+    /// Create a provider for the given pool
+    pub fn new(kind: TaskPoolKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl PerfMetricProvider for TaskPoolThreadsMetricProvider {
+    fn metric_id(&self) -> &str {
+        self.kind.threads_metric_id()
+    }
+
+    fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+        Some(self.kind.pool()?.thread_num() as f32)
+    }
+}
+
+#[derive(Default)]
+struct ProbeShared {
+    in_flight: AtomicBool,
+    latency_us: AtomicU64,
+}
+
+/// Built-in metric provider for task pool queue latency in milliseconds.
+///
+/// Reports the time the most recent probe task spent queued before running.
+/// While a probe is still waiting, the time waited so far is reported, so a
+/// fully saturated pool shows a steadily growing latency instead of a flat line.
+///
+/// Each probe is a newly spawned task, so the provider allocates and wakes a
+/// pool thread while sampling. It is not registered by default; add it with
+/// [`PerfHudAppExt::add_perf_metric_provider`](crate::PerfHudAppExt::add_perf_metric_provider)
+/// for the pools you want to watch.
+pub struct TaskPoolLatencyMetricProvider {
+    kind: TaskPoolKind,
+    shared: Arc<ProbeShared>,
+    spawned_at: Option<Instant>,
+}
+
+impl TaskPoolLatencyMetricProvider {
+    /// Create a provider for the given pool
+    pub fn new(kind: TaskPoolKind) -> Self {
+        Self {
+            kind,
+            shared: Arc::default(),
+            spawned_at: None,
+        }
+    }
+}
+
+impl PerfMetricProvider for TaskPoolLatencyMetricProvider {
+    fn metric_id(&self) -> &str {
+        self.kind.latency_metric_id()
+    }
+
+    fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+        let pool = self.kind.pool()?;
+        let last_ms = self.shared.latency_us.load(Ordering::Acquire) as f32 / 1000.0;
+
+        if self.shared.in_flight.load(Ordering::Acquire) {
+            // Probe still queued: report how long it has been waiting so far
+            let waiting_ms = self
+                .spawned_at
+                .map_or(0.0, |t| t.elapsed().as_secs_f32() * 1000.0);
+            return Some(waiting_ms.max(last_ms));
+        }
+
+        let spawned_at = Instant::now();
+        let shared = Arc::clone(&self.shared);
+        shared.in_flight.store(true, Ordering::Release);
+        pool.spawn(async move {
+            let latency_us = spawned_at.elapsed().as_micros() as u64;
+            shared.latency_us.store(latency_us, Ordering::Release);
+            shared.in_flight.store(false, Ordering::Release);
+        })
+        .detach();
+        self.spawned_at = Some(spawned_at);
+
+        Some(last_ms)
+    }
+}
@@ -9,13 +9,17 @@
 
 use bevy::{
     asset::Asset,
-    math::Vec4,
+    color::{Color, ColorToComponents},
+    math::{UVec4, Vec2, Vec4},
     reflect::TypePath,
     render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
     ui::UiMaterial,
 };
 
-use crate::constants::{MAX_CURVES, SAMPLES_VEC4};
+use crate::{
+    config::{CurveStyle, LineSmoothing},
+    constants::{BARS_VEC4, MAX_BARS, MAX_CURVES, MISSING_VEC4, SAMPLES_VEC4},
+};
 
 // ============================================================================
 // SHADER PARAMETER STRUCTURES
@@ -31,11 +35,18 @@ pub struct MultiLineGraphParams {
     /// 2D array storing all graph values \[curve_index\]\[vec4_chunk_index\]
     /// Each curve's data is packed into Vec4 chunks for efficient GPU access
     pub values: [[Vec4; SAMPLES_VEC4]; MAX_CURVES],
+    /// Per-sample min/max ranges \[curve_index\]\[vec4_chunk_index\], used by candlestick curves.
+    /// Each u32 packs the range normalized to the displayed Y axis as two unorm16 values
+    /// (min in the low half, max in the high half)
+    pub ranges: [[UVec4; SAMPLES_VEC4]; MAX_CURVES],
+    /// Per-sample missing-data bits \[curve_index\]\[vec4_chunk_index\]
+    /// Bit `k % 32` of word `k / 32` is set when sample k has no value and the curve breaks there
+    pub missing: [[UVec4; MISSING_VEC4]; MAX_CURVES],
     /// Number of valid data points currently stored in the values array
     pub length: u32,
-    /// Minimum Y-axis value for scaling the graph display
+    /// Minimum Y-axis value shared by ungrouped curves
     pub min_y: f32,
-    /// Maximum Y-axis value for scaling the graph display
+    /// Maximum Y-axis value shared by ungrouped curves
     pub max_y: f32,
     /// Line thickness factor for rendering graph curves (0.0-1.0 normalized)
     pub thickness: f32,
@@ -59,14 +70,99 @@ pub struct MultiLineGraphParams {
     pub border_top: u32,
     /// Array of colors for each curve in the graph (RGBA format)
     pub colors: [Vec4; MAX_CURVES],
+    /// Y-axis range each curve is drawn against, as (min, max, series_start, previous_opacity)
+    /// Curves of a group autoscaled on its own get that group's range; curves
+    /// rebound to another metric get the sample index their current series
+    /// starts at (0 = not rebound) and the opacity of the previous series
+    pub scales: [Vec4; MAX_CURVES],
     /// Number of curves currently active in the graph
     pub curve_count: u32,
+    /// Bitmask of curves rendered as candlesticks (bit i = curve i)
+    pub candlestick_mask: u32,
+    /// Shader quality level (0 = high, 1 = medium, 2 = low), see `GraphQuality`
+    pub quality: u32,
+    /// Curve index drawn at each layer, bottom first, packed 4 bits per layer
+    /// (layer n in bits 4n..4n+4)
+    pub draw_order: u32,
+    /// Opacity multiplier of the whole graph (0.0-1.0), animated by HUD transitions
+    pub opacity: f32,
+    /// Line style of each curve, packed 2 bits per curve (curve i in bits
+    /// 2i..2i+2), see [`CurveStyle`](crate::CurveStyle)
+    pub curve_styles: u32,
+    /// Budget thresholds of each curve, as (warning, critical, shown, 0) in the
+    /// curve's units; lines are drawn only when `shown` is 1
+    pub budget_lines: [Vec4; MAX_CURVES],
+    /// Color of warning budget lines (RGBA format)
+    pub budget_warning_color: Vec4,
+    /// Color of critical budget lines (RGBA format)
+    pub budget_critical_color: Vec4,
+    /// Threshold shading of each curve, as (threshold, direction, shown, 0) in
+    /// the curve's units; direction is 1 to shade above and -1 below, and the
+    /// area is shaded only when `shown` is 1
+    pub shading: [Vec4; MAX_CURVES],
+    /// Color of each curve's shaded area (RGBA format)
+    pub shading_colors: [Vec4; MAX_CURVES],
+    /// Color of the horizontal grid lines (RGBA format)
+    pub grid_color: Vec4,
+    /// Number of horizontal grid lines, evenly spaced from the bottom edge to
+    /// the top edge (below 2 = no grid lines)
+    pub grid_lines: u32,
+    /// Line smoothing of each curve, packed 2 bits per curve (curve i in bits
+    /// 2i..2i+2), see [`LineSmoothing`](crate::LineSmoothing)
+    pub line_smoothing: u32,
+    /// First sample shown at the left edge while zoomed into the history
+    pub view_start: u32,
+    /// Number of samples spread across the width while zoomed (below 2 = all samples)
+    pub view_len: u32,
+    /// Horizontal span of the zoom selection being dragged, in normalized
+    /// coordinates (equal ends = no selection)
+    pub selection: Vec2,
+    /// Normalized X position of the sample under the pointer, highlighted
+    /// while the graph tooltip is shown (negative = none)
+    pub cursor_x: f32,
+}
+
+impl MultiLineGraphParams {
+    /// Pack curve styles, in curve order, into the [`curve_styles`](Self::curve_styles) layout
+    pub fn pack_curve_styles(styles: impl IntoIterator<Item = CurveStyle>) -> u32 {
+        styles
+            .into_iter()
+            .take(MAX_CURVES)
+            .enumerate()
+            .fold(0, |packed, (curve, style)| {
+                packed | ((style as u32 & 0x3) << (2 * curve))
+            })
+    }
+
+    /// Pack line smoothing modes, in curve order, into the [`line_smoothing`](Self::line_smoothing) layout
+    pub fn pack_line_smoothing(modes: impl IntoIterator<Item = LineSmoothing>) -> u32 {
+        modes
+            .into_iter()
+            .take(MAX_CURVES)
+            .enumerate()
+            .fold(0, |packed, (curve, mode)| {
+                packed | ((mode as u32 & 0x3) << (2 * curve))
+            })
+    }
+
+    /// Pack curve indices, bottom first, into the [`draw_order`](Self::draw_order) layout
+    pub fn pack_draw_order(order: &[usize]) -> u32 {
+        order
+            .iter()
+            .take(MAX_CURVES)
+            .enumerate()
+            .fold(0, |packed, (layer, &curve)| {
+                packed | ((curve as u32 & 0xF) << (4 * layer))
+            })
+    }
 }
 
 impl Default for MultiLineGraphParams {
     fn default() -> Self {
         Self {
             values: [[Vec4::ZERO; SAMPLES_VEC4]; MAX_CURVES],
+            ranges: [[UVec4::ZERO; SAMPLES_VEC4]; MAX_CURVES],
+            missing: [[UVec4::ZERO; MISSING_VEC4]; MAX_CURVES],
             length: 0,
             min_y: 0.0,
             max_y: 1.0,
@@ -81,7 +177,25 @@ impl Default for MultiLineGraphParams {
             border_right: 0,
             border_top: 0,
             colors: [Vec4::ZERO; MAX_CURVES],
+            scales: [Vec4::new(0.0, 1.0, 0.0, 0.0); MAX_CURVES],
             curve_count: 0,
+            candlestick_mask: 0,
+            quality: 0,
+            draw_order: Self::pack_draw_order(&[0, 1, 2, 3, 4, 5]),
+            opacity: 1.0,
+            curve_styles: 0,
+            budget_lines: [Vec4::ZERO; MAX_CURVES],
+            budget_warning_color: Vec4::ZERO,
+            budget_critical_color: Vec4::ZERO,
+            shading: [Vec4::ZERO; MAX_CURVES],
+            shading_colors: [Vec4::ZERO; MAX_CURVES],
+            grid_color: Vec4::ZERO,
+            grid_lines: 0,
+            line_smoothing: 0,
+            view_start: 0,
+            view_len: 0,
+            selection: Vec2::ZERO,
+            cursor_x: -1.0,
         }
     }
 }
@@ -111,7 +225,7 @@ impl UiMaterial for MultiLineGraphMaterial {
 ///
 /// This structure contains data needed to render a single performance metric
 /// as a horizontal progress bar, including value, foreground color, and background color.
-#[derive(Debug, Clone, ShaderType)]
+#[derive(Debug, Clone, PartialEq, ShaderType)]
 pub struct BarParams {
     /// Current normalized value for the bar (0.0-1.0 range)
     pub value: f32,
@@ -131,6 +245,41 @@ pub struct BarParams {
     pub bg_b: f32,
     /// Alpha component of the bar's background color
     pub bg_a: f32,
+    /// Fill mode (0 = from the left edge, 1 = from `origin` towards `value`)
+    pub mode: u32,
+    /// Normalized position (0.0-1.0) the fill starts from in center mode
+    pub origin: f32,
+    /// Opacity multiplier of the whole bar (0.0-1.0), animated by HUD transitions
+    pub opacity: f32,
+}
+
+impl BarParams {
+    /// Create parameters for a bar filled to `value` (0.0-1.0) with the given colors
+    pub fn new(value: f32, color: Color, bg_color: Color) -> Self {
+        let fg = color.to_linear().to_vec4();
+        let bg = bg_color.to_linear().to_vec4();
+        Self {
+            value,
+            r: fg.x,
+            g: fg.y,
+            b: fg.z,
+            a: fg.w,
+            bg_r: bg.x,
+            bg_g: bg.y,
+            bg_b: bg.z,
+            bg_a: bg.w,
+            mode: 0,
+            origin: 0.0,
+            opacity: 1.0,
+        }
+    }
+
+    /// Fill from `origin` (0.0-1.0) towards the value instead of from the left edge
+    pub fn centered(mut self, origin: f32) -> Self {
+        self.mode = 1;
+        self.origin = origin;
+        self
+    }
 }
 
 /// Material definition for rendering performance bars in the HUD.
@@ -153,3 +302,117 @@ impl UiMaterial for BarMaterial {
         ShaderRef::Path("shaders/bar.wgsl".into())
     }
 }
+
+/// Parameters for the bar grid shader.
+///
+/// The whole grid of performance bars is drawn by one quad; each bar is a
+/// rectangle inside it with its own fill value and color.
+#[derive(Debug, Clone, PartialEq, ShaderType)]
+pub struct BarsParams {
+    /// Rectangle of each bar as (x, y, width, height), normalized to the grid node
+    pub rects: [Vec4; MAX_BARS],
+    /// Foreground color of each bar
+    pub colors: [Vec4; MAX_BARS],
+    /// Fill value of each bar (0.0-1.0), packed four per Vec4
+    pub values: [Vec4; BARS_VEC4],
+    /// Position each center-filled bar fills from (0.0-1.0), packed four per Vec4
+    pub origins: [Vec4; BARS_VEC4],
+    /// Bitmask of bars filled from their origin (bit i = bar i)
+    pub center_mask: u32,
+    /// Background color of each bar
+    pub bg_colors: [Vec4; MAX_BARS],
+    /// Number of bars currently laid out in the grid
+    pub count: u32,
+    /// Opacity multiplier of the whole grid (0.0-1.0), animated by HUD transitions
+    pub opacity: f32,
+    /// Bitmask of bars split into a value bar and a rolling average bar (bit i = bar i)
+    pub average_mask: u32,
+    /// Rolling average fill of each split bar (0.0-1.0), packed four per Vec4
+    pub averages: [Vec4; BARS_VEC4],
+}
+
+impl Default for BarsParams {
+    fn default() -> Self {
+        Self {
+            rects: [Vec4::ZERO; MAX_BARS],
+            colors: [Vec4::ZERO; MAX_BARS],
+            values: [Vec4::ZERO; BARS_VEC4],
+            origins: [Vec4::ZERO; BARS_VEC4],
+            center_mask: 0,
+            bg_colors: [Vec4::ZERO; MAX_BARS],
+            count: 0,
+            opacity: 1.0,
+            average_mask: 0,
+            averages: [Vec4::ZERO; BARS_VEC4],
+        }
+    }
+}
+
+impl BarsParams {
+    /// Set the fill value (0.0-1.0) of the bar at `index`
+    pub fn set_value(&mut self, index: usize, value: f32) {
+        self.values[index / 4][index % 4] = value;
+    }
+
+    /// Split the bar at `index` to show a rolling average (0.0-1.0) below its
+    /// value, or show the value alone if None
+    pub fn set_average(&mut self, index: usize, average: Option<f32>) {
+        self.averages[index / 4][index % 4] = average.unwrap_or(0.0);
+        if average.is_some() {
+            self.average_mask |= 1 << index;
+        } else {
+            self.average_mask &= !(1 << index);
+        }
+    }
+
+    /// Fill the bar at `index` from `origin` (0.0-1.0), or from its left edge if None
+    pub fn set_origin(&mut self, index: usize, origin: Option<f32>) {
+        self.origins[index / 4][index % 4] = origin.unwrap_or(0.0);
+        if origin.is_some() {
+            self.center_mask |= 1 << index;
+        } else {
+            self.center_mask &= !(1 << index);
+        }
+    }
+}
+
+/// Material rendering the entire bar grid in a single draw call.
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+pub struct BarsMaterial {
+    /// Shader parameters containing all data for bar grid rendering
+    #[uniform(0)]
+    pub params: BarsParams,
+}
+
+impl UiMaterial for BarsMaterial {
+    /// Returns the fragment shader path for bar grid rendering.
+    ///
+    /// This shader finds the bar under each pixel and fills it up to the
+    /// bar's value; pixels between bars stay transparent.
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/bars.wgsl".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::settings::WgpuLimits;
+
+    use super::*;
+
+    #[test]
+    fn test_uniform_params_fit_webgl2_limit() {
+        let limit =
+            u64::from(WgpuLimits::downlevel_webgl2_defaults().max_uniform_buffer_binding_size);
+        for (name, size) in [
+            ("MultiLineGraphParams", MultiLineGraphParams::min_size()),
+            ("BarParams", BarParams::min_size()),
+            ("BarsParams", BarsParams::min_size()),
+        ] {
+            assert!(
+                size.get() <= limit,
+                "{name} is {size} bytes, over the {limit} byte limit"
+            );
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Recording and replay of metric samples.
+//!
+//! [`PerfHudCommand::StartSampleRecording`](crate::PerfHudCommand) records
+//! every provider sample with its time, and
+//! [`PerfHudCommand::SaveSampleRecording`](crate::PerfHudCommand) writes the
+//! recording to a CSV file on the IO task pool. The recording keeps the
+//! latest frames within [`PerfHudSettings::sample_recording`](crate::PerfHudSettings).
+//! [`PerfHudCommand::ReplaySamples`](crate::PerfHudCommand)
+//! loads such a file and feeds its values to the HUD instead of the
+//! providers, with the original timing, so HUD styling and layout can be
+//! worked on against the same data without running the game workload.
+
+use std::{
+    collections::VecDeque,
+    ffi::OsString,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::system::{Res, ResMut},
+    log::{info, warn},
+    prelude::Resource,
+    tasks::{IoTaskPool, TaskPool},
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::{PerfHudSettings, SampleRecordingSettings},
+    resources::SampledValues,
+};
+
+/// Metric samples of one frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RecordedFrame {
+    /// Seconds since the recording started
+    time: f64,
+    /// Value of each metric of [`SampleRecording::metric_ids`] (None = missing)
+    values: Vec<Option<f32>>,
+}
+
+/// Metric samples recorded frame by frame.
+///
+/// Stored as CSV: a `time` column in seconds, then one column per metric,
+/// left empty on frames the metric had no value. Metric IDs must not contain
+/// commas.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SampleRecording {
+    metric_ids: Vec<String>,
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl SampleRecording {
+    /// Number of recorded frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frame is recorded
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// IDs of the recorded metrics
+    pub fn metric_ids(&self) -> &[String] {
+        &self.metric_ids
+    }
+
+    /// Time between the first and the last recorded frame in seconds
+    pub fn duration(&self) -> f64 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Time of a recorded frame relative to the first one, in seconds
+    fn frame_time(&self, index: usize) -> f64 {
+        let start = self.frames.front().map_or(0.0, |frame| frame.time);
+        self.frames[index].time - start
+    }
+
+    /// Record the fresh samples of a frame taken `time` seconds into the recording
+    pub fn record(&mut self, time: f64, samples: &SampledValues) {
+        for (id, _) in samples.iter() {
+            if !self.metric_ids.iter().any(|known| known == id) {
+                self.metric_ids.push(id.to_owned());
+            }
+        }
+        let values = self
+            .metric_ids
+            .iter()
+            .map(|id| samples.get_fresh(id))
+            .collect();
+        self.frames.push_back(RecordedFrame { time, values });
+    }
+
+    /// Drop the oldest frames until at most `limits.capacity` frames spanning
+    /// at most `limits.duration` remain
+    pub fn trim(&mut self, limits: &SampleRecordingSettings) {
+        let max_duration = limits.duration.as_secs_f64();
+        while self.frames.len() > limits.capacity || self.duration() > max_duration {
+            self.frames.pop_front();
+        }
+    }
+
+    /// The recording as CSV, with times relative to its first frame
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time");
+        for id in &self.metric_ids {
+            let _ = write!(csv, ",{id}");
+        }
+        csv.push('\n');
+        for (index, frame) in self.frames.iter().enumerate() {
+            let _ = write!(csv, "{:.6}", self.frame_time(index));
+            // Metrics first seen later have no value on earlier frames
+            for index in 0..self.metric_ids.len() {
+                csv.push(',');
+                if let Some(Some(value)) = frame.values.get(index) {
+                    let _ = write!(csv, "{value}");
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Parse a recording written by [`to_csv`](Self::to_csv)
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+        let header = lines.next().ok_or("empty recording")?;
+        let mut columns = header.split(',');
+        if columns.next() != Some("time") {
+            return Err("first column must be `time`".into());
+        }
+        let metric_ids: Vec<String> = columns.map(str::to_owned).collect();
+
+        let mut frames = VecDeque::new();
+        for (row, line) in lines.enumerate() {
+            let mut cells = line.split(',');
+            let time = cells
+                .next()
+                .and_then(|cell| cell.trim().parse::<f64>().ok())
+                .ok_or_else(|| format!("row {}: invalid time", row + 1))?;
+            let values = cells
+                .map(|cell| match cell.trim() {
+                    "" => Ok(None),
+                    cell => cell.parse::<f32>().map(Some),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("row {}: {err}", row + 1))?;
+            if values.len() != metric_ids.len() {
+                return Err(format!(
+                    "row {}: expected {} values, found {}",
+                    row + 1,
+                    metric_ids.len(),
+                    values.len()
+                ));
+            }
+            frames.push_back(RecordedFrame { time, values });
+        }
+        Ok(Self { metric_ids, frames })
+    }
+}
+
+/// Resource recording metric samples while it exists.
+///
+/// Inserted by [`PerfHudCommand::StartSampleRecording`](crate::PerfHudCommand)
+/// and removed when the recording is saved. Only the latest frames within
+/// [`PerfHudSettings::sample_recording`] are kept.
+#[derive(Resource, Default)]
+pub struct SampleRecorder {
+    /// Real time the recording started at, set on its first frame
+    start: Option<f64>,
+    /// Samples recorded so far
+    pub recording: SampleRecording,
+}
+
+/// Resource replaying a recording into the HUD while it exists.
+///
+/// Metric providers are not sampled during a replay. Each frame, the values
+/// of the latest recorded frame due at the time elapsed since the replay
+/// started are reported; the replay starts over once it reaches the end.
+#[derive(Resource)]
+pub struct SampleReplay {
+    recording: SampleRecording,
+    /// Real time the current pass started at, set on its first frame
+    start: Option<f64>,
+    /// Index of the first recorded frame not yet due
+    next: usize,
+}
+
+impl SampleReplay {
+    /// Replay `recording`
+    pub fn new(recording: SampleRecording) -> Self {
+        Self {
+            recording,
+            start: None,
+            next: 0,
+        }
+    }
+
+    /// The replayed recording
+    pub fn recording(&self) -> &SampleRecording {
+        &self.recording
+    }
+
+    /// Index of the recorded frame due `now` seconds of real time, looping at the end
+    fn due_frame(&mut self, now: f64) -> Option<usize> {
+        let start = *self.start.get_or_insert(now);
+        let duration = self.recording.duration();
+        let mut elapsed = now - start;
+        if elapsed > duration {
+            // Start a new pass, keeping the overshoot so the timing does not drift
+            elapsed = if duration > 0.0 {
+                elapsed % duration
+            } else {
+                0.0
+            };
+            self.start = Some(now - elapsed);
+            self.next = 0;
+        }
+        while self.next < self.recording.len() && self.recording.frame_time(self.next) <= elapsed {
+            self.next += 1;
+        }
+        self.next.checked_sub(1)
+    }
+}
+
+/// Run condition: no recording is being replayed.
+pub fn not_replaying(replay: Option<Res<SampleReplay>>) -> bool {
+    replay.is_none()
+}
+
+/// System reporting the due values of the replayed recording as samples.
+pub fn replay_samples(
+    time: Res<Time<Real>>,
+    mut replay: ResMut<SampleReplay>,
+    mut samples: ResMut<SampledValues>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Replay);
+    let Some(index) = replay.due_frame(time.elapsed_secs_f64()) else {
+        return;
+    };
+    let recording = &replay.recording;
+    for (id, value) in recording
+        .metric_ids
+        .iter()
+        .zip(&recording.frames[index].values)
+    {
+        match value {
+            Some(value) => samples.set(id, *value),
+            None => samples.mark_missing(id),
+        }
+    }
+}
+
+/// System adding the frame's samples to the recording.
+pub fn record_samples(
+    settings: Res<PerfHudSettings>,
+    time: Res<Time<Real>>,
+    samples: Res<SampledValues>,
+    mut recorder: ResMut<SampleRecorder>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Replay);
+    let now = time.elapsed_secs_f64();
+    let start = *recorder.start.get_or_insert(now);
+    recorder.recording.record(now - start, &samples);
+    recorder.recording.trim(&settings.sample_recording);
+}
+
+/// Write `recording` to `path` as CSV on the IO task pool.
+///
+/// The file is written under a temporary name and renamed once complete,
+/// so it never appears half written.
+pub(crate) fn save_sample_recording(recording: SampleRecording, path: PathBuf) {
+    IoTaskPool::get_or_init(TaskPool::new)
+        .spawn(async move {
+            match write_recording(&recording, &path) {
+                Ok(()) => info!(
+                    "perf HUD: wrote {} recorded frames to {path:?}",
+                    recording.len()
+                ),
+                Err(err) => warn!("perf HUD: cannot write sample recording to {path:?}: {err}"),
+            }
+        })
+        .detach();
+}
+
+fn write_recording(recording: &SampleRecording, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut partial = OsString::from(path);
+    partial.push(".part");
+    fs::write(&partial, recording.to_csv())?;
+    fs::rename(&partial, path)
+}
+
+/// Read a recording written by [`save_sample_recording`].
+pub(crate) fn load_sample_recording(path: &Path) -> Option<SampleRecording> {
+    let loaded = fs::read_to_string(path).and_then(|csv| {
+        SampleRecording::from_csv(&csv)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    });
+    match loaded {
+        Ok(recording) => Some(recording),
+        Err(err) => {
+            warn!("perf HUD: cannot replay sample recording {path:?}: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> SampleRecording {
+        let mut samples = SampledValues::default();
+        let mut recording = SampleRecording::default();
+        samples.set("fps", 60.0);
+        recording.record(0.0, &samples);
+        samples.set("fps", 30.0);
+        samples.set("frame_time_ms", 33.3);
+        recording.record(0.5, &samples);
+        samples.mark_missing("fps");
+        recording.record(1.0, &samples);
+        recording
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let recording = recording();
+        let csv = recording.to_csv();
+        assert_eq!(
+            csv,
+            "time,fps,frame_time_ms\n0.000000,60,\n0.500000,30,33.3\n1.000000,,33.3\n"
+        );
+        let parsed = SampleRecording::from_csv(&csv).unwrap();
+        assert_eq!(parsed.metric_ids(), recording.metric_ids());
+        assert_eq!(parsed.to_csv(), csv);
+        assert!(SampleRecording::from_csv("time,fps\n0.0,1,2\n").is_err());
+    }
+
+    #[test]
+    fn test_recording_keeps_latest_frames() {
+        let mut recording = recording();
+        recording.trim(&SampleRecordingSettings {
+            capacity: 2,
+            ..Default::default()
+        });
+        assert_eq!(recording.len(), 2);
+        // Times restart from the oldest kept frame
+        assert!(recording
+            .to_csv()
+            .contains("\n0.000000,30,33.3\n0.500000,,33.3\n"));
+
+        let mut recording = self::recording();
+        recording.trim(&SampleRecordingSettings {
+            duration: std::time::Duration::from_secs_f32(0.6),
+            ..Default::default()
+        });
+        assert_eq!(recording.len(), 2);
+        assert_eq!(recording.duration(), 0.5);
+    }
+
+    #[test]
+    fn test_replay_follows_recorded_timing_and_loops() {
+        let mut replay = SampleReplay::new(recording());
+        assert_eq!(replay.due_frame(10.0), Some(0));
+        assert_eq!(replay.due_frame(10.4), Some(0));
+        assert_eq!(replay.due_frame(10.6), Some(1));
+        assert_eq!(replay.due_frame(11.0), Some(2));
+        // 0.25 s into the second pass
+        assert_eq!(replay.due_frame(11.25), Some(0));
+        assert_eq!(replay.due_frame(11.75), Some(1));
+    }
+}
@@ -2,10 +2,28 @@
 //!
 //! This module contains all runtime state resources used by the HUD systems.
 
-use bevy::{asset::Handle, ecs::entity::Entity, prelude::Resource};
-use std::collections::HashMap;
+use bevy::{
+    asset::Handle,
+    color::{Color, LinearRgba, Mix},
+    ecs::entity::Entity,
+    math::curve::Curve,
+    prelude::{ReflectResource, Resource},
+    reflect::Reflect,
+    time::{Timer, TimerMode},
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
 
-use crate::{BarMaterial, BarScaleState, MultiLineGraphMaterial, MAX_CURVES, MAX_SAMPLES};
+use crate::{
+    BarColorTransition, BarConfig, BarScaleState, BarsMaterial, BudgetLevel, CurveConfig,
+    LabelFilter, MetricBudget, MultiLineGraphMaterial, RebindHistory, MAX_CURVES, MAX_SAMPLES,
+};
+
+/// Weight of the newest sample in the running average of the time a timed
+/// graph sample covers
+const PERIOD_AVERAGE_WEIGHT: f32 = 0.1;
 
 /// Handle to a graph label entity, linking it to its metric.
 ///
@@ -18,12 +36,23 @@ pub struct GraphLabelHandle {
     pub entity: Entity,
 }
 
+/// Handle to a group of per-core CPU usage bars drawn by a single material.
+#[derive(Clone)]
+pub struct CpuCoreBarsHandle {
+    /// IDs of the per-core usage metrics of the group's bars, in bar order
+    pub metric_ids: Vec<String>,
+    /// Bevy entity ID for the group's node
+    pub entity: Entity,
+    /// Material handle for the bar grid shader
+    pub material: Handle<BarsMaterial>,
+}
+
 /// Resource containing handles to all HUD-related entities and materials.
 ///
 /// This resource is created automatically by the plugin and contains references
 /// to all the UI entities and materials that make up the performance HUD.
 /// Used internally by systems to update HUD appearance and content.
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct HudHandles {
     /// Root entity for the entire HUD UI hierarchy
     pub root: Option<Entity>,
@@ -35,16 +64,70 @@ pub struct HudHandles {
     pub graph_material: Option<Handle<MultiLineGraphMaterial>>,
     /// Handles to all graph label entities
     pub graph_labels: Vec<GraphLabelHandle>,
+    /// Text entity in the graph's corner showing the unit shared by all curves
+    pub graph_unit_label: Option<Entity>,
     /// Width allocated for graph labels in pixels
     pub graph_label_width: f32,
-    /// Entity for the bars container
+    /// Entity for the bars grid, drawn by a single material
     pub bars_root: Option<Entity>,
-    /// Entities for individual bar graphics
-    pub bar_entities: Vec<Entity>,
-    /// Material handles for bar shaders
-    pub bar_materials: Vec<Handle<BarMaterial>>,
-    /// Entities for bar label text
+    /// Material handle for the bars grid shader
+    pub bars_material: Option<Handle<BarsMaterial>>,
+    /// Entities for bar label text, in bar configuration order
     pub bar_labels: Vec<Entity>,
+    /// Grid row of each bar, in bar configuration order
+    pub bar_rows: Vec<usize>,
+    /// Rows of the percentile statistics panel, one per configured metric
+    pub stats_labels: Vec<GraphLabelHandle>,
+    /// Node of the traffic light indicator
+    pub traffic_light: Option<Entity>,
+    /// Rows of the slow frame list, newest first
+    pub slow_frame_labels: Vec<Entity>,
+    /// Text entities labeling the oldest and newest frame under the graph,
+    /// spawned when the X axis counts frames
+    pub frame_axis_labels: Option<[Entity; 2]>,
+    /// Text entities labeling the start and end of the graph's time window,
+    /// spawned when [`GraphSettings::time_axis_labels`](crate::GraphSettings::time_axis_labels) is set
+    pub time_axis_labels: Option<[Entity; 2]>,
+    /// Text entities labeling the Y-axis ticks of the graph, bottom first
+    pub y_tick_labels: Vec<Entity>,
+    /// Tooltip text shown while hovering the graph, spawned when
+    /// [`HudInteraction::tooltip`](crate::HudInteraction::tooltip) is set
+    pub graph_tooltip: Option<Entity>,
+    /// Entity for the per-core CPU usage strip container
+    pub cpu_cores_root: Option<Entity>,
+    /// Handles to the per-core CPU usage bar groups, each of up to
+    /// [`MAX_BARS`](crate::MAX_BARS) cores
+    pub cpu_core_bars: Vec<CpuCoreBarsHandle>,
+    /// Buttons of the control strip, spawned when
+    /// [`PerfHudSettings::show_controls`](crate::PerfHudSettings::show_controls) is set
+    pub control_buttons: Vec<Entity>,
+}
+
+impl HudHandles {
+    /// All entities of the HUD referenced by these handles
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        [
+            self.root,
+            self.graph_row,
+            self.graph_entity,
+            self.graph_unit_label,
+            self.bars_root,
+            self.cpu_cores_root,
+            self.traffic_light,
+            self.graph_tooltip,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.graph_labels.iter().map(|label| label.entity))
+        .chain(self.bar_labels.iter().copied())
+        .chain(self.stats_labels.iter().map(|label| label.entity))
+        .chain(self.slow_frame_labels.iter().copied())
+        .chain(self.frame_axis_labels.into_iter().flatten())
+        .chain(self.time_axis_labels.into_iter().flatten())
+        .chain(self.y_tick_labels.iter().copied())
+        .chain(self.cpu_core_bars.iter().map(|bars| bars.entity))
+        .chain(self.control_buttons.iter().copied())
+    }
 }
 
 /// Resource storing the most recent sampled values for all performance metrics.
@@ -55,6 +138,11 @@ pub struct HudHandles {
 pub struct SampledValues {
     /// Map from metric ID to its current value
     values: HashMap<String, f32>,
+    /// Metric IDs whose provider returned no value on the latest sample
+    missing: HashSet<String>,
+    /// Range of the values folded into the current value of metrics that
+    /// received several values this frame
+    ranges: HashMap<String, (f32, f32)>,
 }
 
 impl SampledValues {
@@ -69,6 +157,40 @@ impl SampledValues {
         } else {
             self.values.insert(id.to_owned(), value);
         }
+        self.missing.remove(id);
+        self.ranges.remove(id);
+    }
+
+    /// Set the current value of a metric that folds several values measured
+    /// this frame, keeping their range for candlestick curves and statistics.
+    ///
+    /// # Arguments
+    /// * `id` - The metric identifier
+    /// * `value` - The folded value, e.g. the mean
+    /// * `min` - Smallest folded value
+    /// * `max` - Largest folded value
+    pub fn set_range(&mut self, id: &str, value: f32, min: f32, max: f32) {
+        self.set(id, value);
+        if let Some(existing) = self.ranges.get_mut(id) {
+            *existing = (min, max);
+        } else {
+            self.ranges.insert(id.to_owned(), (min, max));
+        }
+    }
+
+    /// Forget the ranges set by [`set_range`](Self::set_range), once their frame is over
+    pub fn clear_ranges(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Record that a metric could not be sampled this frame.
+    ///
+    /// The last value stays available through [`get`](Self::get), while
+    /// [`get_fresh`](Self::get_fresh) reports the metric as missing.
+    pub fn mark_missing(&mut self, id: &str) {
+        if !self.missing.contains(id) {
+            self.missing.insert(id.to_owned());
+        }
     }
 
     /// Get the current value for a performance metric.
@@ -81,6 +203,34 @@ impl SampledValues {
     pub fn get(&self, id: &str) -> Option<f32> {
         self.values.get(id).copied()
     }
+
+    /// Get the value of a metric only if it was sampled on the latest frame.
+    ///
+    /// # Returns
+    /// None if the metric was never sampled or its provider returned no value
+    pub fn get_fresh(&self, id: &str) -> Option<f32> {
+        if self.missing.contains(id) {
+            return None;
+        }
+        self.get(id)
+    }
+
+    /// Get the range of the values folded into a metric's current value.
+    ///
+    /// # Returns
+    /// The range set by [`set_range`](Self::set_range) this frame, otherwise
+    /// the current value as both ends; None if the metric was never sampled
+    pub fn range(&self, id: &str) -> Option<(f32, f32)> {
+        self.ranges
+            .get(id)
+            .copied()
+            .or_else(|| self.get(id).map(|value| (value, value)))
+    }
+
+    /// Iterate over all metric IDs and their current values
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.values.iter().map(|(id, value)| (id.as_str(), *value))
+    }
 }
 
 /// Resource storing historical values for graph curve rendering.
@@ -91,18 +241,507 @@ impl SampledValues {
 #[derive(Resource)]
 pub struct HistoryBuffers {
     /// 2D array: \[curve_index\]\[sample_index\] containing historical values
-    /// Each curve can store up to MAX_SAMPLES historical data points
+    /// Each curve can store up to MAX_SAMPLES historical data points;
+    /// samples with missing data are NaN
     pub values: [[f32; MAX_SAMPLES]; MAX_CURVES],
-    /// Number of valid samples currently stored (0 to MAX_SAMPLES)
+    /// Minimum raw value aggregated into each sample (same layout as `values`)
+    pub mins: [[f32; MAX_SAMPLES]; MAX_CURVES],
+    /// Maximum raw value aggregated into each sample (same layout as `values`)
+    pub maxs: [[f32; MAX_SAMPLES]; MAX_CURVES],
+    /// Number of valid samples currently stored (0 to `capacity`)
     pub length: u32,
+    /// Number of samples kept before the oldest scroll out (2 to MAX_SAMPLES)
+    pub capacity: u32,
+    /// Frame number each sample was completed on (0 when unknown, e.g. backfilled)
+    pub frames: [u32; MAX_SAMPLES],
+    /// Number of samples stored since the buffers were last reset,
+    /// including those that have since scrolled out
+    pub total: u64,
+    /// Where each curve's current series starts, for curves rebound to
+    /// another metric while samples of the previous one are still shown
+    pub rebinds: [Option<CurveRebind>; MAX_CURVES],
+}
+
+/// Start of a curve's series after it was bound to another metric.
+#[derive(Debug, Clone)]
+pub struct CurveRebind {
+    /// Value of [`HistoryBuffers::total`] when the new series started
+    pub start: u64,
+    /// Fade-out of the previous series (None = shown until it scrolls out)
+    pub fade: Option<Timer>,
 }
 
 impl Default for HistoryBuffers {
     fn default() -> Self {
         Self {
             values: [[0.0; MAX_SAMPLES]; MAX_CURVES],
+            mins: [[0.0; MAX_SAMPLES]; MAX_CURVES],
+            maxs: [[0.0; MAX_SAMPLES]; MAX_CURVES],
             length: 0,
+            capacity: MAX_SAMPLES as u32,
+            frames: [0; MAX_SAMPLES],
+            total: 0,
+            rebinds: Default::default(),
+        }
+    }
+}
+
+impl HistoryBuffers {
+    /// Replace the buffers with previously recorded per-curve values.
+    ///
+    /// Each entry in `curves` holds one curve's values, oldest first. Series are
+    /// right-aligned so their newest samples line up, and shorter series are
+    /// padded with their oldest value. Does nothing if every series is empty.
+    pub fn backfill(&mut self, curves: &[Vec<f32>]) {
+        let len = curves
+            .iter()
+            .take(MAX_CURVES)
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .min(self.capacity as usize);
+        if len == 0 {
+            return;
+        }
+
+        *self = Self {
+            capacity: self.capacity,
+            ..Self::default()
+        };
+        for (i, series) in curves.iter().take(MAX_CURVES).enumerate() {
+            let series = &series[series.len().saturating_sub(len)..];
+            let Some(&oldest) = series.first() else {
+                continue;
+            };
+            let pad = len - series.len();
+            self.values[i][..pad].fill(oldest);
+            self.values[i][pad..len].copy_from_slice(series);
+        }
+        self.mins = self.values;
+        self.maxs = self.values;
+        self.length = len as u32;
+        self.total = len as u64;
+    }
+
+    /// Append one sample per curve, dropping the oldest once the buffer is full.
+    ///
+    /// # Arguments
+    /// * `values` - Displayed value of each curve
+    /// * `mins` - Minimum raw value aggregated into this sample for each curve
+    /// * `maxs` - Maximum raw value aggregated into this sample for each curve
+    pub fn push(
+        &mut self,
+        values: &[f32; MAX_CURVES],
+        mins: &[f32; MAX_CURVES],
+        maxs: &[f32; MAX_CURVES],
+    ) {
+        if self.length >= self.capacity {
+            // Buffer is full: implement sliding window by shifting all values left
+            // This maintains the most recent `capacity` values for graphing
+            self.drop_oldest(1);
+        }
+        // Append new values at the end
+        self.length += 1;
+        let idx = self.length as usize - 1;
+
+        self.total += 1;
+        for i in 0..MAX_CURVES {
+            self.values[i][idx] = values[i];
+            self.mins[i][idx] = mins[i];
+            self.maxs[i][idx] = maxs[i];
+        }
+    }
+
+    /// Convert a curve's samples between FPS and milliseconds per frame, the
+    /// minimum and maximum trading places (samples of 0 FPS become missing)
+    pub fn convert_frame_unit(&mut self, curve: usize) {
+        if curve >= MAX_CURVES {
+            return;
+        }
+        let invert = |value: f32| {
+            if value > 0.0 {
+                1000.0 / value
+            } else {
+                f32::NAN
+            }
+        };
+        for i in 0..self.length as usize {
+            let (min, max) = (self.mins[curve][i], self.maxs[curve][i]);
+            self.values[curve][i] = invert(self.values[curve][i]);
+            self.mins[curve][i] = invert(max);
+            self.maxs[curve][i] = invert(min);
+        }
+    }
+
+    /// Record the frame number the latest sample was completed on
+    pub fn set_frame(&mut self, frame: u32) {
+        if let Some(last) = (self.length as usize).checked_sub(1) {
+            self.frames[last] = frame;
+        }
+    }
+
+    /// Change the number of samples kept, dropping the oldest ones that no longer fit
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let capacity = capacity.clamp(2, MAX_SAMPLES);
+        self.capacity = capacity as u32;
+        let len = self.length as usize;
+        if len > capacity {
+            self.drop_oldest(len - capacity);
+        }
+    }
+
+    /// Shift out the `count` oldest samples
+    fn drop_oldest(&mut self, count: usize) {
+        let len = self.length as usize;
+        for i in 0..MAX_CURVES {
+            self.values[i].copy_within(count..len, 0);
+            self.mins[i].copy_within(count..len, 0);
+            self.maxs[i].copy_within(count..len, 0);
+        }
+        self.frames.copy_within(count..len, 0);
+        self.length = (len - count) as u32;
+    }
+
+    /// Number of the oldest stored samples that are still within the first
+    /// `warmup` samples since the last reset
+    pub fn warmup_len(&self, warmup: u32) -> usize {
+        let scrolled_out = self.total - self.length as u64;
+        (warmup as u64)
+            .saturating_sub(scrolled_out)
+            .min(self.length as u64) as usize
+    }
+
+    /// Get the most recent value of a curve, if any sample has been stored
+    pub fn last(&self, curve: usize) -> Option<f32> {
+        let len = self.length as usize;
+        (len > 0).then(|| self.values[curve][len - 1])
+    }
+
+    /// Start a new series for a curve bound to another metric.
+    ///
+    /// # Arguments
+    /// * `curve` - Index of the rebound curve
+    /// * `mode` - What happens to the samples of the previous metric
+    pub fn rebind(&mut self, curve: usize, mode: RebindHistory) {
+        if curve >= MAX_CURVES {
+            return;
         }
+        self.rebinds[curve] = match mode {
+            _ if self.length == 0 => None,
+            RebindHistory::Clear => {
+                self.clear_before(curve, self.length as usize);
+                None
+            }
+            RebindHistory::Marker => Some(CurveRebind {
+                start: self.total,
+                fade: None,
+            }),
+            RebindHistory::Crossfade(duration) => Some(CurveRebind {
+                start: self.total,
+                fade: Some(Timer::new(duration, TimerMode::Once)),
+            }),
+        };
+    }
+
+    /// Percentile statistics of the stored samples of a curve's current series.
+    ///
+    /// Missing samples are skipped; None if there is no sample at all.
+    pub fn percentiles(&self, curve: usize) -> Option<CurvePercentiles> {
+        let start = self.series_start(curve).unwrap_or(0);
+        let mut sorted = [0.0_f32; MAX_SAMPLES];
+        let mut count = 0;
+        for &value in self.values.get(curve)?[start..self.length as usize].iter() {
+            if !value.is_nan() {
+                sorted[count] = value;
+                count += 1;
+            }
+        }
+        let sorted = &mut sorted[..count];
+        sorted.sort_unstable_by(f32::total_cmp);
+        let &max = sorted.last()?;
+        let at = |percentile: f32| sorted[(percentile / 100.0 * (count - 1) as f32) as usize];
+        Some(CurvePercentiles {
+            p50: at(50.0),
+            p95: at(95.0),
+            p99: at(99.0),
+            max,
+        })
+    }
+
+    /// Index of the first stored sample of a rebound curve's current series,
+    /// or None if no samples of a previous metric are shown
+    pub fn series_start(&self, curve: usize) -> Option<usize> {
+        let rebind = self.rebinds.get(curve)?.as_ref()?;
+        let scrolled_out = self.total - self.length as u64;
+        (rebind.start > scrolled_out).then(|| (rebind.start - scrolled_out) as usize)
+    }
+
+    /// Opacity of the previous series of a rebound curve (1.0 until it fades)
+    pub fn previous_series_opacity(&self, curve: usize) -> f32 {
+        match self.rebinds.get(curve) {
+            Some(Some(CurveRebind {
+                fade: Some(timer), ..
+            })) => 1.0 - timer.fraction(),
+            _ => 1.0,
+        }
+    }
+
+    /// Advance the fade-out of previous series, dropping those that have
+    /// faded out or scrolled out of the buffers
+    pub fn tick_rebinds(&mut self, delta: Duration) {
+        for curve in 0..MAX_CURVES {
+            let Some(start) = self.series_start(curve) else {
+                self.rebinds[curve] = None;
+                continue;
+            };
+            let Some(timer) = self.rebinds[curve].as_mut().and_then(|r| r.fade.as_mut()) else {
+                continue;
+            };
+            if timer.tick(delta).finished() {
+                self.clear_before(curve, start);
+                self.rebinds[curve] = None;
+            }
+        }
+    }
+
+    /// Mark the first `end` samples of a curve as missing
+    fn clear_before(&mut self, curve: usize, end: usize) {
+        self.values[curve][..end].fill(f32::NAN);
+        self.mins[curve][..end].fill(f32::NAN);
+        self.maxs[curve][..end].fill(f32::NAN);
+    }
+}
+
+/// Percentile statistics of the samples shown for one curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurvePercentiles {
+    /// Median sample
+    pub p50: f32,
+    /// 95th percentile sample
+    pub p95: f32,
+    /// 99th percentile sample
+    pub p99: f32,
+    /// Largest sample
+    pub max: f32,
+}
+
+/// One aggregated graph sample covering several frames.
+#[derive(Debug, Clone, Copy)]
+pub struct AggregatedSample {
+    /// Mean raw value of each curve
+    pub mean: [f32; MAX_CURVES],
+    /// Minimum raw value of each curve
+    pub min: [f32; MAX_CURVES],
+    /// Maximum raw value of each curve
+    pub max: [f32; MAX_CURVES],
+}
+
+/// Resource accumulating per-frame curve values into graph samples.
+///
+/// When more frames are sampled than the graph can display, several frames
+/// are folded into one sample, keeping their mean, minimum and maximum so
+/// candlestick curves can show the volatility hidden by the average.
+#[derive(Resource)]
+pub struct SampleAggregator {
+    sum: [f32; MAX_CURVES],
+    min: [f32; MAX_CURVES],
+    max: [f32; MAX_CURVES],
+    present: [u32; MAX_CURVES],
+    count: u32,
+    /// Time covered by the frames added so far, for timed samples
+    elapsed: Duration,
+    /// Running average of the time covered by a completed timed sample
+    period: Option<Duration>,
+}
+
+impl Default for SampleAggregator {
+    fn default() -> Self {
+        Self {
+            sum: [0.0; MAX_CURVES],
+            min: [f32::INFINITY; MAX_CURVES],
+            max: [f32::NEG_INFINITY; MAX_CURVES],
+            present: [0; MAX_CURVES],
+            count: 0,
+            elapsed: Duration::ZERO,
+            period: None,
+        }
+    }
+}
+
+impl SampleAggregator {
+    /// Add one frame of raw curve values.
+    ///
+    /// # Arguments
+    /// * `values` - Raw value of each curve for this frame (NaN = missing)
+    /// * `frames_per_sample` - Number of frames folded into one sample (minimum 1)
+    ///
+    /// # Returns
+    /// The aggregated sample once enough frames have been accumulated; curves
+    /// missing on every folded frame get NaN mean, min and max
+    pub fn accumulate(
+        &mut self,
+        values: &[f32; MAX_CURVES],
+        frames_per_sample: u32,
+    ) -> Option<AggregatedSample> {
+        self.add(values);
+        (self.count >= frames_per_sample.max(1)).then(|| self.finish())
+    }
+
+    /// Add one frame of raw curve values, completing a sample once it covers
+    /// both `frames_per_sample` frames and `min_interval` of time.
+    ///
+    /// Time past `min_interval` counts towards the next sample, so samples
+    /// cover `min_interval` on average even when frames do not divide it evenly.
+    ///
+    /// # Arguments
+    /// * `values` - Raw value of each curve for this frame (NaN = missing)
+    /// * `frames_per_sample` - Minimum number of frames folded into one sample
+    /// * `min_interval` - Minimum time covered by one sample
+    /// * `delta` - Time covered by this frame
+    pub fn accumulate_for(
+        &mut self,
+        values: &[f32; MAX_CURVES],
+        frames_per_sample: u32,
+        min_interval: Duration,
+        delta: Duration,
+    ) -> Option<AggregatedSample> {
+        self.add(values);
+        self.elapsed += delta;
+        if self.count < frames_per_sample.max(1) || self.elapsed < min_interval {
+            return None;
+        }
+        let period = self.elapsed;
+        let carry = (self.elapsed - min_interval).min(min_interval);
+        let sample = self.finish();
+        self.elapsed = carry;
+        self.period = Some(match self.period {
+            Some(average) => {
+                average.mul_f32(1.0 - PERIOD_AVERAGE_WEIGHT) + period.mul_f32(PERIOD_AVERAGE_WEIGHT)
+            }
+            None => period,
+        });
+        Some(sample)
+    }
+
+    /// Average time covered by a sample completed by [`Self::accumulate_for`]
+    pub fn sample_period(&self) -> Option<Duration> {
+        self.period
+    }
+
+    /// Add one frame of raw curve values (NaN = missing) without completing a sample
+    pub fn add(&mut self, values: &[f32; MAX_CURVES]) {
+        for (i, &value) in values.iter().enumerate() {
+            if value.is_nan() {
+                continue;
+            }
+            self.sum[i] += value;
+            self.min[i] = self.min[i].min(value);
+            self.max[i] = self.max[i].max(value);
+            self.present[i] += 1;
+        }
+        self.count += 1;
+    }
+
+    /// Complete the sample from all frames added so far and start a new one.
+    ///
+    /// Curves missing on every folded frame get NaN mean, min and max.
+    pub fn finish(&mut self) -> AggregatedSample {
+        let mut sample = AggregatedSample {
+            mean: [f32::NAN; MAX_CURVES],
+            min: [f32::NAN; MAX_CURVES],
+            max: [f32::NAN; MAX_CURVES],
+        };
+        for (i, &present) in self.present.iter().enumerate() {
+            if present > 0 {
+                sample.mean[i] = self.sum[i] / present as f32;
+                sample.min[i] = self.min[i];
+                sample.max[i] = self.max[i];
+            }
+        }
+        *self = Self {
+            period: self.period,
+            ..Self::default()
+        };
+        sample
+    }
+
+    /// Widen the range of the sample in progress for one curve, e.g. with the
+    /// range of several values folded into one frame's raw value
+    pub fn widen(&mut self, curve: usize, min: f32, max: f32) {
+        if curve < MAX_CURVES && !min.is_nan() && !max.is_nan() {
+            self.min[curve] = self.min[curve].min(min);
+            self.max[curve] = self.max[curve].max(max);
+        }
+    }
+
+    /// Drop the frames added so far for one curve, e.g. after it was rebound
+    pub fn discard(&mut self, curve: usize) {
+        if curve < MAX_CURVES {
+            self.sum[curve] = 0.0;
+            self.min[curve] = f32::INFINITY;
+            self.max[curve] = f32::NEG_INFINITY;
+            self.present[curve] = 0;
+        }
+    }
+}
+
+/// Resource holding the coarse, long-term history shown by the trend view.
+///
+/// Every frame's raw curve values are folded into one sample per
+/// [`GraphSettings::trend_interval`](crate::GraphSettings), keeping the mean,
+/// minimum and maximum, so the trend view spans minutes with the same
+/// [`MAX_SAMPLES`] as the live view.
+#[derive(Resource, Default)]
+pub struct TrendHistory {
+    /// Trend samples, laid out like the live history
+    pub history: HistoryBuffers,
+    aggregator: SampleAggregator,
+    elapsed: Duration,
+}
+
+impl TrendHistory {
+    /// Start a new trend series for a curve bound to another metric
+    pub fn rebind(&mut self, curve: usize, mode: RebindHistory) {
+        self.history.rebind(curve, mode);
+        self.aggregator.discard(curve);
+    }
+
+    /// Convert a curve's trend samples between FPS and milliseconds per frame
+    pub fn convert_frame_unit(&mut self, curve: usize) {
+        self.history.convert_frame_unit(curve);
+        self.aggregator.discard(curve);
+    }
+
+    /// Widen the range of the trend sample in progress for one curve
+    pub fn widen(&mut self, curve: usize, min: f32, max: f32) {
+        self.aggregator.widen(curve, min, max);
+    }
+
+    /// Add one frame of raw curve values (NaN = missing) that took `delta`.
+    ///
+    /// # Returns
+    /// Whether a trend sample was completed
+    pub fn record(
+        &mut self,
+        values: &[f32; MAX_CURVES],
+        delta: Duration,
+        interval: Duration,
+    ) -> bool {
+        self.aggregator.add(values);
+        self.elapsed += delta;
+        if self.elapsed < interval {
+            return false;
+        }
+
+        // Stalls longer than an interval yield one sample, not a burst of copies
+        self.elapsed = if interval.is_zero() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.elapsed.as_nanos() % interval.as_nanos()) as u64)
+        };
+        let sample = self.aggregator.finish();
+        self.history.push(&sample.mean, &sample.min, &sample.max);
+        true
     }
 }
 
@@ -111,12 +750,271 @@ impl Default for HistoryBuffers {
 /// When autoscaling is enabled, this maintains smoothed min/max values
 /// to reduce visual jitter from rapid scale changes. The values are
 /// interpolated over time to provide stable graph scaling.
-#[derive(Resource, Default, Clone, Copy)]
+#[derive(Resource, Default, Clone, Copy, Reflect)]
+#[reflect(Resource)]
 pub struct GraphScaleState {
-    /// Current smoothed minimum Y-axis value
+    /// Current smoothed minimum Y-axis value, as a position on the axis
+    /// (see [`GraphYScale`](crate::GraphYScale))
     pub min_y: f32,
-    /// Current smoothed maximum Y-axis value
+    /// Current smoothed maximum Y-axis value, as a position on the axis
     pub max_y: f32,
+    /// Whether the range has snapped to a range derived from data;
+    /// until then it jumps to each new target instead of easing towards it
+    pub settled: bool,
+    /// Rate of change of `min_y` per second, used by spring easing
+    pub min_velocity: f32,
+    /// Rate of change of `max_y` per second, used by spring easing
+    pub max_velocity: f32,
+}
+
+/// Resource storing the smoothed Y-axis scale of each curve group.
+///
+/// Only used when [`GraphSettings::autoscale_per_group`](crate::GraphSettings)
+/// is set; ungrouped curves keep using [`GraphScaleState`].
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct GroupScaleStates {
+    /// Map from group name to its scaling state
+    states: HashMap<String, GraphScaleState>,
+}
+
+impl GroupScaleStates {
+    /// Get mutable reference to a group's scale state, creating it if needed
+    pub fn get_or_create(&mut self, group: &str) -> &mut GraphScaleState {
+        self.states.entry(group.to_owned()).or_default()
+    }
+
+    /// Get a group's scale state if it exists
+    pub fn get(&self, group: &str) -> Option<GraphScaleState> {
+        self.states.get(group).copied()
+    }
+
+    /// Clear all group scaling states
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
+/// Resource tracking whether HUD updates are paused.
+///
+/// Toggled by [`PerfHudCommand::TogglePause`](crate::PerfHudCommand); while
+/// paused the graph, bars and labels keep showing their last values.
+/// [`PerfHudCommand::ToggleGraphFreeze`](crate::PerfHudCommand) only stops
+/// the graph's history from recording, so it can be inspected while the
+/// rest of the HUD stays live.
+#[derive(Resource, Default, Clone, Copy)]
+pub struct HudPauseState {
+    /// Whether display updates are currently paused
+    pub paused: bool,
+    /// Whether the graph history is frozen (no new samples are recorded)
+    pub graph_frozen: bool,
+}
+
+impl HudPauseState {
+    /// Stop recording graph samples, keeping the current history on screen
+    pub fn freeze_graph(&mut self) {
+        self.graph_frozen = true;
+    }
+
+    /// Record graph samples again
+    pub fn resume_graph(&mut self) {
+        self.graph_frozen = false;
+    }
+
+    /// Whether the graph currently shows a fixed history, either because the
+    /// whole HUD is paused or because the graph is frozen
+    pub fn is_graph_frozen(&self) -> bool {
+        self.paused || self.graph_frozen
+    }
+}
+
+/// Resource holding what [`PerfHudCommand::ToggleFrameUnit`](crate::PerfHudCommand)
+/// switched, so the next toggle switches exactly that back.
+///
+/// Cleared when the settings are replaced by a preset or config file, since
+/// the switched entries belong to the previous settings.
+#[derive(Resource, Default)]
+pub struct FrameUnitToggle {
+    pub(crate) switched: Option<FrameUnitSwitch>,
+}
+
+impl FrameUnitToggle {
+    /// Metric the HUD was switched away from (None = not switched)
+    pub fn switched_from(&self) -> Option<&str> {
+        self.switched
+            .as_ref()
+            .map(|switched| switched.primary.as_str())
+    }
+}
+
+/// Entries switched by a frame unit toggle, with their settings from before it.
+pub(crate) struct FrameUnitSwitch {
+    /// `fps` or `frame_time_ms`, whichever was shown first
+    pub primary: String,
+    pub curves: Vec<(usize, CurveConfig)>,
+    /// Curves that showed the other metric already, hidden while switched
+    pub hidden_curves: Vec<usize>,
+    pub bars: Vec<(usize, BarConfig)>,
+    pub stats_rows: Vec<usize>,
+    pub budgets: Vec<(usize, MetricBudget)>,
+}
+
+/// Resource holding the refresh rate of the display the primary window is on.
+///
+/// Updated when the window is created or moves between monitors; the default
+/// [`FpsTarget`](crate::FpsTarget) derives the frame budgets from it.
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub struct DisplayRefreshRate {
+    /// Refresh rate in Hz (None without a window or if the platform does not report it)
+    pub hz: Option<f32>,
+}
+
+/// Budget zone of a bar and its color transition into it.
+#[derive(Debug, Clone, Copy)]
+pub struct BarColorState {
+    /// Budget zone the bar is in
+    pub level: BudgetLevel,
+    /// Color the transition started from
+    from: LinearRgba,
+    /// Color the transition ends at
+    to: LinearRgba,
+    /// Time the transition started at
+    started: Duration,
+}
+
+impl BarColorState {
+    /// Color at time `now` of the transition
+    fn color(&self, transition: &BarColorTransition, now: Duration) -> LinearRgba {
+        let duration = transition.duration.as_secs_f32();
+        let t = if duration > 0.0 {
+            now.saturating_sub(self.started).as_secs_f32() / duration
+        } else {
+            1.0
+        };
+        self.from.mix(&self.to, transition.easing.sample_clamped(t))
+    }
+}
+
+/// Resource storing the budget zone and color transition of each bar, keyed
+/// by metric ID.
+///
+/// Used when [`BarsSettings::color_transition`](crate::BarsSettings) is set.
+#[derive(Resource, Default)]
+pub struct BarColorStates {
+    states: HashMap<String, BarColorState>,
+}
+
+impl BarColorStates {
+    /// Budget zone of a bar (Ok for bars not seen yet)
+    pub fn level(&self, metric_id: &str) -> BudgetLevel {
+        self.states
+            .get(metric_id)
+            .map_or(BudgetLevel::Ok, |state| state.level)
+    }
+
+    /// Move a bar to the budget zone `level` drawn in `target`, returning the
+    /// color to draw it in at time `now`.
+    ///
+    /// A new target starts a transition from the color currently shown; a bar
+    /// seen for the first time takes its target color right away.
+    pub fn update(
+        &mut self,
+        metric_id: &str,
+        level: BudgetLevel,
+        target: Color,
+        transition: &BarColorTransition,
+        now: Duration,
+    ) -> Color {
+        let target = target.to_linear();
+        let Some(state) = self.states.get_mut(metric_id) else {
+            self.states.insert(
+                metric_id.to_owned(),
+                BarColorState {
+                    level,
+                    from: target,
+                    to: target,
+                    started: now,
+                },
+            );
+            return target.into();
+        };
+        if state.to != target {
+            state.from = state.color(transition, now);
+            state.to = target;
+            state.started = now;
+        }
+        state.level = level;
+        state.color(transition, now).into()
+    }
+
+    /// Get a bar's zone and transition if it exists
+    pub fn get(&self, metric_id: &str) -> Option<&BarColorState> {
+        self.states.get(metric_id)
+    }
+}
+
+/// Recent values and shown value of a filtered label.
+#[derive(Debug, Clone, Default)]
+struct LabelFilterState {
+    /// Latest values, oldest first
+    recent: VecDeque<f32>,
+    /// Value the label currently shows
+    shown: f32,
+    /// Refreshes left until the shown value is updated
+    countdown: u32,
+    /// Frame the latest value was taken on
+    frame: u32,
+}
+
+/// Resource storing the recent values of labels with a [`LabelFilter`],
+/// keyed by metric ID.
+#[derive(Resource, Default)]
+pub struct LabelFilterStates {
+    states: HashMap<String, LabelFilterState>,
+    /// Buffer the median is taken in, reused so updates do not allocate
+    sorted: Vec<f32>,
+}
+
+impl LabelFilterStates {
+    /// Record `value` of the filtered metric on `frame`, returning the value
+    /// its labels show.
+    ///
+    /// A metric seen for the first time shows its value right away. Calls
+    /// for the same frame, e.g. from the graph and a bar label, record the
+    /// value only once.
+    pub fn update(&mut self, filter: &LabelFilter, value: f32, frame: u32) -> f32 {
+        if !self.states.contains_key(&filter.metric_id) {
+            self.states
+                .insert(filter.metric_id.clone(), LabelFilterState::default());
+        }
+        let state = self
+            .states
+            .get_mut(&filter.metric_id)
+            .expect("label filter state was just inserted");
+        if !state.recent.is_empty() && state.frame == frame {
+            return state.shown;
+        }
+        state.frame = frame;
+        while state.recent.len() >= filter.samples.max(1) {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(value);
+
+        if state.countdown == 0 {
+            self.sorted.clear();
+            self.sorted.extend(state.recent.iter().copied());
+            self.sorted.sort_unstable_by(f32::total_cmp);
+            state.shown = self.sorted[self.sorted.len() / 2];
+            state.countdown = filter.interval.max(1);
+        }
+        state.countdown -= 1;
+        state.shown
+    }
+
+    /// Forget all recent values
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
 }
 
 /// Resource storing dynamic scaling states for all performance bars.
@@ -124,7 +1022,8 @@ pub struct GraphScaleState {
 /// Each bar can have its own dynamic scaling behavior based on its configured
 /// BarScaleMode. This resource tracks the historical data and current range
 /// for each bar independently.
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 pub struct BarScaleStates {
     /// Map from metric ID to its scaling state
     states: HashMap<String, BarScaleState>,
@@ -133,7 +1032,14 @@ pub struct BarScaleStates {
 impl BarScaleStates {
     /// Get mutable reference to a bar's scale state, creating it if needed
     pub fn get_or_create(&mut self, metric_id: &str) -> &mut BarScaleState {
-        self.states.entry(metric_id.to_owned()).or_default()
+        // Look up before inserting, so existing states do not allocate a key
+        if !self.states.contains_key(metric_id) {
+            self.states
+                .insert(metric_id.to_owned(), BarScaleState::default());
+        }
+        self.states
+            .get_mut(metric_id)
+            .expect("scale state was just inserted")
     }
 
     /// Get reference to a bar's scale state if it exists
@@ -146,8 +1052,273 @@ impl BarScaleStates {
         self.states.clear();
     }
 
+    /// Iterate over all metric IDs and their scaling states
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BarScaleState)> {
+        self.states.iter().map(|(id, state)| (id.as_str(), state))
+    }
+
     /// Remove a specific bar's scaling state
     pub fn remove(&mut self, metric_id: &str) -> Option<BarScaleState> {
         self.states.remove(metric_id)
     }
 }
+
+/// Outcome of sampling one metric provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SampleOutcome {
+    /// The provider returned a value
+    Value,
+    /// The provider returned no value
+    Missing,
+    /// The provider panicked
+    Panicked,
+}
+
+/// Sampling statistics of a single metric provider.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProviderStats {
+    /// Number of samples that returned no value
+    pub missing: u64,
+    /// Number of samples that panicked
+    pub panics: u64,
+    /// Number of failed samples in a row, up to and including the latest one
+    pub consecutive_failures: u32,
+    /// Time spent in the latest sample
+    pub last_duration: Duration,
+}
+
+impl ProviderStats {
+    /// Whether the latest sample failed
+    pub fn is_failing(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+}
+
+/// Resource tracking failures and sampling cost of each metric provider.
+///
+/// A provider that never yields a value (e.g. one reading a misspelled
+/// diagnostic path) shows up here instead of just flatlining in the graph.
+#[derive(Resource, Default)]
+pub struct ProviderHealth {
+    /// Map from metric ID to the statistics of its provider
+    stats: HashMap<String, ProviderStats>,
+}
+
+impl ProviderHealth {
+    /// Get the statistics of the provider for a metric
+    pub fn get(&self, metric_id: &str) -> Option<&ProviderStats> {
+        self.stats.get(metric_id)
+    }
+
+    /// Iterate over all metric IDs and their provider statistics
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ProviderStats)> {
+        self.stats.iter().map(|(id, stats)| (id.as_str(), stats))
+    }
+
+    /// Clear all statistics
+    pub fn clear(&mut self) {
+        self.stats.clear();
+    }
+
+    /// Record one sample of the provider for `metric_id`
+    pub(crate) fn record(
+        &mut self,
+        metric_id: &str,
+        outcome: SampleOutcome,
+        duration: Duration,
+    ) -> &ProviderStats {
+        // Look up before inserting, so known providers do not allocate a key
+        if !self.stats.contains_key(metric_id) {
+            self.stats
+                .insert(metric_id.to_owned(), ProviderStats::default());
+        }
+        let stats = self
+            .stats
+            .get_mut(metric_id)
+            .expect("provider stats were just inserted");
+        stats.last_duration = duration;
+        match outcome {
+            SampleOutcome::Value => stats.consecutive_failures = 0,
+            SampleOutcome::Missing => {
+                stats.missing += 1;
+                stats.consecutive_failures += 1;
+            }
+            SampleOutcome::Panicked => {
+                stats.panics += 1;
+                stats.consecutive_failures += 1;
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregator_skips_missing_frames() {
+        let mut aggregator = SampleAggregator::default();
+        let mut frame = [f32::NAN; MAX_CURVES];
+        frame[0] = 2.0;
+        assert!(aggregator.accumulate(&frame, 2).is_none());
+        frame[0] = f32::NAN;
+        let sample = aggregator.accumulate(&frame, 2).unwrap();
+
+        // Curve 0 has one present frame, the others none at all
+        assert_eq!(sample.mean[0], 2.0);
+        assert_eq!(sample.min[0], 2.0);
+        assert_eq!(sample.max[0], 2.0);
+        assert!(sample.mean[1].is_nan());
+        assert!(sample.min[1].is_nan());
+        assert!(sample.max[1].is_nan());
+    }
+
+    #[test]
+    fn test_timed_aggregator_carries_overshoot() {
+        let mut aggregator = SampleAggregator::default();
+        let frame = [1.0; MAX_CURVES];
+        let interval = Duration::from_millis(100);
+        let delta = Duration::from_millis(40);
+
+        // 40 + 40 + 40 ms completes the first sample 20 ms late...
+        assert!(aggregator
+            .accumulate_for(&frame, 1, interval, delta)
+            .is_none());
+        assert!(aggregator
+            .accumulate_for(&frame, 1, interval, delta)
+            .is_none());
+        assert!(aggregator
+            .accumulate_for(&frame, 1, interval, delta)
+            .is_some());
+        assert_eq!(aggregator.sample_period(), Some(Duration::from_millis(120)));
+
+        // ...which counts towards the second one
+        assert!(aggregator
+            .accumulate_for(&frame, 1, interval, delta)
+            .is_none());
+        assert!(aggregator
+            .accumulate_for(&frame, 1, interval, delta)
+            .is_some());
+    }
+
+    #[test]
+    fn test_history_capacity_drops_oldest_samples() {
+        let mut history = HistoryBuffers::default();
+        let zeros = [0.0; MAX_CURVES];
+        for i in 0..10 {
+            history.push(&[i as f32; MAX_CURVES], &zeros, &zeros);
+        }
+
+        history.set_capacity(4);
+        assert_eq!(history.length, 4);
+        assert_eq!(history.values[0][0], 6.0);
+
+        history.push(&[10.0; MAX_CURVES], &zeros, &zeros);
+        assert_eq!(history.length, 4);
+        assert_eq!(history.values[0][..4], [7.0, 8.0, 9.0, 10.0]);
+    }
+
+    #[test]
+    fn test_percentiles_skip_missing_samples() {
+        let mut history = HistoryBuffers::default();
+        let zeros = [0.0; MAX_CURVES];
+        for i in 1..=100 {
+            history.push(&[i as f32; MAX_CURVES], &zeros, &zeros);
+        }
+        history.push(&[f32::NAN; MAX_CURVES], &zeros, &zeros);
+
+        let stats = history.percentiles(0).unwrap();
+        assert_eq!(stats.p50, 50.0);
+        assert_eq!(stats.p95, 95.0);
+        assert_eq!(stats.p99, 99.0);
+        assert_eq!(stats.max, 100.0);
+        assert!(HistoryBuffers::default().percentiles(0).is_none());
+    }
+
+    #[test]
+    fn test_missing_sample_keeps_last_value() {
+        let mut samples = SampledValues::default();
+        samples.set("fps", 60.0);
+        samples.mark_missing("fps");
+        assert_eq!(samples.get("fps"), Some(60.0));
+        assert_eq!(samples.get_fresh("fps"), None);
+
+        samples.set("fps", 30.0);
+        assert_eq!(samples.get_fresh("fps"), Some(30.0));
+    }
+
+    #[test]
+    fn test_warmup_len_tracks_scrolled_samples() {
+        let mut history = HistoryBuffers::default();
+        let zeros = [0.0; MAX_CURVES];
+        for _ in 0..10 {
+            history.push(&zeros, &zeros, &zeros);
+        }
+        assert_eq!(history.warmup_len(4), 4);
+        assert_eq!(history.warmup_len(20), 10);
+
+        // Once warm-up samples scroll out of the buffer none remain
+        for _ in 0..MAX_SAMPLES + 10 {
+            history.push(&zeros, &zeros, &zeros);
+        }
+        assert_eq!(history.warmup_len(20), 0);
+    }
+
+    #[test]
+    fn test_provider_health_counts_failures() {
+        let mut health = ProviderHealth::default();
+        health.record("a", SampleOutcome::Missing, Duration::ZERO);
+        health.record("a", SampleOutcome::Panicked, Duration::ZERO);
+        let stats = health.get("a").unwrap();
+        assert_eq!((stats.missing, stats.panics), (1, 1));
+        assert_eq!(stats.consecutive_failures, 2);
+        assert!(stats.is_failing());
+
+        let stats = health.record("a", SampleOutcome::Value, Duration::from_micros(5));
+        assert!(!stats.is_failing());
+        assert_eq!(stats.missing, 1);
+        assert_eq!(stats.last_duration, Duration::from_micros(5));
+    }
+
+    #[test]
+    fn test_trend_history_folds_frames_per_interval() {
+        let mut trend = TrendHistory::default();
+        let interval = Duration::from_secs(1);
+        let frame = Duration::from_millis(400);
+        let mut values = [0.0; MAX_CURVES];
+        for value in [1.0, 2.0, 6.0] {
+            values[0] = value;
+            trend.record(&values, frame, interval);
+        }
+        assert_eq!(trend.history.length, 1);
+        assert_eq!(trend.history.values[0][0], 3.0);
+        assert_eq!(trend.history.mins[0][0], 1.0);
+        assert_eq!(trend.history.maxs[0][0], 6.0);
+
+        // The 200 ms carried over count towards the next sample
+        assert!(!trend.record(&values, frame, interval));
+        assert!(trend.record(&values, frame, interval));
+    }
+
+    #[test]
+    fn test_history_rebind_fades_out_previous_series() {
+        let mut history = HistoryBuffers::default();
+        let values = [5.0; MAX_CURVES];
+        for _ in 0..3 {
+            history.push(&values, &values, &values);
+        }
+        history.rebind(0, RebindHistory::Crossfade(Duration::from_secs(1)));
+        history.push(&values, &values, &values);
+        assert_eq!(history.series_start(0), Some(3));
+        assert_eq!(history.series_start(1), None);
+
+        history.tick_rebinds(Duration::from_millis(250));
+        assert_eq!(history.previous_series_opacity(0), 0.75);
+        history.tick_rebinds(Duration::from_secs(1));
+        assert_eq!(history.series_start(0), None);
+        assert!(history.values[0][..3].iter().all(|v| v.is_nan()));
+        assert_eq!(history.values[0][3], 5.0);
+        assert_eq!(history.values[1][0], 5.0);
+    }
+}
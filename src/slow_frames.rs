@@ -0,0 +1,177 @@
+//! Rolling list of recent slow frames.
+//!
+//! With [`PerfHudSettings::slow_frames`](crate::PerfHudSettings) enabled,
+//! every frame over the threshold is logged with the time it happened and how
+//! long it took. The latest ones are listed in a small panel under the HUD,
+//! e.g. `12:03:41 - 58 ms`, so testers can report exactly when a hitch
+//! happened without watching the graph, and the list is added to the spike
+//! log of bug report bundles.
+
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use bevy::{
+    diagnostic::FrameCount,
+    ecs::system::{Res, ResMut},
+    prelude::Resource,
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    resources::DisplayRefreshRate,
+};
+
+/// A frame that took longer than the slow frame threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlowFrame {
+    /// Wall-clock time of day the frame ended, in UTC like Bevy's log output
+    /// (None where no wall clock is available, e.g. on the web)
+    pub time_of_day: Option<Duration>,
+    /// Time since the app started when the frame ended
+    pub elapsed: Duration,
+    /// Number of the frame
+    pub frame: u32,
+    /// How long the frame took
+    pub duration: Duration,
+}
+
+impl fmt::Display for SlowFrame {
+    /// `hh:mm:ss - 58 ms`, with the time since startup prefixed by `+` when
+    /// the time of day is unknown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, time) = match self.time_of_day {
+            Some(time) => ("", time),
+            None => ("+", self.elapsed),
+        };
+        let seconds = time.as_secs();
+        write!(
+            f,
+            "{prefix}{:02}:{:02}:{:02} - {:.0} ms",
+            seconds / 3600 % 24,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.duration.as_secs_f32() * 1000.0
+        )
+    }
+}
+
+/// Resource holding the most recent slow frames.
+#[derive(Resource, Debug, Default)]
+pub struct SlowFrameLog {
+    /// Oldest first
+    entries: VecDeque<SlowFrame>,
+}
+
+impl SlowFrameLog {
+    /// The logged slow frames, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &SlowFrame> + '_ {
+        self.entries.iter().rev()
+    }
+
+    /// Number of logged slow frames
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no slow frame is logged
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Log a slow frame, dropping the oldest ones past `max_entries`
+    pub fn push(&mut self, frame: SlowFrame, max_entries: usize) {
+        self.entries.push_back(frame);
+        while self.entries.len() > max_entries {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Time of day in UTC, from the system clock.
+fn time_of_day() -> Option<Duration> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        Some(Duration::from_secs(since_epoch.as_secs() % 86_400))
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+/// System logging the last frame if it took longer than the slow frame threshold.
+///
+/// Frames excluded as debugger breaks (see [`LongFrameState`](crate::LongFrameState))
+/// are not logged.
+pub fn detect_slow_frames(
+    settings: Res<PerfHudSettings>,
+    time: Res<Time<Real>>,
+    frame: Res<FrameCount>,
+    refresh_rate: Res<DisplayRefreshRate>,
+    mut log: ResMut<SlowFrameLog>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::SlowFrames);
+    let config = &settings.slow_frames;
+    if !settings.enabled || !config.enabled {
+        return;
+    }
+    let threshold = config.threshold.or_else(|| {
+        settings
+            .budget_thresholds("frame_time_ms", refresh_rate.hz)
+            .map(|thresholds| Duration::from_secs_f32(thresholds.critical.max(0.0) / 1000.0))
+    });
+    let duration = time.delta();
+    if threshold.is_none_or(|threshold| duration <= threshold) {
+        return;
+    }
+    log.push(
+        SlowFrame {
+            time_of_day: time_of_day(),
+            elapsed: time.elapsed(),
+            // The delta measured this frame is the previous frame's
+            frame: frame.0.wrapping_sub(1),
+            duration,
+        },
+        config.max_entries,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow_frame(frame: u32) -> SlowFrame {
+        SlowFrame {
+            time_of_day: Some(Duration::from_secs(12 * 3600 + 3 * 60 + 41)),
+            elapsed: Duration::from_secs(75),
+            frame,
+            duration: Duration::from_millis(58),
+        }
+    }
+
+    #[test]
+    fn test_slow_frame_display() {
+        let frame = slow_frame(1);
+        assert_eq!(frame.to_string(), "12:03:41 - 58 ms");
+        let without_clock = SlowFrame {
+            time_of_day: None,
+            ..frame
+        };
+        assert_eq!(without_clock.to_string(), "+00:01:15 - 58 ms");
+    }
+
+    #[test]
+    fn test_log_keeps_the_latest_entries() {
+        let mut log = SlowFrameLog::default();
+        for frame in 0..4 {
+            log.push(slow_frame(frame), 3);
+        }
+        let frames: Vec<u32> = log.iter().map(|entry| entry.frame).collect();
+        assert_eq!(frames, [3, 2, 1]);
+    }
+}
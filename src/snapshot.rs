@@ -0,0 +1,156 @@
+//! Typed snapshots of the HUD's runtime state.
+//!
+//! [`PerfHudSnapshot`] gathers current metric values, graph histories and
+//! scale states into plain structs, serializable with the `serde` feature, so
+//! user code can persist or forward them without reading the HUD's resources
+//! one by one.
+
+use std::collections::BTreeMap;
+
+use bevy::ecs::world::World;
+
+use crate::{
+    BarScaleStates, BuildInfo, GraphScaleState, HistoryBuffers, PerfHudSettings, SampledValues,
+    MAX_CURVES,
+};
+
+/// Point-in-time copy of the HUD's metrics, histories and scale states.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::PerfHudSnapshot;
+///
+/// fn dump_hud(world: &mut World) {
+///     let snapshot = PerfHudSnapshot::capture(world);
+///     info!("fps: {:?}", snapshot.metrics.get("fps"));
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerfHudSnapshot {
+    /// Most recent value of every sampled metric, keyed by metric ID
+    pub metrics: BTreeMap<String, f32>,
+    /// History of each graph curve, in curve order
+    pub curves: Vec<CurveSnapshot>,
+    /// Current Y-axis range of the graph
+    pub graph_scale: ScaleSnapshot,
+    /// Current normalization range of each bar, keyed by metric ID
+    pub bar_scales: BTreeMap<String, ScaleSnapshot>,
+    /// Build and session the data was captured from
+    pub build_info: Option<BuildInfo>,
+}
+
+/// History of a single graph curve, oldest sample first.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurveSnapshot {
+    /// ID of the metric this curve displays
+    pub metric_id: String,
+    /// Displayed (smoothed and quantized) value of each sample (NaN = missing)
+    pub values: Vec<f32>,
+    /// Minimum raw value aggregated into each sample
+    pub mins: Vec<f32>,
+    /// Maximum raw value aggregated into each sample
+    pub maxs: Vec<f32>,
+}
+
+/// A min/max range used to scale a graph or bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScaleSnapshot {
+    /// Lower bound of the range
+    pub min: f32,
+    /// Upper bound of the range
+    pub max: f32,
+}
+
+impl PerfHudSnapshot {
+    /// Capture the current HUD state from the world.
+    ///
+    /// Resources that are missing (e.g. before the plugin is added) leave
+    /// the corresponding fields empty.
+    pub fn capture(world: &World) -> Self {
+        let metrics = world
+            .get_resource::<SampledValues>()
+            .map(|samples| {
+                samples
+                    .iter()
+                    .map(|(id, value)| (id.to_owned(), value))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let curves = match (
+            world.get_resource::<PerfHudSettings>(),
+            world.get_resource::<HistoryBuffers>(),
+        ) {
+            (Some(settings), Some(history)) => {
+                let len = history.length as usize;
+                settings
+                    .graph
+                    .curves
+                    .iter()
+                    .take(MAX_CURVES)
+                    .enumerate()
+                    .map(|(i, curve)| CurveSnapshot {
+                        metric_id: curve.metric.id.clone(),
+                        values: history.values[i][..len].to_vec(),
+                        mins: history.mins[i][..len].to_vec(),
+                        maxs: history.maxs[i][..len].to_vec(),
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let graph_scale = world
+            .get_resource::<GraphScaleState>()
+            .map(|scale| ScaleSnapshot {
+                min: scale.min_y,
+                max: scale.max_y,
+            })
+            .unwrap_or_default();
+
+        let bar_scales = world
+            .get_resource::<BarScaleStates>()
+            .map(|states| {
+                states
+                    .iter()
+                    .map(|(id, state)| {
+                        let (min, max) = state.get_current_range();
+                        (id.to_owned(), ScaleSnapshot { min, max })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            metrics,
+            curves,
+            graph_scale,
+            bar_scales,
+            build_info: world.get_resource::<BuildInfo>().cloned(),
+        }
+    }
+}
+
+/// Write `snapshot` to `path` as RON on the IO task pool.
+#[cfg(feature = "serde")]
+pub(crate) fn save_snapshot(snapshot: PerfHudSnapshot, path: std::path::PathBuf) {
+    use bevy::{
+        log::warn,
+        tasks::{IoTaskPool, TaskPool},
+    };
+
+    IoTaskPool::get_or_init(TaskPool::new)
+        .spawn(async move {
+            let written = ron::ser::to_string_pretty(&snapshot, Default::default())
+                .map_err(std::io::Error::other)
+                .and_then(|ron| std::fs::write(&path, ron));
+            if let Err(err) = written {
+                warn!("perf HUD: cannot write snapshot to {path:?}: {err}");
+            }
+        })
+        .detach();
+}
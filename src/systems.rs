@@ -2,30 +2,76 @@
 //!
 //! This module contains the main systems that manage the HUD lifecycle:
 //! - setup_hud: Creates all UI entities and materials during startup
-//! - sample_diagnostics: Updates metric values each frame
+//! - handle_hud_commands: Executes runtime commands such as rebuilding the HUD
+//! - backfill_history: Seeds graph history from diagnostics when the HUD is enabled
+//! - sample_diagnostics: Updates metric values and provider health each frame
 //! - update_graph_and_bars: Renders current data to the HUD display
+//! - update_display_refresh_rate: Tracks the refresh rate of the primary window's display
+//! - capture_threshold_screenshots: Saves screenshots when metrics turn critical
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{self, Write as _},
+    panic::{self, AssertUnwindSafe},
+    time::Duration,
+};
 
 use bevy::{
     asset::{Assets, Handle},
-    diagnostic::DiagnosticsStore,
+    diagnostic::{DiagnosticsStore, FrameCount, SystemInfo},
     ecs::{
-        entity::Entity,
+        entity::{Entities, Entity},
         system::{Commands, Query, Res, ResMut},
     },
+    platform::time::Instant,
     prelude::*,
+    render::view::screenshot::{save_to_disk, Screenshot},
+    tasks::available_parallelism,
     text::{TextColor, TextFont},
-    ui::{FlexDirection, MaterialNode, Node, Overflow, PositionType, UiRect, Val},
+    ui::{FlexDirection, FocusPolicy, MaterialNode, Node, Overflow, PositionType, UiRect, Val},
+    window::{PrimaryWindow, WindowResized},
+    winit::WinitWindows,
 };
 
+#[cfg(feature = "serde")]
+use crate::snapshot::{save_snapshot, PerfHudSnapshot};
 use crate::{
-    config::PerfHudSettings,
+    bar_layout::layout_bar_rows,
+    budget::{HudStage, HudUpdateCost},
+    build_info::BuildInfo,
+    bundle::export_bundle,
+    chrome_trace::{export_chrome_trace, ChromeTraceRecorder},
+    compat::{despawn_descendants, despawn_tree, set_parent},
+    config::{
+        BarFill, BarLabelAlign, BarScaleMode, BudgetLevel, CurveConfig, GraphHistory,
+        GraphSettings, GraphView, GraphXAxis, GraphYScale, HudAnchor, HudInteraction, HudPlacement,
+        HudPreset, HudTextBackground, LabelColumn, MetricDefinition, MissingData, PerfHudSettings,
+        ScaleEasing, ThresholdShading, TrafficLightShape,
+    },
     constants::*,
-    providers::{MetricProviders, MetricSampleContext},
-    render::{BarMaterial, BarParams, MultiLineGraphMaterial, MultiLineGraphParams},
+    drag::HudDraggable,
+    events::{
+        HudControlButton, MetricBudgetTransition, MetricSample, PerfHudCommand, PerfHudScreenshot,
+    },
+    localization::{
+        LabelLocalizer, CONTROL_EXPORT_LABEL_ID, CONTROL_PAUSE_LABEL_ID, CONTROL_PRESET_LABEL_ID,
+        CONTROL_REBUILD_LABEL_ID, CONTROL_RECORD_LABEL_ID, CONTROL_STOP_RECORDING_LABEL_ID,
+        GRAPH_CAPTION_LABEL_ID, GRAPH_TITLE_LABEL_ID,
+    },
+    metric_stats::{MetricStats, StatsWindow},
+    providers::{cpu_core_usage_id, HudDiagnosticsSource, MetricProviders, MetricSampleContext},
+    render::{BarsMaterial, BarsParams, MultiLineGraphMaterial, MultiLineGraphParams},
+    replay::{load_sample_recording, save_sample_recording, SampleRecorder, SampleReplay},
     resources::{
-        BarScaleStates, GraphLabelHandle, GraphScaleState, HistoryBuffers, HudHandles,
-        SampledValues,
+        BarColorStates, BarScaleStates, CpuCoreBarsHandle, DisplayRefreshRate, FrameUnitSwitch,
+        FrameUnitToggle, GraphLabelHandle, GraphScaleState, GroupScaleStates, HistoryBuffers,
+        HudHandles, HudPauseState, LabelFilterStates, ProviderHealth, SampleAggregator,
+        SampleOutcome, SampledValues, TrendHistory,
     },
+    slow_frames::SlowFrameLog,
+    transition::HudFade,
+    zoom::{graph_cursor, GraphZoom},
 };
 
 /// Startup system that creates all HUD UI entities and materials.
@@ -33,48 +79,152 @@ use crate::{
 pub fn setup_hud(
     mut commands: Commands,
     settings: Option<Res<PerfHudSettings>>,
+    build_info: Option<Res<BuildInfo>>,
+    localizer: Option<Res<LabelLocalizer>>,
     mut graph_mats: ResMut<Assets<MultiLineGraphMaterial>>,
-    mut bar_mats: ResMut<Assets<BarMaterial>>,
+    mut bars_mats: ResMut<Assets<BarsMaterial>>,
 ) {
     let Some(s) = settings else {
         return;
     };
     // UI 2D camera: render after 3D to avoid conflicts
-    let ui_cam = commands.spawn(Camera2d).id();
-    commands.entity(ui_cam).insert(Camera {
-        order: 1,
-        ..default()
-    });
-
-    // Root UI node
-    let root = commands
-        .spawn((Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(s.origin.y),
-            left: Val::Px(s.origin.x),
-            flex_direction: FlexDirection::Column,
+    // An embedded HUD is rendered by the camera of the UI it is parented to
+    if s.parent.is_none() {
+        let ui_cam = commands.spawn(Camera2d).id();
+        commands.entity(ui_cam).insert(Camera {
+            order: 1,
             ..default()
-        },))
-        .id();
+        });
+    }
+
+    let handles = spawn_hud(
+        &mut commands,
+        &s,
+        build_info.as_deref(),
+        localizer.as_deref(),
+        &mut graph_mats,
+        &mut bars_mats,
+    );
+    commands.insert_resource(handles);
+}
+
+/// Build the HUD UI hierarchy and its materials from the given settings.
+///
+/// Returns the handles of everything that was spawned. Used by [`setup_hud`]
+/// and when rebuilding the HUD; the UI camera is not part of the HUD and is
+/// only spawned once at startup. With [`PerfHudSettings::parent`] set, the
+/// hierarchy is built as an ordinary child node of that entity, so it can
+/// live inside an existing panel, tab or scroll view.
+pub fn spawn_hud(
+    commands: &mut Commands,
+    s: &PerfHudSettings,
+    build_info: Option<&BuildInfo>,
+    localizer: Option<&LabelLocalizer>,
+    graph_mats: &mut Assets<MultiLineGraphMaterial>,
+    bars_mats: &mut Assets<BarsMaterial>,
+) -> HudHandles {
+    // Root UI node, floating at `origin` unless embedded in a parent node.
+    // Anchors other than the top-left corner depend on the HUD's laid out
+    // size, so update_hud_anchor moves the root there once it is known.
+    let start = if s.anchor == HudAnchor::TopLeft {
+        s.origin
+    } else {
+        Vec2::ZERO
+    };
+    let root = match s.parent {
+        Some(parent) => {
+            let root = commands
+                .spawn((Node {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },))
+                .id();
+            set_parent(commands, root, parent);
+            root
+        }
+        None => commands
+            .spawn((Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(start.y),
+                left: Val::Px(start.x),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },))
+            .id(),
+    };
     commands.entity(root).insert(if s.enabled {
         Visibility::Visible
     } else {
         Visibility::Hidden
     });
+    spawn_hud_contents(
+        commands, root, s, build_info, localizer, graph_mats, bars_mats,
+    )
+}
+
+/// Build everything below the HUD's `root` node from the given settings.
+///
+/// Returns the handles of `root` and everything spawned under it.
+#[allow(clippy::too_many_arguments)]
+fn spawn_hud_contents(
+    commands: &mut Commands,
+    root: Entity,
+    s: &PerfHudSettings,
+    build_info: Option<&BuildInfo>,
+    localizer: Option<&LabelLocalizer>,
+    graph_mats: &mut Assets<MultiLineGraphMaterial>,
+    bars_mats: &mut Assets<BarsMaterial>,
+) -> HudHandles {
+    // Optional shadow behind all HUD text for readability over bright scenes
+    let text_shadow = s.text_shadow.map(|shadow| TextShadow {
+        offset: shadow.offset,
+        color: shadow.color,
+    });
+
+    // Traffic light indicator at the top (optional)
+    let traffic_light = s.traffic_light.enabled.then(|| {
+        let light = &s.traffic_light;
+        let entity = commands
+            .spawn((
+                Node {
+                    width: Val::Px(light.size),
+                    height: Val::Px(light.size),
+                    margin: UiRect::bottom(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(light.ok_color),
+                match light.shape {
+                    TrafficLightShape::Square => BorderRadius::ZERO,
+                    TrafficLightShape::Circle => BorderRadius::MAX,
+                },
+            ))
+            .id();
+        set_parent(commands, entity, root);
+        entity
+    });
 
     // Graph material and node (optional)
     let mut graph_row_opt: Option<Entity> = None;
     let mut graph_entity_opt: Option<Entity> = None;
     let mut graph_handle_opt: Option<Handle<MultiLineGraphMaterial>> = None;
     let mut graph_labels: Vec<GraphLabelHandle> = Vec::new();
+    let mut graph_unit_label = None;
+    let mut frame_axis_labels = None;
+    let mut time_axis_labels = None;
+    let mut y_tick_labels = Vec::new();
+    let mut graph_tooltip = None;
+    let mut stats_labels = Vec::new();
     if s.graph.enabled {
         let mut graph_params = MultiLineGraphParams::default();
         #[allow(clippy::field_reassign_with_default)]
         {
             graph_params.length = 0;
-            graph_params.min_y = s.graph.min_y;
-            graph_params.max_y = s.graph.max_y;
-            graph_params.thickness = s.graph.thickness;
+            let min_y = s.graph.y_scale.to_axis(s.graph.min_y);
+            let max_y = s.graph.y_scale.to_axis(s.graph.max_y);
+            graph_params.min_y = min_y;
+            graph_params.max_y = max_y;
+            graph_params.scales = [Vec4::new(min_y, max_y, 0.0, 0.0); MAX_CURVES];
+            graph_params.thickness = s.graph.line_thickness_uv(s.graph.size.y);
             graph_params.bg_color = s.graph.bg_color.to_linear().to_vec4();
             graph_params.border_color = s.graph.border.color.to_linear().to_vec4();
             graph_params.border_thickness = s.graph.border.thickness; // pixels
@@ -87,63 +237,149 @@ pub fn setup_hud(
             graph_params.border_right = if s.graph.border.right { 1 } else { 0 };
             graph_params.border_top = if s.graph.border.top { 1 } else { 0 };
             graph_params.curve_count = s.graph.curves.len().min(MAX_CURVES) as u32;
+            graph_params.quality = s.graph.quality as u32;
+            graph_params.draw_order = MultiLineGraphParams::pack_draw_order(&s.graph.draw_order());
+            graph_params.curve_styles =
+                MultiLineGraphParams::pack_curve_styles(s.graph.curves.iter().map(|c| c.style));
+            graph_params.line_smoothing = MultiLineGraphParams::pack_line_smoothing(
+                s.graph.curves.iter().map(|c| c.line_smoothing),
+            );
+            graph_params.grid_color = s.graph.grid_color.to_linear().to_vec4();
+            graph_params.grid_lines = s.graph.y_ticks.max(2);
             // Write curve colors
-            for (i, c) in s.graph.curves.iter().take(MAX_CURVES).enumerate() {
-                let v = c.metric.color.to_linear().to_vec4();
-                graph_params.colors[i] = v;
+            for slot in s.graph.curve_slots() {
+                graph_params.colors[slot.index] = slot.color.to_linear().to_vec4();
+                if s.graph.curves[slot.index].candlestick {
+                    graph_params.candlestick_mask |= 1 << slot.index;
+                }
             }
         }
         // Row container: left labels + right graph
-        let label_width = s.graph.label_width.max(40.0);
+        let label_width = s.graph.label_column.reserved_width();
+        let style = &s.graph.caption_style;
+        if let Some(title) = &s.graph.title {
+            let title = localizer.map_or_else(
+                || title.clone(),
+                |localizer| localizer.localize(GRAPH_TITLE_LABEL_ID, title),
+            );
+            let entity = spawn_graph_text(
+                commands,
+                title,
+                style.title_font_size,
+                style.title_color,
+                UiRect::bottom(Val::Px(2.0)),
+                &s.graph,
+                text_shadow,
+            );
+            set_parent(commands, entity, root);
+        }
         let graph_row = commands
             .spawn((Node {
-                width: Val::Px(s.graph.size.x + label_width),
-                height: Val::Px(s.graph.size.y),
+                // A window-relative graph cannot be summed with the label column
+                width: match s.graph.size_percent {
+                    Some(_) => Val::Auto,
+                    None => Val::Px(s.graph.size.x + label_width),
+                },
+                height: s.graph.height_val(),
                 flex_direction: FlexDirection::Row,
                 ..default()
             },))
             .id();
-        commands.entity(graph_row).insert(ChildOf(root));
+        set_parent(commands, graph_row, root);
         commands.entity(graph_row).insert(if s.enabled {
             Visibility::Visible
         } else {
             Visibility::Hidden
         });
         graph_row_opt = Some(graph_row);
-
-        // Label container (vertical to avoid overlap)
-        let label_container = commands
-            .spawn((Node {
-                width: Val::Px(label_width),
-                height: Val::Px(s.graph.size.y),
-                flex_direction: FlexDirection::Column,
-                ..default()
-            },))
-            .id();
-        commands.entity(label_container).insert(ChildOf(graph_row));
-
-        // Create label rows matching configured curves
-        for curve in s.graph.curves.iter().take(MAX_CURVES) {
-            let eid = commands
-                .spawn((
-                    Text::new(""),
-                    TextColor(Color::WHITE),
-                    TextFont {
-                        font_size: 10.0,
+        let time_axis = s.graph.x_axis == GraphXAxis::Time && s.graph.time_axis_labels;
+        if s.graph.x_axis == GraphXAxis::Frames || time_axis {
+            let axis_row = commands
+                .spawn(Node {
+                    width: s.graph.width_val(),
+                    margin: UiRect {
+                        left: Val::Px(label_width),
+                        top: Val::Px(1.0),
                         ..default()
                     },
-                    Node {
-                        width: Val::Px(label_width),
-                        height: Val::Px(16.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                })
+                .id();
+            set_parent(commands, axis_row, root);
+            let labels = [(); 2].map(|_| {
+                let label = commands
+                    .spawn((
+                        Text::new(""),
+                        TextColor(style.caption_color),
+                        TextFont {
+                            font_size: 9.0,
+                            ..default()
+                        },
+                    ))
+                    .id();
+                set_parent(commands, label, axis_row);
+                if let Some(shadow) = text_shadow {
+                    commands.entity(label).insert(shadow);
+                }
+                label
+            });
+            if time_axis {
+                time_axis_labels = Some(labels);
+            } else {
+                frame_axis_labels = Some(labels);
+            }
+        }
+        if let Some(caption) = &s.graph.caption {
+            let caption = localizer.map_or_else(
+                || caption.clone(),
+                |localizer| localizer.localize(GRAPH_CAPTION_LABEL_ID, caption),
+            );
+            let entity = spawn_graph_text(
+                commands,
+                caption,
+                style.caption_font_size,
+                style.caption_color,
+                UiRect::top(Val::Px(2.0)),
+                &s.graph,
+                text_shadow,
+            );
+            set_parent(commands, entity, root);
+        }
+        if s.stats_panel.enabled {
+            let panel = commands
+                .spawn(Node {
+                    width: s.graph.width_val(),
+                    margin: UiRect {
+                        left: Val::Px(label_width),
+                        top: Val::Px(2.0),
                         ..default()
                     },
-                ))
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                })
                 .id();
-            commands.entity(eid).insert(ChildOf(label_container));
-            graph_labels.push(GraphLabelHandle {
-                metric_id: curve.metric.id.clone(),
-                entity: eid,
-            });
+            set_parent(commands, panel, root);
+            for metric_id in &s.stats_panel.metrics {
+                let eid = commands
+                    .spawn((
+                        Text::new(""),
+                        TextColor(Color::WHITE),
+                        TextFont {
+                            font_size: s.stats_panel.font_size,
+                            ..default()
+                        },
+                    ))
+                    .id();
+                set_parent(commands, eid, panel);
+                if let Some(shadow) = text_shadow {
+                    commands.entity(eid).insert(shadow);
+                }
+                stats_labels.push(GraphLabelHandle {
+                    metric_id: metric_id.clone(),
+                    entity: eid,
+                });
+            }
         }
 
         // Graph node
@@ -154,40 +390,236 @@ pub fn setup_hud(
             .spawn((
                 MaterialNode(gh.clone()),
                 Node {
-                    width: Val::Px(s.graph.size.x),
-                    height: Val::Px(s.graph.size.y),
+                    width: s.graph.width_val(),
+                    height: s.graph.height_val(),
+                    ..default()
+                },
+            ))
+            .id();
+
+        // Label container (vertical to avoid overlap)
+        let overlay = s.graph.label_column == LabelColumn::Overlay;
+        let text_width = if overlay {
+            s.graph.width_val()
+        } else {
+            Val::Px(label_width)
+        };
+        let label_container = (s.graph.label_column != LabelColumn::Hidden).then(|| {
+            commands
+                .spawn((Node {
+                    position_type: if overlay {
+                        PositionType::Absolute
+                    } else {
+                        PositionType::Relative
+                    },
+                    left: if overlay { Val::Px(4.0) } else { Val::Auto },
+                    top: if overlay { Val::Px(2.0) } else { Val::Auto },
+                    width: text_width,
+                    height: s.graph.height_val(),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },))
+                .id()
+        });
+
+        // Left column sits before the graph in the row; overlay labels are
+        // children of the graph node so they render on top of it
+        match label_container {
+            Some(container) if overlay => {
+                set_parent(commands, ge, graph_row);
+                set_parent(commands, container, ge);
+            }
+            Some(container) => {
+                set_parent(commands, container, graph_row);
+                set_parent(commands, ge, graph_row);
+            }
+            None => {
+                set_parent(commands, ge, graph_row);
+            }
+        }
+
+        // Create label rows matching configured curves
+        if let Some(container) = label_container {
+            for curve in s.graph.curves.iter().take(MAX_CURVES) {
+                let eid = commands
+                    .spawn((
+                        Text::new(""),
+                        TextColor(Color::WHITE),
+                        TextFont {
+                            font_size: 10.0,
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Px(16.0),
+                            ..default()
+                        },
+                    ))
+                    .id();
+                set_parent(commands, eid, container);
+                if let Some(shadow) = text_shadow {
+                    commands.entity(eid).insert(shadow);
+                }
+                graph_labels.push(GraphLabelHandle {
+                    metric_id: curve.metric.id.clone(),
+                    entity: eid,
+                });
+            }
+        }
+
+        // Unit shared by all curves, filled in while updating the labels
+        let unit_label = commands
+            .spawn((
+                Text::new(""),
+                TextColor(style.caption_color),
+                TextFont {
+                    font_size: 9.0,
+                    ..default()
+                },
+                Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(3.0),
+                    top: Val::Px(1.0),
                     ..default()
                 },
             ))
             .id();
-        commands.entity(ge).insert(ChildOf(graph_row));
+        set_parent(commands, unit_label, ge);
+        if let Some(shadow) = text_shadow {
+            commands.entity(unit_label).insert(shadow);
+        }
+        graph_unit_label = Some(unit_label);
+
+        // Y-axis tick labels along the right edge, bottom first, each just above
+        // its grid line; the top one hangs below the top edge under the unit label
+        if s.graph.y_tick_labels {
+            let ticks = s.graph.y_ticks.max(2);
+            for tick in 0..ticks {
+                let (top, bottom) = if tick + 1 == ticks {
+                    (Val::Px(12.0), Val::Auto)
+                } else {
+                    (
+                        Val::Auto,
+                        Val::Percent(100.0 * tick as f32 / (ticks - 1) as f32),
+                    )
+                };
+                let label = commands
+                    .spawn((
+                        Text::new(""),
+                        TextColor(style.caption_color),
+                        TextFont {
+                            font_size: 9.0,
+                            ..default()
+                        },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(3.0),
+                            top,
+                            bottom,
+                            ..default()
+                        },
+                    ))
+                    .id();
+                set_parent(commands, label, ge);
+                if let Some(shadow) = text_shadow {
+                    commands.entity(label).insert(shadow);
+                }
+                y_tick_labels.push(label);
+            }
+        }
+
+        // Tooltip with the values under the pointer, moved and filled in by
+        // `update_graph_tooltip`; spawned last so it draws over the other labels
+        if s.interaction.tooltip {
+            let tooltip = commands
+                .spawn((
+                    Text::new(""),
+                    TextColor(Color::WHITE),
+                    TextFont {
+                        font_size: 9.0,
+                        ..default()
+                    },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(2.0),
+                        margin: UiRect::horizontal(Val::Px(4.0)),
+                        padding: UiRect::axes(Val::Px(3.0), Val::Px(1.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                    Visibility::Hidden,
+                ))
+                .id();
+            set_parent(commands, tooltip, ge);
+            graph_tooltip = Some(tooltip);
+        }
+
         graph_entity_opt = Some(ge);
         graph_handle_opt = Some(gh);
     }
 
-    // Bars container placed below the graph
+    // Latest slow frames, filled in by `update_slow_frame_panel`
+    let mut slow_frame_labels = Vec::new();
+    if s.slow_frames.enabled {
+        let panel = commands
+            .spawn(Node {
+                margin: UiRect::top(Val::Px(2.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            })
+            .id();
+        set_parent(commands, panel, root);
+        for _ in 0..s.slow_frames.max_entries.max(1) {
+            let eid = commands
+                .spawn((
+                    Text::new(""),
+                    TextColor(Color::WHITE),
+                    TextFont {
+                        font_size: s.slow_frames.font_size,
+                        ..default()
+                    },
+                ))
+                .id();
+            set_parent(commands, eid, panel);
+            if let Some(shadow) = text_shadow {
+                commands.entity(eid).insert(shadow);
+            }
+            slow_frame_labels.push(eid);
+        }
+    }
+
+    // Bars grid placed below the graph, drawn by a single material
     let mut bars_root_opt: Option<Entity> = None;
-    let mut bar_entities = Vec::new();
-    let mut bar_materials = Vec::new();
+    let mut bars_material_opt: Option<Handle<BarsMaterial>> = None;
     let mut bar_labels = Vec::new();
+    let mut bar_rows = Vec::new();
     if s.bars.enabled && !s.bars.bars.is_empty() {
         let column_count = 2;
         let column_width = (s.graph.size.x - 12.0) / column_count as f32;
+        let bar_count = s.bars.bars.len().min(MAX_BARS);
+        let rows = layout_bar_rows(&s.bars.bars[..bar_count], column_count, column_width);
+        bar_rows = vec![0; bar_count];
+
+        // Each row is 25px tall: 1px gap, a 20px bar and room below it
+        let grid_size = Vec2::new(s.graph.size.x, rows.len() as f32 * 25.0);
+        let mut params = BarsParams {
+            count: bar_count as u32,
+            ..default()
+        };
 
         let bars_root = commands
             .spawn((Node {
-                width: Val::Px(s.graph.size.x),
-                height: Val::Px((s.bars.bars.len() as f32 / column_count as f32).ceil() * 25.0),
-                flex_direction: FlexDirection::Column,
+                width: s.graph.width_val(),
+                height: Val::Px(grid_size.y),
                 margin: UiRect {
-                    left: Val::Px(s.graph.label_width.max(40.0)),
+                    left: Val::Px(s.graph.label_column.reserved_width()),
                     top: Val::Px(4.0),
                     ..default()
                 },
                 ..default()
             },))
             .id();
-        commands.entity(bars_root).insert(ChildOf(root));
+        set_parent(commands, bars_root, root);
         commands.entity(bars_root).insert(if s.enabled {
             Visibility::Visible
         } else {
@@ -195,252 +627,2372 @@ pub fn setup_hud(
         });
         bars_root_opt = Some(bars_root);
 
-        for chunk in s.bars.bars.chunks(column_count) {
-            let row = commands
-                .spawn((Node {
-                    width: Val::Px(s.graph.size.x),
-                    height: Val::Px(24.0),
-                    flex_direction: FlexDirection::Row,
-                    margin: UiRect {
-                        top: Val::Px(1.0),
-                        ..default()
-                    },
-                    ..default()
-                },))
-                .id();
-            commands.entity(row).insert(ChildOf(bars_root));
-
-            for (col_idx, bar_cfg) in chunk.iter().enumerate() {
-                let base_label = bar_cfg
-                    .metric
-                    .label
-                    .clone()
-                    .unwrap_or_else(|| bar_cfg.metric.id.clone());
-
-                let column = commands
-                    .spawn((Node {
-                        width: Val::Px(column_width),
-                        height: Val::Px(24.0),
-                        margin: UiRect {
-                            right: if col_idx + 1 == column_count || col_idx + 1 == chunk.len() {
-                                Val::Px(0.0)
-                            } else {
-                                Val::Px(8.0)
-                            },
-                            ..default()
-                        },
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },))
-                    .id();
-                commands.entity(column).insert(ChildOf(row));
-
-                let mat = bar_mats.add(BarMaterial {
-                    params: BarParams {
-                        value: 0.0,
-                        r: bar_cfg.metric.color.to_linear().to_vec4().x,
-                        g: bar_cfg.metric.color.to_linear().to_vec4().y,
-                        b: bar_cfg.metric.color.to_linear().to_vec4().z,
-                        a: bar_cfg.metric.color.to_linear().to_vec4().w,
-                        bg_r: s.bars.bg_color.to_linear().to_vec4().x,
-                        bg_g: s.bars.bg_color.to_linear().to_vec4().y,
-                        bg_b: s.bars.bg_color.to_linear().to_vec4().z,
-                        bg_a: s.bars.bg_color.to_linear().to_vec4().w,
-                    },
-                });
+        for (row_idx, row_slots) in rows.iter().enumerate() {
+            let top = row_idx as f32 * 25.0 + 1.0;
+            let mut left = 0.0;
+            for slot in row_slots {
+                let bar_cfg = &s.bars.bars[slot.index];
+                let base_label = metric_label(&bar_cfg.metric, localizer);
 
-                let bar_entity = commands
-                    .spawn((
-                        MaterialNode(mat.clone()),
-                        Node {
-                            width: Val::Px(column_width),
-                            height: Val::Px(20.0),
-                            ..default()
+                params.rects[slot.index] = Vec4::new(
+                    left / grid_size.x,
+                    top / grid_size.y,
+                    slot.width / grid_size.x,
+                    20.0 / grid_size.y,
+                );
+                params.colors[slot.index] = bar_cfg.metric.color.to_linear().to_vec4();
+                params.bg_colors[slot.index] =
+                    s.bars.bar_bg_color(bar_cfg, row_idx).to_linear().to_vec4();
+                bar_rows[slot.index] = row_idx;
+
+                // The label box covers the bar so the text centers on it vertically;
+                // its horizontal extent is relative like the bar rects, so it
+                // follows a window-relative grid
+                let layout = &s.bars.label;
+                let label_box = commands
+                    .spawn(Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent((left + layout.padding_x) / grid_size.x * 100.0),
+                        top: Val::Px(top + layout.offset_y),
+                        width: Val::Percent(
+                            (slot.width - 2.0 * layout.padding_x).max(0.0) / grid_size.x * 100.0,
+                        ),
+                        height: Val::Px(20.0),
+                        align_items: AlignItems::Center,
+                        justify_content: match layout.align {
+                            BarLabelAlign::Start => JustifyContent::FlexStart,
+                            BarLabelAlign::Center => JustifyContent::Center,
+                            BarLabelAlign::End => JustifyContent::FlexEnd,
                         },
-                    ))
+                        overflow: Overflow::clip(),
+                        ..default()
+                    })
                     .id();
-                commands.entity(bar_entity).insert(ChildOf(column));
+                set_parent(commands, label_box, bars_root);
 
                 let bar_label = commands
                     .spawn((
                         Text::new(base_label),
                         TextColor(Color::WHITE),
                         TextFont {
-                            font_size: 10.0,
-                            ..default()
-                        },
-                        Node {
-                            position_type: PositionType::Absolute,
-                            left: Val::Px(6.0),
-                            top: Val::Px(5.0),
-                            width: Val::Px(column_width - 12.0),
-                            overflow: Overflow::hidden(),
+                            font_size: layout.font_size,
                             ..default()
                         },
+                        TextLayout::new(
+                            match layout.align {
+                                BarLabelAlign::Start => JustifyText::Left,
+                                BarLabelAlign::Center => JustifyText::Center,
+                                BarLabelAlign::End => JustifyText::Right,
+                            },
+                            if layout.wrap {
+                                LineBreak::WordBoundary
+                            } else {
+                                LineBreak::NoWrap
+                            },
+                        ),
                     ))
                     .id();
-                commands.entity(bar_label).insert(ChildOf(bar_entity));
-
-                bar_entities.push(bar_entity);
-                bar_materials.push(mat);
+                set_parent(commands, bar_label, label_box);
+                if let Some(shadow) = text_shadow {
+                    commands.entity(bar_label).insert(shadow);
+                }
                 bar_labels.push(bar_label);
+
+                left += slot.width + 8.0;
             }
         }
-    }
-
-    // Store handles
-    commands.insert_resource(HudHandles {
-        root: Some(root),
-        graph_row: graph_row_opt,
-        graph_entity: graph_entity_opt,
-        graph_material: graph_handle_opt,
-        graph_labels,
-        graph_label_width: s.graph.label_width.max(40.0),
-        bars_root: bars_root_opt,
-        bar_entities,
-        bar_materials,
-        bar_labels,
-    });
-}
 
-/// System that samples all registered metric providers and updates current values.
-/// The system only runs if PerfHudSettings is present and enabled.
-pub fn sample_diagnostics(
-    diagnostics: Option<Res<DiagnosticsStore>>,
-    settings: Option<Res<PerfHudSettings>>,
-    mut samples: ResMut<SampledValues>,
-    mut providers: ResMut<MetricProviders>,
-) {
-    let Some(s) = settings else {
-        return;
-    };
-    if !s.enabled {
-        return;
+        let mat = bars_mats.add(BarsMaterial { params });
+        commands.entity(bars_root).insert(MaterialNode(mat.clone()));
+        bars_material_opt = Some(mat);
     }
 
-    let ctx = MetricSampleContext {
-        diagnostics: diagnostics.as_deref(),
-    };
-
-    for provider in providers.iter_mut() {
-        if let Some(value) = provider.sample(ctx) {
-            samples.set(provider.metric_id(), value);
-        }
-    }
-}
+    // Per-core CPU usage strip placed below the bars, each group of up to
+    // MAX_BARS cores drawn by a single material
+    let mut cpu_cores_root_opt: Option<Entity> = None;
+    let mut cpu_core_bars = Vec::new();
+    if s.cpu_cores.enabled {
+        let columns = s.cpu_cores.columns.clamp(1, MAX_BARS);
+        let gap = s.cpu_cores.gap.max(0.0);
+        let bar_width = (s.graph.size.x - gap * (columns - 1) as f32) / columns as f32;
+        let rows_per_group = MAX_BARS / columns;
 
-/// System that updates graph and bar displays with current performance data.
-/// The system only runs if both PerfHudSettings and HudHandles are present.
-#[allow(clippy::too_many_arguments)]
-pub fn update_graph_and_bars(
-    settings: Option<Res<PerfHudSettings>>,
-    handles: Option<Res<HudHandles>>,
+        let cpu_cores_root = commands
+            .spawn((Node {
+                width: s.graph.width_val(),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(gap),
+                margin: UiRect {
+                    left: Val::Px(s.graph.label_column.reserved_width()),
+                    top: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },))
+            .id();
+        set_parent(commands, cpu_cores_root, root);
+        commands.entity(cpu_cores_root).insert(if s.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+        cpu_cores_root_opt = Some(cpu_cores_root);
+
+        let cores: Vec<usize> = (0..available_parallelism()).collect();
+        for group in cores.chunks(rows_per_group * columns) {
+            let rows = group.len().div_ceil(columns);
+            let group_size = Vec2::new(
+                s.graph.size.x,
+                rows as f32 * (s.cpu_cores.bar_height + gap) - gap,
+            );
+            let mut params = BarsParams {
+                count: group.len() as u32,
+                ..default()
+            };
+            for slot in 0..group.len() {
+                let (row, column) = (slot / columns, slot % columns);
+                params.rects[slot] = Vec4::new(
+                    column as f32 * (bar_width + gap) / group_size.x,
+                    row as f32 * (s.cpu_cores.bar_height + gap) / group_size.y,
+                    bar_width / group_size.x,
+                    s.cpu_cores.bar_height / group_size.y,
+                );
+                params.colors[slot] = s.cpu_cores.color.to_linear().to_vec4();
+                params.bg_colors[slot] = s.cpu_cores.bg_color.to_linear().to_vec4();
+            }
+            let mat = bars_mats.add(BarsMaterial { params });
+            let group_entity = commands
+                .spawn((
+                    MaterialNode(mat.clone()),
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(group_size.y),
+                        ..default()
+                    },
+                ))
+                .id();
+            set_parent(commands, group_entity, cpu_cores_root);
+            cpu_core_bars.push(CpuCoreBarsHandle {
+                metric_ids: group.iter().map(|&core| cpu_core_usage_id(core)).collect(),
+                entity: group_entity,
+                material: mat,
+            });
+        }
+    }
+
+    // Build and session summary placed at the bottom of the HUD
+    if let Some(info) = build_info.filter(|_| s.show_build_info) {
+        let build_label = commands
+            .spawn((
+                Text::new(info.summary()),
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                TextFont {
+                    font_size: 9.0,
+                    ..default()
+                },
+                Node {
+                    margin: UiRect {
+                        left: Val::Px(s.graph.label_column.reserved_width()),
+                        top: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .id();
+        set_parent(commands, build_label, root);
+        if let Some(shadow) = text_shadow {
+            commands.entity(build_label).insert(shadow);
+        }
+    }
+
+    // Control strip with buttons sending HUD commands
+    let mut control_buttons = Vec::new();
+    if s.show_controls {
+        let controls = commands
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(4.0),
+                margin: UiRect {
+                    left: Val::Px(s.graph.label_column.reserved_width()),
+                    top: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },))
+            .id();
+        set_parent(commands, controls, root);
+
+        // The record button's caption follows the recording state, see
+        // update_record_button
+        for (label_id, label, command) in [
+            (CONTROL_PAUSE_LABEL_ID, "Pause", PerfHudCommand::TogglePause),
+            (
+                CONTROL_RECORD_LABEL_ID,
+                "Record",
+                PerfHudCommand::ToggleSampleRecording(s.controls.recording_path.clone()),
+            ),
+            (
+                CONTROL_EXPORT_LABEL_ID,
+                "Export",
+                PerfHudCommand::ExportBundle(s.controls.export_directory.clone()),
+            ),
+            (
+                CONTROL_PRESET_LABEL_ID,
+                "Preset",
+                PerfHudCommand::CyclePreset,
+            ),
+            (CONTROL_REBUILD_LABEL_ID, "Rebuild", PerfHudCommand::Rebuild),
+        ] {
+            let label = localizer.map_or_else(
+                || label.to_owned(),
+                |localizer| localizer.localize(label_id, label),
+            );
+            let button = commands
+                .spawn((
+                    Button,
+                    HudControlButton(command),
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(s.bars.bg_color),
+                ))
+                .id();
+            set_parent(commands, button, controls);
+            control_buttons.push(button);
+
+            let text = commands
+                .spawn((
+                    Text::new(label),
+                    TextColor(Color::WHITE),
+                    TextFont {
+                        font_size: 10.0,
+                        ..default()
+                    },
+                ))
+                .id();
+            set_parent(commands, text, button);
+            if let Some(shadow) = text_shadow {
+                commands.entity(text).insert(shadow);
+            }
+        }
+    }
+
+    // Let pointer input through the HUD, except for elements that opt in
+    let interaction = s.interaction;
+    let legend: Vec<Entity> = if interaction.legend {
+        graph_labels.iter().map(|label| label.entity).collect()
+    } else {
+        Vec::new()
+    };
+    commands.queue(move |world: &mut World| {
+        apply_hud_interaction(world, root, interaction, &legend);
+    });
+
+    // Background chips behind the labels, once all of them are spawned
+    if let Some(background) = s.text_background {
+        let color = background.resolved_color(s.graph.bg_color);
+        commands.queue(move |world: &mut World| {
+            apply_text_backgrounds(world, root, background, color);
+        });
+    }
+
+    HudHandles {
+        root: Some(root),
+        graph_row: graph_row_opt,
+        graph_entity: graph_entity_opt,
+        graph_material: graph_handle_opt,
+        graph_labels,
+        graph_unit_label,
+        graph_label_width: s.graph.label_column.reserved_width(),
+        bars_root: bars_root_opt,
+        bars_material: bars_material_opt,
+        bar_labels,
+        bar_rows,
+        stats_labels,
+        slow_frame_labels,
+        traffic_light,
+        frame_axis_labels,
+        time_axis_labels,
+        y_tick_labels,
+        graph_tooltip,
+        cpu_cores_root: cpu_cores_root_opt,
+        cpu_core_bars,
+        control_buttons,
+    }
+}
+
+/// Set the focus policy (and pickability) of the HUD tree under `root`.
+///
+/// Nodes pass pointer input through unless they are a control button with
+/// `controls` set or one of the `legend` labels.
+fn apply_hud_interaction(
+    world: &mut World,
+    root: Entity,
+    interaction: HudInteraction,
+    legend: &[Entity],
+) {
+    let mut pending = vec![root];
+    while let Some(entity) = pending.pop() {
+        let Ok(mut node) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        if let Some(children) = node.get::<Children>() {
+            pending.extend_from_slice(children);
+        }
+        let is_legend = legend.contains(&entity);
+        if is_legend || (interaction.controls && node.contains::<HudControlButton>()) {
+            node.insert(FocusPolicy::Block);
+            if is_legend {
+                node.insert(Interaction::default());
+            }
+            #[cfg(feature = "picking")]
+            node.insert(bevy::picking::Pickable::default());
+        } else {
+            node.insert(FocusPolicy::Pass);
+            #[cfg(feature = "picking")]
+            node.insert(bevy::picking::Pickable::IGNORE);
+        }
+    }
+}
+
+/// Give every text node of the HUD tree under `root` a background chip.
+///
+/// The chip is the text node's own background, so it follows the laid out
+/// text as values change. Text nodes that already have a background (the
+/// graph tooltip) are left alone, and labels that would stretch across their
+/// container shrink to their text instead.
+fn apply_text_backgrounds(
+    world: &mut World,
+    root: Entity,
+    background: HudTextBackground,
+    color: Color,
+) {
+    let mut pending = vec![root];
+    while let Some(entity) = pending.pop() {
+        let Ok(mut node) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        if let Some(children) = node.get::<Children>() {
+            pending.extend_from_slice(children);
+        }
+        let has_background = node
+            .get::<BackgroundColor>()
+            .is_some_and(|bg| !bg.0.is_fully_transparent());
+        if !node.contains::<Text>() || has_background {
+            continue;
+        }
+        node.insert((
+            BackgroundColor(color),
+            BorderRadius::all(Val::Px(background.corner_radius)),
+        ));
+        if let Some(mut layout) = node.get_mut::<Node>() {
+            if layout.padding == UiRect::DEFAULT {
+                layout.padding =
+                    UiRect::axes(Val::Px(background.padding.x), Val::Px(background.padding.y));
+            }
+            if layout.align_self == AlignSelf::Auto {
+                layout.align_self = AlignSelf::FlexStart;
+            }
+        }
+    }
+}
+
+/// Show the HUD with a ready-made layout.
+///
+/// Inserts the preset's [`PerfHudSettings`], replacing any existing ones, and
+/// rebuilds the HUD if it is already shown. Requires [`BevyPerfHudPlugin`](crate::BevyPerfHudPlugin).
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::{spawn_perf_hud, HudPreset};
+///
+/// fn setup(mut commands: Commands) {
+///     spawn_perf_hud(&mut commands, HudPreset::Compact);
+/// }
+/// ```
+pub fn spawn_perf_hud(commands: &mut Commands, preset: HudPreset) {
+    let settings = preset.settings();
+    commands.queue(move |world: &mut World| {
+        world.insert_resource(settings);
+        world.insert_resource(FrameUnitToggle::default());
+        if world.contains_resource::<HudHandles>() {
+            world.send_event(PerfHudCommand::Rebuild);
+        }
+    });
+}
+
+/// Despawn the HUD UI hierarchy and remove its materials from the asset stores.
+///
+/// Counterpart of [`spawn_hud`]; the handles must not be used afterwards.
+pub fn despawn_hud(
+    commands: &mut Commands,
+    handles: &HudHandles,
+    graph_mats: &mut Assets<MultiLineGraphMaterial>,
+    bars_mats: &mut Assets<BarsMaterial>,
+) {
+    if let Some(root) = handles.root {
+        despawn_tree(commands, root);
+    }
+    remove_hud_materials(handles, graph_mats, bars_mats);
+}
+
+/// Remove the materials referenced by `handles` from the asset stores.
+fn remove_hud_materials(
+    handles: &HudHandles,
+    graph_mats: &mut Assets<MultiLineGraphMaterial>,
+    bars_mats: &mut Assets<BarsMaterial>,
+) {
+    if let Some(handle) = &handles.graph_material {
+        graph_mats.remove(handle);
+    }
+    if let Some(handle) = &handles.bars_material {
+        bars_mats.remove(handle);
+    }
+    for core_bars in &handles.cpu_core_bars {
+        bars_mats.remove(&core_bars.material);
+    }
+}
+
+/// System repairing the HUD when some of its entities were despawned.
+///
+/// A scene reload or user code may despawn part of the HUD hierarchy; the
+/// update systems skip entities that no longer exist. A despawned root is
+/// taken as removal of the HUD: its materials are removed along with
+/// [`HudHandles`]. Otherwise the contents of the root are respawned in place,
+/// keeping the root, the history and the scaling state, so the HUD does not
+/// stay half drawn.
+#[allow(clippy::too_many_arguments)]
+pub fn repair_hud(
+    mut commands: Commands,
+    handles: Res<HudHandles>,
+    entities: &Entities,
+    settings: Option<Res<PerfHudSettings>>,
+    build_info: Option<Res<BuildInfo>>,
+    localizer: Option<Res<LabelLocalizer>>,
+    (mut graph_mats, mut bars_mats): HudMaterialAssets,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Commands);
+    let Some(missing) = handles
+        .entities()
+        .find(|&entity| !entities.contains(entity))
+    else {
+        return;
+    };
+    let Some(root) = handles.root.filter(|&root| entities.contains(root)) else {
+        warn!("perf HUD root {missing} was despawned; removing the HUD");
+        despawn_hud(&mut commands, &handles, &mut graph_mats, &mut bars_mats);
+        commands.remove_resource::<HudHandles>();
+        return;
+    };
+    let Some(s) = settings else {
+        return;
+    };
+
+    warn!("perf HUD entity {missing} was despawned; respawning the HUD contents");
+    despawn_descendants(&mut commands, root);
+    remove_hud_materials(&handles, &mut graph_mats, &mut bars_mats);
+    let repaired = spawn_hud_contents(
+        &mut commands,
+        root,
+        &s,
+        build_info.as_deref(),
+        localizer.as_deref(),
+        &mut graph_mats,
+        &mut bars_mats,
+    );
+    commands.insert_resource(repaired);
+}
+
+/// System that executes [`PerfHudCommand`] events.
+///
+/// [`PerfHudCommand::Rebuild`] despawns the current HUD and spawns it again from
+/// the latest [`PerfHudSettings`], resetting history and scaling state since
+/// the configured curves and bars may have changed. With a
+/// [`PerfHudSettings::transition`] the two HUDs cross-fade instead.
+/// [`PerfHudCommand::TogglePause`] and [`PerfHudCommand::ToggleGraphFreeze`]
+/// flip [`HudPauseState`],
+/// [`PerfHudCommand::ToggleCurveGroup`] flips the group in
+/// [`GraphSettings::hidden_groups`], [`PerfHudCommand::ToggleCurve`] flips a
+/// curve's `enabled` flag and [`PerfHudCommand::ToggleGraphView`]
+/// flips [`GraphSettings::view`]. [`PerfHudCommand::RebindCurve`] swaps a
+/// curve's metric and starts a new series in its history.
+/// [`PerfHudCommand::ExportBundle`] and [`PerfHudCommand::ExportChromeTrace`]
+/// write their files at the end of the frame's commands.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_hud_commands(
+    mut events: EventReader<PerfHudCommand>,
+    mut commands: Commands,
+    mut settings: Option<ResMut<PerfHudSettings>>,
+    build_info: Option<Res<BuildInfo>>,
+    localizer: Option<Res<LabelLocalizer>>,
+    mut handles: Option<ResMut<HudHandles>>,
+    mut pause: ResMut<HudPauseState>,
+    mut history: ResMut<HistoryBuffers>,
+    mut aggregator: ResMut<SampleAggregator>,
+    mut trend: ResMut<TrendHistory>,
+    mut scale_state: ResMut<GraphScaleState>,
+    mut bar_scale_states: ResMut<BarScaleStates>,
+    mut group_scale_states: ResMut<GroupScaleStates>,
+    (mut graph_mats, mut bars_mats): HudMaterialAssets,
+    roots: Query<Option<&HudDraggable>>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Commands);
+    let mut rebuild = false;
+    for event in events.read() {
+        match event {
+            PerfHudCommand::Rebuild => rebuild = true,
+            PerfHudCommand::TogglePause => pause.paused = !pause.paused,
+            PerfHudCommand::ToggleGraphFreeze => pause.graph_frozen = !pause.graph_frozen,
+            PerfHudCommand::ToggleCurveGroup(group) => {
+                if let Some(s) = settings.as_mut() {
+                    let visible = s.graph.is_group_visible(group);
+                    s.graph.set_group_visible(group, !visible);
+                }
+            }
+            PerfHudCommand::ToggleCurve(curve) => {
+                if let Some(cfg) = settings
+                    .as_mut()
+                    .and_then(|s| s.graph.curves.get_mut(*curve))
+                {
+                    cfg.enabled = !cfg.enabled;
+                }
+            }
+            PerfHudCommand::ToggleGraphView => {
+                if let Some(s) = settings.as_mut() {
+                    s.graph.view = s.graph.view.toggled();
+                }
+            }
+            PerfHudCommand::ToggleFrameUnit => {
+                commands.run_system_cached(toggle_frame_unit);
+            }
+            PerfHudCommand::RebindCurve {
+                curve,
+                metric,
+                history: mode,
+            } => {
+                let Some(cfg) = settings
+                    .as_mut()
+                    .and_then(|s| s.graph.curves.get_mut(*curve))
+                else {
+                    continue;
+                };
+                cfg.metric = metric.clone();
+                // Label rows are spawned one per curve, in curve order
+                if let Some(label) = handles
+                    .as_mut()
+                    .and_then(|h| h.graph_labels.get_mut(*curve))
+                {
+                    label.metric_id = metric.id.clone();
+                }
+                history.rebind(*curve, *mode);
+                aggregator.discard(*curve);
+                trend.rebind(*curve, *mode);
+            }
+            PerfHudCommand::ExportBundle(directory) => {
+                let directory = directory.clone();
+                commands.queue(move |world: &mut World| export_bundle(world, &directory));
+            }
+            PerfHudCommand::ExportChromeTrace(path) => {
+                let path = path.clone();
+                commands.queue(move |world: &mut World| {
+                    export_chrome_trace(world.resource::<ChromeTraceRecorder>(), &path);
+                });
+            }
+            PerfHudCommand::StartSampleRecording => {
+                commands.insert_resource(SampleRecorder::default());
+            }
+            PerfHudCommand::SaveSampleRecording(path) => {
+                let path = path.clone();
+                commands.queue(move |world: &mut World| {
+                    match world.remove_resource::<SampleRecorder>() {
+                        Some(recorder) => save_sample_recording(recorder.recording, path),
+                        None => warn!("perf HUD: no sample recording to save to {path:?}"),
+                    }
+                });
+            }
+            PerfHudCommand::ToggleSampleRecording(path) => {
+                let path = path.clone();
+                commands.queue(move |world: &mut World| {
+                    match world.remove_resource::<SampleRecorder>() {
+                        Some(recorder) => save_sample_recording(recorder.recording, path),
+                        None => world.insert_resource(SampleRecorder::default()),
+                    }
+                });
+            }
+            PerfHudCommand::CyclePreset => {
+                if let Some(s) = settings.as_mut() {
+                    let preset = s.preset.map_or(HudPreset::default(), HudPreset::next);
+                    let mut next = preset.settings();
+                    // Keep the strip, so the next preset can be picked too
+                    next.show_controls = s.show_controls;
+                    next.controls = s.controls.clone();
+                    next.parent = s.parent;
+                    **s = next;
+                    // The previous preset's frame unit switch does not apply
+                    commands.insert_resource(FrameUnitToggle::default());
+                    rebuild = true;
+                }
+            }
+            PerfHudCommand::ReplaySamples(path) => {
+                if let Some(recording) = load_sample_recording(path) {
+                    commands.insert_resource(SampleReplay::new(recording));
+                }
+            }
+            PerfHudCommand::StopReplay => commands.remove_resource::<SampleReplay>(),
+        }
+    }
+    if !rebuild {
+        return;
+    }
+    let Some(s) = settings else {
+        return;
+    };
+
+    // A draggable HUD stays draggable, and where it was dragged to
+    let draggable = handles
+        .as_ref()
+        .and_then(|h| h.root)
+        .and_then(|root| roots.get(root).ok().flatten())
+        .map(HudDraggable::carried_over);
+    if let Some(h) = handles {
+        match h.root {
+            // The old HUD fades out and cleans up after itself, unless its
+            // root is already gone
+            Some(root) if !s.transition.is_zero() && roots.contains(root) => {
+                commands
+                    .entity(root)
+                    .insert(HudFade::fade_out(s.transition, h.clone()));
+            }
+            _ => despawn_hud(&mut commands, &h, &mut graph_mats, &mut bars_mats),
+        }
+    }
+    *history = HistoryBuffers::default();
+    *aggregator = SampleAggregator::default();
+    *trend = TrendHistory::default();
+    *scale_state = GraphScaleState::default();
+    bar_scale_states.clear();
+    group_scale_states.clear();
+
+    let new_handles = spawn_hud(
+        &mut commands,
+        &s,
+        build_info.as_deref(),
+        localizer.as_deref(),
+        &mut graph_mats,
+        &mut bars_mats,
+    );
+    if let Some(root) = new_handles.root {
+        if !s.transition.is_zero() {
+            commands.entity(root).insert(HudFade::fade_in(s.transition));
+        }
+        if let Some(draggable) = draggable {
+            commands.entity(root).insert(draggable);
+        }
+    }
+    commands.insert_resource(new_handles);
+}
+
+/// System captioning the control strip's record button after the recording
+/// state: "Record" while not recording, "Stop" while recording.
+pub fn update_record_button(
+    recorder: Option<Res<SampleRecorder>>,
+    localizer: Option<Res<LabelLocalizer>>,
+    buttons: Query<(Ref<HudControlButton>, &Children)>,
+    mut texts: Query<&mut Text>,
+    mut was_recording: Local<bool>,
+) {
+    let recording = recorder.is_some();
+    let changed = recording != *was_recording;
+    *was_recording = recording;
+    for (button, children) in &buttons {
+        if !matches!(button.0, PerfHudCommand::ToggleSampleRecording(_))
+            || !(changed || button.is_added())
+        {
+            continue;
+        }
+        let (label_id, label) = if recording {
+            (CONTROL_STOP_RECORDING_LABEL_ID, "Stop")
+        } else {
+            (CONTROL_RECORD_LABEL_ID, "Record")
+        };
+        let label = localizer.as_ref().map_or_else(
+            || label.to_owned(),
+            |localizer| localizer.localize(label_id, label),
+        );
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
+
+/// System that sends the command of every HUD control button pressed this frame.
+pub fn handle_hud_control_buttons(
+    buttons: Query<(&Interaction, &HudControlButton), Changed<Interaction>>,
+    mut events: EventWriter<PerfHudCommand>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            events.write(button.0.clone());
+        }
+    }
+}
+
+/// System that toggles the curve of every graph label clicked this frame.
+///
+/// Labels only get an [`Interaction`] with [`HudInteraction::legend`] set.
+pub fn handle_hud_legend_clicks(
+    handles: Option<Res<HudHandles>>,
+    labels: Query<(Entity, &Interaction), Changed<Interaction>>,
+    mut events: EventWriter<PerfHudCommand>,
+) {
+    let Some(handles) = handles else {
+        return;
+    };
+    for (entity, interaction) in &labels {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Label rows are spawned one per curve, in curve order
+        if let Some(curve) = handles
+            .graph_labels
+            .iter()
+            .position(|label| label.entity == entity)
+        {
+            events.write(PerfHudCommand::ToggleCurve(curve));
+        }
+    }
+}
+
+/// System that backfills the graph history from the providers' recorded history.
+///
+/// Runs when [`PerfHudSettings`] changes. Whenever the HUD transitions to enabled,
+/// the history buffers are refilled from diagnostics history (where providers
+/// expose it) so the graph is not empty after toggling the HUD on mid-session.
+#[allow(clippy::too_many_arguments)]
+pub fn backfill_history(
+    settings: Option<Res<PerfHudSettings>>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    diagnostics_source: Option<Res<HudDiagnosticsSource>>,
+    system_info: Option<Res<SystemInfo>>,
+    providers: Res<MetricProviders>,
+    mut history: ResMut<HistoryBuffers>,
+    mut was_enabled: Local<bool>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Backfill);
+    let Some(s) = settings else {
+        return;
+    };
+
+    if s.enabled && !*was_enabled {
+        let ctx = MetricSampleContext {
+            diagnostics: diagnostics_source
+                .as_deref()
+                .map(|source| &source.0)
+                .or(diagnostics.as_deref()),
+            system_info: system_info.as_deref(),
+        };
+        let curves: Vec<Vec<f32>> = s
+            .graph
+            .curves
+            .iter()
+            .take(MAX_CURVES)
+            .map(|c| {
+                providers
+                    .get(&c.metric.id)
+                    .map(|p| p.history(ctx))
+                    .unwrap_or_default()
+            })
+            .collect();
+        history.backfill(&curves);
+    }
+    *was_enabled = s.enabled;
+}
+
+/// System that samples all registered metric providers and updates current values.
+/// The system only runs if PerfHudSettings is present and enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn sample_diagnostics(
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    diagnostics_source: Option<Res<HudDiagnosticsSource>>,
+    system_info: Option<Res<SystemInfo>>,
+    settings: Option<Res<PerfHudSettings>>,
+    mut samples: ResMut<SampledValues>,
+    mut providers: ResMut<MetricProviders>,
+    mut health: ResMut<ProviderHealth>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Sample);
+    let Some(s) = settings else {
+        return;
+    };
+    if !s.enabled {
+        return;
+    }
+
+    let ctx = MetricSampleContext {
+        diagnostics: diagnostics_source
+            .as_deref()
+            .map(|source| &source.0)
+            .or(diagnostics.as_deref()),
+        system_info: system_info.as_deref(),
+    };
+
+    // Only failures of metrics the HUD displays count towards the error metric
+    let displayed = |id: &str| {
+        s.graph.curves.iter().any(|c| c.metric.id == id)
+            || s.bars.bars.iter().any(|b| b.metric.id == id)
+    };
+    let mut errors = 0;
+
+    for provider in providers.iter_mut() {
+        // A panicking user provider must not take the whole app down
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| provider.sample(ctx)));
+        let elapsed = started.elapsed();
+
+        let id = provider.metric_id();
+        let outcome = match result {
+            Ok(Some(value)) => {
+                samples.set(id, value);
+                SampleOutcome::Value
+            }
+            Ok(None) => {
+                samples.mark_missing(id);
+                SampleOutcome::Missing
+            }
+            Err(_) => {
+                samples.mark_missing(id);
+                SampleOutcome::Panicked
+            }
+        };
+
+        let stats = health.record(id, outcome, elapsed);
+        if stats.is_failing() && displayed(id) {
+            errors += 1;
+        }
+        if s.log_provider_errors {
+            match outcome {
+                SampleOutcome::Panicked => {
+                    warn!("perf HUD metric provider '{id}' panicked; its sample was discarded")
+                }
+                SampleOutcome::Missing
+                    if stats.consecutive_failures == PROVIDER_ERROR_LOG_SAMPLES =>
+                {
+                    warn!(
+                        "perf HUD metric provider '{id}' returned no value for {} samples in a row",
+                        stats.consecutive_failures
+                    )
+                }
+                _ => {}
+            }
+        }
+    }
+
+    samples.set(PROVIDER_ERRORS_ID, errors as f32);
+}
+
+/// Per-metric state of [`apply_metric_samples`]: the values pushed during
+/// the current frame and the newest timestamp shown.
+#[derive(Clone, Copy)]
+pub struct PushedValues {
+    sum: f32,
+    min: f32,
+    max: f32,
+    count: u32,
+    shown: Option<Duration>,
+}
+
+impl Default for PushedValues {
+    fn default() -> Self {
+        Self {
+            sum: 0.0,
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            count: 0,
+            shown: None,
+        }
+    }
+}
+
+/// System applying the [`MetricSample`]s pushed since the last frame.
+///
+/// Runs right after the providers are sampled, so a pushed value wins over
+/// a provider of the same metric. Several values of a metric pushed in one
+/// frame are shown as their mean, with their range kept for candlestick
+/// curves and statistics (see [`SampledValues::range`]).
+pub fn apply_metric_samples(
+    mut events: EventReader<MetricSample>,
+    mut samples: ResMut<SampledValues>,
+    mut pushed: Local<HashMap<String, PushedValues>>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Sample);
+    samples.clear_ranges();
+    // Entries are reset rather than removed, so known metrics do not allocate
+    for values in pushed.values_mut() {
+        *values = PushedValues {
+            shown: values.shown,
+            ..default()
+        };
+    }
+
+    for sample in events.read() {
+        if !pushed.contains_key(&sample.metric_id) {
+            pushed.insert(sample.metric_id.clone(), default());
+        }
+        let Some(values) = pushed.get_mut(&sample.metric_id) else {
+            continue;
+        };
+        if let Some(timestamp) = sample.timestamp {
+            // Drop values measured before one already shown
+            if values.shown.is_some_and(|shown| shown > timestamp) {
+                continue;
+            }
+            values.shown = Some(timestamp);
+        }
+        values.sum += sample.value;
+        values.min = values.min.min(sample.value);
+        values.max = values.max.max(sample.value);
+        values.count += 1;
+    }
+
+    for (id, values) in pushed.iter() {
+        match values.count {
+            0 => {}
+            1 => samples.set(id, values.sum),
+            count => samples.set_range(id, values.sum / count as f32, values.min, values.max),
+        }
+    }
+}
+
+/// Consecutive missing samples after which a provider is reported as broken
+const PROVIDER_ERROR_LOG_SAMPLES: u32 = 120;
+
+/// A timed history window is resized once its sample count is off by more
+/// than one part in this many
+const HISTORY_RESIZE_TOLERANCE: usize = 20;
+
+/// Color of the graph label of a disabled curve
+const DISABLED_LABEL_COLOR: Color = Color::srgba(0.5, 0.5, 0.5, 0.6);
+
+/// Material assets of the graph and bar grids.
+pub(crate) type HudMaterialAssets<'w> = (
+    ResMut<'w, Assets<MultiLineGraphMaterial>>,
+    ResMut<'w, Assets<BarsMaterial>>,
+);
+
+/// Real-time clock, frame counter, refresh rate and metric statistics read
+/// by [`update_graph_and_bars`], plus its cost accounting.
+///
+/// The HUD runs on real time so virtual time scaling or pausing does not
+/// stretch the history window, the trend intervals or the scale easing.
+type DisplayInputs<'w> = (
+    Res<'w, Time<Real>>,
+    Res<'w, FrameCount>,
+    Res<'w, DisplayRefreshRate>,
+    Res<'w, MetricStats>,
+    ResMut<'w, HudUpdateCost>,
+);
+
+/// System that folds the latest sampled values into the graph history and
+/// the long-term trend.
+///
+/// Runs every frame, including frames the display skips while the HUD is
+/// over its [budget](crate::HudBudgetSettings), so degrading the display
+/// refresh never drops samples. While [`HudPauseState::graph_frozen`] is set,
+/// no samples are added to the graph history.
+pub fn capture_graph_samples(
+    settings: Option<Res<PerfHudSettings>>,
+    (time, frame, pause): (Res<Time<Real>>, Res<FrameCount>, Res<HudPauseState>),
+    samples: Res<SampledValues>,
+    mut history: ResMut<HistoryBuffers>,
+    mut aggregator: ResMut<SampleAggregator>,
+    mut trend: ResMut<TrendHistory>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Capture);
+    let Some(s) = settings else {
+        return;
+    };
+    if !s.enabled || pause.graph_frozen {
+        return;
+    }
+
+    let curve_count = s.graph.curves.len().min(MAX_CURVES);
+
+    // Fold raw frame values into one graph sample per `samples_per_point` frames
+    let mut raw_values = [0.0_f32; MAX_CURVES];
+    for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
+        let id = cfg.metric.id.as_str();
+        raw_values[i] = match s.graph.missing_data {
+            MissingData::Hold => samples.get(id).unwrap_or(0.0),
+            // NaN marks the sample as missing through aggregation and history
+            MissingData::Gap => samples.get_fresh(id).unwrap_or(f32::NAN),
+        };
+    }
+
+    // Counting frames takes exactly one sample per frame
+    let history_window = s.graph.history;
+    let (frames_per_sample, min_interval) = match s.graph.x_axis {
+        GraphXAxis::Time => (s.graph.samples_per_point, history_window.sample_interval()),
+        GraphXAxis::Frames => (1, Duration::ZERO),
+    };
+    // Values pushed several times this frame keep their burst in the sample range
+    for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
+        if let Some((min, max)) = samples.range(cfg.metric.id.as_str()) {
+            if min < max && !raw_values[i].is_nan() {
+                aggregator.widen(i, min, max);
+                trend.widen(i, min, max);
+            }
+        }
+    }
+    let sample =
+        aggregator.accumulate_for(&raw_values, frames_per_sample, min_interval, time.delta());
+    if let Some(sample) = sample {
+        // Process aggregated means through smoothing and quantization pipeline
+        let mut filtered_values = [0.0_f32; MAX_CURVES];
+        for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
+            let raw = sample.mean[i];
+
+            // Step 1: Apply exponential smoothing to reduce noise
+            // Formula: new_value = prev_value + (raw_value - prev_value) * smoothing_factor
+            let smoothing = if s.instant {
+                1.0
+            } else {
+                cfg.smoothing
+                    .unwrap_or(s.graph.curve_defaults.smoothing)
+                    .clamp(0.0, 1.0)
+            };
+
+            // Get the most recent value from history as the previous value,
+            // restarting from the raw value after a gap or a rebind
+            let prev = history
+                .last(i)
+                .filter(|v| !v.is_nan() && history.series_start(i) != Some(history.length as usize))
+                .unwrap_or(raw);
+
+            let smoothed = prev + (raw - prev) * smoothing;
+
+            // Step 2: Apply quantization to create cleaner stepped values
+            // Rounds to the nearest multiple of quantize_step
+            let step = cfg
+                .quantize_step
+                .unwrap_or(s.graph.curve_defaults.quantize_step);
+            filtered_values[i] = if step > 0.0 && !s.instant {
+                (smoothed / step).round() * step
+            } else {
+                smoothed // No quantization
+            };
+        }
+
+        // Update history buffers with new values using circular buffer approach
+        history.push(&filtered_values, &sample.min, &sample.max);
+        history.set_frame(frame.0);
+    }
+    // A timed window is only resized when the sample rate drifts noticeably,
+    // so frame time jitter does not keep trimming the oldest samples
+    let capacity = history_window.capacity(aggregator.sample_period());
+    let current = history.capacity as usize;
+    let drifted = capacity.abs_diff(current) * HISTORY_RESIZE_TOLERANCE > current;
+    if capacity != current && (drifted || !matches!(history_window, GraphHistory::Seconds(_))) {
+        history.set_capacity(capacity);
+    }
+    trend.record(&raw_values, time.delta(), s.graph.trend_interval);
+    history.tick_rebinds(time.delta());
+    trend.history.tick_rebinds(time.delta());
+}
+
+/// System that updates graph and bar displays with current performance data.
+/// The system only runs if both PerfHudSettings and HudHandles are present.
+///
+/// Only draws the history filled by [`capture_graph_samples`], so it can skip
+/// frames (see [`display_refresh_due`]) without losing samples.
+#[allow(clippy::too_many_arguments)]
+pub fn update_graph_and_bars(
+    settings: Option<Res<PerfHudSettings>>,
+    handles: Option<Res<HudHandles>>,
+    localizer: Option<Res<LabelLocalizer>>,
+    (time, frame, refresh_rate, stats, mut cost): DisplayInputs,
+    samples: Res<SampledValues>,
+    (history, trend, aggregator): (
+        Res<HistoryBuffers>,
+        Res<TrendHistory>,
+        Res<SampleAggregator>,
+    ),
+    mut scale_state: ResMut<GraphScaleState>,
+    (mut bar_scale_states, mut bar_colors, mut label_filters): (
+        ResMut<BarScaleStates>,
+        ResMut<BarColorStates>,
+        ResMut<LabelFilterStates>,
+    ),
+    mut group_scale_states: ResMut<GroupScaleStates>,
+    (mut graph_mats, mut bars_mats): HudMaterialAssets,
+    mut label_node_q: Query<&mut Node>,
+    graph_node_q: Query<&ComputedNode>,
+    mut label_text_q: Query<&mut Text>,
+    mut label_color_q: Query<&mut TextColor>,
+    mut last_refresh: Local<Option<Duration>>,
+) {
+    let _cost = cost.time(HudStage::Display);
+    let Some(s) = settings else {
+        return;
+    };
+    if !s.enabled {
+        return;
+    }
+    let Some(h) = handles else {
+        return;
+    };
+
+    let curve_count = s.graph.curves.len().min(MAX_CURVES);
+
+    // Value shown in a metric's labels, filtered against flicker if configured
+    let mut label_value = |id: &str, value: f32| match s.label_filter(id) {
+        Some(filter) => label_filters.update(filter, value, frame.0),
+        None => value,
+    };
+
+    // Ease over the time since the last refresh, which spans several frames
+    // while the display refresh is degraded
+    let now = time.elapsed();
+    let dt = last_refresh.map_or(time.delta(), |last| now.saturating_sub(last));
+    *last_refresh = Some(now);
+    let dt = dt.as_secs_f32();
+
+    // History drawn by the graph; warm-up only applies to the live samples
+    let (history, warmup) = match s.graph.view {
+        GraphView::Live => (&*history, s.graph.warmup_samples),
+        GraphView::Trend => (&trend.history, 0),
+    };
+
+    // Graph-wide Y range, shared by all curves unless groups autoscale on their own
+    let per_group = s.graph.autoscale_per_group;
+    let shared_target = target_scale_range(&s.graph, history, warmup, s.instant, |cfg| {
+        !per_group || cfg.group.is_none()
+    });
+    let (current_min, current_max) =
+        smooth_scale_range(&mut scale_state, shared_target, &s.graph, s.instant, dt);
+
+    // Y range of each curve, as drawn by the graph shader
+    let mut curve_scales = [Vec4::new(current_min, current_max, 0.0, 0.0); MAX_CURVES];
+    if per_group {
+        for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
+            let Some(group) = cfg.group.as_deref() else {
+                continue;
+            };
+            // Each group's range is updated once, then reused by its other curves
+            let (min, max) = if let Some(first) = s.graph.curves[..i]
+                .iter()
+                .position(|c| c.group.as_deref() == Some(group))
+            {
+                (curve_scales[first].x, curve_scales[first].y)
+            } else {
+                let target = target_scale_range(&s.graph, history, warmup, s.instant, |c| {
+                    c.group.as_deref() == Some(group)
+                });
+                smooth_scale_range(
+                    group_scale_states.get_or_create(group),
+                    target,
+                    &s.graph,
+                    s.instant,
+                    dt,
+                )
+            };
+            curve_scales[i] = Vec4::new(min, max, 0.0, 0.0);
+        }
+    }
+    // Rebound curves: where the current series starts and how visible the previous one is
+    for (i, scale) in curve_scales.iter_mut().enumerate().take(curve_count) {
+        if let Some(start) = history.series_start(i) {
+            scale.z = start as f32;
+            scale.w = history.previous_series_opacity(i);
+        }
+    }
+
+    // A unit all curves share is shown once instead of in every label
+    let shared_unit = s.graph.shared_unit();
+    if let Some(mut tx) = h
+        .graph_unit_label
+        .and_then(|entity| label_text_q.get_mut(entity).ok())
+    {
+        set_text_fmt(&mut tx, format_args!("{}", shared_unit.unwrap_or("")));
+    }
+
+    // Update graph labels dynamically based on configured curves
+    if s.graph.enabled && !h.graph_labels.is_empty() {
+        for label_handle in &h.graph_labels {
+            let Some(curve) = s
+                .graph
+                .curves
+                .iter()
+                .find(|c| c.metric.id == label_handle.metric_id)
+            else {
+                continue;
+            };
+
+            // Labels of hidden groups collapse so the remaining ones stay
+            // packed; disabled curves keep a greyed out label to click
+            let display = if !curve.enabled || s.graph.is_curve_visible(curve) {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if let Ok(mut node) = label_node_q.get_mut(label_handle.entity) {
+                if node.display != display {
+                    node.display = display;
+                }
+            }
+
+            let definition = &curve.metric;
+            let precision = curve.precision.unwrap_or(definition.precision) as usize;
+            let unit = match shared_unit {
+                Some(_) => "",
+                None => definition.unit.as_deref().unwrap_or(""),
+            };
+
+            let value = label_value(
+                &curve.metric.id,
+                samples.get(curve.metric.id.as_str()).unwrap_or(0.0),
+            );
+            let separator = if unit.is_empty() { "" } else { " " };
+
+            if let Ok(mut tx) = label_text_q.get_mut(label_handle.entity) {
+                set_text_fmt(
+                    &mut tx,
+                    format_args!("{value:.precision$}{separator}{unit}"),
+                );
+            }
+            if let Ok(mut col) = label_color_q.get_mut(label_handle.entity) {
+                *col = TextColor(if curve.enabled {
+                    metric_draw_color(&s, &curve.metric, &samples, refresh_rate.hz)
+                } else {
+                    DISABLED_LABEL_COLOR
+                });
+            }
+        }
+    }
+
+    // Frame axis labels span the live samples; the trend view covers time
+    if let Some([oldest, newest]) = h.frame_axis_labels {
+        let len = history.length as usize;
+        let span = (s.graph.view == GraphView::Live && len > 0)
+            .then(|| (history.frames[0], history.frames[len - 1]));
+        for (entity, frame) in [(oldest, span.map(|s| s.0)), (newest, span.map(|s| s.1))] {
+            if let Ok(mut tx) = label_text_q.get_mut(entity) {
+                match frame {
+                    Some(frame) => set_text_fmt(&mut tx, format_args!("frame {frame}")),
+                    None => set_text_fmt(&mut tx, format_args!("")),
+                }
+            }
+        }
+    }
+
+    // Time axis labels span the samples across the graph's width
+    if let Some([oldest, newest]) = h.time_axis_labels {
+        let period = match s.graph.view {
+            GraphView::Live => aggregator.sample_period(),
+            GraphView::Trend => Some(s.graph.trend_interval),
+        };
+        let span =
+            period.map(|period| period.as_secs_f32() * history.length.saturating_sub(1) as f32);
+        if let Ok(mut tx) = label_text_q.get_mut(oldest) {
+            match span {
+                Some(span) if span >= 120.0 => {
+                    set_text_fmt(&mut tx, format_args!("-{:.0}m", span / 60.0))
+                }
+                Some(span) if span >= 10.0 => set_text_fmt(&mut tx, format_args!("-{span:.0}s")),
+                Some(span) => set_text_fmt(&mut tx, format_args!("-{span:.1}s")),
+                None => set_text_fmt(&mut tx, format_args!("")),
+            }
+        }
+        if let Ok(mut tx) = label_text_q.get_mut(newest) {
+            set_text_fmt(&mut tx, format_args!("now"));
+        }
+    }
+
+    // Y-axis tick values follow the shared (autoscaled) range
+    if let Some(last) = h.y_tick_labels.len().checked_sub(1) {
+        let step = (current_max - current_min) / last.max(1) as f32;
+        let tick_value = |tick: usize| s.graph.y_scale.from_axis(current_min + step * tick as f32);
+        // A log axis has its closest ticks at the bottom
+        let precision = tick_precision((tick_value(1) - tick_value(0)).abs());
+        for (tick, &entity) in h.y_tick_labels.iter().enumerate() {
+            if let Ok(mut tx) = label_text_q.get_mut(entity) {
+                let value = tick_value(tick);
+                set_text_fmt(&mut tx, format_args!("{value:.precision$}"));
+            }
+        }
+    }
+
+    // Update graph material (when enabled)
+    if s.graph.enabled {
+        if let Some(handle) = &h.graph_material {
+            if let Some(mat) = graph_mats.get_mut(handle) {
+                mat.params.length = history.length;
+                mat.params.min_y = current_min;
+                mat.params.max_y = current_max;
+                // Pixel sizes follow the laid-out node, so they hold across resizes
+                let node_size = h
+                    .graph_entity
+                    .and_then(|e| graph_node_q.get(e).ok())
+                    .map(|node| node.size() * node.inverse_scale_factor())
+                    .filter(|size| size.x > 0.0 && size.y > 0.0)
+                    .unwrap_or(s.graph.size);
+                mat.params.thickness = s.graph.line_thickness_uv(node_size.y);
+                mat.params.quality = s.graph.quality as u32;
+                mat.params.draw_order = s.graph.packed_draw_order();
+                mat.params.bg_color = s.graph.bg_color.to_linear().to_vec4();
+                mat.params.border_color = s.graph.border.color.to_linear().to_vec4();
+                mat.params.border_thickness = s.graph.border.thickness; // pixels
+                mat.params.border_thickness_uv_x =
+                    (s.graph.border.thickness / node_size.x).max(0.0001);
+                mat.params.border_thickness_uv_y =
+                    (s.graph.border.thickness / node_size.y).max(0.0001);
+                mat.params.border_left = if s.graph.border.left { 1 } else { 0 };
+                mat.params.border_bottom = if s.graph.border.bottom { 1 } else { 0 };
+                mat.params.border_right = if s.graph.border.right { 1 } else { 0 };
+                mat.params.border_top = if s.graph.border.top { 1 } else { 0 };
+                mat.params.curve_count = curve_count as u32;
+                mat.params.scales = curve_scales;
+                // The shader draws axis positions, see `GraphYScale`
+                let axis = |v: f32| s.graph.y_scale.to_axis(v);
+                // Sync curve colors every frame to allow hot updates;
+                // curves of hidden groups are drawn fully transparent and
+                // curves over budget take the warning or critical color
+                for (i, c) in s.graph.curves.iter().take(curve_count).enumerate() {
+                    mat.params.colors[i] = if s.graph.is_curve_visible(c) {
+                        metric_draw_color(&s, &c.metric, &samples, refresh_rate.hz)
+                            .to_linear()
+                            .to_vec4()
+                    } else {
+                        Vec4::ZERO
+                    };
+                    let budget = s
+                        .budget_thresholds(&c.metric.id, refresh_rate.hz)
+                        .filter(|_| s.graph.show_budget_lines);
+                    mat.params.budget_lines[i] = budget.map_or(Vec4::ZERO, |b| {
+                        Vec4::new(axis(b.warning), axis(b.critical), 1.0, 0.0)
+                    });
+                    let shading = c.shading.or(s.graph.curve_defaults.shading);
+                    mat.params.shading[i] = shading.map_or(Vec4::ZERO, |shading| {
+                        let direction = if shading.below { -1.0 } else { 1.0 };
+                        Vec4::new(axis(shading.threshold), direction, 1.0, 0.0)
+                    });
+                    mat.params.shading_colors[i] =
+                        shading.map_or(Vec4::ZERO, |shading| shading.color.to_linear().to_vec4());
+                }
+                for i in curve_count..MAX_CURVES {
+                    mat.params.colors[i] = Vec4::ZERO;
+                    mat.params.budget_lines[i] = Vec4::ZERO;
+                    mat.params.shading[i] = Vec4::ZERO;
+                }
+                mat.params.budget_warning_color = s.budget_colors.warning.to_linear().to_vec4();
+                mat.params.budget_critical_color = s.budget_colors.critical.to_linear().to_vec4();
+                // Write values (pack into vec4); missing samples are uploaded as 0
+                // with their bit set in the missing mask
+                let len = MAX_SAMPLES.min(history.length as usize);
+                let packed_len = len.div_ceil(4); // round up
+                let sample_at = |i: usize, k: usize| {
+                    let v = if k < len { history.values[i][k] } else { 0.0 };
+                    if v.is_nan() {
+                        0.0
+                    } else {
+                        axis(v)
+                    }
+                };
+                for i in 0..MAX_CURVES {
+                    for j in 0..SAMPLES_VEC4 {
+                        let base = j * 4;
+                        mat.params.values[i][j] = Vec4::new(
+                            sample_at(i, base),
+                            sample_at(i, base + 1),
+                            sample_at(i, base + 2),
+                            sample_at(i, base + 3),
+                        );
+                    }
+                    let mut missing = [[0_u32; 4]; MISSING_VEC4];
+                    for (k, v) in history.values[i][..len].iter().enumerate() {
+                        if v.is_nan() {
+                            missing[k / 128][(k / 32) % 4] |= 1 << (k % 32);
+                        }
+                    }
+                    mat.params.missing[i] = missing.map(UVec4::from_array);
+                    // Optional: zero unused segments packed_len..SAMPLES_VEC4
+                    for j in packed_len..SAMPLES_VEC4 {
+                        mat.params.values[i][j] = Vec4::ZERO;
+                    }
+                }
+                // Write candlestick ranges, normalized against each curve's displayed Y range
+                let mut candlestick_mask = 0_u32;
+                for (i, c) in s.graph.curves.iter().take(curve_count).enumerate() {
+                    if !c.candlestick {
+                        continue;
+                    }
+                    candlestick_mask |= 1 << i;
+                    let sample_ranges = history.mins[i][..len]
+                        .iter()
+                        .zip(&history.maxs[i][..len])
+                        .map(|(&min, &max)| (axis(min), axis(max)));
+                    mat.params.ranges[i] =
+                        pack_curve_ranges(sample_ranges, curve_scales[i].x, curve_scales[i].y);
+                }
+                mat.params.candlestick_mask = candlestick_mask;
+                mat.params.grid_color = s.graph.grid_color.to_linear().to_vec4();
+                mat.params.grid_lines = s.graph.y_ticks.max(2);
+                mat.params.curve_styles =
+                    MultiLineGraphParams::pack_curve_styles(s.graph.curves.iter().map(|c| c.style));
+                mat.params.line_smoothing = MultiLineGraphParams::pack_line_smoothing(
+                    s.graph.curves.iter().map(|c| c.line_smoothing),
+                );
+                // Colors set at init; update here if config changed
+            }
+        }
+    }
+
+    // Update bars (when enabled)
+    if s.bars.enabled {
+        // Start from the current grid parameters; only the fill values and
+        // colors change per frame
+        let mut bars_params = h
+            .bars_material
+            .as_ref()
+            .and_then(|handle| bars_mats.get(handle))
+            .map(|mat| mat.params.clone());
+
+        for (i, cfg) in s.bars.bars.iter().enumerate() {
+            if i >= h.bar_labels.len() {
+                break;
+            }
+            let val = samples.get(cfg.metric.id.as_str()).unwrap_or(0.0);
+
+            // Get or create the scale state for this bar
+            let scale_state = bar_scale_states.get_or_create(&cfg.metric.id);
+
+            // Add current value to the scale state's history
+            scale_state.add_sample(val);
+
+            // Instant mode keeps auto ranges from lagging behind the data
+            let scale_mode = match cfg.scale_mode {
+                BarScaleMode::Auto {
+                    min_span,
+                    margin_frac,
+                    ..
+                } if s.instant => BarScaleMode::Auto {
+                    smoothing: 0.0,
+                    min_span,
+                    margin_frac,
+                },
+                ref mode => mode.clone(),
+            };
+
+            // Calculate the dynamic range based on the bar's scale mode
+            let (range_min, range_max) = scale_state.calculate_range(
+                &scale_mode,
+                cfg.min_value,
+                cfg.max_value,
+                cfg.min_limit,
+                cfg.max_limit,
+            );
+
+            // Normalize the value using the calculated range
+            let normalize = |v: f32| {
+                if range_max > range_min {
+                    ((v - range_min) / (range_max - range_min)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            };
+            let norm = normalize(val);
+            // Center-filled bars grow from where zero sits in the range
+            let origin = (cfg.fill == BarFill::Center).then(|| normalize(0.0));
+            // Paired bars show the rolling average next to the value
+            let average = cfg.rolling_average.map(|window| {
+                stats
+                    .window(&cfg.metric.id, window)
+                    .and_then(StatsWindow::mean)
+                    .unwrap_or(val)
+            });
+            // Trend arrows flag a value heading towards the end of its range
+            let trend = cfg.trend.as_ref().map_or("", |trend| {
+                stats
+                    .window(&cfg.metric.id, trend.window)
+                    .and_then(StatsWindow::slope)
+                    .map_or(trend.steady.as_str(), |slope| {
+                        trend.symbol(slope, range_max - range_min)
+                    })
+            });
+            let trend_gap = if trend.is_empty() { "" } else { " " };
+
+            if let Some(params) = bars_params.as_mut() {
+                params.set_value(i, norm);
+                params.set_average(i, average.map(normalize));
+                params.set_origin(i, origin);
+                let color = match s.bars.color_transition.as_ref().filter(|_| !s.instant) {
+                    Some(transition) => {
+                        let id = cfg.metric.id.as_str();
+                        let level =
+                            match (samples.get(id), s.budget_thresholds(id, refresh_rate.hz)) {
+                                (Some(value), Some(thresholds)) => thresholds
+                                    .level_with_hysteresis(
+                                        value,
+                                        bar_colors.level(id),
+                                        transition.hysteresis,
+                                    ),
+                                _ => BudgetLevel::Ok,
+                            };
+                        let target = s.level_color(&cfg.metric, level);
+                        bar_colors.update(id, level, target, transition, time.elapsed())
+                    }
+                    None => metric_draw_color(&s, &cfg.metric, &samples, refresh_rate.hz),
+                };
+                params.colors[i] = color.to_linear().to_vec4();
+                let row = h.bar_rows.get(i).copied().unwrap_or(0);
+                params.bg_colors[i] = s.bars.bar_bg_color(cfg, row).to_linear().to_vec4();
+            }
+
+            // Update bar labels with current values and formatting
+            if let Some(&label_entity) = h.bar_labels.get(i) {
+                let definition = &cfg.metric;
+                let base_label = metric_label(definition, localizer.as_deref());
+                let precision = cfg.precision.unwrap_or(definition.precision) as usize;
+                let unit = definition.unit.as_deref().unwrap_or("");
+                let val = label_value(&definition.id, val);
+
+                let show_value = cfg.show_value.unwrap_or(s.bars.show_value_default);
+
+                if let Ok(mut tx) = label_text_q.get_mut(label_entity) {
+                    if let Some(avg) = average.filter(|_| show_value) {
+                        set_text_fmt(
+                            &mut tx,
+                            format_args!(
+                                "{base_label} {val:.precision$}{unit} (avg {avg:.precision$}{unit}){trend_gap}{trend}"
+                            ),
+                        );
+                    } else if show_value {
+                        set_text_fmt(
+                            &mut tx,
+                            format_args!("{base_label} {val:.precision$}{unit}{trend_gap}{trend}"),
+                        );
+                    } else {
+                        set_text_fmt(&mut tx, format_args!("{base_label}{trend_gap}{trend}"));
+                    }
+                }
+                if let Ok(mut col) = label_color_q.get_mut(label_entity) {
+                    *col = TextColor(Color::WHITE);
+                }
+            }
+        }
+
+        // Upload the whole grid at once, and only when something changed
+        if let (Some(handle), Some(params)) = (&h.bars_material, bars_params) {
+            write_bars_params(&mut bars_mats, handle, params);
+        }
+    }
+
+    // Update per-core CPU usage strip (when enabled), one upload per group
+    if s.cpu_cores.enabled {
+        for core_bars in &h.cpu_core_bars {
+            let Some(mut params) = bars_mats
+                .get(&core_bars.material)
+                .map(|mat| mat.params.clone())
+            else {
+                continue;
+            };
+            for (slot, metric_id) in core_bars.metric_ids.iter().enumerate() {
+                let usage = samples.get(metric_id).unwrap_or(0.0);
+                params.set_value(slot, (usage / 100.0).clamp(0.0, 1.0));
+            }
+            write_bars_params(&mut bars_mats, &core_bars.material, params);
+        }
+    }
+}
+
+/// System adding the curves of [`GraphSettings::auto_curves`].
+///
+/// Runs before the HUD is spawned; with
+/// [`AutoCurves::follow_registrations`](crate::AutoCurves), metrics registered
+/// later get curves as well and the HUD is rebuilt to show them.
+pub fn add_auto_curves(
+    settings: Option<ResMut<PerfHudSettings>>,
+    providers: Res<MetricProviders>,
+    handles: Option<Res<HudHandles>>,
+    mut hud_commands: EventWriter<PerfHudCommand>,
+) {
+    let Some(mut settings) = settings else {
+        return;
+    };
+    let Some(auto) = settings.graph.auto_curves else {
+        return;
+    };
+    let spawned = handles.is_some();
+    if (spawned && !auto.follow_registrations) || !(providers.is_changed() || settings.is_changed())
+    {
+        return;
+    }
+    // Settings only count as changed when a curve was actually added
+    let added = settings
+        .bypass_change_detection()
+        .graph
+        .add_auto_curves(providers.ids());
+    if added {
+        settings.set_changed();
+        if spawned {
+            hud_commands.write(PerfHudCommand::Rebuild);
+        }
+    }
+}
+
+/// System coloring the traffic light by its metric's budget level.
+pub fn update_traffic_light(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
     samples: Res<SampledValues>,
-    mut history: ResMut<HistoryBuffers>,
-    mut scale_state: ResMut<GraphScaleState>,
-    mut bar_scale_states: ResMut<BarScaleStates>,
+    refresh_rate: Res<DisplayRefreshRate>,
+    time: Res<Time<Real>>,
+    mut backgrounds: Query<&mut BackgroundColor>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::TrafficLight);
+    let Some(mut background) = handles
+        .traffic_light
+        .and_then(|entity| backgrounds.get_mut(entity).ok())
+    else {
+        return;
+    };
+    let light = &settings.traffic_light;
+    let level = match (
+        samples.get(&light.metric_id),
+        settings.budget_thresholds(&light.metric_id, refresh_rate.hz),
+    ) {
+        (Some(value), Some(thresholds)) => thresholds.level(value),
+        _ => BudgetLevel::Ok,
+    };
+    let color = match level {
+        BudgetLevel::Ok => light.ok_color,
+        BudgetLevel::Warning => settings.budget_colors.warning,
+        BudgetLevel::Critical => {
+            let period = light.blink.map_or(0.0, |blink| blink.as_secs_f32());
+            // Dark for the second half of each blink period
+            let dark = period > 0.0 && time.elapsed_secs() % period >= period * 0.5;
+            if dark {
+                Color::NONE
+            } else {
+                settings.budget_colors.critical
+            }
+        }
+    };
+    background.set_if_neq(BackgroundColor(color));
+}
+
+/// System updating the rows of the percentile statistics panel.
+///
+/// Statistics cover the samples the graph currently shows, live or trend.
+pub fn update_stats_panel(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    localizer: Option<Res<LabelLocalizer>>,
+    history: Res<HistoryBuffers>,
+    trend: Res<TrendHistory>,
+    mut label_text_q: Query<&mut Text>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::StatsPanel);
+    let history = match settings.graph.view {
+        GraphView::Live => &*history,
+        GraphView::Trend => &trend.history,
+    };
+    for label in &handles.stats_labels {
+        let Ok(mut tx) = label_text_q.get_mut(label.entity) else {
+            continue;
+        };
+        let Some((curve, cfg)) = settings
+            .graph
+            .curves
+            .iter()
+            .take(MAX_CURVES)
+            .enumerate()
+            .find(|(_, cfg)| cfg.metric.id == label.metric_id)
+        else {
+            set_text_fmt(
+                &mut tx,
+                format_args!("{}: not on the graph", label.metric_id),
+            );
+            continue;
+        };
+        let definition = &cfg.metric;
+        let name = metric_label(definition, localizer.as_deref());
+        let precision = cfg.precision.unwrap_or(definition.precision) as usize;
+        let unit = definition.unit.as_deref().unwrap_or("");
+        match history.percentiles(curve) {
+            Some(p) => set_text_fmt(
+                &mut tx,
+                format_args!(
+                    "{name} p50 {:.precision$} p95 {:.precision$} p99 {:.precision$} max {:.precision$}{unit}",
+                    p.p50, p.p95, p.p99, p.max
+                ),
+            ),
+            None => set_text_fmt(&mut tx, format_args!("{name} -")),
+        }
+    }
+}
+
+/// System showing the exact value of each visible curve at the sample under
+/// the pointer while it hovers the graph, see [`HudInteraction::tooltip`].
+///
+/// The hovered sample follows the zoomed range of [`GraphZoom`], and its
+/// column is highlighted by the graph shader.
+#[allow(clippy::too_many_arguments)]
+pub fn update_graph_tooltip(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    localizer: Option<Res<LabelLocalizer>>,
+    (live, trend, zoom): (Res<HistoryBuffers>, Res<TrendHistory>, Res<GraphZoom>),
+    windows: Query<&Window, With<PrimaryWindow>>,
+    areas: Query<(&ComputedNode, &GlobalTransform)>,
     mut graph_mats: ResMut<Assets<MultiLineGraphMaterial>>,
-    mut bar_mats: ResMut<Assets<BarMaterial>>,
-    _label_node_q: Query<&mut Node>,
+    mut tooltip_q: Query<(&mut Text, &mut Node, &mut Visibility)>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Tooltip);
+    let (Some(tooltip), Some(handle)) = (handles.graph_tooltip, &handles.graph_material) else {
+        return;
+    };
+    let history = match settings.graph.view {
+        GraphView::Live => &*live,
+        GraphView::Trend => &trend.history,
+    };
+
+    // Samples across the graph, clamped like the shader does
+    let length = history.length;
+    let (start, len) = zoom.view.map_or((0, length), |(start, len)| {
+        let len = len.clamp(2, length.max(2));
+        (start.min(length.saturating_sub(len)), len)
+    });
+    // Hovered sample and its normalized position across the graph
+    let hovered = graph_cursor(&windows, handles.graph_entity, &areas)
+        .filter(|(cursor, rect)| {
+            settings.enabled && settings.graph.enabled && length >= 2 && rect.contains(*cursor)
+        })
+        .map(|(cursor, rect)| {
+            let x = ((cursor.x - rect.min.x) / rect.width().max(1.0)).clamp(0.0, 1.0);
+            let offset = (x * (len - 1) as f32).round() as u32;
+            (start + offset, offset as f32 / (len - 1) as f32)
+        });
+
+    if let Ok((mut tx, mut node, mut visibility)) = tooltip_q.get_mut(tooltip) {
+        let shown = if hovered.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(shown);
+        if let Some((sample, x)) = hovered {
+            let sample = sample as usize;
+            let mut text = String::new();
+            if history.frames[sample] != 0 {
+                let _ = write!(text, "frame {}", history.frames[sample]);
+            }
+            for (i, cfg) in settings.graph.curves.iter().take(MAX_CURVES).enumerate() {
+                if !settings.graph.is_curve_visible(cfg) {
+                    continue;
+                }
+                let definition = &cfg.metric;
+                let name = metric_label(definition, localizer.as_deref());
+                let precision = cfg.precision.unwrap_or(definition.precision) as usize;
+                let unit = definition.unit.as_deref().unwrap_or("");
+                let separator = if unit.is_empty() { "" } else { " " };
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                let value = history.values[i][sample];
+                if value.is_nan() {
+                    let _ = write!(text, "{name}: -");
+                } else {
+                    let _ = write!(text, "{name}: {value:.precision$}{separator}{unit}");
+                }
+            }
+            set_text_fmt(&mut tx, format_args!("{text}"));
+
+            // Keep the box inside the graph, on the side of the cursor with more room
+            let (left, right) = if x <= 0.5 {
+                (Val::Percent(x * 100.0), Val::Auto)
+            } else {
+                (Val::Auto, Val::Percent((1.0 - x) * 100.0))
+            };
+            if node.left != left || node.right != right {
+                node.left = left;
+                node.right = right;
+            }
+        }
+    }
+
+    // Only touch the material when the highlighted column moves
+    let cursor_x = hovered.map_or(-1.0, |(_, x)| x);
+    if graph_mats
+        .get(handle)
+        .is_some_and(|mat| mat.params.cursor_x != cursor_x)
+    {
+        if let Some(mat) = graph_mats.get_mut(handle) {
+            mat.params.cursor_x = cursor_x;
+        }
+    }
+}
+
+/// Switch the HUD's primary frame metric between `fps` and `frame_time_ms`,
+/// see [`PerfHudCommand::ToggleFrameUnit`].
+///
+/// The primary metric is whichever of the two a curve, bar or statistics row
+/// shows first. Every entry showing it switches to the other metric, with its
+/// recorded samples, shading threshold and explicit budget converted; curves
+/// already showing the other metric are hidden meanwhile. The next toggle
+/// restores exactly these entries, as far as they were not changed since.
+fn toggle_frame_unit(
+    mut settings: ResMut<PerfHudSettings>,
+    mut handles: Option<ResMut<HudHandles>>,
+    mut history: ResMut<HistoryBuffers>,
+    mut aggregator: ResMut<SampleAggregator>,
+    mut trend: ResMut<TrendHistory>,
+    mut toggle: ResMut<FrameUnitToggle>,
+) {
+    let s = &mut *settings;
+    // A switch none of whose entries is left (e.g. the settings were
+    // replaced since) is dropped, and the current settings switched instead
+    let restored = toggle
+        .switched
+        .take()
+        .and_then(|switched| restore_frame_unit(s, switched));
+    let curves: Vec<usize> = match restored {
+        Some(curves) => curves,
+        None => {
+            let Some(switched) = switch_frame_unit(s) else {
+                return;
+            };
+            let curves = switched.curves.iter().map(|&(i, _)| i).collect();
+            toggle.switched = Some(switched);
+            curves
+        }
+    };
+
+    for i in curves {
+        history.convert_frame_unit(i);
+        aggregator.discard(i);
+        trend.convert_frame_unit(i);
+    }
+    // Label rows are spawned one per curve and statistics metric, in order
+    if let Some(handles) = handles.as_mut() {
+        for (label, curve) in handles.graph_labels.iter_mut().zip(&s.graph.curves) {
+            label.metric_id.clone_from(&curve.metric.id);
+        }
+        for (label, id) in handles.stats_labels.iter_mut().zip(&s.stats_panel.metrics) {
+            label.metric_id.clone_from(id);
+        }
+    }
+}
+
+/// The other one of the `fps` and `frame_time_ms` frame metrics.
+fn frame_unit_counterpart_id(id: &str) -> Option<&'static str> {
+    match id {
+        "fps" => Some("frame_time_ms"),
+        "frame_time_ms" => Some("fps"),
+        _ => None,
+    }
+}
+
+/// Restore the entries of `s` a frame unit toggle switched, returning the
+/// restored curves (None if no entry still shows the switched metric).
+///
+/// Entries are checked before being restored: one that no longer shows the
+/// metric it was switched to was changed since, and is left alone.
+fn restore_frame_unit(s: &mut PerfHudSettings, switched: FrameUnitSwitch) -> Option<Vec<usize>> {
+    let other = frame_unit_counterpart_id(&switched.primary)?;
+    let mut restored = false;
+    let mut curves = Vec::new();
+    for (i, curve) in switched.curves {
+        if let Some(c) = s.graph.curves.get_mut(i).filter(|c| c.metric.id == other) {
+            *c = curve;
+            curves.push(i);
+            restored = true;
+        }
+    }
+    for i in switched.hidden_curves {
+        let hidden = |c: &&mut CurveConfig| c.metric.id == other && !c.enabled;
+        if let Some(c) = s.graph.curves.get_mut(i).filter(hidden) {
+            c.enabled = true;
+            restored = true;
+        }
+    }
+    for (i, bar) in switched.bars {
+        if let Some(b) = s.bars.bars.get_mut(i).filter(|b| b.metric.id == other) {
+            *b = bar;
+            restored = true;
+        }
+    }
+    for i in switched.stats_rows {
+        if let Some(id) = s.stats_panel.metrics.get_mut(i).filter(|id| *id == other) {
+            id.clone_from(&switched.primary);
+            restored = true;
+        }
+    }
+    for (i, budget) in switched.budgets {
+        if let Some(b) = s.metric_budgets.get_mut(i).filter(|b| b.metric_id == other) {
+            *b = budget;
+            restored = true;
+        }
+    }
+    restored.then_some(curves)
+}
+
+/// Switch every entry of `s` showing the primary frame metric to the other
+/// one, returning what was switched (None if neither metric is shown).
+fn switch_frame_unit(s: &mut PerfHudSettings) -> Option<FrameUnitSwitch> {
+    let primary = s
+        .graph
+        .curves
+        .iter()
+        .map(|curve| curve.metric.id.as_str())
+        .chain(s.bars.bars.iter().map(|bar| bar.metric.id.as_str()))
+        .chain(s.stats_panel.metrics.iter().map(String::as_str))
+        .find(|id| frame_unit_counterpart_id(id).is_some())?
+        .to_owned();
+    let other = frame_unit_counterpart_id(&primary)?;
+    // FPS and milliseconds per frame are reciprocal; a threshold of 0 FPS
+    // has no end in milliseconds and is left as is
+    let invert = |value: f32| if value > 0.0 { 1000.0 / value } else { value };
+
+    let mut switched = FrameUnitSwitch {
+        primary: primary.clone(),
+        curves: Vec::new(),
+        hidden_curves: Vec::new(),
+        bars: Vec::new(),
+        stats_rows: Vec::new(),
+        budgets: Vec::new(),
+    };
+    let default_shading = s.graph.curve_defaults.shading;
+    for i in 0..s.graph.curves.len() {
+        let metric = &s.graph.curves[i].metric;
+        if metric.id == other {
+            if s.graph.curves[i].enabled {
+                s.graph.curves[i].enabled = false;
+                switched.hidden_curves.push(i);
+            }
+            continue;
+        }
+        if metric.id != primary {
+            continue;
+        }
+        let Some(counterpart) = s.frame_unit_counterpart(metric) else {
+            continue;
+        };
+        let curve = &mut s.graph.curves[i];
+        switched.curves.push((i, curve.clone()));
+        curve.metric = counterpart;
+        curve.shading = curve
+            .shading
+            .or(default_shading)
+            .map(|shading| ThresholdShading {
+                threshold: invert(shading.threshold),
+                below: !shading.below,
+                ..shading
+            });
+    }
+    for i in 0..s.bars.bars.len() {
+        if s.bars.bars[i].metric.id != primary {
+            continue;
+        }
+        let Some(counterpart) = s.frame_unit_counterpart(&s.bars.bars[i].metric) else {
+            continue;
+        };
+        let bar = &mut s.bars.bars[i];
+        switched.bars.push((i, bar.clone()));
+        bar.metric = counterpart;
+        // A range reaching 0 FPS has no end in milliseconds, and is left as is
+        if bar.min_value > 0.0 && bar.max_value > 0.0 {
+            (bar.min_value, bar.max_value) = (1000.0 / bar.max_value, 1000.0 / bar.min_value);
+        }
+    }
+    for (i, id) in s.stats_panel.metrics.iter_mut().enumerate() {
+        if *id == primary {
+            switched.stats_rows.push(i);
+            *id = other.to_owned();
+        }
+    }
+    for (i, budget) in s.metric_budgets.iter_mut().enumerate() {
+        if budget.metric_id != primary {
+            continue;
+        }
+        switched.budgets.push((i, budget.clone()));
+        budget.metric_id = other.to_owned();
+        budget.warning = invert(budget.warning);
+        budget.critical = invert(budget.critical);
+        budget.lower_is_worse = !budget.lower_is_worse;
+    }
+    Some(switched)
+}
+
+/// System listing the latest slow frames, newest first.
+pub fn update_slow_frame_panel(
+    handles: Res<HudHandles>,
+    log: Res<SlowFrameLog>,
     mut label_text_q: Query<&mut Text>,
-    mut label_color_q: Query<&mut TextColor>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::SlowFramePanel);
+    let mut entries = log.iter();
+    for (row, &entity) in handles.slow_frame_labels.iter().enumerate() {
+        let Ok(mut tx) = label_text_q.get_mut(entity) else {
+            continue;
+        };
+        match entries.next() {
+            Some(entry) => set_text_fmt(&mut tx, format_args!("{entry}")),
+            None if row == 0 => set_text_fmt(&mut tx, format_args!("no slow frames")),
+            None => set_text_fmt(&mut tx, format_args!("")),
+        }
+    }
+}
+
+/// System tracking the refresh rate of the display the primary window is on.
+///
+/// Runs when a window is created, moved or changes scale factor, which covers
+/// moving the window to another monitor.
+pub fn update_display_refresh_rate(
+    winit_windows: Option<NonSend<WinitWindows>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut refresh_rate: ResMut<DisplayRefreshRate>,
+) {
+    let hz = winit_windows
+        .zip(primary_window.single().ok())
+        .and_then(|(windows, entity)| {
+            let monitor = windows.get_window(entity)?.current_monitor()?;
+            Some(monitor.refresh_rate_millihertz()? as f32 / 1000.0)
+        });
+    refresh_rate.set_if_neq(DisplayRefreshRate { hz });
+}
+
+/// System that captures a screenshot when a watched metric turns critical.
+///
+/// Driven by the same [`MetricBudgetTransition`]s as the alerts, so a metric
+/// is captured once each time it passes the critical threshold of its budget.
+/// At most one screenshot is taken per
+/// [`ScreenshotSettings::cooldown`](crate::ScreenshotSettings) across all
+/// metrics. With the `serde` feature, a [`PerfHudSnapshot`](crate::PerfHudSnapshot)
+/// is written next to the image.
+pub fn capture_threshold_screenshots(
+    mut commands: Commands,
+    settings: Res<PerfHudSettings>,
+    mut transitions: EventReader<MetricBudgetTransition>,
+    time: Res<Time<Real>>,
+    mut events: EventWriter<PerfHudScreenshot>,
+    mut last_capture: Local<Option<f64>>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Screenshots);
+    let screenshots = &settings.screenshots;
+    // Transitions are consumed even while disabled, so old ones never trigger
+    let Some(transition) = transitions
+        .read()
+        .filter(|transition| transition.level == BudgetLevel::Critical)
+        .find(|transition| screenshots.watches(&transition.metric_id))
+    else {
+        return;
+    };
+    if !screenshots.enabled {
+        return;
+    }
+    let now = time.elapsed_secs_f64();
+    if last_capture.is_some_and(|last| now - last < screenshots.cooldown.as_secs_f64()) {
+        return;
+    }
+    *last_capture = Some(now);
+
+    if let Err(err) = std::fs::create_dir_all(&screenshots.directory) {
+        warn!(
+            "perf HUD: cannot create screenshot directory {:?}: {err}",
+            screenshots.directory
+        );
+        return;
+    }
+    // Metric IDs contain path separators, keep file names flat
+    let metric_name: String = transition
+        .metric_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = screenshots
+        .directory
+        .join(format!("{metric_name}_{}.png", time.elapsed().as_millis()));
+
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path.clone()));
+    #[cfg(feature = "serde")]
+    let snapshot = {
+        let snapshot_path = path.with_extension("ron");
+        let written = snapshot_path.clone();
+        commands.queue(move |world: &mut World| {
+            save_snapshot(PerfHudSnapshot::capture(world), written);
+        });
+        Some(snapshot_path)
+    };
+    #[cfg(not(feature = "serde"))]
+    let snapshot = None;
+    events.write(PerfHudScreenshot {
+        metric_id: transition.metric_id.clone(),
+        value: transition.value,
+        threshold: transition.threshold,
+        path,
+        snapshot,
+    });
+}
+
+/// System that synchronizes HUD visibility with the latest settings.
+///
+/// Runs when [`PerfHudSettings`] changes, toggling visibility of the root
+/// container, graph row and bars section without requiring entity rebuild.
+pub fn sync_hud_visibility(
+    settings: Option<Res<PerfHudSettings>>,
+    handles: Option<Res<HudHandles>>,
+    mut commands: Commands,
+) {
+    let Some(settings) = settings else {
+        return;
+    };
+    let Some(handles) = handles else {
+        return;
+    };
+
+    if let Some(root) = handles.root {
+        commands.entity(root).try_insert(if settings.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    if let Some(graph_row) = handles.graph_row {
+        let graph_visible = settings.enabled && settings.graph.enabled;
+        commands.entity(graph_row).try_insert(if graph_visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    if let Some(bars_root) = handles.bars_root {
+        let bars_visible =
+            settings.enabled && settings.bars.enabled && !settings.bars.bars.is_empty();
+        commands.entity(bars_root).try_insert(if bars_visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    if let Some(cpu_cores_root) = handles.cpu_cores_root {
+        let cpu_cores_visible = settings.enabled && settings.cpu_cores.enabled;
+        commands
+            .entity(cpu_cores_root)
+            .try_insert(if cpu_cores_visible {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            });
+    }
+}
+
+/// System keeping a fixed-placement HUD at its anchor.
+///
+/// Repositions the root when the window is resized, the settings change or
+/// the HUD's size changes. A HUD dragged by the user stays where it was
+/// dropped.
+pub fn update_hud_anchor(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut nodes: Query<(&mut Node, Ref<ComputedNode>, Option<&HudDraggable>)>,
+    mut cost: ResMut<HudUpdateCost>,
 ) {
-    let Some(s) = settings else {
+    let _cost = cost.time(HudStage::Placement);
+    let resized = resized.read().count() > 0;
+    // Embedded HUDs are laid out by their parent; smart placements pick a corner
+    if settings.parent.is_some() || settings.placement != HudPlacement::Fixed {
+        return;
+    }
+    let Some(root) = handles.root else {
         return;
     };
-    if !s.enabled {
+    let Ok((mut node, computed, draggable)) = nodes.get_mut(root) else {
+        return;
+    };
+    if !(resized || settings.is_changed() || handles.is_changed() || computed.is_changed()) {
         return;
     }
-    let Some(h) = handles else {
+    if draggable.is_some_and(|draggable| draggable.position.is_some()) {
+        return;
+    }
+    let Ok(window) = windows.single() else {
         return;
     };
 
-    let curve_count = s.graph.curves.len().min(MAX_CURVES);
+    let size = computed.size() * computed.inverse_scale_factor();
+    let window = Vec2::new(window.width(), window.height());
+    let target = settings.anchor.position(settings.origin, window, size);
+    if node.left != Val::Px(target.x) || node.top != Val::Px(target.y) {
+        node.left = Val::Px(target.x);
+        node.top = Val::Px(target.y);
+    }
+}
 
-    // Process raw metric values through smoothing and quantization pipeline
-    let mut filtered_values = [0.0_f32; MAX_CURVES];
-    for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
-        let raw = samples.get(cfg.metric.id.as_str()).unwrap_or(0.0);
-
-        // Step 1: Apply exponential smoothing to reduce noise
-        // Formula: new_value = prev_value + (raw_value - prev_value) * smoothing_factor
-        let smoothing = cfg
-            .smoothing
-            .unwrap_or(s.graph.curve_defaults.smoothing)
-            .clamp(0.0, 1.0);
-
-        // Get the most recent value from history as the previous value
-        let prev = if history.length == 0 {
-            raw // No history yet, use raw value
-        } else if (history.length as usize) < MAX_SAMPLES {
-            history.values[i][history.length as usize - 1] // Buffer not full
-        } else {
-            history.values[i][MAX_SAMPLES - 1] // Buffer is full, use last element
-        };
+/// System that moves the HUD to a clear window corner for smart placements.
+///
+/// Keeps the HUD where it is while its corner does not cover the cursor or
+/// critical rect; otherwise picks the first corner that does, falling back to
+/// the one farthest from the area to avoid. A HUD dragged by the user stays
+/// where it was dropped.
+pub fn update_hud_placement(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut nodes: Query<(&mut Node, &ComputedNode, Option<&HudDraggable>)>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Placement);
+    // Embedded HUDs are laid out by their parent
+    if settings.parent.is_some() {
+        return;
+    }
+    let (avoid, margin) = match settings.placement {
+        HudPlacement::Fixed => return,
+        HudPlacement::AvoidCursor { margin } => {
+            let Some(cursor) = windows.single().ok().and_then(Window::cursor_position) else {
+                return;
+            };
+            (Rect::from_center_size(cursor, Vec2::ZERO), margin)
+        }
+        HudPlacement::AvoidRect { rect, margin } => (rect, margin),
+    };
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(root) = handles.root else {
+        return;
+    };
+    let Ok((mut node, computed, draggable)) = nodes.get_mut(root) else {
+        return;
+    };
+    if draggable.is_some_and(|draggable| draggable.position.is_some()) {
+        return;
+    }
 
-        let smoothed = prev + (raw - prev) * smoothing;
+    let size = computed.size() * computed.inverse_scale_factor();
+    let far = Vec2::new(window.width(), window.height()) - size - margin;
+    let corners = [
+        Vec2::splat(margin),
+        Vec2::new(far.x, margin),
+        Vec2::new(margin, far.y),
+        far,
+    ];
+    let covers = |pos: Vec2| {
+        let hud = Rect::from_corners(pos, pos + size);
+        !hud.intersect(avoid).is_empty() || hud.contains(avoid.min)
+    };
 
-        // Step 2: Apply quantization to create cleaner stepped values
-        // Rounds to the nearest multiple of quantize_step
-        let step = cfg
-            .quantize_step
-            .unwrap_or(s.graph.curve_defaults.quantize_step);
-        filtered_values[i] = if step > 0.0 {
-            (smoothed / step).round() * step
-        } else {
-            smoothed // No quantization
-        };
+    let current = match (node.left, node.top) {
+        (Val::Px(x), Val::Px(y)) => Vec2::new(x, y),
+        _ => settings.origin,
+    };
+    if corners.contains(&current) && !covers(current) {
+        return;
     }
 
-    // Update history buffers with new values using circular buffer approach
-    if (history.length as usize) < MAX_SAMPLES {
-        // Buffer not yet full: append new values at the end
-        let idx = history.length as usize;
-        for (i, value) in filtered_values.iter().enumerate().take(MAX_CURVES) {
-            history.values[i][idx] = *value;
-        }
-        // Pad unused curves with zeros
-        for i in curve_count..MAX_CURVES {
-            history.values[i][idx] = 0.0;
-        }
-        history.length += 1;
-    } else {
-        // Buffer is full: implement sliding window by shifting all values left
-        // This maintains the most recent MAX_SAMPLES values for graphing
-        for (i, value) in filtered_values.iter().enumerate().take(MAX_CURVES) {
-            history.values[i].copy_within(1..MAX_SAMPLES, 0); // Shift left
-            history.values[i][MAX_SAMPLES - 1] = *value; // Insert new value at end
-        }
-        // Handle unused curves with zeros
-        for i in curve_count..MAX_CURVES {
-            history.values[i].copy_within(1..MAX_SAMPLES, 0); // Shift left
-            history.values[i][MAX_SAMPLES - 1] = 0.0; // Insert zero at end
-        }
+    let target = corners
+        .iter()
+        .copied()
+        .find(|&pos| !covers(pos))
+        .unwrap_or_else(|| {
+            // Every corner overlaps: take the one farthest from the area
+            let center = avoid.center();
+            corners
+                .iter()
+                .copied()
+                .max_by(|a, b| {
+                    let da = (*a + size * 0.5).distance_squared(center);
+                    let db = (*b + size * 0.5).distance_squared(center);
+                    da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(current)
+        });
+    if target != current {
+        node.left = Val::Px(target.x);
+        node.top = Val::Px(target.y);
     }
+}
+
+/// Compute the Y range a graph should scale to, before smoothing.
+///
+/// Uses the fixed `min_y`/`max_y` unless one of the visible curves selected by
+/// `include` autoscales, then applies the zero, span, margin and step rules.
+/// The first `warmup` samples since the last reset are ignored.
+fn target_scale_range(
+    graph: &GraphSettings,
+    history: &HistoryBuffers,
+    warmup: u32,
+    instant: bool,
+    include: impl Fn(&CurveConfig) -> bool,
+) -> ScaleTarget {
+    let curve_count = graph.curves.len().min(MAX_CURVES);
+    let autoscaled = |cfg: &CurveConfig| {
+        include(cfg)
+            && graph.is_curve_visible(cfg)
+            && cfg.autoscale.unwrap_or(graph.curve_defaults.autoscale)
+    };
 
-    // Calculate target Y-axis range: either fixed from config or auto-scaled from data
-    let mut target_min = s.graph.min_y;
-    let mut target_max = s.graph.max_y;
+    // Calculate target Y-axis range: either fixed from config or auto-scaled
+    // from data, in axis positions so a log axis scales on the logarithms
+    let axis = |v: f32| graph.y_scale.to_axis(v);
+    let mut target_min = axis(graph.min_y);
+    let mut target_max = axis(graph.max_y);
+    let mut from_data = false;
+    let mut newest = None;
+
+    // Samples still in history from the warm-up period do not count
+    let len = history.length as usize;
+    let start = history.warmup_len(warmup);
 
     // Check if any curves want autoscaling and we have historical data
-    if s.graph
-        .curves
-        .iter()
-        .any(|c| c.autoscale.unwrap_or(s.graph.curve_defaults.autoscale))
-        && history.length > 0
-    {
+    if graph.curves.iter().take(curve_count).any(autoscaled) && start < len {
         // Scan all historical data to find the actual min/max range
-        let len = history.length as usize;
         let mut mn = f32::INFINITY;
         let mut mx = f32::NEG_INFINITY;
+        let mut newest_mn = f32::INFINITY;
+        let mut newest_mx = f32::NEG_INFINITY;
 
-        for (i, cfg) in s.graph.curves.iter().take(curve_count).enumerate() {
+        for (i, cfg) in graph.curves.iter().take(curve_count).enumerate() {
             // Only include curves that want autoscaling in the calculation
-            if cfg.autoscale.unwrap_or(s.graph.curve_defaults.autoscale) {
-                for k in 0..len {
-                    mn = mn.min(history.values[i][k]);
-                    mx = mx.max(history.values[i][k]);
+            if autoscaled(cfg) {
+                for k in start..len {
+                    mn = mn.min(axis(history.values[i][k]));
+                    mx = mx.max(axis(history.values[i][k]));
                 }
+                // Candlestick ranges must stay visible as well
+                if cfg.candlestick {
+                    for k in start..len {
+                        mn = mn.min(axis(history.mins[i][k]));
+                        mx = mx.max(axis(history.maxs[i][k]));
+                    }
+                    newest_mn = newest_mn.min(axis(history.mins[i][len - 1]));
+                    newest_mx = newest_mx.max(axis(history.maxs[i][len - 1]));
+                }
+                newest_mn = newest_mn.min(axis(history.values[i][len - 1]));
+                newest_mx = newest_mx.max(axis(history.values[i][len - 1]));
             }
         }
 
@@ -448,17 +3000,21 @@ pub fn update_graph_and_bars(
         if mn.is_finite() && mx.is_finite() {
             target_min = mn;
             target_max = mx;
+            from_data = true;
+        }
+        if newest_mn.is_finite() && newest_mx.is_finite() {
+            newest = Some((newest_mn, newest_mx));
         }
     }
 
-    if s.graph.y_include_zero {
+    if graph.y_include_zero {
         target_min = target_min.min(0.0);
         target_max = target_max.max(0.0);
     }
 
     let span = (target_max - target_min)
         .abs()
-        .max(s.graph.y_min_span.max(1e-3));
+        .max(axis(graph.y_min_span).max(1e-3));
     if target_max - target_min < span {
         let mid = 0.5 * (target_max + target_min);
         target_min = mid - 0.5 * span;
@@ -466,258 +3022,344 @@ pub fn update_graph_and_bars(
     }
 
     // Margins
-    let margin_frac = s.graph.y_margin_frac.clamp(0.0, 0.45);
+    let margin_frac = graph.y_margin_frac.clamp(0.0, 0.45);
     let margin = span * margin_frac;
     target_min -= margin;
     target_max += margin;
     // Step quantization
-    if s.graph.y_step_quantize > 0.0 {
-        let step = s.graph.y_step_quantize;
+    if graph.y_step_quantize > 0.0 && !instant && graph.y_scale == GraphYScale::Linear {
+        let step = graph.y_step_quantize;
         target_min = (target_min / step).floor() * step;
         target_max = (target_max / step).ceil() * step;
     }
 
-    // Smoothing
-    let a = s.graph.y_scale_smoothing.clamp(0.0, 1.0);
-    if scale_state.max_y <= scale_state.min_y {
-        scale_state.min_y = target_min;
-        scale_state.max_y = target_max;
-    } else {
-        scale_state.min_y = scale_state.min_y + (target_min - scale_state.min_y) * a;
-        scale_state.max_y = scale_state.max_y + (target_max - scale_state.max_y) * a;
+    ScaleTarget {
+        min: target_min,
+        max: target_max,
+        from_data,
+        newest,
     }
+}
 
-    let current_min = scale_state.min_y;
-    let current_max = (scale_state.max_y).max(current_min + 1e-3);
-
-    // Update graph labels dynamically based on configured curves
-    if s.graph.enabled && !h.graph_labels.is_empty() {
-        for label_handle in &h.graph_labels {
-            let Some(curve) = s
-                .graph
-                .curves
-                .iter()
-                .find(|c| c.metric.id == label_handle.metric_id)
-            else {
-                continue;
-            };
-
-            let definition = &curve.metric;
-            let precision = definition.precision as usize;
-            let unit = definition.unit.as_deref().unwrap_or("");
-
-            let value = samples.get(curve.metric.id.as_str()).unwrap_or(0.0);
-            let formatted = if precision == 0 {
-                format!("{value:.0}")
-            } else {
-                format!("{value:.precision$}", precision = precision)
-            };
-            let text_value = if unit.is_empty() {
-                formatted
-            } else {
-                format!("{formatted} {unit}")
-            };
+/// Y range a scale state eases towards.
+struct ScaleTarget {
+    min: f32,
+    max: f32,
+    /// Whether the range was derived from data rather than the fixed fallback
+    from_data: bool,
+    /// Value range of the newest autoscaled sample
+    newest: Option<(f32, f32)>,
+}
 
-            if let Ok(mut tx) = label_text_q.get_mut(label_handle.entity) {
-                if **tx != text_value {
-                    **tx = text_value.clone();
-                }
+/// Move a scale state towards `target` and return the range to display.
+///
+/// The state jumps straight to the target until the first target derived
+/// from data, so the graph does not ease in from the fixed fallback range,
+/// and always in instant mode.
+fn smooth_scale_range(
+    scale_state: &mut GraphScaleState,
+    target: ScaleTarget,
+    graph: &GraphSettings,
+    instant: bool,
+    dt: f32,
+) -> (f32, f32) {
+    if instant || !scale_state.settled || scale_state.max_y <= scale_state.min_y {
+        scale_state.min_y = target.min;
+        scale_state.max_y = target.max;
+        scale_state.min_velocity = 0.0;
+        scale_state.max_velocity = 0.0;
+        scale_state.settled = target.from_data;
+    } else {
+        match graph.scale_easing {
+            ScaleEasing::Lerp => {
+                let a = graph.y_scale_smoothing.clamp(0.0, 1.0);
+                scale_state.min_y = scale_state.min_y + (target.min - scale_state.min_y) * a;
+                scale_state.max_y = scale_state.max_y + (target.max - scale_state.max_y) * a;
             }
-            if let Ok(mut col) = label_color_q.get_mut(label_handle.entity) {
-                *col = TextColor(curve.metric.color);
+            ScaleEasing::Spring(frequency) => {
+                let omega = frequency.max(0.0);
+                spring_step(
+                    &mut scale_state.min_y,
+                    &mut scale_state.min_velocity,
+                    target.min,
+                    omega,
+                    dt,
+                );
+                spring_step(
+                    &mut scale_state.max_y,
+                    &mut scale_state.max_velocity,
+                    target.max,
+                    omega,
+                    dt,
+                );
             }
         }
     }
 
-    // Update graph material (when enabled)
-    if s.graph.enabled {
-        if let Some(handle) = &h.graph_material {
-            if let Some(mat) = graph_mats.get_mut(handle) {
-                mat.params.length = history.length;
-                mat.params.min_y = current_min;
-                mat.params.max_y = current_max;
-                mat.params.thickness = s.graph.thickness;
-                mat.params.bg_color = s.graph.bg_color.to_linear().to_vec4();
-                mat.params.border_color = s.graph.border.color.to_linear().to_vec4();
-                mat.params.border_thickness = s.graph.border.thickness; // pixels
-                mat.params.border_thickness_uv_x =
-                    (s.graph.border.thickness / s.graph.size.x).max(0.0001);
-                mat.params.border_thickness_uv_y =
-                    (s.graph.border.thickness / s.graph.size.y).max(0.0001);
-                mat.params.border_left = if s.graph.border.left { 1 } else { 0 };
-                mat.params.border_bottom = if s.graph.border.bottom { 1 } else { 0 };
-                mat.params.border_right = if s.graph.border.right { 1 } else { 0 };
-                mat.params.border_top = if s.graph.border.top { 1 } else { 0 };
-                mat.params.curve_count = curve_count as u32;
-                // Sync curve colors every frame to allow hot updates
-                for (i, c) in s.graph.curves.iter().take(curve_count).enumerate() {
-                    mat.params.colors[i] = c.metric.color.to_linear().to_vec4();
-                }
-                for i in curve_count..MAX_CURVES {
-                    mat.params.colors[i] = Vec4::ZERO;
-                }
-                // Write values (pack into vec4)
-                let len = MAX_SAMPLES.min(history.length as usize);
-                let packed_len = len.div_ceil(4); // round up
-                for i in 0..MAX_CURVES {
-                    for j in 0..SAMPLES_VEC4 {
-                        let base = j * 4;
-                        let x0 = if base < len {
-                            history.values[i][base]
-                        } else {
-                            0.0
-                        };
-                        let x1 = if base + 1 < len {
-                            history.values[i][base + 1]
-                        } else {
-                            0.0
-                        };
-                        let x2 = if base + 2 < len {
-                            history.values[i][base + 2]
-                        } else {
-                            0.0
-                        };
-                        let x3 = if base + 3 < len {
-                            history.values[i][base + 3]
-                        } else {
-                            0.0
-                        };
-                        mat.params.values[i][j] = Vec4::new(x0, x1, x2, x3);
-                    }
-                    // Optional: zero unused segments packed_len..SAMPLES_VEC4
-                    for j in packed_len..SAMPLES_VEC4 {
-                        mat.params.values[i][j] = Vec4::ZERO;
-                    }
-                }
-                // Colors set at init; update here if config changed
-            }
+    // Never leave the newest sample off-screen: widen at once instead of easing
+    if let Some((newest_min, newest_max)) = target.newest {
+        if newest_max > scale_state.max_y {
+            scale_state.max_y = target.max.max(newest_max);
+            scale_state.max_velocity = 0.0;
+        }
+        if newest_min < scale_state.min_y {
+            scale_state.min_y = target.min.min(newest_min);
+            scale_state.min_velocity = 0.0;
         }
     }
 
-    // Update bars (when enabled)
-    if s.bars.enabled {
-        for (i, cfg) in s.bars.bars.iter().enumerate() {
-            if i >= h.bar_materials.len() {
-                break;
-            }
-            let val = samples.get(cfg.metric.id.as_str()).unwrap_or(0.0);
+    let current_min = scale_state.min_y;
+    (current_min, scale_state.max_y.max(current_min + 1e-3))
+}
 
-            // Get or create the scale state for this bar
-            let scale_state = bar_scale_states.get_or_create(&cfg.metric.id);
+/// Advance a critically damped spring from `value` towards `target` by `dt` seconds.
+fn spring_step(value: &mut f32, velocity: &mut f32, target: f32, omega: f32, dt: f32) {
+    let offset = *value - target;
+    let temp = (*velocity + omega * offset) * dt;
+    let decay = (-omega * dt).exp();
+    *velocity = (*velocity - omega * temp) * decay;
+    *value = target + (offset + temp) * decay;
+}
 
-            // Add current value to the scale state's history
-            scale_state.add_sample(val);
+/// Spawn a graph title or caption, centered over the graph area.
+fn spawn_graph_text(
+    commands: &mut Commands,
+    text: String,
+    font_size: f32,
+    color: Color,
+    margin: UiRect,
+    graph: &GraphSettings,
+    text_shadow: Option<TextShadow>,
+) -> Entity {
+    let entity = commands
+        .spawn((
+            Text::new(text),
+            TextColor(color),
+            TextFont {
+                font_size,
+                ..default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                width: graph.width_val(),
+                margin: UiRect {
+                    left: Val::Px(graph.label_column.reserved_width()),
+                    ..margin
+                },
+                ..default()
+            },
+        ))
+        .id();
+    if let Some(shadow) = text_shadow {
+        commands.entity(entity).insert(shadow);
+    }
+    entity
+}
 
-            // Calculate the dynamic range based on the bar's scale mode
-            let (range_min, range_max) = scale_state.calculate_range(
-                &cfg.scale_mode,
-                cfg.min_value,
-                cfg.max_value,
-                cfg.min_limit,
-                cfg.max_limit,
-            );
+/// Color a metric is drawn in for its latest sample, see [`PerfHudSettings::metric_color`].
+fn metric_draw_color(
+    s: &PerfHudSettings,
+    definition: &MetricDefinition,
+    samples: &SampledValues,
+    display_hz: Option<f32>,
+) -> Color {
+    samples
+        .get(&definition.id)
+        .map_or(definition.color, |value| {
+            s.metric_color(definition, value, display_hz)
+        })
+}
 
-            // Normalize the value using the calculated range
-            let norm = if range_max > range_min {
-                ((val - range_min) / (range_max - range_min)).clamp(0.0, 1.0)
-            } else {
-                0.0
-            };
+/// Display label of a metric, translated by the [`LabelLocalizer`] if present.
+///
+/// Borrowed from the definition without a localizer, so untranslated labels
+/// can be written every frame without allocating.
+fn metric_label<'a>(
+    definition: &'a MetricDefinition,
+    localizer: Option<&LabelLocalizer>,
+) -> Cow<'a, str> {
+    let default = definition.label.as_deref().unwrap_or(&definition.id);
+    match localizer {
+        Some(localizer) => Cow::Owned(localizer.localize(&definition.id, default)),
+        None => Cow::Borrowed(default),
+    }
+}
 
-            if let Some(mat) = bar_mats.get_mut(&h.bar_materials[i]) {
-                mat.params.value = norm;
-                let v = cfg.metric.color.to_linear().to_vec4();
-                mat.params.r = v.x;
-                mat.params.g = v.y;
-                mat.params.b = v.z;
-                mat.params.a = v.w;
-                let bg = s.bars.bg_color.to_linear().to_vec4();
-                mat.params.bg_r = bg.x;
-                mat.params.bg_g = bg.y;
-                mat.params.bg_b = bg.z;
-                mat.params.bg_a = bg.w;
-            }
+/// Decimal places showing Y-axis ticks `step` apart distinctly (0-3).
+fn tick_precision(step: f32) -> usize {
+    if step.is_finite() && step > 0.0 && step < 1.0 {
+        (-step.log10()).ceil().clamp(0.0, 3.0) as usize
+    } else {
+        0
+    }
+}
 
-            // Update bar labels with current values and formatting
-            if let Some(&label_entity) = h.bar_labels.get(i) {
-                let definition = &cfg.metric;
-                let base_label = definition
-                    .label
-                    .clone()
-                    .unwrap_or_else(|| definition.id.clone());
-                let precision = definition.precision as usize;
-                let unit = definition.unit.as_deref().unwrap_or("");
+/// Set a text to formatted `args`, reusing its buffer.
+///
+/// Text that already reads the same is left untouched, so it is neither
+/// marked changed (and laid out again) nor reallocated.
+fn set_text_fmt(text: &mut Mut<Text>, args: fmt::Arguments) {
+    let mut matcher = TextMatcher {
+        rest: &text.0,
+        matches: true,
+    };
+    // The matcher never fails, it only records a mismatch
+    let _ = matcher.write_fmt(args);
+    if matcher.matches && matcher.rest.is_empty() {
+        return;
+    }
+    let text = &mut text.0;
+    text.clear();
+    let _ = text.write_fmt(args);
+}
 
-                let formatted = if precision == 0 {
-                    format!("{val:.0}")
-                } else {
-                    format!("{val:.precision$}", precision = precision)
-                };
-                let show_value = cfg.show_value.unwrap_or(s.bars.show_value_default);
-                let display_text = if show_value {
-                    let value_text = if unit.is_empty() {
-                        formatted
-                    } else {
-                        format!("{formatted}{unit}")
-                    };
-                    format!("{} {}", base_label, value_text)
-                } else {
-                    base_label.clone()
-                };
+/// [`fmt::Write`] sink comparing formatted output against existing text.
+struct TextMatcher<'a> {
+    /// Text not matched yet
+    rest: &'a str,
+    /// Whether all output so far matched
+    matches: bool,
+}
 
-                if let Ok(mut tx) = label_text_q.get_mut(label_entity) {
-                    if **tx != display_text {
-                        **tx = display_text;
-                    }
-                }
-                if let Ok(mut col) = label_color_q.get_mut(label_entity) {
-                    *col = TextColor(Color::WHITE);
-                }
-            }
+impl fmt::Write for TextMatcher<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.rest.strip_prefix(s) {
+            Some(rest) if self.matches => self.rest = rest,
+            _ => self.matches = false,
         }
+        Ok(())
     }
 }
 
-/// System that synchronizes HUD visibility with the latest settings.
+/// Write a bar grid's shader parameters, touching the asset only when they
+/// changed.
 ///
-/// Runs when [`PerfHudSettings`] changes, toggling visibility of the root
-/// container, graph row and bars section without requiring entity rebuild.
-pub fn sync_hud_visibility(
-    settings: Option<Res<PerfHudSettings>>,
-    handles: Option<Res<HudHandles>>,
-    mut commands: Commands,
+/// Mutable asset access marks the material modified, which re-prepares its
+/// bind group and re-uploads its uniform, so grids whose fills and colors are
+/// unchanged this frame are skipped.
+fn write_bars_params(
+    bars_mats: &mut Assets<BarsMaterial>,
+    handle: &Handle<BarsMaterial>,
+    params: BarsParams,
 ) {
-    let Some(settings) = settings else {
-        return;
-    };
-    let Some(handles) = handles else {
-        return;
-    };
+    if bars_mats
+        .get(handle)
+        .is_some_and(|mat| mat.params != params)
+    {
+        if let Some(mat) = bars_mats.get_mut(handle) {
+            mat.params = params;
+        }
+    }
+}
 
-    if let Some(root) = handles.root {
-        commands.entity(root).insert(if settings.enabled {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        });
+/// Pack a curve's per-sample min/max ranges into the graph shader layout.
+///
+/// Sample `k` lands in lane `k % 4` of chunk `k / 4`; chunks past the given
+/// samples stay zero.
+fn pack_curve_ranges(
+    ranges: impl Iterator<Item = (f32, f32)>,
+    min_y: f32,
+    max_y: f32,
+) -> [UVec4; SAMPLES_VEC4] {
+    let mut packed = [[0_u32; 4]; SAMPLES_VEC4];
+    for (k, (min, max)) in ranges.take(MAX_SAMPLES).enumerate() {
+        packed[k / 4][k % 4] = pack_sample_range(min, max, min_y, max_y);
     }
+    packed.map(UVec4::from_array)
+}
 
-    if let Some(graph_row) = handles.graph_row {
-        let graph_visible = settings.enabled && settings.graph.enabled;
-        commands.entity(graph_row).insert(if graph_visible {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        });
+/// Pack a sample's min/max range into one `u32` for the graph shader.
+///
+/// Both ends are normalized against the displayed Y range and stored as
+/// unorm16 (min in the low half, max in the high half), matching WGSL's
+/// `unpack2x16unorm`.
+fn pack_sample_range(min: f32, max: f32, min_y: f32, max_y: f32) -> u32 {
+    let inv_range = 1.0 / (max_y - min_y).max(1e-6);
+    let to_unorm16 = |v: f32| (((v - min_y) * inv_range).clamp(0.0, 1.0) * 65535.0).round() as u32;
+    to_unorm16(min) | (to_unorm16(max) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_range_packing() {
+        let packed = pack_sample_range(2.5, 7.5, 0.0, 10.0);
+        assert_eq!(packed & 0xFFFF, 16384);
+        assert_eq!(packed >> 16, 49151);
+
+        // Ends outside the displayed range clamp to its edges
+        let clamped = pack_sample_range(-5.0, 20.0, 0.0, 10.0);
+        assert_eq!(clamped, 0xFFFF << 16);
     }
 
-    if let Some(bars_root) = handles.bars_root {
-        let bars_visible =
-            settings.enabled && settings.bars.enabled && !settings.bars.bars.is_empty();
-        commands.entity(bars_root).insert(if bars_visible {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        });
+    #[test]
+    fn test_curve_ranges_fill_lanes_in_sample_order() {
+        let ranges = (0..6).map(|k| (k as f32, k as f32 + 1.0));
+        let packed = pack_curve_ranges(ranges, 0.0, 10.0);
+
+        for k in 0..6 {
+            let expected = pack_sample_range(k as f32, k as f32 + 1.0, 0.0, 10.0);
+            assert_eq!(packed[k / 4].to_array()[k % 4], expected, "sample {k}");
+        }
+        assert_eq!(packed[1].to_array()[2..], [0, 0]);
+        assert!(packed[2..].iter().all(|chunk| *chunk == UVec4::ZERO));
+    }
+
+    #[test]
+    fn test_spring_step_is_frame_rate_independent() {
+        let (mut coarse, mut coarse_velocity) = (0.0, 0.0);
+        spring_step(&mut coarse, &mut coarse_velocity, 10.0, 8.0, 0.1);
+
+        let (mut fine, mut fine_velocity) = (0.0, 0.0);
+        for _ in 0..10 {
+            spring_step(&mut fine, &mut fine_velocity, 10.0, 8.0, 0.01);
+        }
+
+        assert!((coarse - fine).abs() < 1e-3, "{coarse} vs {fine}");
+        assert!((coarse_velocity - fine_velocity).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_spring_step_settles_without_overshoot() {
+        let (mut value, mut velocity) = (0.0, 0.0);
+        let mut previous = value;
+        for _ in 0..120 {
+            spring_step(&mut value, &mut velocity, 10.0, 8.0, 1.0 / 60.0);
+            assert!(value >= previous && value <= 10.0, "{value}");
+            previous = value;
+        }
+        assert!((value - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_scale_range_widens_at_once_for_newest_sample() {
+        let mut graph = PerfHudSettings::default().graph;
+        graph.scale_easing = ScaleEasing::Lerp;
+        graph.y_scale_smoothing = 0.1;
+        let settled = || GraphScaleState {
+            min_y: 0.0,
+            max_y: 10.0,
+            settled: true,
+            ..default()
+        };
+        let target = |newest| ScaleTarget {
+            min: 0.0,
+            max: 50.0,
+            from_data: true,
+            newest,
+        };
+
+        // An older spike eases the range up
+        let mut state = settled();
+        let (_, max) = smooth_scale_range(&mut state, target(None), &graph, false, 0.016);
+        assert!((max - 14.0).abs() < 1e-4);
+
+        // A new sample past the current range is shown immediately
+        let mut state = settled();
+        let (min, max) =
+            smooth_scale_range(&mut state, target(Some((-5.0, 40.0))), &graph, false, 0.016);
+        assert_eq!((min, max), (-5.0, 50.0));
     }
 }
@@ -0,0 +1,150 @@
+//! Aggregated metrics for forwarding to external telemetry.
+//!
+//! With [`PerfHudSettings::telemetry`](crate::PerfHudSettings) enabled, a
+//! [`TelemetryTick`] is sent once per interval with the mean, min and max of
+//! every metric sampled during it. With the `serde` feature the event is
+//! serializable, so game code can forward it to its own analytics backend in
+//! whatever format that expects.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::{
+    ecs::{
+        event::EventWriter,
+        system::{Local, Res, ResMut},
+    },
+    prelude::Event,
+    time::{Real, Time},
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    resources::SampledValues,
+};
+
+/// Sent once per telemetry interval with the metrics sampled during it.
+///
+/// # Example
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::TelemetryTick;
+///
+/// fn forward_telemetry(mut ticks: EventReader<TelemetryTick>) {
+///     for tick in ticks.read() {
+///         if let Some(fps) = tick.metrics.get("fps") {
+///             info!("fps over the last second: {:.1} mean, {:.1} min", fps.mean, fps.min);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TelemetryTick {
+    /// Real time since startup at the end of the interval, in seconds
+    pub time: f64,
+    /// Statistics of every metric sampled during the interval, keyed by metric ID
+    pub metrics: BTreeMap<String, TelemetryStats>,
+}
+
+/// Statistics of one metric over a telemetry interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TelemetryStats {
+    /// Mean of the samples
+    pub mean: f32,
+    /// Smallest sample
+    pub min: f32,
+    /// Largest sample
+    pub max: f32,
+}
+
+/// Running statistics of a metric during the current interval.
+#[derive(Debug, Clone, Copy)]
+struct TelemetryAccumulator {
+    sum: f64,
+    count: u32,
+    min: f32,
+    max: f32,
+}
+
+impl TelemetryAccumulator {
+    fn new(value: f32) -> Self {
+        Self {
+            sum: f64::from(value),
+            count: 1,
+            min: value,
+            max: value,
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        self.sum += f64::from(value);
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn stats(&self) -> TelemetryStats {
+        TelemetryStats {
+            mean: (self.sum / f64::from(self.count)) as f32,
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+/// Samples collected since the last [`TelemetryTick`].
+#[derive(Default)]
+pub struct TelemetryInterval {
+    /// Real time the interval started at (None until the first sample)
+    started: Option<f64>,
+    /// Running statistics per metric ID
+    metrics: HashMap<String, TelemetryAccumulator>,
+}
+
+/// System collecting fresh samples and sending a [`TelemetryTick`] at the end
+/// of each interval.
+pub fn send_telemetry_ticks(
+    settings: Res<PerfHudSettings>,
+    samples: Res<SampledValues>,
+    time: Res<Time<Real>>,
+    mut ticks: EventWriter<TelemetryTick>,
+    mut interval: Local<TelemetryInterval>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Telemetry);
+    if !settings.telemetry.enabled {
+        *interval = TelemetryInterval::default();
+        return;
+    }
+
+    let now = time.elapsed_secs_f64();
+    let started = *interval.started.get_or_insert(now);
+    for (id, _) in samples.iter() {
+        let Some(value) = samples.get_fresh(id).filter(|value| value.is_finite()) else {
+            continue;
+        };
+        match interval.metrics.get_mut(id) {
+            Some(metric) => metric.push(value),
+            None => {
+                interval
+                    .metrics
+                    .insert(id.to_owned(), TelemetryAccumulator::new(value));
+            }
+        }
+    }
+
+    if now - started < settings.telemetry.interval.as_secs_f64() {
+        return;
+    }
+    let metrics = interval
+        .metrics
+        .drain()
+        .map(|(id, metric)| (id, metric.stats()))
+        .collect::<BTreeMap<_, _>>();
+    interval.started = Some(now);
+    if !metrics.is_empty() {
+        ticks.write(TelemetryTick { time: now, metrics });
+    }
+}
@@ -0,0 +1,169 @@
+//! Deterministic test driver for the performance HUD.
+//!
+//! Available with the `test-utils` feature. [`PerfHudTestDriver`] swaps all
+//! metric providers for scripted value sequences and advances time by a fixed
+//! step per frame, so tests can assert exact history contents, label strings
+//! and scale decisions on every platform.
+
+use std::time::Duration;
+
+use bevy::{app::App, time::TimeUpdateStrategy, ui::widget::Text};
+
+use crate::{
+    GraphScaleState, HistoryBuffers, HudHandles, MetricProviders, MetricSampleContext,
+    PerfHudSettings, PerfMetricProvider, SampledValues,
+};
+
+/// Metric provider returning a fixed sequence of values, one per frame.
+///
+/// Once the sequence is exhausted the provider returns no value.
+pub struct ScriptedMetricProvider {
+    metric_id: String,
+    values: Vec<f32>,
+    next: usize,
+}
+
+impl ScriptedMetricProvider {
+    /// Create a provider reporting `values` for `metric_id`, in order
+    pub fn new(metric_id: impl Into<String>, values: impl IntoIterator<Item = f32>) -> Self {
+        Self {
+            metric_id: metric_id.into(),
+            values: values.into_iter().collect(),
+            next: 0,
+        }
+    }
+}
+
+impl PerfMetricProvider for ScriptedMetricProvider {
+    fn metric_id(&self) -> &str {
+        &self.metric_id
+    }
+
+    fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+        let value = self.values.get(self.next).copied();
+        self.next += 1;
+        value
+    }
+}
+
+/// Drives an app with the HUD frame by frame under scripted conditions.
+///
+/// # Example
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_perf_hud::{BevyPerfHudPlugin, PerfHudSettings, PerfHudTestDriver};
+///
+/// let mut app = App::new();
+/// app.add_plugins(DefaultPlugins)
+///     .insert_resource(PerfHudSettings::default())
+///     .add_plugins(BevyPerfHudPlugin);
+///
+/// let mut driver = PerfHudTestDriver::new(app);
+/// driver.script("fps", [60.0, 30.0]);
+/// driver.run_frames(2);
+/// assert_eq!(driver.sample("fps"), Some(30.0));
+/// ```
+pub struct PerfHudTestDriver {
+    app: App,
+}
+
+impl PerfHudTestDriver {
+    /// Default time advanced per frame (60 FPS)
+    pub const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+    /// Take over an app that already has [`BevyPerfHudPlugin`](crate::BevyPerfHudPlugin).
+    ///
+    /// Removes every registered provider, including the built-in ones, and
+    /// advances time by [`Self::FRAME_TIME`] per frame.
+    pub fn new(mut app: App) -> Self {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Self::FRAME_TIME));
+        app.world_mut().resource_mut::<MetricProviders>().clear();
+        Self { app }
+    }
+
+    /// Advance time by `frame_time` per frame instead of [`Self::FRAME_TIME`]
+    pub fn set_frame_time(&mut self, frame_time: Duration) -> &mut Self {
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+        self
+    }
+
+    /// Report `values` for `metric_id`, one per frame, then no value
+    pub fn script(
+        &mut self,
+        metric_id: impl Into<String>,
+        values: impl IntoIterator<Item = f32>,
+    ) -> &mut Self {
+        self.app
+            .world_mut()
+            .resource_mut::<MetricProviders>()
+            .add_provider(ScriptedMetricProvider::new(metric_id, values));
+        self
+    }
+
+    /// Run one frame
+    pub fn step(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    /// Run `frames` frames
+    pub fn run_frames(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// The driven app
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// The driven app, for inserting resources or sending events
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Latest sampled value of a metric
+    pub fn sample(&self, metric_id: &str) -> Option<f32> {
+        self.app.world().resource::<SampledValues>().get(metric_id)
+    }
+
+    /// Live graph history of the curve showing `metric_id`, oldest first
+    pub fn history(&self, metric_id: &str) -> Vec<f32> {
+        let world = self.app.world();
+        let Some(curve) = world
+            .get_resource::<PerfHudSettings>()
+            .and_then(|s| s.graph.curves.iter().position(|c| c.metric.id == metric_id))
+        else {
+            return Vec::new();
+        };
+        let Some(history) = world.get_resource::<HistoryBuffers>() else {
+            return Vec::new();
+        };
+        history
+            .values
+            .get(curve)
+            .map(|values| values[..history.length as usize].to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Current text of the graph label showing `metric_id`
+    pub fn label_text(&self, metric_id: &str) -> Option<String> {
+        let world = self.app.world();
+        let label = world
+            .get_resource::<HudHandles>()?
+            .graph_labels
+            .iter()
+            .find(|l| l.metric_id == metric_id)?
+            .entity;
+        world.get::<Text>(label).map(|text| text.0.clone())
+    }
+
+    /// Graph-wide Y range currently displayed
+    pub fn scale_range(&self) -> (f32, f32) {
+        let state = self.app.world().resource::<GraphScaleState>();
+        (state.min_y, state.max_y)
+    }
+}
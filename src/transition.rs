@@ -0,0 +1,145 @@
+//! Cross-fade of the HUD when it is rebuilt.
+//!
+//! With [`PerfHudSettings::transition`](crate::PerfHudSettings) set, a rebuild
+//! keeps the old HUD while it fades out and fades the new one in, so switching
+//! presets during a recording does not pop.
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        hierarchy::Children,
+        system::{Commands, Query, Res},
+    },
+    prelude::*,
+    ui::MaterialNode,
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    render::{BarMaterial, BarsMaterial, MultiLineGraphMaterial},
+    resources::HudHandles,
+    systems::{despawn_hud, HudMaterialAssets},
+};
+
+/// Fade of a HUD root and everything below it.
+///
+/// Inserted on the new and old HUD roots by a rebuild with a transition.
+#[derive(Component)]
+pub struct HudFade {
+    timer: Timer,
+    fade_in: bool,
+    /// Handles of a HUD fading out, despawned once it is invisible
+    retired: Option<HudHandles>,
+    /// Unfaded alpha of each text color and background in the tree
+    text_alpha: HashMap<Entity, f32>,
+    background_alpha: HashMap<Entity, f32>,
+}
+
+impl HudFade {
+    /// Fade a newly spawned HUD in over `duration`
+    pub fn fade_in(duration: Duration) -> Self {
+        Self::new(duration, true, None)
+    }
+
+    /// Fade the HUD described by `retired` out over `duration`, then despawn it
+    pub fn fade_out(duration: Duration, retired: HudHandles) -> Self {
+        Self::new(duration, false, Some(retired))
+    }
+
+    fn new(duration: Duration, fade_in: bool, retired: Option<HudHandles>) -> Self {
+        Self {
+            timer: Timer::new(duration, TimerMode::Once),
+            fade_in,
+            retired,
+            text_alpha: HashMap::new(),
+            background_alpha: HashMap::new(),
+        }
+    }
+
+    /// Current opacity of the faded tree (0.0-1.0)
+    pub fn opacity(&self) -> f32 {
+        let t = self.timer.fraction();
+        let eased = t * t * (3.0 - 2.0 * t);
+        if self.fade_in {
+            eased
+        } else {
+            1.0 - eased
+        }
+    }
+}
+
+/// System advancing [`HudFade`]s and applying their opacity.
+///
+/// Runs after the HUD update so the live HUD's freshly written colors are
+/// faded as well. Finished fade-outs despawn their HUD and its materials.
+#[allow(clippy::too_many_arguments)]
+pub fn animate_hud_fades(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut fades: Query<(Entity, &mut HudFade)>,
+    children: Query<&Children>,
+    mut text_colors: Query<&mut TextColor>,
+    mut backgrounds: Query<&mut BackgroundColor>,
+    graph_nodes: Query<&MaterialNode<MultiLineGraphMaterial>>,
+    bar_nodes: Query<&MaterialNode<BarMaterial>>,
+    bars_nodes: Query<&MaterialNode<BarsMaterial>>,
+    (mut graph_mats, mut bars_mats): HudMaterialAssets,
+    mut bar_mats: ResMut<Assets<BarMaterial>>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Fades);
+    for (root, mut fade) in &mut fades {
+        fade.timer.tick(time.delta());
+        let opacity = fade.opacity();
+        let fade = &mut *fade;
+
+        for entity in std::iter::once(root).chain(children.iter_descendants(root)) {
+            if let Ok(mut color) = text_colors.get_mut(entity) {
+                let base = *fade.text_alpha.entry(entity).or_insert(color.0.alpha());
+                color.0.set_alpha(base * opacity);
+            }
+            if let Ok(mut color) = backgrounds.get_mut(entity) {
+                let base = *fade
+                    .background_alpha
+                    .entry(entity)
+                    .or_insert(color.0.alpha());
+                color.0.set_alpha(base * opacity);
+            }
+            if let Some(mat) = graph_nodes
+                .get(entity)
+                .ok()
+                .and_then(|node| graph_mats.get_mut(&node.0))
+            {
+                mat.params.opacity = opacity;
+            }
+            if let Some(mat) = bar_nodes
+                .get(entity)
+                .ok()
+                .and_then(|node| bar_mats.get_mut(&node.0))
+            {
+                mat.params.opacity = opacity;
+            }
+            if let Some(mat) = bars_nodes
+                .get(entity)
+                .ok()
+                .and_then(|node| bars_mats.get_mut(&node.0))
+            {
+                mat.params.opacity = opacity;
+            }
+        }
+
+        if fade.timer.finished() {
+            match fade.retired.take() {
+                Some(retired) => {
+                    despawn_hud(&mut commands, &retired, &mut graph_mats, &mut bars_mats)
+                }
+                None => {
+                    commands.entity(root).remove::<HudFade>();
+                }
+            }
+        }
+    }
+}
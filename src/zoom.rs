@@ -0,0 +1,186 @@
+//! Zooming the paused graph into a time range.
+//!
+//! With [`HudInteraction::zoom`](crate::HudInteraction) set, dragging across
+//! the paused or frozen graph (see [`HudPauseState`]) with the left mouse
+//! button zooms into the samples under the selection, so the shape of a spike
+//! a few pixels wide can be inspected.
+//! Selections can be nested; Escape, a double-click on the graph or resuming
+//! updates show the whole history again.
+
+use bevy::{
+    asset::Assets,
+    ecs::system::{Query, Res, ResMut},
+    input::{keyboard::KeyCode, mouse::MouseButton, ButtonInput},
+    prelude::*,
+    time::{Real, Time},
+    ui::ComputedNode,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    budget::{HudStage, HudUpdateCost},
+    config::PerfHudSettings,
+    render::MultiLineGraphMaterial,
+    resources::{HudHandles, HudPauseState},
+};
+
+/// Two presses on the graph within this many seconds reset the zoom
+const DOUBLE_CLICK_SECS: f64 = 0.3;
+
+/// Selections narrower than this many physical pixels are clicks, not zooms
+const MIN_SELECTION_PX: f32 = 3.0;
+
+/// Resource holding the zoomed range of the paused graph.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct GraphZoom {
+    /// Samples shown across the graph as (first sample, number of samples)
+    /// (None = the whole history)
+    pub view: Option<(u32, u32)>,
+    /// Ends of the selection being dragged, in normalized graph coordinates
+    selection: Option<(f32, f32)>,
+    /// Real time of the last press on the graph, to detect double-clicks
+    last_press: Option<f64>,
+}
+
+impl GraphZoom {
+    /// Show the whole history again
+    pub fn reset(&mut self) {
+        self.view = None;
+        self.selection = None;
+    }
+
+    /// Whether a zoom selection is being dragged
+    pub fn is_selecting(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    /// Zoom into the part of the current view between normalized graph
+    /// coordinates `from` and `to`, out of `length` samples in the history
+    pub fn zoom_to(&mut self, from: f32, to: f32, length: u32) {
+        if length < 2 {
+            return;
+        }
+        let (start, len) = self.view.unwrap_or((0, length));
+        let last = (len.min(length) - 1) as f32;
+        let first = (start as f32 + from.min(to).clamp(0.0, 1.0) * last).floor() as u32;
+        let end = (start as f32 + from.max(to).clamp(0.0, 1.0) * last).ceil() as u32;
+        // At least two samples, so there is a segment to draw
+        let len = (end.saturating_sub(first) + 1).max(2).min(length);
+        self.view = Some((first.min(length - len), len));
+    }
+}
+
+/// Physical cursor position and the graph's physical rectangle, if the
+/// primary window has a cursor and the graph is laid out.
+pub(crate) fn graph_cursor(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    graph: Option<Entity>,
+    areas: &Query<(&ComputedNode, &GlobalTransform)>,
+) -> Option<(Vec2, Rect)> {
+    let cursor = windows.single().ok()?.physical_cursor_position()?;
+    let (computed, transform) = areas.get(graph?).ok()?;
+    let rect = Rect::from_center_size(transform.translation().truncate(), computed.size());
+    Some((cursor, rect))
+}
+
+/// System zooming the paused graph into the range dragged across it.
+///
+/// Writes the view and the selection straight to the graph material, since
+/// the display is not updated while paused.
+#[allow(clippy::too_many_arguments)]
+pub fn zoom_graph(
+    settings: Res<PerfHudSettings>,
+    handles: Res<HudHandles>,
+    pause: Res<HudPauseState>,
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    time: Res<Time<Real>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    areas: Query<(&ComputedNode, &GlobalTransform)>,
+    mut graph_mats: ResMut<Assets<MultiLineGraphMaterial>>,
+    mut zoom: ResMut<GraphZoom>,
+    mut cost: ResMut<HudUpdateCost>,
+) {
+    let _cost = cost.time(HudStage::Zoom);
+    let Some(handle) = &handles.graph_material else {
+        return;
+    };
+    let Some(length) = graph_mats.get(handle).map(|mat| mat.params.length) else {
+        return;
+    };
+    let cursor = graph_cursor(&windows, handles.graph_entity, &areas);
+
+    if !pause.is_graph_frozen() || !settings.interaction.zoom {
+        if zoom.view.is_some() || zoom.is_selecting() {
+            zoom.reset();
+        }
+    } else {
+        if keys.is_some_and(|keys| keys.just_pressed(KeyCode::Escape)) {
+            zoom.reset();
+        }
+        if let (Some(mouse), Some((cursor, rect))) = (mouse, cursor) {
+            // Cursor position across the graph, 0 at the left edge and 1 at the right
+            let x = (cursor.x - rect.min.x) / rect.width().max(1.0);
+            if mouse.just_pressed(MouseButton::Left) && rect.contains(cursor) {
+                let now = time.elapsed_secs_f64();
+                if zoom
+                    .last_press
+                    .is_some_and(|last| now - last < DOUBLE_CLICK_SECS)
+                {
+                    zoom.reset();
+                    zoom.last_press = None;
+                } else {
+                    zoom.selection = Some((x, x));
+                    zoom.last_press = Some(now);
+                }
+            } else if let Some((from, _)) = zoom.selection {
+                if mouse.pressed(MouseButton::Left) {
+                    zoom.selection = Some((from, x));
+                } else {
+                    zoom.selection = None;
+                    if (x - from).abs() * rect.width() >= MIN_SELECTION_PX {
+                        zoom.zoom_to(from, x, length);
+                    }
+                }
+            }
+        }
+    }
+
+    // Only touch the material when something changed, to avoid re-uploads
+    let (view_start, view_len) = zoom.view.unwrap_or((0, 0));
+    let selection = zoom.selection.map_or(Vec2::ZERO, |(from, to)| {
+        Vec2::new(from, to).clamp(Vec2::ZERO, Vec2::ONE)
+    });
+    let params = graph_mats.get(handle).map(|mat| &mat.params);
+    if params.is_some_and(|params| {
+        params.view_start != view_start
+            || params.view_len != view_len
+            || params.selection != selection
+    }) {
+        if let Some(mat) = graph_mats.get_mut(handle) {
+            mat.params.view_start = view_start;
+            mat.params.view_len = view_len;
+            mat.params.selection = selection;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_zoom_maps_to_history_samples() {
+        let mut zoom = GraphZoom::default();
+        zoom.zoom_to(0.5, 0.25, 101);
+        assert_eq!(zoom.view, Some((25, 26)));
+        // The second selection is within the first one
+        zoom.zoom_to(0.0, 0.2, 101);
+        assert_eq!(zoom.view, Some((25, 6)));
+        // Too narrow to draw a segment
+        zoom.zoom_to(0.5, 0.5, 101);
+        assert_eq!(zoom.view.map(|(_, len)| len), Some(2));
+        zoom.reset();
+        assert_eq!(zoom.view, None);
+    }
+}
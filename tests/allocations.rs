@@ -0,0 +1,159 @@
+//! Allocation tests for the HUD's per-frame update path.
+//!
+//! A counting global allocator checks that, once warmed up, sampling the
+//! metric providers and refreshing the graph, bars and labels performs no
+//! heap allocations.
+
+#![cfg(feature = "hud")]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use bevy::{ecs::schedule::ExecutorKind, prelude::*, render::settings::RenderCreation};
+use bevy_perf_hud::*;
+
+/// Global allocator counting the allocations made on the current thread
+/// while counting is enabled.
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record_allocation() {
+    if COUNTING.with(Cell::get) {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of allocations `f` makes on the current thread
+fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|count| count.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn app_with_headless_rendering() -> App {
+    let mut app = App::new();
+
+    let mut render_plugin = bevy::render::RenderPlugin::default();
+
+    if let RenderCreation::Automatic(settings) = &mut render_plugin.render_creation {
+        settings.backends = None;
+    }
+
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<bevy::winit::WinitPlugin>()
+            .disable::<bevy::log::LogPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: bevy::window::ExitCondition::DontExit,
+                ..default()
+            })
+            .set(render_plugin),
+    );
+
+    app
+}
+
+/// Schedule running the HUD's per-frame systems on their own, on the calling
+/// thread, so the rest of Bevy's frame is not counted.
+fn hud_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    schedule.add_systems(
+        (
+            (
+                detect_long_frames,
+                sample_diagnostics,
+                apply_metric_samples,
+                update_metric_stats,
+                track_budget_levels,
+                capture_threshold_screenshots,
+                run_perf_alerts,
+                send_telemetry_ticks,
+                detect_leaks,
+                detect_slow_frames,
+            )
+                .chain(),
+            (
+                capture_graph_samples,
+                update_graph_and_bars,
+                update_stats_panel,
+                update_traffic_light,
+                update_hud_capacity,
+                drag_hud,
+                update_hud_anchor,
+                update_hud_placement,
+                animate_hud_fades,
+                check_hud_budget,
+                record_perf_counters,
+            )
+                .chain(),
+        )
+            .chain(),
+    );
+    schedule
+}
+
+#[test]
+fn hud_update_does_not_allocate_in_steady_state() {
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    // sysinfo allocates while reading per-core CPU counters, outside the
+    // HUD's own path
+    {
+        let mut providers = app.world_mut().resource_mut::<MetricProviders>();
+        let mut core = 0;
+        while providers.remove(&cpu_core_usage_id(core)) {
+            core += 1;
+        }
+    }
+    let mut hud = hud_schedule();
+
+    // Warm up: spawn the HUD, fill the histories and grow every buffer once
+    for _ in 0..300 {
+        app.update();
+        hud.run(app.world_mut());
+    }
+
+    let mut allocations = 0;
+    for _ in 0..100 {
+        // Real frames in between keep the sampled values moving
+        app.update();
+        allocations += count_allocations(|| hud.run(app.world_mut()));
+    }
+    assert_eq!(allocations, 0);
+}
@@ -3,6 +3,8 @@
 //! These tests verify that the plugin integrates correctly with Bevy
 //! and that all systems work together properly.
 
+#![cfg(feature = "hud")]
+
 use bevy::prelude::*;
 use bevy::render::settings::RenderCreation;
 use bevy_perf_hud::{BevyPerfHudPlugin, PerfHudSettings};
@@ -10,17 +12,26 @@ use bevy_perf_hud::{BevyPerfHudPlugin, PerfHudSettings};
 fn app_with_headless_rendering() -> App {
     let mut app = App::new();
 
-    app.add_plugins(bevy::MinimalPlugins);
-    app.add_plugins(bevy::asset::AssetPlugin::default());
-
     let mut render_plugin = bevy::render::RenderPlugin::default();
 
     if let RenderCreation::Automatic(settings) = &mut render_plugin.render_creation {
         settings.backends = None;
     }
-    app.add_plugins(render_plugin);
 
-    app.add_plugins(bevy::ui::UiPlugin::default());
+    // Full default plugin set so the HUD's UI, text and camera systems have
+    // everything they read, minus the window event loop and global logger
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<bevy::winit::WinitPlugin>()
+            .disable::<bevy::log::LogPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: bevy::window::ExitCondition::DontExit,
+                ..default()
+            })
+            .set(render_plugin),
+    );
 
     app
 }
@@ -42,6 +53,9 @@ fn plugin_can_be_added_to_app() {
     assert!(app
         .world()
         .contains_resource::<bevy_perf_hud::HistoryBuffers>());
+    assert!(app
+        .world()
+        .contains_resource::<bevy_perf_hud::SampleAggregator>());
     assert!(app
         .world()
         .contains_resource::<bevy_perf_hud::GraphScaleState>());
@@ -81,3 +95,2731 @@ fn providers_are_registered_correctly() {
         .world()
         .contains_resource::<bevy_perf_hud::MetricProviders>());
 }
+
+#[test]
+fn snapshot_captures_graph_curves() {
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin);
+    app.insert_resource(PerfHudSettings::default());
+
+    let snapshot = bevy_perf_hud::PerfHudSnapshot::capture(app.world());
+
+    // One (still empty) history per configured curve, in curve order
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert_eq!(snapshot.curves.len(), settings.graph.curves.len());
+    for (curve, cfg) in snapshot.curves.iter().zip(&settings.graph.curves) {
+        assert_eq!(curve.metric_id, cfg.metric.id);
+        assert!(curve.values.is_empty());
+    }
+}
+
+#[test]
+fn hud_spawns_when_settings_are_inserted_in_startup() {
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin);
+
+    // Settings appear from a user Startup system, in no particular order
+    // relative to the plugin's own setup
+    app.add_systems(Startup, |mut commands: Commands| {
+        commands.insert_resource(PerfHudSettings::default());
+    });
+
+    app.update();
+    app.update();
+
+    assert!(app.world().contains_resource::<bevy_perf_hud::HudHandles>());
+}
+
+#[test]
+fn rebuild_keeps_material_and_entity_counts_constant() {
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let counts = |app: &mut App| {
+        let world = app.world_mut();
+        let graph_mats = world
+            .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>()
+            .len();
+        let bar_mats = world.resource::<Assets<bevy_perf_hud::BarMaterial>>().len();
+        let bars_mats = world
+            .resource::<Assets<bevy_perf_hud::BarsMaterial>>()
+            .len();
+        let nodes = world.query::<&Node>().iter(world).count();
+        (graph_mats, bar_mats, bars_mats, nodes)
+    };
+    let before = counts(&mut app);
+
+    for _ in 0..3 {
+        app.world_mut()
+            .send_event(bevy_perf_hud::PerfHudCommand::Rebuild);
+        app.update();
+        app.update();
+    }
+
+    assert_eq!(counts(&mut app), before);
+}
+
+#[test]
+fn cpu_core_bars_share_one_material_per_group() {
+    use bevy::tasks::available_parallelism;
+    use bevy_perf_hud::{cpu_core_usage_id, BarsMaterial, HudHandles, MetricSample, MAX_BARS};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.cpu_cores.enabled = true;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.world_mut()
+        .send_event(MetricSample::new(cpu_core_usage_id(0), 50.0));
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>();
+    let cores = available_parallelism();
+    assert_eq!(handles.cpu_core_bars.len(), cores.div_ceil(MAX_BARS));
+    let first = &handles.cpu_core_bars[0];
+    assert_eq!(first.metric_ids.len(), cores.min(MAX_BARS));
+    let mats = app.world().resource::<Assets<BarsMaterial>>();
+    assert_eq!(mats.get(&first.material).unwrap().params.values[0].x, 0.5);
+    assert_eq!(
+        app.world()
+            .resource::<Assets<bevy_perf_hud::BarMaterial>>()
+            .len(),
+        0
+    );
+}
+
+#[test]
+fn toggle_curve_group_command_hides_and_shows_group() {
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].group = Some("frame".into());
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let toggle = bevy_perf_hud::PerfHudCommand::ToggleCurveGroup("frame".into());
+    app.world_mut().send_event(toggle.clone());
+    app.update();
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert!(!settings.graph.is_group_visible("frame"));
+    assert!(!settings.graph.is_curve_visible(&settings.graph.curves[0]));
+    assert!(settings.graph.is_curve_visible(&settings.graph.curves[1]));
+
+    app.world_mut().send_event(toggle);
+    app.update();
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert!(settings.graph.hidden_groups.is_empty());
+}
+
+#[test]
+fn fixed_timestep_preset_uses_fixed_metrics() {
+    let settings = PerfHudSettings::fixed_timestep();
+    let ids: Vec<&str> = settings
+        .graph
+        .curves
+        .iter()
+        .map(|c| c.metric.id.as_str())
+        .chain(settings.bars.bars.iter().map(|b| b.metric.id.as_str()))
+        .collect();
+    for id in [
+        bevy_perf_hud::FIXED_DELTA_ID,
+        bevy_perf_hud::FIXED_UPDATE_TIME_ID,
+        bevy_perf_hud::FIXED_OVERSTEP_ID,
+        bevy_perf_hud::FIXED_STEPS_PER_FRAME_ID,
+    ] {
+        assert!(ids.contains(&id), "preset is missing {id}");
+    }
+
+    // Every metric of the preset has a registered provider
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin);
+    let providers = app.world().resource::<bevy_perf_hud::MetricProviders>();
+    assert!(ids.iter().all(|id| providers.contains(id)));
+}
+
+#[test]
+fn curves_are_drawn_by_z_index() {
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].z_index = 0;
+    settings.graph.curves.push(settings.graph.curves[1].clone());
+    settings.graph.curves[1].z_index = 5;
+
+    // Slot 1 goes on top, equal z-indices keep configuration order
+    assert_eq!(settings.graph.draw_order(), vec![0, 2, 1]);
+    let layers: Vec<usize> = settings.graph.curve_slots().map(|s| s.layer).collect();
+    assert_eq!(layers, vec![0, 2, 1]);
+    assert_eq!(
+        bevy_perf_hud::MultiLineGraphParams::pack_draw_order(&[0, 2, 1]),
+        0x120
+    );
+}
+
+#[test]
+fn hud_spawns_under_user_parent() {
+    let mut app = app_with_headless_rendering();
+    let panel = app.world_mut().spawn(Node::default()).id();
+    app.insert_resource(PerfHudSettings {
+        parent: Some(panel),
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let root = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .root
+        .expect("HUD root spawned");
+    let child_of = app
+        .world()
+        .get::<ChildOf>(root)
+        .expect("HUD root has a parent");
+    assert_eq!(child_of.parent(), panel);
+    assert_eq!(
+        app.world().get::<Node>(root).unwrap().position_type,
+        PositionType::Relative
+    );
+}
+
+#[test]
+fn panicking_provider_is_reported_as_provider_error() {
+    use bevy_perf_hud::{MetricSampleContext, PerfHudAppExt, PerfMetricProvider};
+
+    struct BrokenProvider;
+
+    impl PerfMetricProvider for BrokenProvider {
+        fn metric_id(&self) -> &str {
+            "broken"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            panic!("broken provider");
+        }
+    }
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].metric.id = "broken".into();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_provider(BrokenProvider);
+    app.update();
+    app.update();
+
+    let health = app.world().resource::<bevy_perf_hud::ProviderHealth>();
+    let stats = health.get("broken").expect("broken provider sampled");
+    assert_eq!(stats.panics, 2);
+    assert!(stats.is_failing());
+    let errors = app
+        .world()
+        .resource::<bevy_perf_hud::SampledValues>()
+        .get(bevy_perf_hud::PROVIDER_ERRORS_ID)
+        .unwrap();
+    assert!(errors >= 1.0);
+}
+
+#[test]
+fn event_count_metric_counts_events_per_frame() {
+    use bevy_perf_hud::PerfHudAppExt;
+
+    #[derive(Event)]
+    struct Ping;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_event_count_metric::<Ping>("events/ping");
+    app.update();
+
+    for _ in 0..3 {
+        app.world_mut().send_event(Ping);
+    }
+    // Counted at the end of this frame, sampled on the next one
+    app.update();
+    app.update();
+
+    let sampled = app.world().resource::<bevy_perf_hud::SampledValues>();
+    assert_eq!(sampled.get("events/ping"), Some(3.0));
+    assert!(sampled.get(bevy_perf_hud::WINDOW_EVENTS_ID).is_some());
+}
+
+#[test]
+fn metric_fn_plots_a_resource_field() {
+    use bevy_perf_hud::PerfHudAppExt;
+
+    #[derive(Resource)]
+    struct Score(u32);
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.insert_resource(Score(7));
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_fn("game/score", |world| world.resource::<Score>().0 as f32);
+    app.update();
+
+    let sampled = app.world().resource::<bevy_perf_hud::SampledValues>();
+    assert_eq!(sampled.get("game/score"), Some(7.0));
+
+    app.world_mut().resource_mut::<Score>().0 = 12;
+    app.update();
+    let sampled = app.world().resource::<bevy_perf_hud::SampledValues>();
+    assert_eq!(sampled.get("game/score"), Some(12.0));
+}
+
+#[test]
+fn disabled_hud_does_not_sample_providers() {
+    use bevy_perf_hud::{PerfHudAppExt, SampledValues};
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    let reads = Arc::new(AtomicU32::new(0));
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        enabled: false,
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    let counted = reads.clone();
+    app.add_perf_metric_fn("game/reads", move |_| {
+        counted.fetch_add(1, Ordering::Relaxed) as f32
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(reads.load(Ordering::Relaxed), 0);
+    let sampled = app.world().resource::<SampledValues>();
+    assert_eq!(sampled.get("fps"), None);
+    assert_eq!(
+        app.world()
+            .resource::<bevy_perf_hud::HistoryBuffers>()
+            .length,
+        0
+    );
+
+    app.world_mut().resource_mut::<PerfHudSettings>().enabled = true;
+    app.update();
+    assert_eq!(reads.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn pushed_metric_samples_feed_sampled_values() {
+    use bevy_perf_hud::{MetricSample, SampledValues};
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    app.world_mut().send_event_batch([
+        MetricSample::new("net/latency_ms", 40.0).at(Duration::from_millis(20)),
+        MetricSample::new("net/latency_ms", 30.0).at(Duration::from_millis(10)),
+    ]);
+    app.update();
+    let sampled = app.world().resource::<SampledValues>();
+    // The late sample measured earlier does not replace the newer one
+    assert_eq!(sampled.get("net/latency_ms"), Some(40.0));
+
+    // Without new samples the latest value is kept
+    app.update();
+    let sampled = app.world().resource::<SampledValues>();
+    assert_eq!(sampled.get_fresh("net/latency_ms"), Some(40.0));
+
+    app.world_mut()
+        .send_event(MetricSample::new("net/latency_ms", 55.0));
+    app.update();
+    let sampled = app.world().resource::<SampledValues>();
+    assert_eq!(sampled.get("net/latency_ms"), Some(55.0));
+}
+
+#[test]
+fn samples_pushed_in_one_frame_keep_their_range() {
+    use bevy_perf_hud::{HistoryBuffers, MetricSample, MetricStats, SampledValues};
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].metric.id = "net/latency_ms".into();
+    settings.graph.curves[0].candlestick = true;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.world_mut()
+        .resource_mut::<MetricStats>()
+        .register_window("net/latency_ms", Duration::from_secs(10));
+    app.update();
+
+    app.world_mut().send_event_batch([
+        MetricSample::new("net/latency_ms", 10.0),
+        MetricSample::new("net/latency_ms", 80.0),
+        MetricSample::new("net/latency_ms", 30.0),
+    ]);
+    app.update();
+
+    // The mean is shown, the burst stays visible as the sample's range
+    let sampled = app.world().resource::<SampledValues>();
+    assert_eq!(sampled.get("net/latency_ms"), Some(40.0));
+    assert_eq!(sampled.range("net/latency_ms"), Some((10.0, 80.0)));
+    let history = app.world().resource::<HistoryBuffers>();
+    let last = history.length as usize - 1;
+    assert_eq!(history.mins[0][last], 10.0);
+    assert_eq!(history.maxs[0][last], 80.0);
+    let stats = app.world().resource::<MetricStats>();
+    let window = stats
+        .window("net/latency_ms", Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(window.max(), Some(80.0));
+    assert_eq!(window.mean(), Some(40.0));
+
+    // The range only covers the frame the values were pushed in
+    app.update();
+    let sampled = app.world().resource::<SampledValues>();
+    assert_eq!(sampled.range("net/latency_ms"), Some((40.0, 40.0)));
+}
+
+#[test]
+fn log_y_scale_autoscales_on_the_logarithms() {
+    use bevy_perf_hud::{GraphScaleState, GraphYScale, HudHandles, MetricSample};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings {
+        instant: true,
+        ..default()
+    };
+    settings.graph.y_scale = GraphYScale::Log;
+    settings.graph.y_margin_frac = 0.0;
+    settings.graph.warmup_samples = 0;
+    let mut curve = settings.graph.curves[0].clone();
+    curve.metric.id = "game/allocated_bytes".into();
+    curve.autoscale = Some(true);
+    settings.graph.curves = vec![curve];
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.world_mut()
+            .send_event(MetricSample::new("game/allocated_bytes", 9999.0));
+        app.update();
+    }
+
+    // log10(1 + 9999) = 4 decades, with zero included at the bottom
+    let scale = *app.world().resource::<GraphScaleState>();
+    assert!((scale.max_y - 4.0).abs() < 1e-3, "max {}", scale.max_y);
+    assert_eq!(scale.min_y, 0.0);
+
+    // Tick labels show values, not logarithms
+    let handles = app.world().resource::<HudHandles>();
+    let top = *handles.y_tick_labels.last().unwrap();
+    assert_eq!(app.world().get::<Text>(top).unwrap().0, "9999");
+}
+
+#[test]
+fn settings_are_editable_through_reflection() {
+    use bevy::ecs::reflect::ReflectResource;
+    use bevy::reflect::GetPath;
+    use bevy_perf_hud::{BarScaleMode, BarScaleStates, MetricDefinition};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let registry = app.world().resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    for type_id in [
+        std::any::TypeId::of::<MetricDefinition>(),
+        std::any::TypeId::of::<BarScaleMode>(),
+        std::any::TypeId::of::<BarScaleStates>(),
+    ] {
+        assert!(registry.get(type_id).is_some());
+    }
+
+    // Edit a nested field the way an inspector would
+    let reflect_settings = registry
+        .get_type_data::<ReflectResource>(std::any::TypeId::of::<PerfHudSettings>())
+        .expect("settings are registered as a reflected resource");
+    let mut settings = reflect_settings
+        .reflect_mut(app.world_mut())
+        .expect("settings resource exists");
+    *settings
+        .reflect_path_mut("graph.size")
+        .unwrap()
+        .try_downcast_mut::<Vec2>()
+        .unwrap() = Vec2::new(200.0, 50.0);
+
+    assert_eq!(
+        app.world().resource::<PerfHudSettings>().graph.size,
+        Vec2::new(200.0, 50.0)
+    );
+}
+
+#[test]
+fn hud_over_budget_degrades_display_refresh() {
+    use bevy_perf_hud::{HistoryBuffers, HudBudgetState, HudStage, HudUpdateCost};
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.budget.enabled = true;
+    settings.budget.limit = Duration::from_nanos(1);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let cost = app.world().resource::<HudUpdateCost>();
+    assert!(cost.get(HudStage::Sample).is_some());
+    assert!(cost.total() >= cost.get(HudStage::Sample).unwrap());
+    let state = app.world().resource::<HudBudgetState>();
+    assert!(state.over_budget);
+    assert!(state.degraded);
+
+    // Skipped display refreshes still capture every sample into history
+    app.world_mut()
+        .resource_mut::<PerfHudSettings>()
+        .budget
+        .degraded_interval = 1000;
+    let before = app.world().resource::<HistoryBuffers>().total;
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<HistoryBuffers>().total, before + 5);
+
+    app.world_mut()
+        .resource_mut::<PerfHudSettings>()
+        .budget
+        .limit = Duration::from_secs(1);
+    app.update();
+    assert!(!app.world().resource::<HudBudgetState>().degraded);
+}
+
+#[test]
+fn graph_title_and_caption_are_spawned() {
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.title = Some("Frame pacing".into());
+    settings.graph.caption = Some("Release build, 1080p".into());
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let texts: Vec<String> = app
+        .world_mut()
+        .query::<&Text>()
+        .iter(app.world())
+        .map(|text| text.0.clone())
+        .collect();
+    assert!(texts.iter().any(|t| t == "Frame pacing"));
+    assert!(texts.iter().any(|t| t == "Release build, 1080p"));
+}
+
+#[test]
+fn input_latency_is_recorded_after_input() {
+    use bevy::input::{mouse::MouseButtonInput, ButtonState};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins((
+        BevyPerfHudPlugin,
+        bevy_perf_hud::InputLatencyDiagnosticsPlugin,
+    ));
+    app.update();
+    app.world_mut().send_event(MouseButtonInput {
+        button: MouseButton::Left,
+        state: ButtonState::Pressed,
+        window: Entity::PLACEHOLDER,
+    });
+    for _ in 0..4 {
+        app.update();
+    }
+
+    let latency = app
+        .world()
+        .resource::<bevy_perf_hud::SampledValues>()
+        .get(bevy_perf_hud::INPUT_LATENCY_ID)
+        .expect("input latency sampled");
+    assert!(latency >= 0.0);
+}
+
+#[test]
+fn alert_action_runs_once_when_metric_becomes_critical() {
+    use bevy_perf_hud::{MetricBudget, MetricSampleContext, PerfHudAppExt, PerfMetricProvider};
+
+    struct ConstantProvider;
+
+    impl PerfMetricProvider for ConstantProvider {
+        fn metric_id(&self) -> &str {
+            "constant"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            Some(100.0)
+        }
+    }
+
+    #[derive(Resource, Default)]
+    struct AlertRuns(u32);
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("constant", 20.0, 50.0));
+    app.insert_resource(settings);
+    app.init_resource::<AlertRuns>();
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_provider(ConstantProvider);
+    app.add_perf_alert("constant", |mut runs: ResMut<AlertRuns>| runs.0 += 1);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert_eq!(app.world().resource::<AlertRuns>().0, 1);
+}
+
+#[test]
+fn metric_sitting_on_a_threshold_does_not_flap() {
+    use bevy_perf_hud::{BudgetLevel, MetricBudget, MetricBudgetTransition, MetricSample};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("game/load", 20.0, 50.0));
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    let mut reader = app
+        .world_mut()
+        .resource_mut::<Events<MetricBudgetTransition>>()
+        .get_cursor();
+    // Within 5% of the warning threshold, then clearly back within budget
+    for value in [21.0, 19.5, 20.5, 19.5, 21.0, 18.0] {
+        app.world_mut()
+            .send_event(MetricSample::new("game/load", value));
+        app.update();
+    }
+
+    let events = app.world().resource::<Events<MetricBudgetTransition>>();
+    let levels: Vec<_> = reader.read(events).map(|t| t.level).collect();
+    assert_eq!(levels, [BudgetLevel::Warning, BudgetLevel::Ok]);
+}
+
+#[test]
+fn critical_metric_triggers_one_screenshot() {
+    use bevy_perf_hud::{
+        MetricBudget, MetricSampleContext, PerfHudAppExt, PerfHudScreenshot, PerfMetricProvider,
+    };
+
+    struct ConstantProvider;
+
+    impl PerfMetricProvider for ConstantProvider {
+        fn metric_id(&self) -> &str {
+            "game/constant"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            Some(100.0)
+        }
+    }
+
+    let directory =
+        std::env::temp_dir().join(format!("perf_hud_screenshots_{}", std::process::id()));
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("game/constant", 20.0, 50.0));
+    settings.screenshots.enabled = true;
+    settings.screenshots.metrics = vec!["game/constant".into()];
+    settings.screenshots.directory = directory.clone();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_provider(ConstantProvider);
+    let mut reader = app
+        .world_mut()
+        .resource_mut::<Events<PerfHudScreenshot>>()
+        .get_cursor();
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let events = app.world().resource::<Events<PerfHudScreenshot>>();
+    let screenshots: Vec<_> = reader.read(events).collect();
+    assert_eq!(screenshots.len(), 1);
+    assert_eq!(screenshots[0].metric_id, "game/constant");
+    assert_eq!(screenshots[0].threshold, 50.0);
+    assert!(screenshots[0].path.starts_with(&directory));
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn curve_over_budget_takes_critical_color() {
+    use bevy_perf_hud::{
+        MetricBudget, MetricSampleContext, MultiLineGraphMaterial, PerfHudAppExt,
+        PerfMetricProvider,
+    };
+
+    struct ConstantProvider;
+
+    impl PerfMetricProvider for ConstantProvider {
+        fn metric_id(&self) -> &str {
+            "constant"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            Some(100.0)
+        }
+    }
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].metric.id = "constant".into();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("constant", 50.0, 80.0));
+    let critical = settings.budget_colors.critical.to_linear().to_vec4();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_provider(ConstantProvider);
+    app.update();
+    app.update();
+
+    let handle = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let materials = app.world().resource::<Assets<MultiLineGraphMaterial>>();
+    let params = &materials.get(&handle).unwrap().params;
+    assert_eq!(params.colors[0], critical);
+    assert_eq!(params.budget_lines[0], Vec4::new(50.0, 80.0, 1.0, 0.0));
+}
+
+#[test]
+fn line_smoothing_reaches_the_graph_shader() {
+    use bevy_perf_hud::{HudHandles, LineSmoothing, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].line_smoothing = LineSmoothing::BSpline;
+    settings.graph.curves[1].line_smoothing = LineSmoothing::Linear;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    let handle = app
+        .world()
+        .resource::<HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let packed = app
+        .world()
+        .resource::<Assets<MultiLineGraphMaterial>>()
+        .get(&handle)
+        .unwrap()
+        .params
+        .line_smoothing;
+    assert_eq!(packed & 0b11, LineSmoothing::BSpline as u32);
+    assert_eq!((packed >> 2) & 0b11, LineSmoothing::Linear as u32);
+}
+
+#[test]
+fn curve_styles_reach_the_graph_shader() {
+    use bevy_perf_hud::{CurveStyle, HudHandles, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[1].style = CurveStyle::Dashed;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    let handle = app
+        .world()
+        .resource::<HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let material = |app: &App| {
+        app.world()
+            .resource::<Assets<MultiLineGraphMaterial>>()
+            .get(&handle)
+            .unwrap()
+            .params
+            .curve_styles
+    };
+    assert_eq!(material(&app), 0b01 << 2);
+
+    // Styles follow the settings without a rebuild
+    app.world_mut()
+        .resource_mut::<PerfHudSettings>()
+        .graph
+        .curves[0]
+        .style = CurveStyle::FilledArea;
+    app.update();
+    assert_eq!(material(&app), 0b11 | 0b01 << 2);
+}
+
+#[test]
+fn traffic_light_shows_the_budget_level() {
+    use bevy_perf_hud::{HudHandles, HudPreset, MetricBudget, PerfHudAppExt};
+
+    #[derive(Resource)]
+    struct Load(f32);
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = HudPreset::TrafficLight.settings();
+    settings.traffic_light.metric_id = "game/load".into();
+    settings.traffic_light.blink = None;
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("game/load", 50.0, 80.0));
+    let ok = settings.traffic_light.ok_color;
+    let critical = settings.budget_colors.critical;
+    app.insert_resource(settings);
+    app.insert_resource(Load(10.0));
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_fn("game/load", |world| world.resource::<Load>().0);
+    app.update();
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    assert!(handles.graph_entity.is_none());
+    assert!(handles.bars_root.is_none());
+    let light = handles.traffic_light.unwrap();
+    assert_eq!(app.world().get::<BackgroundColor>(light).unwrap().0, ok);
+
+    app.world_mut().resource_mut::<Load>().0 = 100.0;
+    app.update();
+    assert_eq!(
+        app.world().get::<BackgroundColor>(light).unwrap().0,
+        critical
+    );
+}
+
+#[test]
+fn fps_budget_follows_display_refresh_rate() {
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        fps_target: bevy_perf_hud::FpsTarget::Display,
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.insert_resource(bevy_perf_hud::DisplayRefreshRate { hz: Some(144.0) });
+    app.update();
+    app.update();
+
+    let handle = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let materials = app
+        .world()
+        .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>();
+    let params = &materials.get(&handle).unwrap().params;
+    // The default graph shows frame time, then FPS
+    let frame_ms = 1000.0 / 144.0;
+    assert_eq!(
+        params.budget_lines[0],
+        Vec4::new(frame_ms * 1.05, frame_ms * 2.0, 1.0, 0.0)
+    );
+    assert_eq!(
+        params.budget_lines[1],
+        Vec4::new(144.0 * 0.95, 144.0 * 0.5, 1.0, 0.0)
+    );
+}
+
+#[test]
+fn frame_metrics_have_no_budget_by_default() {
+    let settings = PerfHudSettings::default();
+
+    assert_eq!(settings.budget_thresholds("fps", Some(144.0)), None);
+    assert_eq!(settings.budget_thresholds("frame_time_ms", None), None);
+}
+
+#[test]
+fn telemetry_tick_aggregates_sampled_metrics() {
+    use bevy_perf_hud::{
+        MetricSampleContext, PerfHudAppExt, PerfMetricProvider, TelemetryStats, TelemetryTick,
+    };
+
+    struct ConstantProvider;
+
+    impl PerfMetricProvider for ConstantProvider {
+        fn metric_id(&self) -> &str {
+            "constant"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            Some(42.0)
+        }
+    }
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.telemetry.enabled = true;
+    settings.telemetry.interval = std::time::Duration::ZERO;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_provider(ConstantProvider);
+    app.update();
+
+    let events = app.world().resource::<Events<TelemetryTick>>();
+    let tick = events
+        .iter_current_update_events()
+        .last()
+        .expect("a telemetry tick is sent");
+    assert_eq!(
+        tick.metrics.get("constant"),
+        Some(&TelemetryStats {
+            mean: 42.0,
+            min: 42.0,
+            max: 42.0,
+        })
+    );
+}
+
+#[test]
+fn dragged_hud_position_survives_rebuild() {
+    use bevy_perf_hud::{HudDraggable, HudHandles, PerfHudCommand};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let root = app.world().resource::<HudHandles>().root.unwrap();
+    let mut draggable = HudDraggable::default();
+    draggable.position = Some(Vec2::new(120.0, 40.0));
+    app.world_mut().entity_mut(root).insert(draggable);
+    app.world_mut().send_event(PerfHudCommand::Rebuild);
+    app.update();
+    app.update();
+
+    let new_root = app.world().resource::<HudHandles>().root.unwrap();
+    assert_ne!(new_root, root);
+    let draggable = app.world().get::<HudDraggable>(new_root).unwrap();
+    assert_eq!(draggable.position, Some(Vec2::new(120.0, 40.0)));
+    assert!(!draggable.is_dragging());
+    let node = app.world().get::<Node>(new_root).unwrap();
+    assert_eq!((node.left, node.top), (Val::Px(120.0), Val::Px(40.0)));
+}
+
+#[test]
+fn graph_history_window_limits_samples() {
+    use bevy_perf_hud::{GraphHistory, HistoryBuffers};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.history = GraphHistory::Samples(8);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let history = app.world().resource::<HistoryBuffers>();
+    assert_eq!(history.capacity, 8);
+    assert_eq!(history.length, 8);
+}
+
+#[test]
+fn bar_with_rolling_average_is_split() {
+    use bevy_perf_hud::{BarsMaterial, HudHandles};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.bars.bars[0].rolling_average = Some(std::time::Duration::from_secs(1));
+    settings.bars.bars[0].show_value = Some(true);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    let bars = handles.bars_material.unwrap();
+    let params = &app
+        .world()
+        .resource::<Assets<BarsMaterial>>()
+        .get(&bars)
+        .unwrap()
+        .params;
+    assert_eq!(params.average_mask, 0b1);
+
+    let label = app.world().get::<Text>(handles.bar_labels[0]).unwrap();
+    assert!(label.0.contains("(avg "), "{}", label.0);
+}
+
+#[test]
+fn bar_trend_arrow_flags_a_rising_value() {
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_perf_hud::{BarTrend, HudHandles, PerfHudAppExt};
+    use std::time::Duration;
+
+    #[derive(Resource)]
+    struct Level(f32);
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    let mut settings = PerfHudSettings::default();
+    let bar = &mut settings.bars.bars[0];
+    bar.metric.id = "game/level".into();
+    bar.metric.unit = None;
+    bar.scale_mode = bevy_perf_hud::BarScaleMode::Fixed;
+    bar.min_value = 0.0;
+    bar.max_value = 100.0;
+    bar.show_value = Some(true);
+    bar.trend = Some(BarTrend::default());
+    app.insert_resource(settings);
+    app.insert_resource(Level(10.0));
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_perf_metric_fn("game/level", |world| world.resource::<Level>().0);
+
+    let label = |app: &App| {
+        let handles = app.world().resource::<HudHandles>();
+        app.world()
+            .get::<Text>(handles.bar_labels[0])
+            .unwrap()
+            .0
+            .clone()
+    };
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(label(&app).ends_with(" ="), "{}", label(&app));
+
+    // 10 units per 100 ms is well over 5% of the range per second
+    for _ in 0..5 {
+        app.world_mut().resource_mut::<Level>().0 += 10.0;
+        app.update();
+    }
+    assert!(label(&app).ends_with(" ^"), "{}", label(&app));
+}
+
+#[test]
+fn curves_over_capacity_are_reported() {
+    use bevy_perf_hud::{HudCapacity, HudCapacityKind, PerfHudCapacityExceeded, MAX_CURVES};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    let extra = settings.graph.curves[0].clone();
+    settings.graph.curves.resize(MAX_CURVES + 2, extra);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    let capacity = app.world().resource::<HudCapacity>();
+    assert_eq!(capacity.curves.used, MAX_CURVES);
+    assert_eq!(capacity.curves.max, MAX_CURVES);
+    assert_eq!(capacity.materials, 2);
+
+    // Reported once, not every frame
+    let events = app.world().resource::<Events<PerfHudCapacityExceeded>>();
+    let reported: Vec<_> = events.get_cursor().read(events).cloned().collect();
+    assert_eq!(
+        reported,
+        vec![PerfHudCapacityExceeded {
+            kind: HudCapacityKind::Curves,
+            requested: MAX_CURVES + 2,
+            max: MAX_CURVES,
+        }]
+    );
+}
+
+#[test]
+fn time_axis_labels_show_the_graph_window() {
+    use bevy_perf_hud::HudHandles;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.time_axis_labels = true;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let handles = app.world().resource::<HudHandles>();
+    assert!(handles.frame_axis_labels.is_none());
+    let [oldest, newest] = handles.time_axis_labels.unwrap();
+    let text = |entity| app.world().get::<Text>(entity).unwrap().0.clone();
+    assert_eq!(text(newest), "now");
+    let oldest = text(oldest);
+    assert!(
+        oldest.starts_with('-') && oldest.ends_with('s'),
+        "window start: {oldest:?}"
+    );
+}
+
+#[test]
+fn frame_axis_takes_one_sample_per_frame() {
+    use bevy_perf_hud::{GraphXAxis, HistoryBuffers, HudHandles};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.x_axis = GraphXAxis::Frames;
+    // Ignored when counting frames
+    settings.graph.samples_per_point = 4;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let history = app.world().resource::<HistoryBuffers>();
+    let len = history.length as usize;
+    assert!(len >= 9, "{len} samples");
+    assert_eq!(history.frames[len - 1] - history.frames[0], len as u32 - 1);
+    let newest = history.frames[len - 1];
+
+    let [_, newest_label] = app
+        .world()
+        .resource::<HudHandles>()
+        .frame_axis_labels
+        .unwrap();
+    let text = app.world().get::<Text>(newest_label).unwrap();
+    assert_eq!(text.0, format!("frame {newest}"));
+}
+
+#[test]
+fn y_tick_labels_follow_the_graph_range() {
+    use bevy_perf_hud::{HudHandles, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.y_ticks = 3;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    assert_eq!(handles.y_tick_labels.len(), 3);
+    let ticks: Vec<f32> = handles
+        .y_tick_labels
+        .iter()
+        .map(|&entity| app.world().get::<Text>(entity).unwrap().0.parse().unwrap())
+        .collect();
+    assert!(ticks[0] < ticks[2], "ticks go up: {ticks:?}");
+    assert!((ticks[1] - (ticks[0] + ticks[2]) / 2.0).abs() <= 1.0);
+
+    let params = &app
+        .world()
+        .resource::<Assets<MultiLineGraphMaterial>>()
+        .get(handles.graph_material.as_ref().unwrap())
+        .unwrap()
+        .params;
+    assert_eq!(params.grid_lines, 3);
+    assert!(params.grid_color.w > 0.0);
+}
+
+#[test]
+fn threshold_shading_falls_back_to_graph_default() {
+    use bevy_perf_hud::{MultiLineGraphMaterial, ThresholdShading};
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curve_defaults.shading = Some(ThresholdShading::below(30.0, Color::WHITE));
+    settings.graph.curves[0].shading = Some(ThresholdShading::above(16.6, Color::WHITE));
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    let handle = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let params = &app
+        .world()
+        .resource::<Assets<MultiLineGraphMaterial>>()
+        .get(&handle)
+        .unwrap()
+        .params;
+    assert_eq!(params.shading[0], Vec4::new(16.6, 1.0, 1.0, 0.0));
+    assert_eq!(params.shading[1], Vec4::new(30.0, -1.0, 1.0, 0.0));
+    assert_eq!(params.shading[2], Vec4::ZERO);
+}
+
+#[test]
+fn shared_curve_unit_is_shown_once_on_the_graph() {
+    use bevy_perf_hud::HudHandles;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    for curve in &mut settings.graph.curves {
+        curve.metric.unit = Some("ms".into());
+    }
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let text = |app: &App, entity| app.world().get::<Text>(entity).unwrap().0.clone();
+    let handles = app.world().resource::<HudHandles>().clone();
+    let unit_label = handles.graph_unit_label.unwrap();
+    let first_label = handles.graph_labels[0].entity;
+    assert_eq!(text(&app, unit_label), "ms");
+    assert!(!text(&app, first_label).contains("ms"));
+
+    // Once the units differ every label shows its own again
+    app.world_mut()
+        .resource_mut::<PerfHudSettings>()
+        .graph
+        .curves[1]
+        .metric
+        .unit = Some("fps".into());
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(text(&app, unit_label), "");
+    assert!(text(&app, first_label).ends_with(" ms"));
+}
+
+#[test]
+fn slow_frames_are_listed_newest_first() {
+    use bevy_perf_hud::{HudHandles, SlowFrameLog};
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.slow_frames.enabled = true;
+    settings.slow_frames.max_entries = 3;
+    settings.slow_frames.threshold = Some(Duration::from_millis(500));
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    assert_eq!(handles.slow_frame_labels.len(), 3);
+    let text = |app: &App, row: usize| {
+        app.world()
+            .get::<Text>(handles.slow_frame_labels[row])
+            .unwrap()
+            .0
+            .clone()
+    };
+    assert_eq!(text(&app, 0), "no slow frames");
+
+    // Every frame counts as slow from now on
+    app.world_mut()
+        .resource_mut::<PerfHudSettings>()
+        .slow_frames
+        .threshold = Some(Duration::ZERO);
+    for _ in 0..5 {
+        app.update();
+    }
+    let log = app.world().resource::<SlowFrameLog>();
+    assert_eq!(log.len(), 3);
+    let frames: Vec<u32> = log.iter().map(|entry| entry.frame).collect();
+    assert!(
+        frames.windows(2).all(|pair| pair[0] > pair[1]),
+        "{frames:?}"
+    );
+    let row = text(&app, 0);
+    assert!(
+        row.ends_with(" ms") && row.as_bytes()[2] == b':',
+        "row {row:?}"
+    );
+}
+
+#[test]
+fn stats_panel_shows_percentiles_of_graph_curves() {
+    use bevy_perf_hud::HudHandles;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.stats_panel.enabled = true;
+    settings.stats_panel.metrics = vec!["frame_time_ms".into(), "not_a_curve".into()];
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let rows: Vec<String> = app
+        .world()
+        .resource::<HudHandles>()
+        .stats_labels
+        .iter()
+        .map(|label| app.world().get::<Text>(label.entity).unwrap().0.clone())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert!(
+        rows[0].contains(" p50 ") && rows[0].contains(" max "),
+        "{}",
+        rows[0]
+    );
+    assert_eq!(rows[1], "not_a_curve: not on the graph");
+}
+
+#[test]
+fn auto_curves_follow_registered_metrics() {
+    use bevy_perf_hud::{
+        AutoCurves, HudHandles, MetricProviders, MetricSampleContext, PerfHudAppExt,
+        PerfMetricProvider,
+    };
+
+    struct Probe;
+
+    impl PerfMetricProvider for Probe {
+        fn metric_id(&self) -> &str {
+            "fixed/probe"
+        }
+
+        fn sample(&mut self, _ctx: MetricSampleContext) -> Option<f32> {
+            Some(1.0)
+        }
+    }
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves.clear();
+    settings.graph.auto_curves = Some(AutoCurves {
+        follow_registrations: true,
+        ..AutoCurves::matching(|id| id.starts_with("fixed/"))
+    });
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let curve_ids = |app: &App| -> Vec<String> {
+        let settings = app.world().resource::<PerfHudSettings>();
+        settings
+            .graph
+            .curves
+            .iter()
+            .map(|c| c.metric.id.clone())
+            .collect()
+    };
+    assert_eq!(
+        curve_ids(&app),
+        [
+            "fixed/delta_ms",
+            "fixed/steps_per_frame",
+            "fixed/overstep_ms",
+            "fixed/update_time_ms"
+        ]
+    );
+    assert_eq!(app.world().resource::<HudHandles>().graph_labels.len(), 4);
+
+    // A metric registered later gets a curve, and the HUD is rebuilt to show it
+    app.add_perf_metric_provider(Probe);
+    assert!(app
+        .world()
+        .resource::<MetricProviders>()
+        .contains("fixed/probe"));
+    app.update();
+    app.update();
+    assert_eq!(
+        curve_ids(&app).last().map(String::as_str),
+        Some("fixed/probe")
+    );
+    assert_eq!(app.world().resource::<HudHandles>().graph_labels.len(), 5);
+}
+
+#[test]
+fn bar_backgrounds_follow_row_tint_and_overrides() {
+    use bevy_perf_hud::{BarsMaterial, HudHandles};
+
+    let tint = Color::srgba(0.2, 0.2, 0.2, 0.6);
+    let own = Color::srgba(0.5, 0.0, 0.0, 0.6);
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.bars.row_tint = Some(tint);
+    settings.bars.bars[1].bg_color = Some(own);
+    let shared = settings.bars.bg_color;
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    // Two columns: bars 0 and 1 share the first row, bar 2 starts the second
+    let handles = app.world().resource::<HudHandles>().clone();
+    assert_eq!(handles.bar_rows, [0, 0, 1]);
+    let params = &app
+        .world()
+        .resource::<Assets<BarsMaterial>>()
+        .get(&handles.bars_material.unwrap())
+        .unwrap()
+        .params;
+    assert_eq!(params.bg_colors[0], shared.to_linear().to_vec4());
+    assert_eq!(params.bg_colors[1], own.to_linear().to_vec4());
+    assert_eq!(params.bg_colors[2], tint.to_linear().to_vec4());
+}
+
+#[test]
+fn presets_spawn_their_layout() {
+    use bevy_perf_hud::{spawn_perf_hud, HudHandles, HudPreset};
+
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_systems(Startup, |mut commands: Commands| {
+        spawn_perf_hud(&mut commands, HudPreset::Minimal);
+    });
+    app.update();
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>();
+    assert_eq!(handles.graph_labels.len(), 1);
+    assert!(handles.bar_labels.is_empty());
+    assert!(handles.stats_labels.is_empty());
+
+    // Switching preset on a shown HUD rebuilds it
+    app.add_systems(Update, |mut commands: Commands, mut done: Local<bool>| {
+        if !std::mem::replace(&mut *done, true) {
+            spawn_perf_hud(&mut commands, HudPreset::Detailed);
+        }
+    });
+    app.update();
+    app.update();
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>();
+    assert_eq!(handles.graph_labels.len(), 2);
+    assert_eq!(handles.bar_labels.len(), 3);
+    assert_eq!(handles.stats_labels.len(), 2);
+}
+
+#[test]
+fn right_anchored_hud_follows_window_resize() {
+    use bevy::ui::ComputedNode;
+    use bevy::window::{PrimaryWindow, WindowResized, WindowResolution};
+    use bevy_perf_hud::{HudAnchor, HudHandles};
+
+    let mut app = app_with_headless_rendering();
+    let window = app
+        .world_mut()
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800.0, 600.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    app.insert_resource(PerfHudSettings {
+        anchor: HudAnchor::TopRight,
+        origin: Vec2::new(16.0, 8.0),
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let root = app.world().resource::<HudHandles>().root.unwrap();
+    let left = |app: &App| {
+        let node = app.world().get::<Node>(root).unwrap();
+        assert_eq!(node.top, Val::Px(8.0));
+        match node.left {
+            Val::Px(left) => left,
+            other => panic!("unexpected left {other:?}"),
+        }
+    };
+    let computed = app.world().get::<ComputedNode>(root).unwrap();
+    let width = computed.size().x * computed.inverse_scale_factor();
+    assert!(width > 0.0);
+    assert_eq!(left(&app), 800.0 - width - 16.0);
+
+    app.world_mut()
+        .get_mut::<Window>(window)
+        .unwrap()
+        .resolution
+        .set(1000.0, 600.0);
+    app.world_mut().send_event(WindowResized {
+        window,
+        width: 1000.0,
+        height: 600.0,
+    });
+    app.update();
+    assert_eq!(left(&app), 1000.0 - width - 16.0);
+}
+
+#[test]
+fn window_relative_graph_scales_with_window() {
+    use bevy::ui::ComputedNode;
+    use bevy::window::{PrimaryWindow, WindowResized, WindowResolution};
+    use bevy_perf_hud::{HudHandles, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    let window = app
+        .world_mut()
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800.0, 600.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.size_percent = Some(Vec2::new(50.0, 10.0));
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    let graph = handles.graph_entity.unwrap();
+    let graph_size = |app: &App| {
+        let computed = app.world().get::<ComputedNode>(graph).unwrap();
+        computed.size() * computed.inverse_scale_factor()
+    };
+    let border_uv_x = |app: &App| {
+        let materials = app.world().resource::<Assets<MultiLineGraphMaterial>>();
+        let material = materials.get(handles.graph_material.as_ref().unwrap());
+        material.unwrap().params.border_thickness_uv_x
+    };
+    let border = app
+        .world()
+        .resource::<PerfHudSettings>()
+        .graph
+        .border
+        .thickness;
+    assert_eq!(graph_size(&app), Vec2::new(400.0, 60.0));
+    assert_eq!(border_uv_x(&app), border / 400.0);
+
+    app.world_mut()
+        .get_mut::<Window>(window)
+        .unwrap()
+        .resolution
+        .set(1000.0, 600.0);
+    app.world_mut().send_event(WindowResized {
+        window,
+        width: 1000.0,
+        height: 600.0,
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(graph_size(&app), Vec2::new(500.0, 60.0));
+    assert_eq!(border_uv_x(&app), border / 500.0);
+}
+
+#[test]
+fn control_strip_records_exports_and_cycles_presets() {
+    use bevy_perf_hud::{HudControlButton, HudHandles, HudPreset, PerfHudCommand, SampleRecorder};
+
+    let path = std::env::temp_dir().join(format!("perf_hud_controls_{}.csv", std::process::id()));
+    let mut settings = PerfHudSettings {
+        show_controls: true,
+        ..default()
+    };
+    settings.controls.recording_path = path.clone();
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let buttons = app.world().resource::<HudHandles>().control_buttons.clone();
+    let commands: Vec<_> = buttons
+        .iter()
+        .map(|&button| {
+            app.world()
+                .get::<HudControlButton>(button)
+                .unwrap()
+                .0
+                .clone()
+        })
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            PerfHudCommand::TogglePause,
+            PerfHudCommand::ToggleSampleRecording(path.clone()),
+            PerfHudCommand::ExportBundle("perf_hud_report".into()),
+            PerfHudCommand::CyclePreset,
+            PerfHudCommand::Rebuild,
+        ]
+    );
+    let record_caption = |app: &App| {
+        let children = app.world().get::<Children>(buttons[1]).unwrap();
+        app.world().get::<Text>(children[0]).unwrap().0.clone()
+    };
+    assert_eq!(record_caption(&app), "Record");
+
+    app.world_mut()
+        .send_event(PerfHudCommand::ToggleSampleRecording(path.clone()));
+    app.update();
+    app.update();
+    assert!(app.world().contains_resource::<SampleRecorder>());
+    assert_eq!(record_caption(&app), "Stop");
+
+    app.world_mut()
+        .send_event(PerfHudCommand::ToggleSampleRecording(path.clone()));
+    app.update();
+    app.update();
+    assert!(!app.world().contains_resource::<SampleRecorder>());
+    assert_eq!(record_caption(&app), "Record");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !path.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(path.exists());
+    let _ = std::fs::remove_file(&path);
+
+    // Custom settings cycle to the default preset first, keeping the strip
+    for expected in [
+        HudPreset::Compact,
+        HudPreset::Detailed,
+        HudPreset::TrafficLight,
+        HudPreset::Minimal,
+    ] {
+        app.world_mut().send_event(PerfHudCommand::CyclePreset);
+        app.update();
+        let settings = app.world().resource::<PerfHudSettings>();
+        assert_eq!(settings.preset, Some(expected));
+        assert!(settings.show_controls);
+        assert_eq!(settings.controls.recording_path, path);
+    }
+}
+
+#[test]
+fn hud_passes_pointer_input_through_unless_opted_in() {
+    use bevy::ui::FocusPolicy;
+    use bevy_perf_hud::{HudControlButton, HudHandles, HudInteraction};
+
+    let spawn = |interaction: HudInteraction| {
+        let mut app = app_with_headless_rendering();
+        app.insert_resource(PerfHudSettings {
+            show_controls: true,
+            interaction,
+            ..default()
+        });
+        app.add_plugins(BevyPerfHudPlugin);
+        app.update();
+        app.update();
+        app
+    };
+
+    let mut app = spawn(HudInteraction::default());
+    let mut buttons = app
+        .world_mut()
+        .query_filtered::<&FocusPolicy, With<HudControlButton>>();
+    assert!(buttons
+        .iter(app.world())
+        .all(|policy| *policy == FocusPolicy::Pass));
+    let root = app.world().resource::<HudHandles>().root.unwrap();
+    assert_eq!(
+        app.world().get::<FocusPolicy>(root),
+        Some(&FocusPolicy::Pass)
+    );
+
+    let mut app = spawn(HudInteraction {
+        controls: true,
+        legend: true,
+        zoom: false,
+        tooltip: false,
+    });
+    let mut buttons = app
+        .world_mut()
+        .query_filtered::<&FocusPolicy, With<HudControlButton>>();
+    assert!(buttons
+        .iter(app.world())
+        .all(|policy| *policy == FocusPolicy::Block));
+    let label = app.world().resource::<HudHandles>().graph_labels[0].entity;
+    assert_eq!(
+        app.world().get::<FocusPolicy>(label),
+        Some(&FocusPolicy::Block)
+    );
+    assert!(app.world().get::<Interaction>(label).is_some());
+}
+
+#[test]
+fn export_bundle_writes_bug_report_files() {
+    use bevy_perf_hud::{
+        PerfHudBundleExported, PerfHudCommand, BUNDLE_BUILD_INFO_FILE, BUNDLE_METRICS_FILE,
+        BUNDLE_SETTINGS_FILE, BUNDLE_SPIKES_FILE,
+    };
+
+    let directory = std::env::temp_dir().join(format!("perf_hud_bundle_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    #[derive(Resource, Default)]
+    struct Exported(Vec<PerfHudBundleExported>);
+    app.init_resource::<Exported>().add_systems(
+        Update,
+        |mut events: EventReader<PerfHudBundleExported>, mut exported: ResMut<Exported>| {
+            exported.0.extend(events.read().cloned());
+        },
+    );
+    for _ in 0..5 {
+        app.update();
+    }
+    app.world_mut()
+        .send_event(PerfHudCommand::ExportBundle(directory.clone()));
+    app.update();
+    app.update();
+
+    let exported = &app.world().resource::<Exported>().0;
+    assert_eq!(exported.len(), 1);
+    // No primary window in the headless app, so no screenshot, and the
+    // settings are only written with the `serde` feature
+    let mut files = vec![BUNDLE_METRICS_FILE, BUNDLE_SPIKES_FILE];
+    if cfg!(feature = "serde") {
+        files.push(BUNDLE_SETTINGS_FILE);
+    }
+    files.push(BUNDLE_BUILD_INFO_FILE);
+    assert_eq!(
+        exported[0].files,
+        files
+            .iter()
+            .map(|name| directory.join(name))
+            .collect::<Vec<_>>()
+    );
+    let csv = std::fs::read_to_string(directory.join(BUNDLE_METRICS_FILE)).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("frame,frame_time_ms.min,frame_time_ms.max,fps.min,fps.max")
+    );
+    assert!(lines.next().is_some());
+    #[cfg(feature = "serde")]
+    {
+        let settings = std::fs::read_to_string(directory.join(BUNDLE_SETTINGS_FILE)).unwrap();
+        let settings: PerfHudSettings = ron::from_str(&settings).unwrap();
+        assert_eq!(settings.origin, PerfHudSettings::default().origin);
+    }
+    let build_info = std::fs::read_to_string(directory.join(BUNDLE_BUILD_INFO_FILE)).unwrap();
+    assert!(build_info.contains("session_id: "));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn chrome_trace_export_writes_recorded_counters() {
+    use bevy_perf_hud::{ChromeTraceRecorder, PerfHudCommand};
+
+    let path = std::env::temp_dir().join(format!("perf_hud_trace_{}.json", std::process::id()));
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.chrome_trace.enabled = true;
+    settings.chrome_trace.metrics = vec!["fps".into()];
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+    let recorded = app.world().resource::<ChromeTraceRecorder>().len();
+    assert!(recorded > 0);
+
+    app.world_mut()
+        .send_event(PerfHudCommand::ExportChromeTrace(path.clone()));
+    app.update();
+
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(json.starts_with("{\"traceEvents\":["));
+    assert!(json.matches("\"ph\":\"C\"").count() >= recorded);
+    assert!(!json.contains("frame_time_ms"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn recorded_samples_replay_instead_of_providers() {
+    use bevy_perf_hud::{PerfHudCommand, SampleReplay, SampledValues};
+
+    let path = std::env::temp_dir().join(format!("perf_hud_samples_{}.csv", std::process::id()));
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.world_mut()
+        .send_event(PerfHudCommand::StartSampleRecording);
+    for _ in 0..5 {
+        app.update();
+    }
+    app.world_mut()
+        .send_event(PerfHudCommand::SaveSampleRecording(path.clone()));
+    app.update();
+
+    // The recording is written on the IO task pool
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !path.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let header = csv.lines().next().unwrap();
+    assert!(header.starts_with("time,"));
+    assert!(header.split(',').any(|column| column == "fps"));
+    assert!(csv.lines().count() > 2);
+
+    // Replace the recording with known values; the FPS provider is not sampled
+    std::fs::write(&path, "time,fps\n0.0,42\n").unwrap();
+    app.world_mut()
+        .send_event(PerfHudCommand::ReplaySamples(path.clone()));
+    app.update();
+    app.update();
+    assert!(app.world().contains_resource::<SampleReplay>());
+    assert_eq!(
+        app.world().resource::<SampledValues>().get_fresh("fps"),
+        Some(42.0)
+    );
+
+    app.world_mut().send_event(PerfHudCommand::StopReplay);
+    app.update();
+    assert!(!app.world().contains_resource::<SampleReplay>());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn graph_zoom_remaps_the_shader_window_while_paused() {
+    use bevy_perf_hud::{
+        GraphZoom, HudHandles, HudInteraction, HudPauseState, MultiLineGraphMaterial,
+    };
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        interaction: HudInteraction {
+            zoom: true,
+            ..default()
+        },
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+    let handle = app
+        .world()
+        .resource::<HudHandles>()
+        .graph_material
+        .clone()
+        .unwrap();
+    let view = |app: &App| {
+        let params = &app
+            .world()
+            .resource::<Assets<MultiLineGraphMaterial>>()
+            .get(&handle)
+            .unwrap()
+            .params;
+        (params.view_start, params.view_len)
+    };
+
+    app.world_mut().resource_mut::<HudPauseState>().paused = true;
+    let length = app
+        .world()
+        .resource::<Assets<MultiLineGraphMaterial>>()
+        .get(&handle)
+        .unwrap()
+        .params
+        .length;
+    app.world_mut()
+        .resource_mut::<GraphZoom>()
+        .zoom_to(0.0, 0.5, length);
+    app.update();
+    let (start, len) = view(&app);
+    assert_eq!(start, 0);
+    assert!(len >= 2 && len < length, "zoomed to {len} of {length}");
+
+    // Resuming updates shows the whole history again
+    app.world_mut().resource_mut::<HudPauseState>().paused = false;
+    app.update();
+    assert_eq!(view(&app), (0, 0));
+    assert_eq!(app.world().resource::<GraphZoom>().view, None);
+}
+
+#[test]
+fn frozen_graph_stops_recording_history() {
+    use bevy_perf_hud::{HistoryBuffers, PerfHudCommand};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        instant: true,
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    app.world_mut()
+        .send_event(PerfHudCommand::ToggleGraphFreeze);
+    app.update();
+    let frozen = app.world().resource::<HistoryBuffers>().length;
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<HistoryBuffers>().length, frozen);
+
+    app.world_mut()
+        .send_event(PerfHudCommand::ToggleGraphFreeze);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().resource::<HistoryBuffers>().length > frozen);
+}
+
+#[test]
+fn hovering_the_graph_shows_a_value_tooltip() {
+    use bevy::ui::ComputedNode;
+    use bevy::window::{PrimaryWindow, WindowResolution};
+    use bevy_perf_hud::{HudHandles, HudInteraction, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    let window = app
+        .world_mut()
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800.0, 600.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    app.insert_resource(PerfHudSettings {
+        instant: true,
+        interaction: HudInteraction {
+            tooltip: true,
+            ..default()
+        },
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    let tooltip = handles.graph_tooltip.unwrap();
+    let cursor_x = |app: &App| {
+        let materials = app.world().resource::<Assets<MultiLineGraphMaterial>>();
+        let material = materials.get(handles.graph_material.as_ref().unwrap());
+        material.unwrap().params.cursor_x
+    };
+    assert_eq!(
+        app.world().get::<Visibility>(tooltip),
+        Some(&Visibility::Hidden)
+    );
+    assert!(cursor_x(&app) < 0.0);
+
+    let graph = handles.graph_entity.unwrap();
+    let center = app
+        .world()
+        .get::<GlobalTransform>(graph)
+        .unwrap()
+        .translation()
+        .truncate();
+    assert!(app.world().get::<ComputedNode>(graph).unwrap().size().x > 0.0);
+    let cursor_to = |app: &mut App, position: Option<Vec2>| {
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_physical_cursor_position(position.map(|p| p.as_dvec2()));
+        app.update();
+    };
+
+    cursor_to(&mut app, Some(center));
+    assert_eq!(
+        app.world().get::<Visibility>(tooltip),
+        Some(&Visibility::Inherited)
+    );
+    assert!((0.0..=1.0).contains(&cursor_x(&app)));
+    assert!(!app.world().get::<Text>(tooltip).unwrap().0.is_empty());
+
+    cursor_to(&mut app, None);
+    assert_eq!(
+        app.world().get::<Visibility>(tooltip),
+        Some(&Visibility::Hidden)
+    );
+    assert!(cursor_x(&app) < 0.0);
+}
+
+#[test]
+fn text_backgrounds_put_a_chip_behind_each_label() {
+    use bevy::ui::BorderRadius;
+    use bevy_perf_hud::{HudHandles, HudTextBackground};
+
+    let mut app = app_with_headless_rendering();
+    let background = HudTextBackground {
+        color: Some(Color::srgb(0.1, 0.2, 0.3)),
+        opacity: 0.5,
+        ..default()
+    };
+    app.insert_resource(PerfHudSettings {
+        text_background: Some(background),
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    let labels: Vec<Entity> = handles
+        .graph_labels
+        .iter()
+        .map(|label| label.entity)
+        .chain(handles.bar_labels.iter().copied())
+        .collect();
+    assert!(!labels.is_empty());
+    for label in labels {
+        assert_eq!(
+            app.world().get::<BackgroundColor>(label),
+            Some(&BackgroundColor(Color::srgba(0.1, 0.2, 0.3, 0.5)))
+        );
+        assert!(app.world().get::<BorderRadius>(label).is_some());
+        assert_eq!(
+            app.world().get::<Node>(label).unwrap().padding,
+            UiRect::axes(Val::Px(3.0), Val::Px(1.0))
+        );
+    }
+}
+
+#[test]
+fn frame_unit_toggle_switches_the_primary_frame_metric() {
+    use bevy_perf_hud::{
+        HistoryBuffers, HudHandles, MetricBudget, PerfHudCommand, ThresholdShading,
+    };
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("frame_time_ms", 20.0, 50.0));
+    settings.graph.curve_defaults.shading = Some(ThresholdShading::above(25.0, Color::WHITE));
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+    let curves = |app: &App| -> Vec<(String, bool)> {
+        let settings = app.world().resource::<PerfHudSettings>();
+        settings
+            .graph
+            .curves
+            .iter()
+            .map(|curve| (curve.metric.id.clone(), curve.enabled))
+            .collect()
+    };
+    let before = curves(&app);
+    // The default graph shows frame time first, then FPS
+    assert_eq!(before[0], ("frame_time_ms".to_owned(), true));
+    assert_eq!(before[1], ("fps".to_owned(), true));
+    let color = app.world().resource::<PerfHudSettings>().graph.curves[0]
+        .metric
+        .color;
+    app.world_mut().resource_mut::<HistoryBuffers>().values[0][0] = 20.0;
+
+    app.world_mut().send_event(PerfHudCommand::ToggleFrameUnit);
+    app.update();
+    let after = curves(&app);
+    // Only the primary metric switches; the FPS curve it duplicates is hidden
+    assert_eq!(after[0], ("fps".to_owned(), true));
+    assert_eq!(after[1], ("fps".to_owned(), false));
+    let settings = app.world().resource::<PerfHudSettings>();
+    let curve = &settings.graph.curves[0];
+    assert_eq!(curve.metric.color, color);
+    let shading = curve.shading.expect("converted default shading");
+    assert!(shading.below);
+    assert_eq!(shading.threshold, 40.0);
+    let budget = &settings.metric_budgets[0];
+    assert_eq!(budget.metric_id, "fps");
+    assert_eq!((budget.warning, budget.critical), (50.0, 20.0));
+    assert!(budget.lower_is_worse);
+    assert_eq!(
+        app.world().resource::<HudHandles>().graph_labels[0].metric_id,
+        "fps"
+    );
+    assert_eq!(app.world().resource::<HistoryBuffers>().values[0][0], 50.0);
+
+    // Toggling again restores every switched entry
+    app.world_mut().send_event(PerfHudCommand::ToggleFrameUnit);
+    app.update();
+    assert_eq!(curves(&app), before);
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert!(settings.graph.curves[0].shading.is_none());
+    assert_eq!(settings.metric_budgets[0].metric_id, "frame_time_ms");
+    assert_eq!(
+        app.world().resource::<HudHandles>().graph_labels[0].metric_id,
+        "frame_time_ms"
+    );
+    assert_eq!(app.world().resource::<HistoryBuffers>().values[0][0], 20.0);
+}
+
+#[test]
+fn frame_unit_toggle_after_a_preset_switches_the_new_preset() {
+    use bevy_perf_hud::{FrameUnitToggle, HudHandles, PerfHudCommand};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..5 {
+        app.update();
+    }
+    let curves = |app: &App| -> Vec<(String, bool)> {
+        let settings = app.world().resource::<PerfHudSettings>();
+        settings
+            .graph
+            .curves
+            .iter()
+            .map(|curve| (curve.metric.id.clone(), curve.enabled))
+            .collect()
+    };
+
+    app.world_mut().send_event(PerfHudCommand::ToggleFrameUnit);
+    app.update();
+    assert_eq!(
+        app.world().resource::<FrameUnitToggle>().switched_from(),
+        Some("frame_time_ms")
+    );
+    app.world_mut().send_event(PerfHudCommand::CyclePreset);
+    app.update();
+    app.update();
+    // The new preset's settings are not switched, and neither is the toggle
+    assert!(app
+        .world()
+        .resource::<FrameUnitToggle>()
+        .switched_from()
+        .is_none());
+    let preset = curves(&app);
+    assert_eq!(preset[0], ("frame_time_ms".to_owned(), true));
+    assert_eq!(preset[1], ("fps".to_owned(), true));
+
+    // The next toggle switches the new preset instead of restoring the old one
+    app.world_mut().send_event(PerfHudCommand::ToggleFrameUnit);
+    app.update();
+    let switched = curves(&app);
+    assert_eq!(switched[0], ("fps".to_owned(), true));
+    assert_eq!(switched[1], ("fps".to_owned(), false));
+    assert_eq!(
+        app.world().resource::<HudHandles>().graph_labels[0].metric_id,
+        "fps"
+    );
+
+    app.world_mut().send_event(PerfHudCommand::ToggleFrameUnit);
+    app.update();
+    assert_eq!(curves(&app), preset);
+}
+
+#[test]
+fn clicking_a_legend_label_toggles_its_curve() {
+    use bevy_perf_hud::{HudHandles, HudInteraction, MultiLineGraphMaterial};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        interaction: HudInteraction {
+            legend: true,
+            ..default()
+        },
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    app.update();
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    let label = handles.graph_labels[1].entity;
+    // Press the label once, after the UI focus system updated interactions
+    app.add_systems(
+        PreUpdate,
+        (move |mut interactions: Query<&mut Interaction>, mut done: Local<bool>| {
+            if !std::mem::replace(&mut *done, true) {
+                *interactions.get_mut(label).unwrap() = Interaction::Pressed;
+            }
+        })
+        .after(bevy::ui::UiSystem::Focus),
+    );
+    app.update();
+
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert!(settings.graph.curves[0].enabled);
+    assert!(!settings.graph.curves[1].enabled);
+    let params = &app
+        .world()
+        .resource::<Assets<MultiLineGraphMaterial>>()
+        .get(&handles.graph_material.unwrap())
+        .unwrap()
+        .params;
+    assert_ne!(params.colors[0], Vec4::ZERO);
+    assert_eq!(params.colors[1], Vec4::ZERO);
+    // The label stays to be clicked again, greyed out
+    let node = app.world().get::<Node>(label).unwrap();
+    assert_eq!(node.display, Display::Flex);
+    let color = app.world().get::<TextColor>(label).unwrap().0;
+    assert_ne!(color, settings.graph.curves[1].metric.color);
+}
+
+#[cfg(feature = "config-file")]
+#[test]
+fn config_file_spawns_the_hud_with_its_layout() {
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin::from_config_file("perf_hud.ron"));
+
+    // The file is read on the IO task pool
+    for _ in 0..200 {
+        app.update();
+        if app.world().contains_resource::<bevy_perf_hud::HudHandles>() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    assert!(app.world().contains_resource::<bevy_perf_hud::HudHandles>());
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert_eq!(settings.anchor, bevy_perf_hud::HudAnchor::TopLeft);
+    let curves: Vec<_> = settings
+        .graph
+        .curves
+        .iter()
+        .map(|curve| curve.metric.id.as_str())
+        .collect();
+    assert_eq!(curves, ["frame_time_ms", "fps"]);
+    assert_eq!(settings.bars.bars.len(), 2);
+    assert!(settings
+        .bars
+        .bars
+        .iter()
+        .all(|bar| bar.show_value == Some(false)));
+}
+
+#[cfg(feature = "config-file")]
+#[test]
+fn changed_config_file_rebuilds_the_running_hud() {
+    use bevy_perf_hud::{HudHandles, PerfHudConfigFile, PerfHudConfigHandle};
+
+    let mut app = app_with_headless_rendering();
+    app.add_plugins(BevyPerfHudPlugin::from_config_file("perf_hud.ron"));
+    for _ in 0..200 {
+        app.update();
+        if app.world().contains_resource::<HudHandles>() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    let old_root = app.world().resource::<HudHandles>().root;
+
+    // Editing the asset sends the same modified event as saving the file
+    // with the asset server watching for changes
+    let handle = app.world().resource::<PerfHudConfigHandle>().0.clone();
+    let mut files = app.world_mut().resource_mut::<Assets<PerfHudConfigFile>>();
+    let file = files.get_mut(&handle).unwrap();
+    file.0.origin = Vec2::new(40.0, 24.0);
+    file.0.graph.curves[0].metric.color = Color::srgb(1.0, 0.0, 0.0);
+    file.0.bars.bars.truncate(1);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let settings = app.world().resource::<PerfHudSettings>();
+    assert_eq!(settings.origin, Vec2::new(40.0, 24.0));
+    assert_eq!(
+        settings.graph.curves[0].metric.color,
+        Color::srgb(1.0, 0.0, 0.0)
+    );
+    let handles = app.world().resource::<HudHandles>();
+    assert_ne!(handles.root, old_root);
+    assert_eq!(handles.bar_labels.len(), 1);
+    let root = app.world().get::<Node>(handles.root.unwrap()).unwrap();
+    assert_eq!(root.left, Val::Px(40.0));
+    assert_eq!(root.top, Val::Px(24.0));
+}
+
+#[test]
+fn filtered_label_shows_median_at_reduced_cadence() {
+    use bevy_perf_hud::{LabelFilter, PerfHudTestDriver};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        label_filters: vec![LabelFilter::median("frame_time_ms", 3, 3)],
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.script("frame_time_ms", [10.0, 50.0, 20.0, 30.0, 90.0, 40.0]);
+
+    // The first value shows at once, then holds until the next update
+    driver.run_frames(3);
+    assert_eq!(
+        driver.label_text("frame_time_ms").as_deref(),
+        Some("10.0 ms")
+    );
+
+    // Median of 50, 20 and 30, ignoring the spike that follows
+    driver.run_frames(1);
+    assert_eq!(
+        driver.label_text("frame_time_ms").as_deref(),
+        Some("30.0 ms")
+    );
+    driver.run_frames(2);
+    assert_eq!(
+        driver.label_text("frame_time_ms").as_deref(),
+        Some("30.0 ms")
+    );
+}
+
+#[test]
+fn debugger_break_frame_is_left_out_of_history() {
+    use bevy_perf_hud::{LongFrameState, PerfHudTestDriver};
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.graph.curves[0].smoothing = Some(1.0);
+    settings.graph.curves[0].quantize_step = Some(0.0);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.script("frame_time_ms", [10.0, 10.0, 30_000.0, 12.0]);
+    driver.run_frames(2);
+    driver.set_frame_time(Duration::from_secs(30)).step();
+    driver
+        .set_frame_time(PerfHudTestDriver::FRAME_TIME)
+        .run_frames(1);
+
+    assert_eq!(driver.history("frame_time_ms"), vec![10.0, 10.0, 12.0]);
+    let state = driver.app().world().resource::<LongFrameState>();
+    assert_eq!(state.count, 1);
+    assert_eq!(state.longest, Duration::from_secs(30));
+    assert!(!state.excluded);
+}
+
+#[test]
+fn render_stats_count_visible_meshes_and_triangles() {
+    use bevy::diagnostic::DiagnosticsStore;
+    use bevy_perf_hud::{
+        MetricSampleContext, PerfMetricProvider, RenderStatsDiagnosticsPlugin, RenderStatsMetric,
+        RenderStatsMetricProvider,
+    };
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins((BevyPerfHudPlugin, RenderStatsDiagnosticsPlugin));
+    app.world_mut().spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    let cube = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Cuboid::default());
+    app.world_mut().spawn(Mesh3d(cube.clone()));
+    app.world_mut().spawn((Mesh3d(cube), Visibility::Hidden));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let store = app.world().resource::<DiagnosticsStore>();
+    let ctx = MetricSampleContext {
+        diagnostics: Some(store),
+        system_info: None,
+    };
+    let sample = |metric| RenderStatsMetricProvider::new(metric).sample(ctx);
+    assert_eq!(sample(RenderStatsMetric::MeshDraws), Some(1.0));
+    assert_eq!(sample(RenderStatsMetric::Triangles), Some(12.0));
+    assert!(sample(RenderStatsMetric::VisibleEntities).is_some_and(|count| count >= 1.0));
+}
+
+#[test]
+fn asset_memory_tracks_loaded_meshes_and_images() {
+    use bevy::diagnostic::DiagnosticsStore;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+    use bevy_perf_hud::{
+        AssetMemoryMetric, AssetMemoryMetricProvider, MetricSampleContext, PerfMetricProvider,
+    };
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let sample = |app: &App, metric| {
+        let ctx = MetricSampleContext {
+            diagnostics: Some(app.world().resource::<DiagnosticsStore>()),
+            system_info: None,
+        };
+        AssetMemoryMetricProvider::new(metric).sample(ctx).unwrap()
+    };
+    let meshes_before = sample(&app, AssetMemoryMetric::MeshCount);
+    let images_before = sample(&app, AssetMemoryMetric::ImageCount);
+    let image_memory_before = sample(&app, AssetMemoryMetric::ImageMemory);
+
+    let mesh = Mesh::from(Cuboid::default());
+    let mesh_bytes = mesh.get_vertex_buffer_size() + mesh.indices().unwrap().len() * 4;
+    // 1 MiB of RGBA8 pixels
+    let image = Image::new_fill(
+        Extent3d {
+            width: 512,
+            height: 512,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        default(),
+    );
+    // Assets are freed once their last strong handle is dropped
+    let _mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(mesh);
+    let _image = app.world_mut().resource_mut::<Assets<Image>>().add(image);
+    app.update();
+
+    assert_eq!(
+        sample(&app, AssetMemoryMetric::MeshCount),
+        meshes_before + 1.0
+    );
+    assert!(sample(&app, AssetMemoryMetric::MeshMemory) * 1024.0 * 1024.0 >= mesh_bytes as f32);
+    assert_eq!(
+        sample(&app, AssetMemoryMetric::ImageCount),
+        images_before + 1.0
+    );
+    let image_memory = sample(&app, AssetMemoryMetric::ImageMemory) - image_memory_before;
+    assert!((image_memory - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn counters_become_metrics_when_first_used() {
+    use bevy::diagnostic::DiagnosticsStore;
+    use bevy_perf_hud::{
+        CounterMetricProvider, MetricProviders, MetricSampleContext, PerfCounters,
+        PerfMetricProvider,
+    };
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.add_systems(Update, |mut perf_counters: ResMut<PerfCounters>| {
+        perf_counters.add("game/bullets_spawned", 1.0);
+        perf_counters.add("game/bullets_spawned", 2.0);
+        perf_counters.set("game/players", 12.0);
+    });
+    app.update();
+    app.update();
+
+    let providers = app.world().resource::<MetricProviders>();
+    assert!(providers.contains("game/bullets_spawned"));
+    assert!(providers.contains("game/players"));
+
+    let ctx = MetricSampleContext {
+        diagnostics: Some(app.world().resource::<DiagnosticsStore>()),
+        system_info: None,
+    };
+    // Added amounts are totalled per frame rather than over the run
+    let mut bullets = CounterMetricProvider::new("game/bullets_spawned");
+    assert_eq!(bullets.sample(ctx), Some(3.0));
+    let mut players = CounterMetricProvider::new("game/players");
+    assert_eq!(players.sample(ctx), Some(12.0));
+    assert_eq!(bullets.history(ctx), vec![3.0, 3.0]);
+}
+
+#[test]
+fn partially_despawned_hud_is_repaired_in_place() {
+    use bevy_perf_hud::{HistoryBuffers, HudHandles};
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        show_controls: true,
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+    let length = app.world().resource::<HistoryBuffers>().length;
+    assert!(length > 0);
+    let graph_materials = app
+        .world()
+        .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>()
+        .len();
+
+    let handles = app.world().resource::<HudHandles>().clone();
+    app.world_mut().despawn(handles.bar_labels[0]);
+    app.world_mut().despawn(handles.control_buttons[0]);
+    app.update();
+    app.update();
+
+    let repaired = app.world().resource::<HudHandles>();
+    assert_eq!(repaired.root, handles.root);
+    assert_ne!(repaired.bar_labels, handles.bar_labels);
+    assert!(repaired
+        .entities()
+        .all(|entity| app.world().get_entity(entity).is_ok()));
+    // The old contents and their materials are gone, the history is kept
+    assert!(app.world().get_entity(handles.bar_labels[1]).is_err());
+    assert_eq!(
+        app.world()
+            .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>()
+            .len(),
+        graph_materials
+    );
+    assert!(app.world().resource::<HistoryBuffers>().length > length);
+}
+
+#[test]
+fn despawned_hud_root_removes_the_hud() {
+    use bevy_perf_hud::HudHandles;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        transition: std::time::Duration::from_millis(200),
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+
+    let root = app.world().resource::<HudHandles>().root.unwrap();
+    app.world_mut().entity_mut(root).despawn();
+    app.update();
+
+    assert!(!app.world().contains_resource::<HudHandles>());
+    assert!(app
+        .world()
+        .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>()
+        .is_empty());
+    assert!(app
+        .world()
+        .resource::<Assets<bevy_perf_hud::BarsMaterial>>()
+        .is_empty());
+}
+
+#[test]
+fn toggle_graph_view_switches_between_live_and_trend() {
+    use bevy_perf_hud::GraphView;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings::default());
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    assert_eq!(
+        app.world().resource::<PerfHudSettings>().graph.view,
+        GraphView::Live
+    );
+
+    app.world_mut()
+        .send_event(bevy_perf_hud::PerfHudCommand::ToggleGraphView);
+    app.update();
+    assert_eq!(
+        app.world().resource::<PerfHudSettings>().graph.view,
+        GraphView::Trend
+    );
+    assert!(app
+        .world()
+        .contains_resource::<bevy_perf_hud::TrendHistory>());
+}
+
+#[test]
+fn test_driver_records_scripted_values() {
+    use bevy_perf_hud::PerfHudTestDriver;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    // Unsmoothed, unquantized values keep the history exact
+    settings.graph.curves[0].smoothing = Some(1.0);
+    settings.graph.curves[0].quantize_step = Some(0.0);
+    // Show every value in the label as it is recorded
+    settings.label_filters.clear();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.script("frame_time_ms", [10.0, 20.0, 30.0]);
+    driver.run_frames(3);
+
+    assert_eq!(driver.history("frame_time_ms"), vec![10.0, 20.0, 30.0]);
+    assert_eq!(
+        driver.label_text("frame_time_ms").as_deref(),
+        Some("30.0 ms")
+    );
+}
+
+#[test]
+fn instant_mode_records_raw_values() {
+    use bevy_perf_hud::PerfHudTestDriver;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(PerfHudSettings {
+        instant: true,
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.script("frame_time_ms", [10.04, 33.37]);
+    driver.run_frames(2);
+
+    // Neither smoothed towards the previous value nor quantized to 0.1
+    assert_eq!(driver.history("frame_time_ms"), vec![10.04, 33.37]);
+}
+
+#[test]
+fn steadily_rising_entities_and_memory_suggest_a_leak() {
+    use bevy_perf_hud::{
+        LeakDetector, PerfHudLeakSuspected, PerfHudTestDriver, PROCESS_MEM_RSS_ID,
+    };
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings.leak_detection.enabled = true;
+    settings.leak_detection.window = Duration::from_secs(60);
+    settings.leak_detection.sample_interval = Duration::from_secs(1);
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+    #[derive(Resource, Default)]
+    struct Suspected(usize);
+    app.init_resource::<Suspected>().add_systems(
+        Update,
+        |mut events: EventReader<PerfHudLeakSuspected>, mut suspected: ResMut<Suspected>| {
+            suspected.0 += events.read().count();
+        },
+    );
+
+    // 10 frames per second for 70 seconds: +60 entities and +12 MB per minute
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.set_frame_time(Duration::from_millis(100));
+    driver.script("entity_count", (0..700).map(|i| 100.0 + i as f32 * 0.1));
+    driver.script(
+        PROCESS_MEM_RSS_ID,
+        (0..700).map(|i| 200.0 + i as f32 * 0.02),
+    );
+    driver.run_frames(300);
+    assert!(driver
+        .app()
+        .world()
+        .resource::<LeakDetector>()
+        .entities
+        .is_none());
+    driver.run_frames(400);
+
+    let detector = driver.app().world().resource::<LeakDetector>();
+    assert!(detector.suspected);
+    let entities = detector.entities.unwrap();
+    assert!((entities.slope - 60.0).abs() < 1.0);
+    assert!(entities.fit > 0.99);
+    assert!((detector.memory.unwrap().slope - 12.0).abs() < 0.5);
+    assert_eq!(driver.app().world().resource::<Suspected>().0, 1);
+}
+
+#[test]
+fn bar_color_eases_between_budget_zones() {
+    use bevy::math::curve::EaseFunction;
+    use bevy_perf_hud::{
+        BarColorStates, BarColorTransition, BarsMaterial, BudgetLevel, HudHandles, MetricBudget,
+        PerfHudTestDriver,
+    };
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    let mut settings = PerfHudSettings::default();
+    settings
+        .metric_budgets
+        .push(MetricBudget::max("entity_count", 100.0, 200.0));
+    settings.bars.color_transition = Some(BarColorTransition {
+        duration: Duration::from_secs(1),
+        easing: EaseFunction::Linear,
+        hysteresis: 0.05,
+    });
+    let ok = settings.bars.bars[2].metric.color.to_linear();
+    let critical = settings.budget_colors.critical.to_linear();
+    app.insert_resource(settings);
+    app.add_plugins(BevyPerfHudPlugin);
+
+    let mut driver = PerfHudTestDriver::new(app);
+    driver.set_frame_time(Duration::from_millis(100));
+    // Within budget, then critical for 6 frames, then just under the threshold
+    driver.script(
+        "entity_count",
+        [
+            50.0, 50.0, 250.0, 250.0, 250.0, 250.0, 250.0, 250.0, 195.0, 195.0,
+        ],
+    );
+    driver.run_frames(2);
+    let bar_color = |driver: &PerfHudTestDriver| {
+        let world = driver.app().world();
+        let handle = world
+            .resource::<HudHandles>()
+            .bars_material
+            .clone()
+            .unwrap();
+        world
+            .resource::<Assets<BarsMaterial>>()
+            .get(&handle)
+            .unwrap()
+            .params
+            .colors[2]
+    };
+    assert!(bar_color(&driver).distance(ok.to_vec4()) < 1e-5);
+
+    // Halfway through the one second transition, 5 frames after it started
+    driver.run_frames(6);
+    let halfway = ok.mix(&critical, 0.5).to_vec4();
+    assert!(bar_color(&driver).distance(halfway) < 1e-3);
+
+    // 195 is within 5% of the critical threshold, so the bar stays critical
+    driver.run_frames(2);
+    let levels = driver.app().world().resource::<BarColorStates>();
+    assert_eq!(levels.level("entity_count"), BudgetLevel::Critical);
+}
+
+#[test]
+fn rebuild_with_transition_fades_out_the_old_hud() {
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    let mut app = app_with_headless_rendering();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    app.insert_resource(PerfHudSettings {
+        transition: Duration::from_millis(250),
+        ..default()
+    });
+    app.add_plugins(BevyPerfHudPlugin);
+    app.update();
+    let old_root = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .root
+        .unwrap();
+
+    app.world_mut()
+        .send_event(bevy_perf_hud::PerfHudCommand::Rebuild);
+    app.update();
+    let new_root = app
+        .world()
+        .resource::<bevy_perf_hud::HudHandles>()
+        .root
+        .unwrap();
+    assert_ne!(old_root, new_root);
+    // Both HUDs are shown while they cross-fade
+    assert!(app.world().get_entity(old_root).is_ok());
+    assert!(app
+        .world()
+        .get::<bevy_perf_hud::HudFade>(new_root)
+        .is_some());
+
+    for _ in 0..4 {
+        app.update();
+    }
+    assert!(app.world().get_entity(old_root).is_err());
+    assert!(app
+        .world()
+        .get::<bevy_perf_hud::HudFade>(new_root)
+        .is_none());
+    assert_eq!(
+        app.world()
+            .resource::<Assets<bevy_perf_hud::MultiLineGraphMaterial>>()
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,51 @@
+//! Tests of the no-op facade compiled without the `hud` feature.
+//!
+//! The plugins must add no HUD systems or diagnostics, while game code using
+//! the counters, commands, pushed samples and app extensions keeps running
+//! unchanged.
+
+#![cfg(not(feature = "hud"))]
+
+use bevy::prelude::*;
+use bevy_perf_hud::*;
+
+#[derive(Resource, Default)]
+struct GameFrames(u32);
+
+fn game_system(
+    mut counters: ResMut<PerfCounters>,
+    mut commands: EventWriter<PerfHudCommand>,
+    mut samples: EventWriter<MetricSample>,
+    mut frames: ResMut<GameFrames>,
+) {
+    counters.add("game/spawned", 1.0);
+    commands.write(PerfHudCommand::Rebuild);
+    samples.write(MetricSample::new("game/load", 0.5));
+    frames.0 += 1;
+}
+
+fn lower_detail() {}
+
+#[test]
+fn plugins_without_hud_are_no_ops() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(PerfHudSettings::default())
+        .init_resource::<GameFrames>()
+        .add_plugins((
+            BevyPerfHudPlugin,
+            InputLatencyDiagnosticsPlugin,
+            AssetMemoryDiagnosticsPlugin,
+        ))
+        .add_perf_metric_fn("game/value", |_| 1.0)
+        .add_perf_alert("game/value", lower_detail)
+        .add_systems(Update, game_system);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert!(!app.is_plugin_added::<bevy::diagnostic::FrameTimeDiagnosticsPlugin>());
+    let world = app.world();
+    assert_eq!(world.resource::<GameFrames>().0, 3);
+    assert_eq!(world.resource::<PerfCounters>().get("game/spawned"), None);
+}
@@ -3,7 +3,12 @@
 //! These tests verify that each metric provider correctly samples
 //! and processes performance data.
 
-use bevy::diagnostic::DiagnosticsStore;
+#![cfg(feature = "hud")]
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticMeasurement, DiagnosticsStore, FrameTimeDiagnosticsPlugin,
+};
+use bevy::platform::time::Instant;
 use bevy_perf_hud::{
     EntityCountMetricProvider, FpsMetricProvider, FrameTimeMetricProvider, MetricSampleContext,
     PerfMetricProvider,
@@ -33,7 +38,10 @@ fn providers_handle_missing_diagnostics_gracefully() {
     let mut frame_time_provider = FrameTimeMetricProvider;
     let mut entity_count_provider = EntityCountMetricProvider;
 
-    let ctx = MetricSampleContext { diagnostics: None };
+    let ctx = MetricSampleContext {
+        diagnostics: None,
+        system_info: None,
+    };
 
     // Providers should return None when diagnostics are unavailable
     assert_eq!(fps_provider.sample(ctx), None);
@@ -50,6 +58,7 @@ fn providers_handle_empty_diagnostics_gracefully() {
     let diagnostics = DiagnosticsStore::default();
     let ctx = MetricSampleContext {
         diagnostics: Some(&diagnostics),
+        system_info: None,
     };
 
     // Providers should return None when specific metrics are unavailable
@@ -57,3 +66,27 @@ fn providers_handle_empty_diagnostics_gracefully() {
     assert_eq!(frame_time_provider.sample(ctx), None);
     assert_eq!(entity_count_provider.sample(ctx), None);
 }
+
+#[test]
+fn providers_return_recorded_diagnostic_history() {
+    let fps_provider = FpsMetricProvider;
+    let entity_count_provider = EntityCountMetricProvider;
+
+    let mut diagnostics = DiagnosticsStore::default();
+    let mut fps = Diagnostic::new(FrameTimeDiagnosticsPlugin::FPS);
+    for value in [58.0, 59.0, 60.0] {
+        fps.add_measurement(DiagnosticMeasurement {
+            time: Instant::now(),
+            value,
+        });
+    }
+    diagnostics.add(fps);
+    let ctx = MetricSampleContext {
+        diagnostics: Some(&diagnostics),
+        system_info: None,
+    };
+
+    // History is returned oldest first; missing diagnostics yield no history
+    assert_eq!(fps_provider.history(ctx), vec![58.0, 59.0, 60.0]);
+    assert!(entity_count_provider.history(ctx).is_empty());
+}
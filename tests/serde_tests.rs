@@ -0,0 +1,84 @@
+//! Round trips of the HUD settings through serde.
+//!
+//! Settings are written to RON and read back; reading must not lose or
+//! change anything, so a second write gives the same text.
+
+#![cfg(feature = "serde")]
+
+use bevy::prelude::*;
+use bevy_perf_hud::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Write `value` to RON, read it back and write it again, returning both texts
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> (String, String) {
+    let first = ron::to_string(value).expect("settings serialize");
+    let read: T = ron::from_str(&first).expect("settings deserialize");
+    let second = ron::to_string(&read).expect("settings serialize again");
+    (first, second)
+}
+
+#[test]
+fn default_settings_round_trip() {
+    let (first, second) = round_trip(&PerfHudSettings::default());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn preset_settings_round_trip() {
+    for preset in [
+        HudPreset::Minimal,
+        HudPreset::Compact,
+        HudPreset::Detailed,
+        HudPreset::TrafficLight,
+    ] {
+        let (first, second) = round_trip(&preset.settings());
+        assert_eq!(first, second, "{preset:?}");
+    }
+}
+
+#[test]
+fn customized_config_types_round_trip() {
+    let metric = MetricDefinition {
+        id: "net/rtt_ms".into(),
+        label: Some("RTT".into()),
+        unit: Some("ms".into()),
+        precision: 1,
+        color: Color::srgba(0.2, 0.4, 0.6, 0.8),
+    };
+    let read: MetricDefinition = ron::from_str(&ron::to_string(&metric).unwrap()).unwrap();
+    assert_eq!(read, metric);
+
+    let mode = BarScaleMode::Percentile {
+        lower: 5.0,
+        upper: 95.0,
+        sample_count: 120,
+    };
+    let read: BarScaleMode = ron::from_str(&ron::to_string(&mode).unwrap()).unwrap();
+    assert_eq!(read, mode);
+
+    let mut settings = PerfHudSettings {
+        anchor: HudAnchor::BottomRight,
+        placement: HudPlacement::AvoidCursor { margin: 8.0 },
+        metric_budgets: vec![MetricBudget::max("net/rtt_ms", 80.0, 150.0)],
+        ..default()
+    };
+    settings.graph.history = GraphHistory::Seconds(10.0);
+    settings.graph.label_column = LabelColumn::Overlay;
+    settings.bars.color_transition = Some(BarColorTransition::default());
+    let (first, second) = round_trip(&settings);
+    assert_eq!(first, second);
+    let read: PerfHudSettings = ron::from_str(&first).unwrap();
+    assert_eq!(read.anchor, HudAnchor::BottomRight);
+    assert_eq!(read.graph.history, GraphHistory::Seconds(10.0));
+}
+
+#[test]
+fn missing_settings_fields_keep_their_defaults() {
+    let read: PerfHudSettings = ron::from_str("(enabled: false)").unwrap();
+    assert!(!read.enabled);
+    assert_eq!(read.anchor, PerfHudSettings::default().anchor);
+    assert_eq!(
+        read.graph.curves.len(),
+        PerfHudSettings::default().graph.curves.len()
+    );
+}